@@ -0,0 +1,590 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod allowance_manager {
+    use core::fmt;
+
+    use ink_env as env;
+
+    use ink_prelude::{
+        format,
+        string::String,
+    };
+
+    use ink_storage::{
+        collections::HashMap as StorageHashMap,
+        traits::{
+            PackedLayout,
+            SpreadLayout,
+        },
+    };
+
+    /// Permission for `spender` to pull up to `amount` tokens of its signer until `expiration`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Permit {
+        pub spender: AccountId,
+        pub amount: Balance,
+        pub expiration: Timestamp,
+        /// Must equal the signer's current nonce for `spender`
+        pub nonce: u64,
+    }
+
+    /// Allowance granted to a spender through the manager.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Allowance {
+        pub amount: Balance,
+        /// Timestamp after which the allowance can no longer be used
+        pub expiration: Timestamp,
+        /// Nonce the next signed permit for this owner and spender must carry
+        pub nonce: u64,
+    }
+
+    /// Returns whether `signature` is a valid sr25519 signature of `message_hash` by `signer`,
+    /// as reported by the runtime's chain extension.
+    #[cfg(not(test))]
+    fn sr25519_verify(signer: AccountId, message_hash: [u8; 32], signature: [u8; 64]) -> bool {
+        /// Function id of the runtime chain extension verifying sr25519 signatures.
+        const SR25519_VERIFY_FUNC_ID: u32 = 0x0101;
+
+        env::chain_extension::ChainExtensionMethod::build(SR25519_VERIFY_FUNC_ID)
+            .input::<(AccountId, [u8; 32], [u8; 64])>()
+            .output::<bool>()
+            .ignore_error_code()
+            .call(&(signer, message_hash, signature))
+    }
+
+    /// Off-chain stand-in for the runtime's verification, as ink's test environment can't register
+    /// chain extensions from outside `ink_env`. Accepts signatures made by `tests::mock_sign`.
+    #[cfg(test)]
+    fn sr25519_verify(signer: AccountId, message_hash: [u8; 32], signature: [u8; 64]) -> bool {
+        signature == tests::mock_sign(signer, message_hash)
+    }
+
+    /// Shared allowance manager for Entropy.
+    ///
+    /// Users approve the manager once on the Entropy contract, then grant each protocol a scoped
+    /// allowance here, either directly with `approve` or with a signed `Permit` the protocol submits.
+    /// Protocols pull tokens through the manager, which calls Entropy's `transfer_from`.
+    #[ink(storage)]
+    pub struct AllowanceManager {
+        /// Entropy contract the manager pulls tokens from
+        token: AccountId,
+
+        /// Mapping of an owner and spender pair to the spender's allowance
+        allowances: StorageHashMap<(AccountId, AccountId), Allowance>
+    }
+
+    /// Event emitted when an owner grants a spender an allowance, directly or through a permit.
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        amount: Balance,
+        expiration: Timestamp
+    }
+
+    /// Event emitted when a spender pulls tokens through the manager.
+    #[ink(event)]
+    pub struct Pulled {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance
+    }
+
+    /// Event emitted when an owner invalidates outstanding permits for a spender.
+    #[ink(event)]
+    pub struct NonceInvalidation {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        new_nonce: u64
+    }
+
+    /// Event emitted when error occurs
+    #[ink(event)]
+    pub struct TransactionFailed {
+        #[ink(topic)]
+        error: String
+    }
+
+    /// AllowanceManager error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if a permit is submitted by an account other than its spender
+        PermissionDenied,
+        /// Returned if not enough allowance to fulfill a request is available.
+        InsufficientAllowance,
+        /// Returned if an allowance or permit is used after its expiration
+        AllowanceExpired,
+        /// Returned if a permit signature does not verify against the owner
+        InvalidSignature,
+        /// Returned if a permit does not carry the expected nonce, or a nonce is not increased
+        InvalidNonce,
+        /// Returned if the Entropy contract rejects the transfer
+        TokenTransferFailed
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Self::PermissionDenied => write!(f, "PermissionDenied"),
+                Self::InsufficientAllowance => write!(f, "InsufficientAllowance"),
+                Self::AllowanceExpired => write!(f, "AllowanceExpired"),
+                Self::InvalidSignature => write!(f, "InvalidSignature"),
+                Self::InvalidNonce => write!(f, "InvalidNonce"),
+                Self::TokenTransferFailed => write!(f, "TokenTransferFailed")
+            }
+        }
+    }
+
+    /// AllowanceManager result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl AllowanceManager {
+
+        /// Creates a new allowance manager for the Entropy contract at `token`.
+        #[ink(constructor)]
+        pub fn new(token: AccountId) -> Self {
+            Self {
+                token,
+                allowances: StorageHashMap::new()
+            }
+        }
+
+        /// Returns the Entropy contract the manager pulls tokens from.
+        #[ink(message)]
+        pub fn token(&self) -> AccountId {
+            self.token
+        }
+
+        /// Returns the allowance `owner` granted `spender` through the manager.
+        #[ink(message)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Allowance {
+            self.allowances.get(&(owner, spender)).copied().unwrap_or_default()
+        }
+
+        /// Returns the hash `owner` signs to grant `permit`.
+        ///
+        /// The hash commits to this contract's account so a permit can't be replayed on another manager.
+        #[ink(message)]
+        pub fn permit_hash(&self, owner: AccountId, permit: Permit) -> [u8; 32] {
+            self.env().hash_encoded::<env::hash::Blake2x256, _>(&(self.env().account_id(), owner, permit))
+        }
+
+        /// Allows `spender` to pull up to `amount` of the caller's tokens until `expiration`.
+        ///
+        /// Overwrites the current allowance but keeps its nonce.
+        ///
+        /// An `Approval` event is emitted.
+        #[ink(message)]
+        pub fn approve(&mut self, spender: AccountId, amount: Balance, expiration: Timestamp) {
+            let owner = self.env().caller();
+            let nonce = self.allowance(owner, spender).nonce;
+            self.set_allowance(owner, spender, Allowance { amount, expiration, nonce });
+        }
+
+        /// Invalidates every outstanding permit of the caller for `spender` with a nonce below `new_nonce`.
+        ///
+        /// On success a `NonceInvalidation` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidNonce` error if `new_nonce` is not above the current nonce.
+        #[ink(message)]
+        pub fn invalidate_nonces(&mut self, spender: AccountId, new_nonce: u64) -> Result<()> {
+            let owner = self.env().caller();
+            let mut allowance = self.allowance(owner, spender);
+            if new_nonce <= allowance.nonce {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InvalidNonce)
+                });
+                return Err(Error::InvalidNonce);
+            }
+
+            allowance.nonce = new_nonce;
+            self.allowances.insert((owner, spender), allowance);
+
+            self.env().emit_event(NonceInvalidation {
+                owner,
+                spender,
+                new_nonce
+            });
+
+            Ok(())
+        }
+
+        /// Transfers `amount` of `owner`'s tokens to `to`, using the caller's allowance.
+        ///
+        /// On success a `Pulled` event is emitted, along with Entropy's own `Transfer` event.
+        ///
+        /// # Errors
+        ///
+        /// Returns `AllowanceExpired` error if the caller's allowance expired.
+        ///
+        /// Returns `InsufficientAllowance` error if the caller's allowance is below `amount`.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the transfer, e.g. because `owner`
+        /// did not approve the manager for enough tokens.
+        #[ink(message)]
+        pub fn transfer_from(&mut self, owner: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+            let spender = self.env().caller();
+            let mut allowance = self.allowance(owner, spender);
+            if self.env().block_timestamp() > allowance.expiration {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::AllowanceExpired)
+                });
+                return Err(Error::AllowanceExpired);
+            }
+            if allowance.amount < amount {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InsufficientAllowance)
+                });
+                return Err(Error::InsufficientAllowance);
+            }
+
+            if !pull(self.token, owner, to, amount) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenTransferFailed)
+                });
+                return Err(Error::TokenTransferFailed);
+            }
+
+            allowance.amount -= amount;
+            self.allowances.insert((owner, spender), allowance);
+
+            self.env().emit_event(Pulled {
+                owner,
+                spender,
+                to,
+                amount
+            });
+
+            Ok(())
+        }
+
+        /// Grants the caller the allowance `owner` signed in `permit`, then transfers `amount` of
+        /// `owner`'s tokens to `to` with it.
+        ///
+        /// `signature` is `owner`'s sr25519 signature of `permit_hash(owner, permit)`, verified through
+        /// the runtime's chain extension. On success an `Approval` event and a `Pulled` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if the caller is not the permit's spender.
+        ///
+        /// Returns `AllowanceExpired` error if the permit expired.
+        ///
+        /// Returns `InvalidNonce` error if the permit's nonce is not the current one, e.g. it was used
+        /// or invalidated.
+        ///
+        /// Returns `InvalidSignature` error if `signature` is not `owner`'s signature of the permit.
+        ///
+        /// Returns any error `transfer_from` returns.
+        #[ink(message)]
+        pub fn transfer_from_with_permit(
+            &mut self,
+            owner: AccountId,
+            to: AccountId,
+            amount: Balance,
+            permit: Permit,
+            signature: [u8; 64],
+        ) -> Result<()> {
+            let spender = self.env().caller();
+            if permit.spender != spender {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::PermissionDenied)
+                });
+                return Err(Error::PermissionDenied);
+            }
+
+            if self.env().block_timestamp() > permit.expiration {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::AllowanceExpired)
+                });
+                return Err(Error::AllowanceExpired);
+            }
+
+            if permit.nonce != self.allowance(owner, spender).nonce {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InvalidNonce)
+                });
+                return Err(Error::InvalidNonce);
+            }
+
+            if !sr25519_verify(owner, self.permit_hash(owner, permit), signature) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InvalidSignature)
+                });
+                return Err(Error::InvalidSignature);
+            }
+
+            self.set_allowance(owner, spender, Allowance {
+                amount: permit.amount,
+                expiration: permit.expiration,
+                nonce: permit.nonce + 1
+            });
+
+            self.transfer_from(owner, to, amount)
+        }
+
+        /// Stores the allowance of `spender` over `owner`'s tokens and emits an `Approval` event.
+        fn set_allowance(&mut self, owner: AccountId, spender: AccountId, allowance: Allowance) {
+            self.allowances.insert((owner, spender), allowance);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                amount: allowance.amount,
+                expiration: allowance.expiration
+            });
+        }
+    }
+
+    /// Pulls `amount` of `owner`'s tokens to `to` through Entropy's `transfer_from`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn pull(token: AccountId, owner: AccountId, to: AccountId, amount: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer_from(owner, to, amount).is_ok()
+    }
+
+    /// Off-chain stand-in for the cross-contract call, as ink's test environment can't execute other
+    /// contracts. Pulls from the ledger of `tests::MockToken`.
+    #[cfg(test)]
+    fn pull(token: AccountId, owner: AccountId, to: AccountId, amount: Balance) -> bool {
+        tests::MockToken::transfer_from(token, owner, to, amount)
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+        use std::{
+            cell::RefCell,
+            collections::HashMap,
+        };
+
+        type Event = <AllowanceManager as ::ink_lang::BaseEvent>::Type;
+
+        thread_local! {
+            /// Balances of `MockToken` and the allowances its holders granted the manager
+            static LEDGER: RefCell<(HashMap<AccountId, Balance>, HashMap<AccountId, Balance>)> = RefCell::new(Default::default());
+        }
+
+        /// Stand-in for the Entropy contract, holding balances and the allowances granted to the manager.
+        pub(super) struct MockToken;
+
+        impl MockToken {
+            fn fund(owner: AccountId, balance: Balance, manager_allowance: Balance) {
+                LEDGER.with(|ledger| {
+                    let mut ledger = ledger.borrow_mut();
+                    ledger.0.insert(owner, balance);
+                    ledger.1.insert(owner, manager_allowance);
+                });
+            }
+
+            fn balance_of(account: AccountId) -> Balance {
+                LEDGER.with(|ledger| ledger.borrow().0.get(&account).copied().unwrap_or(0))
+            }
+
+            pub(super) fn transfer_from(token: AccountId, owner: AccountId, to: AccountId, amount: Balance) -> bool {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| {
+                    let mut ledger = ledger.borrow_mut();
+                    let balance = ledger.0.get(&owner).copied().unwrap_or(0);
+                    let allowance = ledger.1.get(&owner).copied().unwrap_or(0);
+                    if balance < amount || allowance < amount {
+                        return false;
+                    }
+                    ledger.0.insert(owner, balance - amount);
+                    ledger.1.insert(owner, allowance - amount);
+                    *ledger.0.entry(to).or_insert(0) += amount;
+                    true
+                })
+            }
+        }
+
+        /// Protocol pulling its users' tokens through the manager, e.g. to settle a purchase.
+        struct MockProtocol {
+            account: AccountId,
+        }
+
+        impl MockProtocol {
+            fn charge(&self, manager: &mut AllowanceManager, user: AccountId, amount: Balance) -> Result<()> {
+                set_caller(self.account);
+                manager.transfer_from(user, self.account, amount)
+            }
+
+            fn charge_with_permit(&self, manager: &mut AllowanceManager, user: AccountId, amount: Balance, permit: Permit, signature: [u8; 64]) -> Result<()> {
+                set_caller(self.account);
+                manager.transfer_from_with_permit(user, self.account, amount, permit, signature)
+            }
+        }
+
+        /// Creates a manager for `MockToken`, clearing its ledger left over by previous tests.
+        fn new_manager() -> AllowanceManager {
+            LEDGER.with(|ledger| *ledger.borrow_mut() = Default::default());
+            AllowanceManager::new(token_account())
+        }
+
+        fn token_account() -> AccountId {
+            AccountId::from([0x10; 32])
+        }
+
+        pub(super) fn mock_sign(signer: AccountId, hash: [u8; 32]) -> [u8; 64] {
+            let mut digest = [0x0; 32];
+            ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(&(signer, hash), &mut digest);
+            let mut signature = [0x0; 64];
+            signature[..32].copy_from_slice(&digest);
+            signature[32..].copy_from_slice(&hash);
+            signature
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap_or_else(|_| [0x0; 32].into());
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(caller, callee, 1000000, 1000000, data);
+        }
+
+        fn assert_pulled_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_owner: AccountId,
+            expected_spender: AccountId,
+            expected_amount: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Pulled(Pulled { owner, spender, to, amount }) = decoded_event {
+                assert_eq!(owner, expected_owner, "encountered invalid Pulled.owner");
+                assert_eq!(spender, expected_spender, "encountered invalid Pulled.spender");
+                assert_eq!(to, expected_spender, "encountered invalid Pulled.to");
+                assert_eq!(amount, expected_amount, "encountered invalid Pulled.amount");
+            } else {
+                panic!("encountered unexpected event kind: expected a Pulled event")
+            }
+        }
+
+        #[ink::test]
+        fn approve_and_transfer_from_works() {
+            let mut manager = new_manager();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            let protocol = MockProtocol { account: accounts.charlie };
+            MockToken::fund(accounts.alice, 100, 100);
+
+            assert_eq!(manager.token(), token_account());
+            assert_eq!(protocol.charge(&mut manager, accounts.alice, 10), Err(Error::InsufficientAllowance));
+
+            set_caller(accounts.alice);
+            manager.approve(accounts.charlie, 30, 100);
+            assert_eq!(manager.allowance(accounts.alice, accounts.charlie), Allowance { amount: 30, expiration: 100, nonce: 0 });
+
+            assert_eq!(protocol.charge(&mut manager, accounts.alice, 20), Ok(()));
+            assert_eq!(MockToken::balance_of(accounts.charlie), 20);
+            assert_eq!(manager.allowance(accounts.alice, accounts.charlie).amount, 10);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_pulled_event(&emitted_events[emitted_events.len() - 1], accounts.alice, accounts.charlie, 20);
+
+            assert_eq!(protocol.charge(&mut manager, accounts.alice, 11), Err(Error::InsufficientAllowance));
+
+            // Other protocols need their own allowance
+            let other = MockProtocol { account: accounts.django };
+            assert_eq!(other.charge(&mut manager, accounts.alice, 1), Err(Error::InsufficientAllowance));
+
+            // The allowance expires, timestamp 105 after 21 blocks
+            for _ in 0..21 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>().expect("Cannot advance block");
+            }
+            assert_eq!(protocol.charge(&mut manager, accounts.alice, 10), Err(Error::AllowanceExpired));
+            assert_eq!(MockToken::balance_of(accounts.alice), 80);
+        }
+
+        #[ink::test]
+        fn token_rejection_keeps_allowance() {
+            let mut manager = new_manager();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            let protocol = MockProtocol { account: accounts.charlie };
+
+            // Alice approved the manager on the token for less than the protocol may pull
+            MockToken::fund(accounts.alice, 100, 5);
+            set_caller(accounts.alice);
+            manager.approve(accounts.charlie, 30, 100);
+
+            assert_eq!(protocol.charge(&mut manager, accounts.alice, 10), Err(Error::TokenTransferFailed));
+            assert_eq!(manager.allowance(accounts.alice, accounts.charlie).amount, 30);
+            assert_eq!(MockToken::balance_of(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn transfer_from_with_permit_works() {
+            let mut manager = new_manager();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            let protocol = MockProtocol { account: accounts.charlie };
+            MockToken::fund(accounts.bob, 100, 100);
+
+            let permit = Permit { spender: accounts.charlie, amount: 50, expiration: 100, nonce: 0 };
+            let signature = mock_sign(accounts.bob, manager.permit_hash(accounts.bob, permit));
+
+            // Only the permit's spender can use it
+            let other = MockProtocol { account: accounts.django };
+            assert_eq!(other.charge_with_permit(&mut manager, accounts.bob, 10, permit, signature), Err(Error::PermissionDenied));
+
+            assert_eq!(protocol.charge_with_permit(&mut manager, accounts.bob, 10, permit, signature), Ok(()));
+            assert_eq!(MockToken::balance_of(accounts.charlie), 10);
+            assert_eq!(manager.allowance(accounts.bob, accounts.charlie), Allowance { amount: 40, expiration: 100, nonce: 1 });
+
+            // The permit can't be replayed, but the granted allowance stays usable
+            assert_eq!(protocol.charge_with_permit(&mut manager, accounts.bob, 10, permit, signature), Err(Error::InvalidNonce));
+            assert_eq!(protocol.charge(&mut manager, accounts.bob, 40), Ok(()));
+            assert_eq!(MockToken::balance_of(accounts.charlie), 50);
+        }
+
+        #[ink::test]
+        fn invalid_permits_are_rejected() {
+            let mut manager = new_manager();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            let protocol = MockProtocol { account: accounts.charlie };
+            MockToken::fund(accounts.bob, 100, 100);
+
+            // Signed by someone other than the owner
+            let permit = Permit { spender: accounts.charlie, amount: 50, expiration: 100, nonce: 0 };
+            let signature = mock_sign(accounts.eve, manager.permit_hash(accounts.bob, permit));
+            assert_eq!(protocol.charge_with_permit(&mut manager, accounts.bob, 10, permit, signature), Err(Error::InvalidSignature));
+
+            // Signed for a smaller amount
+            let signature = mock_sign(accounts.bob, manager.permit_hash(accounts.bob, Permit { amount: 5, ..permit }));
+            assert_eq!(protocol.charge_with_permit(&mut manager, accounts.bob, 10, permit, signature), Err(Error::InvalidSignature));
+
+            // Invalidated before use
+            let signature = mock_sign(accounts.bob, manager.permit_hash(accounts.bob, permit));
+            set_caller(accounts.bob);
+            assert_eq!(manager.invalidate_nonces(accounts.charlie, 0), Err(Error::InvalidNonce));
+            assert_eq!(manager.invalidate_nonces(accounts.charlie, 3), Ok(()));
+            assert_eq!(protocol.charge_with_permit(&mut manager, accounts.bob, 10, permit, signature), Err(Error::InvalidNonce));
+
+            // Expired, timestamp 10 after two blocks
+            let permit = Permit { spender: accounts.charlie, amount: 50, expiration: 5, nonce: 3 };
+            let signature = mock_sign(accounts.bob, manager.permit_hash(accounts.bob, permit));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().expect("Cannot advance block");
+            assert_eq!(protocol.charge_with_permit(&mut manager, accounts.bob, 10, permit, signature), Err(Error::AllowanceExpired));
+
+            assert_eq!(MockToken::balance_of(accounts.bob), 100);
+            assert_eq!(manager.allowance(accounts.bob, accounts.charlie), Allowance { amount: 0, expiration: 0, nonce: 3 });
+        }
+    }
+}
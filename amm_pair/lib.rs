@@ -0,0 +1,947 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod amm_pair {
+    use core::fmt;
+
+    use ink_prelude::{
+        format,
+        string::String,
+    };
+
+    use ink_storage::collections::HashMap as StorageHashMap;
+
+    /// Shares minted to the zero account on the first deposit, so the share price can't be inflated
+    /// to round later deposits down to nothing.
+    pub const MINIMUM_SHARES: Balance = 1_000;
+
+    /// Constant-product market pairing Entropy with the native currency.
+    ///
+    /// Liquidity providers deposit both sides at the current ratio for shares of the reserves, and
+    /// traders swap against the reserves, keeping `reserve_token * reserve_native` from decreasing.
+    #[ink(storage)]
+    pub struct AmmPair {
+        owner: AccountId,
+
+        /// Entropy contract traded by the pair
+        token: AccountId,
+
+        /// Swap fee in basis points, kept by the reserves
+        fee_bps: Balance,
+
+        /// Entropy held for trading, tracked apart from the pair's balance so donations don't move the price
+        reserve_token: Balance,
+
+        /// Native currency held for trading
+        reserve_native: Balance,
+
+        /// Total liquidity shares, including `MINIMUM_SHARES` owned by the zero account
+        total_shares: Balance,
+
+        /// Mapping of liquidity shares held by each provider
+        shares: StorageHashMap<AccountId, Balance>,
+
+        /// Whether a message is executing, rejecting reentrant calls
+        locked: bool
+    }
+
+    /// Event emitted when liquidity is added.
+    #[ink(event)]
+    pub struct LiquidityAdded {
+        #[ink(topic)]
+        provider: AccountId,
+        token_amount: Balance,
+        native_amount: Balance,
+        shares: Balance
+    }
+
+    /// Event emitted when liquidity is removed.
+    #[ink(event)]
+    pub struct LiquidityRemoved {
+        #[ink(topic)]
+        provider: AccountId,
+        token_amount: Balance,
+        native_amount: Balance,
+        shares: Balance
+    }
+
+    /// Event emitted when a swap occurs.
+    #[ink(event)]
+    pub struct Swapped {
+        #[ink(topic)]
+        trader: AccountId,
+        token_in: Balance,
+        native_in: Balance,
+        token_out: Balance,
+        native_out: Balance
+    }
+
+    /// Event emitted when the swap fee is set.
+    #[ink(event)]
+    pub struct FeeChanged {
+        fee_bps: Balance
+    }
+
+    /// Event emitted when error occurs
+    #[ink(event)]
+    pub struct TransactionFailed {
+        #[ink(topic)]
+        error: String
+    }
+
+    /// AmmPair error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if not privileged.
+        PermissionDenied,
+        /// Returned if a swap fee of 100% or more is set
+        InvalidFee,
+        /// Returned if an amount is zero
+        ZeroAmount,
+        /// Returned if the reserves are empty or too small for the first deposit
+        InsufficientLiquidity,
+        /// Returned if removing more shares than held
+        InsufficientShares,
+        /// Returned if an output is below the requested minimum or a deposit above the requested maximum
+        SlippageExceeded,
+        /// Returned if a message is called while another is executing
+        Reentrancy,
+        /// Returned if the Entropy contract rejects a transfer
+        TokenTransferFailed,
+        /// Returned if amounts are too large to compute with
+        Overflow
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Self::PermissionDenied => write!(f, "PermissionDenied"),
+                Self::InvalidFee => write!(f, "InvalidFee"),
+                Self::ZeroAmount => write!(f, "ZeroAmount"),
+                Self::InsufficientLiquidity => write!(f, "InsufficientLiquidity"),
+                Self::InsufficientShares => write!(f, "InsufficientShares"),
+                Self::SlippageExceeded => write!(f, "SlippageExceeded"),
+                Self::Reentrancy => write!(f, "Reentrancy"),
+                Self::TokenTransferFailed => write!(f, "TokenTransferFailed"),
+                Self::Overflow => write!(f, "Overflow")
+            }
+        }
+    }
+
+    /// AmmPair result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl AmmPair {
+
+        /// Creates an empty pair for the Entropy contract at `token`, charging `fee_bps` basis points per swap.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `fee_bps` is 10000 or more.
+        #[ink(constructor)]
+        pub fn new(token: AccountId, fee_bps: Balance) -> Self {
+            assert!(fee_bps < 10_000, "swap fee must be below 100%");
+            Self {
+                owner: Self::env().caller(),
+                token,
+                fee_bps,
+                reserve_token: 0,
+                reserve_native: 0,
+                total_shares: 0,
+                shares: StorageHashMap::new(),
+                locked: false
+            }
+        }
+
+        /// Returns the contract owner.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns the Entropy contract traded by the pair.
+        #[ink(message)]
+        pub fn token(&self) -> AccountId {
+            self.token
+        }
+
+        /// Returns the swap fee in basis points.
+        #[ink(message)]
+        pub fn fee_bps(&self) -> Balance {
+            self.fee_bps
+        }
+
+        /// Returns the Entropy and native reserves.
+        #[ink(message)]
+        pub fn reserves(&self) -> (Balance, Balance) {
+            (self.reserve_token, self.reserve_native)
+        }
+
+        /// Returns the total liquidity shares.
+        #[ink(message)]
+        pub fn total_shares(&self) -> Balance {
+            self.total_shares
+        }
+
+        /// Returns the liquidity shares of `provider`.
+        #[ink(message)]
+        pub fn shares_of(&self, provider: AccountId) -> Balance {
+            self.shares.get(&provider).copied().unwrap_or(0)
+        }
+
+        /// Returns the Entropy paid out for swapping `native_in`, or 0 if it can't be computed.
+        #[ink(message)]
+        pub fn quote_token_out(&self, native_in: Balance) -> Balance {
+            self.amount_out(native_in, self.reserve_native, self.reserve_token).unwrap_or(0)
+        }
+
+        /// Returns the native currency paid out for swapping `token_in`, or 0 if it can't be computed.
+        #[ink(message)]
+        pub fn quote_native_out(&self, token_in: Balance) -> Balance {
+            self.amount_out(token_in, self.reserve_token, self.reserve_native).unwrap_or(0)
+        }
+
+        /// Set the swap fee in basis points
+        ///
+        /// On success a `FeeChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidFee` error if `fee_bps` is 10000 or more.
+        #[ink(message)]
+        pub fn set_fee(&mut self, fee_bps: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::PermissionDenied)
+                });
+                return Err(Error::PermissionDenied);
+            }
+            if fee_bps >= 10_000 {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InvalidFee)
+                });
+                return Err(Error::InvalidFee);
+            }
+
+            self.fee_bps = fee_bps;
+            self.env().emit_event(FeeChanged {
+                fee_bps
+            });
+
+            Ok(())
+        }
+
+        /// Deposits the transferred native currency and up to `max_token` of the caller's Entropy at the
+        /// current reserve ratio, minting liquidity shares to the caller
+        ///
+        /// Entropy is pulled with `transfer_from`, so the caller must have approved the pair. The first
+        /// deposit sets the price and mints `MINIMUM_SHARES` of its shares to the zero account. If Entropy
+        /// charges a transfer fee, shares are minted for what the pair actually received.
+        ///
+        /// On success a `LiquidityAdded` event is emitted and the minted shares are returned. On failure
+        /// the transferred native currency and any pulled Entropy are refunded.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if no native currency is transferred or `max_token` is zero.
+        ///
+        /// Returns `InsufficientLiquidity` error if the first deposit mints no more than `MINIMUM_SHARES`.
+        ///
+        /// Returns `SlippageExceeded` error if the deposit needs more than `max_token` or mints fewer than
+        /// `min_shares` shares.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the pull.
+        ///
+        /// Returns `Reentrancy` error if called while another message is executing.
+        ///
+        /// Returns `Overflow` error if the amounts are too large.
+        #[ink(message, payable)]
+        pub fn add_liquidity(&mut self, max_token: Balance, min_shares: Balance) -> Result<Balance> {
+            let provider = self.env().caller();
+            let native = self.env().transferred_balance();
+            if self.locked {
+                return Err(self.reject(Error::Reentrancy, provider, 0, native));
+            }
+
+            self.locked = true;
+            let result = self.deposit(provider, native, max_token, min_shares);
+            self.locked = false;
+            result
+        }
+
+        /// Burns `shares` of the caller's liquidity shares, paying out their portion of both reserves
+        ///
+        /// Payouts round down in favour of the remaining providers.
+        ///
+        /// On success a `LiquidityRemoved` event is emitted and the Entropy and native amounts paid out
+        /// are returned.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if `shares` is zero.
+        ///
+        /// Returns `InsufficientShares` error if the caller holds fewer than `shares`.
+        ///
+        /// Returns `SlippageExceeded` error if the payouts are below `min_token` or `min_native`.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the payout.
+        ///
+        /// Returns `Reentrancy` error if called while another message is executing.
+        ///
+        /// Returns `Overflow` error if the amounts are too large.
+        ///
+        /// # Panics
+        ///
+        /// Panics, reverting the call, if the native payout fails.
+        #[ink(message)]
+        pub fn remove_liquidity(&mut self, shares: Balance, min_token: Balance, min_native: Balance) -> Result<(Balance, Balance)> {
+            let provider = self.env().caller();
+            if self.locked {
+                return Err(self.reject(Error::Reentrancy, provider, 0, 0));
+            }
+
+            self.locked = true;
+            let result = self.withdraw(provider, shares, min_token, min_native);
+            self.locked = false;
+            result
+        }
+
+        /// Swaps the transferred native currency for at least `min_token_out` Entropy
+        ///
+        /// `min_token_out` is compared with the amount the pair sends, before any Entropy transfer fee.
+        ///
+        /// On success a `Swapped` event is emitted and the Entropy paid out is returned. On failure the
+        /// transferred native currency is refunded.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if no native currency is transferred.
+        ///
+        /// Returns `InsufficientLiquidity` error if the reserves are empty.
+        ///
+        /// Returns `SlippageExceeded` error if the payout is zero or below `min_token_out`.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the payout.
+        ///
+        /// Returns `Reentrancy` error if called while another message is executing.
+        ///
+        /// Returns `Overflow` error if the amounts are too large.
+        #[ink(message, payable)]
+        pub fn swap_native_for_token(&mut self, min_token_out: Balance) -> Result<Balance> {
+            let trader = self.env().caller();
+            let native_in = self.env().transferred_balance();
+            if self.locked {
+                return Err(self.reject(Error::Reentrancy, trader, 0, native_in));
+            }
+
+            self.locked = true;
+            let result = self.buy_token(trader, native_in, min_token_out);
+            self.locked = false;
+            result
+        }
+
+        /// Swaps `token_in` of the caller's Entropy for at least `min_native_out` native currency
+        ///
+        /// Entropy is pulled with `transfer_from`, so the caller must have approved the pair. If Entropy
+        /// charges a transfer fee, the payout is computed from what the pair actually received.
+        ///
+        /// On success a `Swapped` event is emitted and the native currency paid out is returned. On
+        /// failure any pulled Entropy is refunded.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if `token_in` is zero.
+        ///
+        /// Returns `InsufficientLiquidity` error if the reserves are empty.
+        ///
+        /// Returns `SlippageExceeded` error if the payout is zero or below `min_native_out`.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the pull.
+        ///
+        /// Returns `Reentrancy` error if called while another message is executing.
+        ///
+        /// Returns `Overflow` error if the amounts are too large.
+        ///
+        /// # Panics
+        ///
+        /// Panics, reverting the call, if the native payout fails.
+        #[ink(message)]
+        pub fn swap_token_for_native(&mut self, token_in: Balance, min_native_out: Balance) -> Result<Balance> {
+            let trader = self.env().caller();
+            if self.locked {
+                return Err(self.reject(Error::Reentrancy, trader, 0, 0));
+            }
+
+            self.locked = true;
+            let result = self.sell_token(trader, token_in, min_native_out);
+            self.locked = false;
+            result
+        }
+
+        fn deposit(&mut self, provider: AccountId, native: Balance, max_token: Balance, min_shares: Balance) -> Result<Balance> {
+            if native == 0 || max_token == 0 {
+                return Err(self.reject(Error::ZeroAmount, provider, 0, native));
+            }
+
+            // Entropy needed at the current ratio, rounded up in favour of the existing providers
+            let token_required = if self.total_shares == 0 {
+                max_token
+            } else {
+                match mul_div_up(native, self.reserve_token, self.reserve_native) {
+                    Some(token_required) => token_required,
+                    None => return Err(self.reject(Error::Overflow, provider, 0, native))
+                }
+            };
+            if token_required > max_token {
+                return Err(self.reject(Error::SlippageExceeded, provider, 0, native));
+            }
+
+            let received = self.pull(provider, token_required).map_err(|error| self.reject(error, provider, 0, native))?;
+
+            let minted = if self.total_shares == 0 {
+                match received.checked_mul(native).map(isqrt) {
+                    Some(shares) if shares > MINIMUM_SHARES => shares - MINIMUM_SHARES,
+                    Some(_) => return Err(self.reject(Error::InsufficientLiquidity, provider, received, native)),
+                    None => return Err(self.reject(Error::Overflow, provider, received, native))
+                }
+            } else {
+                match (mul_div(native, self.total_shares, self.reserve_native), mul_div(received, self.total_shares, self.reserve_token)) {
+                    (Some(by_native), Some(by_token)) => by_native.min(by_token),
+                    _ => return Err(self.reject(Error::Overflow, provider, received, native))
+                }
+            };
+            if minted == 0 || minted < min_shares {
+                return Err(self.reject(Error::SlippageExceeded, provider, received, native));
+            }
+
+            if self.total_shares == 0 {
+                self.shares.insert(AccountId::default(), MINIMUM_SHARES);
+                self.total_shares = MINIMUM_SHARES;
+            }
+            self.total_shares += minted;
+            let balance = self.shares_of(provider);
+            self.shares.insert(provider, balance + minted);
+            self.reserve_token += received;
+            self.reserve_native += native;
+
+            self.env().emit_event(LiquidityAdded {
+                provider,
+                token_amount: received,
+                native_amount: native,
+                shares: minted
+            });
+
+            Ok(minted)
+        }
+
+        fn withdraw(&mut self, provider: AccountId, shares: Balance, min_token: Balance, min_native: Balance) -> Result<(Balance, Balance)> {
+            if shares == 0 {
+                return Err(self.reject(Error::ZeroAmount, provider, 0, 0));
+            }
+            let balance = self.shares_of(provider);
+            if balance < shares {
+                return Err(self.reject(Error::InsufficientShares, provider, 0, 0));
+            }
+
+            let (token_out, native_out) = match (mul_div(shares, self.reserve_token, self.total_shares), mul_div(shares, self.reserve_native, self.total_shares)) {
+                (Some(token_out), Some(native_out)) => (token_out, native_out),
+                _ => return Err(self.reject(Error::Overflow, provider, 0, 0))
+            };
+            if token_out < min_token || native_out < min_native {
+                return Err(self.reject(Error::SlippageExceeded, provider, 0, 0));
+            }
+
+            // Update the reserves before paying out, the lock guards against reentrant calls in between
+            self.shares.insert(provider, balance - shares);
+            self.total_shares -= shares;
+            self.reserve_token -= token_out;
+            self.reserve_native -= native_out;
+
+            if token_out > 0 && !token_transfer(self.token, provider, token_out) {
+                self.shares.insert(provider, balance);
+                self.total_shares += shares;
+                self.reserve_token += token_out;
+                self.reserve_native += native_out;
+                return Err(self.reject(Error::TokenTransferFailed, provider, 0, 0));
+            }
+            self.pay_native(provider, native_out);
+
+            self.env().emit_event(LiquidityRemoved {
+                provider,
+                token_amount: token_out,
+                native_amount: native_out,
+                shares
+            });
+
+            Ok((token_out, native_out))
+        }
+
+        fn buy_token(&mut self, trader: AccountId, native_in: Balance, min_token_out: Balance) -> Result<Balance> {
+            if native_in == 0 {
+                return Err(self.reject(Error::ZeroAmount, trader, 0, native_in));
+            }
+            if self.total_shares == 0 {
+                return Err(self.reject(Error::InsufficientLiquidity, trader, 0, native_in));
+            }
+
+            let token_out = match self.amount_out(native_in, self.reserve_native, self.reserve_token) {
+                Some(token_out) => token_out,
+                None => return Err(self.reject(Error::Overflow, trader, 0, native_in))
+            };
+            if token_out == 0 || token_out < min_token_out {
+                return Err(self.reject(Error::SlippageExceeded, trader, 0, native_in));
+            }
+
+            self.reserve_native += native_in;
+            self.reserve_token -= token_out;
+
+            if !token_transfer(self.token, trader, token_out) {
+                self.reserve_native -= native_in;
+                self.reserve_token += token_out;
+                return Err(self.reject(Error::TokenTransferFailed, trader, 0, native_in));
+            }
+
+            self.env().emit_event(Swapped {
+                trader,
+                token_in: 0,
+                native_in,
+                token_out,
+                native_out: 0
+            });
+
+            Ok(token_out)
+        }
+
+        fn sell_token(&mut self, trader: AccountId, token_in: Balance, min_native_out: Balance) -> Result<Balance> {
+            if token_in == 0 {
+                return Err(self.reject(Error::ZeroAmount, trader, 0, 0));
+            }
+            if self.total_shares == 0 {
+                return Err(self.reject(Error::InsufficientLiquidity, trader, 0, 0));
+            }
+
+            // Check the quote first, so a swap bound to fail doesn't pull and refund
+            match self.amount_out(token_in, self.reserve_token, self.reserve_native) {
+                Some(native_out) if native_out > 0 && native_out >= min_native_out => (),
+                Some(_) => return Err(self.reject(Error::SlippageExceeded, trader, 0, 0)),
+                None => return Err(self.reject(Error::Overflow, trader, 0, 0))
+            }
+
+            let received = self.pull(trader, token_in).map_err(|error| self.reject(error, trader, 0, 0))?;
+            let native_out = match self.amount_out(received, self.reserve_token, self.reserve_native) {
+                Some(native_out) if native_out > 0 && native_out >= min_native_out => native_out,
+                Some(_) => return Err(self.reject(Error::SlippageExceeded, trader, received, 0)),
+                None => return Err(self.reject(Error::Overflow, trader, received, 0))
+            };
+
+            self.reserve_token += received;
+            self.reserve_native -= native_out;
+            self.pay_native(trader, native_out);
+
+            self.env().emit_event(Swapped {
+                trader,
+                token_in: received,
+                native_in: 0,
+                token_out: 0,
+                native_out
+            });
+
+            Ok(native_out)
+        }
+
+        /// Returns the output for `amount_in` after the swap fee, rounded down so the product of the
+        /// reserves never decreases.
+        fn amount_out(&self, amount_in: Balance, reserve_in: Balance, reserve_out: Balance) -> Option<Balance> {
+            if reserve_in == 0 || reserve_out == 0 {
+                return Some(0);
+            }
+            let amount_in_with_fee = amount_in.checked_mul(10_000 - self.fee_bps)?;
+            let numerator = amount_in_with_fee.checked_mul(reserve_out)?;
+            let denominator = reserve_in.checked_mul(10_000)?.checked_add(amount_in_with_fee)?;
+            Some(numerator / denominator)
+        }
+
+        /// Pulls `value` of `from`'s Entropy to the pair, returning the amount actually received.
+        fn pull(&self, from: AccountId, value: Balance) -> Result<Balance> {
+            let pair = self.env().account_id();
+            let balance = token_balance_of(self.token, pair);
+            if !token_transfer_from(self.token, from, pair, value) {
+                return Err(Error::TokenTransferFailed);
+            }
+            Ok(token_balance_of(self.token, pair).saturating_sub(balance))
+        }
+
+        /// Sends native currency from the pair.
+        ///
+        /// Payouts never exceed the tracked reserves, so a failure means the pair's balance is broken and
+        /// the call is reverted by panicking.
+        fn pay_native(&self, to: AccountId, value: Balance) {
+            if value > 0 && self.env().transfer(to, value).is_err() {
+                panic!("native transfer failed");
+            }
+        }
+
+        /// Emits a `TransactionFailed` event for `error` and refunds what the pair received from `to`
+        /// during the failed call.
+        ///
+        /// An Entropy refund may itself fail, e.g. if `to` was blacklisted meanwhile, leaving the tokens
+        /// in the pair outside the reserves.
+        fn reject(&self, error: Error, to: AccountId, token: Balance, native: Balance) -> Error {
+            self.env().emit_event(TransactionFailed {
+                error: format!("{:?}", error)
+            });
+            if token > 0 {
+                token_transfer(self.token, to, token);
+            }
+            self.pay_native(to, native);
+            error
+        }
+    }
+
+    /// Returns `a * b / c` rounded down, or `None` on overflow.
+    fn mul_div(a: Balance, b: Balance, c: Balance) -> Option<Balance> {
+        a.checked_mul(b)?.checked_div(c)
+    }
+
+    /// Returns `a * b / c` rounded up, or `None` on overflow.
+    fn mul_div_up(a: Balance, b: Balance, c: Balance) -> Option<Balance> {
+        let product = a.checked_mul(b)?;
+        let quotient = product.checked_div(c)?;
+        Some(if product % c > 0 { quotient + 1 } else { quotient })
+    }
+
+    /// Returns the integer square root of `n`, rounded down.
+    fn isqrt(n: Balance) -> Balance {
+        if n < 2 {
+            return n;
+        }
+        let mut x = n;
+        let mut y = n / 2 + n % 2;
+        while y < x {
+            x = y;
+            y = (x + n / x) / 2;
+        }
+        x
+    }
+
+    /// Pulls `value` of `from`'s tokens to `to` through Entropy's `transfer_from`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer_from(from, to, value).is_ok()
+    }
+
+    /// Transfers the pair's own tokens through Entropy's `transfer`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer(to, value, None).is_ok()
+    }
+
+    /// Returns the Entropy balance of `account`.
+    #[cfg(not(test))]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        use ink_env::call::FromAccountId;
+
+        let token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.balance_of(account)
+    }
+
+    // Off-chain stand-ins for the cross-contract calls, as ink's test environment can't execute other
+    // contracts. They operate on the ledger of `tests::MockToken`.
+
+    #[cfg(test)]
+    fn token_transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+        tests::MockToken::transfer(token, from, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        let from = ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account");
+        tests::MockToken::transfer(token, from, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        tests::MockToken::balance_of(token, account)
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+        use std::{
+            cell::RefCell,
+            collections::HashMap,
+        };
+
+        type Event = <AmmPair as ::ink_lang::BaseEvent>::Type;
+
+        thread_local! {
+            /// Balances of `MockToken` and its transfer fee in basis points
+            static LEDGER: RefCell<(HashMap<AccountId, Balance>, Balance)> = RefCell::new(Default::default());
+        }
+
+        /// Stand-in for the Entropy contract, burning its transfer fee from the amount received.
+        pub(super) struct MockToken;
+
+        impl MockToken {
+            fn fund(account: AccountId, balance: Balance) {
+                LEDGER.with(|ledger| ledger.borrow_mut().0.insert(account, balance));
+            }
+
+            fn set_fee(fee_bps: Balance) {
+                LEDGER.with(|ledger| ledger.borrow_mut().1 = fee_bps);
+            }
+
+            pub(super) fn balance_of(token: AccountId, account: AccountId) -> Balance {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| ledger.borrow().0.get(&account).copied().unwrap_or(0))
+            }
+
+            pub(super) fn transfer(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| {
+                    let mut ledger = ledger.borrow_mut();
+                    let balance = ledger.0.get(&from).copied().unwrap_or(0);
+                    if balance < value {
+                        return false;
+                    }
+                    let fee = value * ledger.1 / 10_000;
+                    ledger.0.insert(from, balance - value);
+                    *ledger.0.entry(to).or_insert(0) += value - fee;
+                    true
+                })
+            }
+        }
+
+        fn token_account() -> AccountId {
+            AccountId::from([0x10; 32])
+        }
+
+        fn pair_account() -> AccountId {
+            ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account")
+        }
+
+        /// Creates a pair charging 0.3% per swap, clearing the ledger and native balances left over by
+        /// previous tests.
+        fn new_pair() -> AmmPair {
+            LEDGER.with(|ledger| *ledger.borrow_mut() = Default::default());
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            for account in &[pair_account(), accounts.bob, accounts.charlie] {
+                let balance = if *account == pair_account() { 0 } else { 10_000_000 };
+                ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(*account, balance).expect("Cannot set balance");
+            }
+            AmmPair::new(token_account(), 30)
+        }
+
+        fn native_balance(account: AccountId) -> Balance {
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(account).unwrap_or(0)
+        }
+
+        /// Calls the pair as `caller` transferring `value` native currency, which the off-chain
+        /// environment doesn't move by itself.
+        fn set_caller(caller: AccountId, value: Balance) {
+            let callee = pair_account();
+            let caller_balance = native_balance(caller);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(caller, caller_balance - value).expect("Cannot set balance");
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(callee, native_balance(callee) + value).expect("Cannot set balance");
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(caller, callee, 1000000, value, data);
+        }
+
+        /// Checks that the reserves match the pair's balances and returns their product.
+        fn checked_k(pair: &AmmPair) -> Balance {
+            let (reserve_token, reserve_native) = pair.reserves();
+            assert_eq!(MockToken::balance_of(token_account(), pair_account()), reserve_token);
+            assert_eq!(native_balance(pair_account()), reserve_native);
+            reserve_token * reserve_native
+        }
+
+        fn assert_swapped_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_trader: AccountId,
+            expected_native_in: Balance,
+            expected_token_out: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Swapped(Swapped { trader, token_in, native_in, token_out, native_out }) = decoded_event {
+                assert_eq!(trader, expected_trader, "encountered invalid Swapped.trader");
+                assert_eq!(token_in, 0, "encountered invalid Swapped.token_in");
+                assert_eq!(native_in, expected_native_in, "encountered invalid Swapped.native_in");
+                assert_eq!(token_out, expected_token_out, "encountered invalid Swapped.token_out");
+                assert_eq!(native_out, 0, "encountered invalid Swapped.native_out");
+            } else {
+                panic!("encountered unexpected event kind: expected a Swapped event")
+            }
+        }
+
+        #[ink::test]
+        fn liquidity_works() {
+            let mut pair = new_pair();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 10_000_000);
+            MockToken::fund(accounts.charlie, 10_000_000);
+
+            // The first deposit sets the price, locking the minimum shares
+            set_caller(accounts.bob, 1_000_000);
+            assert_eq!(pair.add_liquidity(4_000_000, 0), Ok(2_000_000 - MINIMUM_SHARES));
+            assert_eq!(pair.reserves(), (4_000_000, 1_000_000));
+            assert_eq!(pair.total_shares(), 2_000_000);
+            assert_eq!(pair.shares_of(AccountId::default()), MINIMUM_SHARES);
+
+            // Later deposits take Entropy at the current ratio, rounded up
+            set_caller(accounts.charlie, 333);
+            assert_eq!(pair.add_liquidity(1_331, 0), Err(Error::SlippageExceeded));
+            assert_eq!(native_balance(accounts.charlie), 10_000_000);
+            set_caller(accounts.charlie, 333);
+            assert_eq!(pair.add_liquidity(1_332, 667), Err(Error::SlippageExceeded));
+            assert_eq!(MockToken::balance_of(token_account(), accounts.charlie), 10_000_000);
+            set_caller(accounts.charlie, 333);
+            assert_eq!(pair.add_liquidity(2_000, 666), Ok(666));
+            assert_eq!(MockToken::balance_of(token_account(), accounts.charlie), 10_000_000 - 1_332);
+            assert_eq!(pair.reserves(), (4_001_332, 1_000_333));
+            checked_k(&pair);
+
+            // Removing rounds down in favour of the remaining providers
+            assert_eq!(pair.remove_liquidity(667, 0, 0), Err(Error::InsufficientShares));
+            assert_eq!(pair.remove_liquidity(1, 0, 1), Err(Error::SlippageExceeded));
+            assert_eq!(pair.remove_liquidity(1, 0, 0), Ok((2, 0)));
+            assert_eq!(pair.remove_liquidity(665, 0, 333), Err(Error::SlippageExceeded));
+            assert_eq!(pair.remove_liquidity(665, 0, 332), Ok((1_330, 332)));
+            assert_eq!(pair.shares_of(accounts.charlie), 0);
+            assert_eq!(pair.reserves(), (4_000_000, 1_000_001));
+            checked_k(&pair);
+
+            set_caller(accounts.bob, 0);
+            assert_eq!(pair.remove_liquidity(2_000_000 - MINIMUM_SHARES, 0, 0), Ok((3_998_000, 999_500)));
+            assert_eq!(pair.reserves(), (2_000, 501));
+            assert_eq!(pair.total_shares(), MINIMUM_SHARES);
+            checked_k(&pair);
+        }
+
+        #[ink::test]
+        fn swap_works() {
+            let mut pair = new_pair();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 10_000_000);
+            MockToken::fund(accounts.charlie, 10_000_000);
+
+            set_caller(accounts.charlie, 0);
+            assert_eq!(pair.swap_token_for_native(100, 0), Err(Error::InsufficientLiquidity));
+            set_caller(accounts.bob, 1_000_000);
+            assert_eq!(pair.add_liquidity(1_000_000, 0), Ok(1_000_000 - MINIMUM_SHARES));
+
+            // 0.3% of 10_000 is kept, the rest trades at 1_000_000 * 9_970 / 1_009_970
+            assert_eq!(pair.quote_token_out(10_000), 9_871);
+            set_caller(accounts.charlie, 10_000);
+            assert_eq!(pair.swap_native_for_token(9_872), Err(Error::SlippageExceeded));
+            assert_eq!(native_balance(accounts.charlie), 10_000_000);
+            set_caller(accounts.charlie, 10_000);
+            assert_eq!(pair.swap_native_for_token(9_871), Ok(9_871));
+            assert_eq!(MockToken::balance_of(token_account(), accounts.charlie), 10_009_871);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_swapped_event(&emitted_events[emitted_events.len() - 1], accounts.charlie, 10_000, 9_871);
+
+            // Swapping back returns less than swapped in
+            let quote = pair.quote_native_out(9_871);
+            assert_eq!(pair.swap_token_for_native(9_871, quote + 1), Err(Error::SlippageExceeded));
+            assert_eq!(pair.swap_token_for_native(9_871, quote), Ok(quote));
+            assert!(quote < 10_000);
+            assert_eq!(native_balance(accounts.charlie), 10_000_000 - 10_000 + quote);
+
+            // Outputs rounding to zero are rejected
+            set_caller(accounts.charlie, 1);
+            assert_eq!(pair.swap_native_for_token(0), Err(Error::SlippageExceeded));
+            assert_eq!(pair.swap_token_for_native(1, 0), Err(Error::SlippageExceeded));
+            assert_eq!(pair.swap_token_for_native(0, 0), Err(Error::ZeroAmount));
+            checked_k(&pair);
+        }
+
+        #[ink::test]
+        fn invariant_holds_across_swaps_and_liquidity() {
+            let mut pair = new_pair();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 100_000_000);
+            MockToken::fund(accounts.charlie, 100_000_000);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(accounts.bob, 100_000_000).expect("Cannot set balance");
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(accounts.charlie, 100_000_000).expect("Cannot set balance");
+
+            set_caller(accounts.bob, 3_000_000);
+            assert!(pair.add_liquidity(7_000_000, 0).is_ok());
+            let mut k = checked_k(&pair);
+            let mut shares = pair.total_shares();
+
+            for i in 1..=30u128 {
+                let amount = i * i * 997 % 500_000 + 1;
+                let k_before = k;
+                match i % 5 {
+                    0 => {
+                        set_caller(accounts.charlie, amount);
+                        assert!(pair.add_liquidity(amount * 10, 0).is_ok());
+                    }
+                    1 => {
+                        set_caller(accounts.bob, 0);
+                        assert!(pair.remove_liquidity(amount / 3 + 1, 0, 0).is_ok());
+                    }
+                    2 | 3 => {
+                        set_caller(accounts.charlie, amount);
+                        assert!(pair.swap_native_for_token(0).is_ok());
+                    }
+                    _ => {
+                        set_caller(accounts.bob, 0);
+                        assert!(pair.swap_token_for_native(amount, 0).is_ok());
+                    }
+                }
+                k = checked_k(&pair);
+                let total_shares = pair.total_shares();
+                if total_shares == shares {
+                    // Swaps never decrease the product of the reserves
+                    assert!(k >= k_before, "k decreased in step {}", i);
+                } else {
+                    // Liquidity changes never decrease the product per squared share
+                    assert!(k * shares * shares >= k_before * total_shares * total_shares, "k per share decreased in step {}", i);
+                }
+                shares = total_shares;
+            }
+        }
+
+        #[ink::test]
+        fn token_transfer_fee_is_accounted() {
+            let mut pair = new_pair();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 10_000_000);
+            MockToken::set_fee(100);
+
+            // 1% of the pulled Entropy is lost to the transfer fee
+            set_caller(accounts.bob, 1_000_000);
+            assert!(pair.add_liquidity(1_000_000, 0).is_ok());
+            assert_eq!(pair.reserves(), (990_000, 1_000_000));
+
+            set_caller(accounts.bob, 0);
+            let quote = pair.quote_native_out(9_900);
+            assert_eq!(pair.swap_token_for_native(10_000, 0), Ok(quote));
+            checked_k(&pair);
+
+            // Only the owner sets the swap fee
+            assert_eq!(pair.set_fee(0), Err(Error::PermissionDenied));
+            set_caller(accounts.alice, 0);
+            assert_eq!(pair.set_fee(10_000), Err(Error::InvalidFee));
+            assert_eq!(pair.set_fee(0), Ok(()));
+            assert_eq!(pair.fee_bps(), 0);
+        }
+    }
+}
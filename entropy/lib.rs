@@ -1,23 +1,852 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+// Only the contract's call forwarders are compiled when used as a dependency, leaving helpers unused.
+#![cfg_attr(feature = "ink-as-dependency", allow(unused_imports, dead_code))]
 
 use ink_lang as ink;
 
-#[ink::contract]
+pub use self::entropy::{
+    Entropy,
+    Error,
+};
+
+/// Environment the contract is compiled against.
+///
+/// Defaults to ink!'s `DefaultEnvironment`; the `custom-environment` feature selects `CustomEnvironment`.
+#[cfg(not(feature = "custom-environment"))]
+pub type EntropyEnvironment = ink_env::DefaultEnvironment;
+
+/// Environment the contract is compiled against, selected by the `custom-environment` feature.
+#[cfg(feature = "custom-environment")]
+pub type EntropyEnvironment = CustomEnvironment;
+
+/// Environment of chains whose runtime uses a `u64` balance.
+///
+/// The contract only relies on the bounds of `ink_env::Environment` plus `AccountId: Default` for
+/// the zero account, so deploying on another runtime is a matter of adjusting these types.
+#[cfg(feature = "custom-environment")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum CustomEnvironment {}
+
+#[cfg(feature = "custom-environment")]
+impl ink_env::Environment for CustomEnvironment {
+    const MAX_EVENT_TOPICS: usize = 4;
+
+    type AccountId = ink_env::AccountId;
+    type Balance = u64;
+    type Hash = ink_env::Hash;
+    type Timestamp = u64;
+    type BlockNumber = u32;
+    type ChainExtension = ink_env::NoChainExtension;
+}
+
+// Entropy relies on a runtime chain extension for what ink! 3.0.0-rc3 has no host function for. The
+// runtime it is deployed on must register a `pallet_contracts` chain extension serving the function
+// ids below, each taking and returning SCALE-encoded values. The status code is ignored, a failed
+// check is reported through the output.
+
+/// Function id of the chain extension verifying sr25519 signatures.
+///
+/// Takes `(signer: AccountId, message_hash: [u8; 32], signature: [u8; 64])` and returns `bool`, as
+/// `sp_io::crypto::sr25519_verify` would for `signature` over `message_hash` by `signer`'s public key.
+pub const SR25519_VERIFY_FUNC_ID: u32 = 0x0101;
+
+/// Function id of the chain extension looking up contract code hashes.
+///
+/// Takes an `AccountId` and returns `Option<Hash>`, the hash of the code the contract at the account
+/// runs, `None` if the account is not a contract.
+pub const CODE_HASH_FUNC_ID: u32 = 0x0102;
+
+/// Function id of the chain extension recovering the Ethereum address of ECDSA signers.
+///
+/// Takes `(signature: EcdsaSignature, message_hash: [u8; 32])` and returns `Option<[u8; 20]>`, the
+/// last 20 bytes of the Keccak-256 hash of the public key `sp_io::crypto::secp256k1_ecdsa_recover`
+/// recovers, `None` if recovery fails.
+pub const ECDSA_RECOVER_FUNC_ID: u32 = 0x0103;
+
+/// Function id of the chain extension replacing the calling contract's code.
+///
+/// Takes a `Hash` and returns `bool`, whether the calling contract now runs the code uploaded under
+/// it, its storage kept. Returns `false` if no code was uploaded under the hash.
+pub const SET_CODE_HASH_FUNC_ID: u32 = 0x0104;
+
+/// ERC-20 subset of the Entropy messages, for other contracts to call Entropy through.
+///
+/// `Entropy` implements it on top of its own messages, so a caller holding an `entropy::Entropy`
+/// built with `FromAccountId` can move tokens without depending on Entropy's extensions.
+#[ink::trait_definition]
+pub trait Erc20 {
+    /// Returns the account balance of `owner`, `0` if it is private to the caller.
+    #[ink(message)]
+    fn balance_of(&self, owner: ink_env::AccountId) -> <EntropyEnvironment as ink_env::Environment>::Balance;
+
+    /// Returns the amount which `spender` is still allowed to withdraw from `owner`.
+    #[ink(message)]
+    fn allowance(&self, owner: ink_env::AccountId, spender: ink_env::AccountId) -> <EntropyEnvironment as ink_env::Environment>::Balance;
+
+    /// Transfers `value` tokens from the caller's account to `to`, with `extra` data as `Entropy::transfer`.
+    ///
+    /// Takes the same arguments as `Entropy::transfer`, which calls made through a dependency's
+    /// `entropy::Entropy` resolve to.
+    #[ink(message)]
+    fn transfer(&mut self, to: ink_env::AccountId, value: <EntropyEnvironment as ink_env::Environment>::Balance, extra: Option<ink_prelude::string::String>) -> Result<(), Error>;
+
+    /// Transfers `value` tokens from `from` to `to` on the caller's allowance.
+    #[ink(message)]
+    fn transfer_from(&mut self, from: ink_env::AccountId, to: ink_env::AccountId, value: <EntropyEnvironment as ink_env::Environment>::Balance) -> Result<(), Error>;
+
+    /// Allows `spender` to withdraw from the caller's account up to `value` tokens.
+    #[ink(message)]
+    fn approve(&mut self, spender: ink_env::AccountId, value: <EntropyEnvironment as ink_env::Environment>::Balance) -> Result<(), Error>;
+}
+
+/// Prints a debug message formatted like `format!`, compiled in with the `debug-logs` feature only.
+#[cfg(feature = "debug-logs")]
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        ink_env::debug_println(&ink_prelude::format!($($arg)*))
+    };
+}
+
+/// Without the `debug-logs` feature a debug message compiles to nothing. Its arguments are still
+/// type checked, but never formatted.
+#[cfg(not(feature = "debug-logs"))]
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = ink_prelude::format!($($arg)*);
+        }
+    };
+}
+
+#[ink::contract(env = crate::EntropyEnvironment)]
 mod entropy {
-    use core::fmt;
+    use core::{
+        convert::TryFrom,
+        fmt,
+    };
 
     use ink_env as env;
 
+    use crate::{
+        Erc20,
+        CODE_HASH_FUNC_ID,
+        ECDSA_RECOVER_FUNC_ID,
+        SET_CODE_HASH_FUNC_ID,
+        SR25519_VERIFY_FUNC_ID,
+    };
+
     use ink_prelude::{
-        format,
-        string::String
+        string::String,
+        vec,
+        vec::Vec
     };
 
     use ink_storage::{
-        collections::HashMap as StorageHashMap,
+        collections::{
+            HashMap as StorageHashMap,
+            Vec as StorageVec,
+        },
         lazy::Lazy,
+        traits::{
+            PackedLayout,
+            SpreadLayout,
+        },
     };
 
+    /// Version of the contract logic, recorded in storage by the constructors.
+    const CONTRACT_VERSION: u32 = 1;
+
+    /// Number of most recent transfers kept for reversal.
+    const MAX_RECENT_TRANSFERS: u64 = 1024;
+
+    /// Maximum number of ownership records kept, the oldest being dropped first.
+    const MAX_OWNERSHIP_HISTORY: usize = 64;
+
+    /// Maximum number of balance changes kept per account, the oldest being dropped first.
+    const MAX_BALANCE_HISTORY: usize = 64;
+
+    /// Default number of accounts on the top holders leaderboard.
+    const DEFAULT_TOP_HOLDERS: u32 = 20;
+
+    /// Maximum number of accounts on the top holders leaderboard.
+    const MAX_TOP_HOLDERS: u32 = 100;
+
+    /// Maximum number of accounts a batch operation may process.
+    const MAX_BATCH_SIZE: usize = 100;
+
+    /// Maximum length in bytes of a transfer memo.
+    const MAX_MEMO_LEN: usize = 64;
+
+    /// Default dispute window within which a transfer can be reversed, 24 hours.
+    const DEFAULT_DISPUTE_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+    /// Default time after funding during which a distribution can be claimed, 30 days.
+    const DEFAULT_DISTRIBUTION_WINDOW_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+    /// Default time a pending appeal blocks destroying the appellant's funds, 14 days.
+    const DEFAULT_APPEAL_TIMEOUT_MS: u64 = 14 * 24 * 60 * 60 * 1000;
+
+    /// Maximum number of recent receipts tracked per account for the minimum holding period.
+    const MAX_HELD_RECEIPTS: usize = 16;
+
+    /// Window over which a self-imposed spending limit applies, 24 hours.
+    #[cfg(not(test))]
+    const SPENDING_LIMIT_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+    /// Delay before raising or removing a self-imposed spending limit takes effect, 24 hours.
+    #[cfg(not(test))]
+    const SPENDING_LIMIT_DELAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    /// Window over which an owner-imposed transfer limit applies, 24 hours.
+    #[cfg(not(test))]
+    const TRANSFER_LIMIT_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+    // Off-chain test blocks are 5 milliseconds apart, so a day can't be waited out.
+    #[cfg(test)]
+    const SPENDING_LIMIT_WINDOW_MS: u64 = 10;
+    #[cfg(test)]
+    const SPENDING_LIMIT_DELAY_MS: u64 = 20;
+    #[cfg(test)]
+    const TRANSFER_LIMIT_WINDOW_MS: u64 = 10;
+
+    /// Tag of the hashes signed for permits, separating them from other signed payloads.
+    const PERMIT_DOMAIN: &[u8] = b"entropy/permit";
+
+    /// Tag of the hashes signed to link an Ethereum address, separating them from other signed payloads.
+    const LINK_ETH_DOMAIN: &[u8] = b"entropy/link-eth";
+
+    /// Record of a transfer executed by the contract, kept for reversal.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct TransferRecord {
+        pub from: AccountId,
+        pub to: AccountId,
+        /// Amount credited to `to`, i.e. the transferred value minus fee
+        pub net: Balance,
+        pub fee: Balance,
+        pub block: BlockNumber,
+        pub timestamp: Timestamp,
+        pub reversed: bool,
+    }
+
+    /// Record of an account becoming the contract owner, kept for audits.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct OwnerRecord {
+        pub owner: AccountId,
+        /// Block from which `owner` controlled the contract
+        pub from_block: BlockNumber,
+    }
+
+    /// Rounding applied to the basis-point fee computation.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub enum FeeRounding {
+        /// Rounds toward zero, so small transfers may pay no fee
+        Down,
+        /// Rounds away from zero, so any positive transfer pays at least 1 unit while the rate is set
+        Up,
+        /// Rounds to the nearest unit, halves away from zero
+        HalfUp,
+    }
+
+    /// Rounds up by default, so splitting a transfer into many small legs can't evade the fee.
+    impl Default for FeeRounding {
+        fn default() -> Self {
+            Self::Up
+        }
+    }
+
+    /// Who set an account's privacy.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub enum PrivacySource {
+        /// The account itself, or the default privacy policy
+        User,
+        /// The owner, whose choice to make an account private the account can't undo
+        Owner,
+    }
+
+    /// Boolean flags of an account, packed so screening a transfer reads a single entry per account.
+    ///
+    /// Details behind a flag, such as a blacklist entry or a freeze timestamp, are kept in their own
+    /// maps and only read when the flag is set.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct AccountFlags {
+        pub private: bool,
+        pub blacklisted: bool,
+        /// Whether a freeze is recorded, which may have expired since
+        pub frozen: bool,
+        pub fee_exempt: bool,
+    }
+
+    /// Contract level transaction fee params.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FeeParams {
+        /// Fee rate in basis points of the transferred value
+        pub basis_points_rate: Balance,
+        /// Maximum fee per transaction
+        pub maximum_fee: Balance,
+        pub rounding: FeeRounding,
+    }
+
+    /// Deployment options of `construct_with_config`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractConfig {
+        /// If false, only the owner and pre-launch allowlisted accounts can send tokens until the
+        /// owner calls `enable_trading`
+        pub trading_enabled: bool,
+        /// If true, charging a fee emits a `Transfer` event to the fee collector besides the
+        /// `FeeCharged` event, for indexers that only follow `Transfer` events
+        pub fee_transfer_events: bool,
+        /// Cap on the total supply for the contract's lifetime, `None` leaves it uncapped
+        pub max_supply: Option<Balance>,
+        /// If true, every failing call emits a `TransactionFailed` event besides returning its error,
+        /// for indexers that track failures
+        pub verbose_errors: bool,
+        /// If true, `approve` refuses to change a nonzero allowance to another nonzero value until it
+        /// is set to zero, see `set_strict_approvals`
+        pub strict_approvals: bool,
+    }
+
+    impl Default for ContractConfig {
+        fn default() -> Self {
+            Self {
+                trading_enabled: true,
+                fee_transfer_events: false,
+                max_supply: None,
+                verbose_errors: false,
+                strict_approvals: false
+            }
+        }
+    }
+
+    /// Transfer escrowed until its recipient claims it or its timeout passes.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct PendingTransfer {
+        pub from: AccountId,
+        pub to: AccountId,
+        /// Amount escrowed, the fee is deducted from it when claimed
+        pub value: Balance,
+        /// Timestamp after which the transfer can no longer be claimed and is refundable
+        pub expiry: Timestamp,
+    }
+
+    /// Headline token metrics.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct GlobalStats {
+        /// Number of executed transfers
+        pub total_transfers: u64,
+        /// Value moved by executed transfers, fees included
+        pub total_volume: Balance,
+        /// Transfer fees collected by the fee collector
+        pub total_fees_collected: Balance,
+        /// Tokens redeemed by the owner
+        pub total_burned: Balance,
+        /// Tokens destroyed on blacklisted accounts
+        pub total_destroyed_black_funds: Balance,
+        /// Number of accounts holding a non-zero balance
+        pub holder_count: u64,
+        /// Total token supply, only filled in by `global_stats`
+        pub current_supply: Balance,
+    }
+
+    /// Compliance and activity details of an account.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AccountInfo {
+        pub balance: Balance,
+        /// Lifetime sent token volume
+        pub sent: Balance,
+        /// Lifetime received token volume
+        pub received: Balance,
+        pub private: bool,
+        pub blacklisted: bool,
+        /// Timestamp until which the account is frozen, if it is
+        pub frozen_until: Option<Timestamp>,
+    }
+
+    /// Token metadata, ownership, fee params and status in one record.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractInfo {
+        pub name: String,
+        pub symbol: String,
+        pub decimals: u32,
+        pub total_supply: Balance,
+        pub owner: AccountId,
+        /// Fee rate in basis points of the transferred value
+        pub basis_points_rate: Balance,
+        /// Maximum fee per transaction
+        pub maximum_fee: Balance,
+        /// Whether token transfers are halted
+        pub paused: bool,
+        /// Version of the contract logic that constructed the storage
+        pub version: u32,
+    }
+
+    /// Balance and compliance flags of an account, as returned by `account_status`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct AccountStatus {
+        /// Balance as returned by `balance_of`, `0` if hidden from the caller
+        pub balance: Balance,
+        pub private: bool,
+        pub blacklisted: bool,
+        pub fee_exempt: bool,
+        pub frozen: bool,
+    }
+
+    /// Finalized Merkle root over the non-zero balances at a snapshot.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct BalancesRoot {
+        /// Root over blake2 leaves of `(account, balance)`, with sorted-pair nodes
+        pub root: [u8; 32],
+        /// Block at which the balances were taken
+        pub block: BlockNumber,
+        /// Snapshot the balances were read from
+        pub snapshot_id: u32,
+    }
+
+    /// Balances root commitment in progress.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Commitment {
+        /// Snapshot the balances are read from
+        pub snapshot_id: u32,
+        /// Block at which the commitment began
+        pub block: BlockNumber,
+        /// Index in the holders index of the next account to commit
+        pub next: u32,
+        /// Length of the holders index when the commitment began
+        pub end: u32,
+        /// Roots of the completed subtrees not merged yet, by height
+        pub frontier: Vec<Option<[u8; 32]>>,
+    }
+
+    /// Part of an account's balance locked until a timestamp, by its holder or by the sender of a
+    /// locked transfer.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct SelfLock {
+        pub amount: Balance,
+        /// Timestamp after which the lock has expired
+        pub until: Timestamp,
+    }
+
+    /// Amount a spender may withdraw from an owner's account, and until when.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct AllowanceEntry {
+        /// Amount left to withdraw
+        pub value: Balance,
+        /// Timestamp after which the allowance counts as zero, or `None` if it never expires
+        pub expires_at: Option<Timestamp>,
+    }
+
+    /// Why and since when an account is blacklisted.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct BlacklistEntry {
+        /// Timestamp the account was first blacklisted at, starting its quarantine
+        pub since: Timestamp,
+        /// Reason given when the account was last blacklisted
+        pub reason: String,
+    }
+
+    /// Outcome of a blacklisted account's appeal.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub enum AppealStatus {
+        /// Awaiting the owner's decision
+        Pending,
+        /// The account was removed from blacklist and its bond refunded
+        Upheld,
+        /// The account stays blacklisted and its bond was forfeited
+        Rejected,
+    }
+
+    /// Appeal lodged by a blacklisted account against its blacklisting.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Appeal {
+        /// Hash of the account's statement, published off-chain
+        pub statement_hash: Hash,
+        /// Native currency deposited with the appeal
+        pub bond: Balance,
+        pub lodged_at: Timestamp,
+        pub status: AppealStatus,
+    }
+
+    /// Announced sweep of balances below a threshold, executable once its notice has passed.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct DustSweep {
+        /// Balances strictly below this are swept
+        pub threshold: Balance,
+        /// Accounts whose balance changed at or after this timestamp are skipped
+        pub announced_at: Timestamp,
+        /// Timestamp after which the sweep can be executed
+        pub execute_after: Timestamp,
+        /// Whether swept balances are burned rather than moved to the owner
+        pub burn: bool,
+    }
+
+    /// Immutable record of an owner redemption, referencing the fiat payout it corresponds to.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct RedemptionReceipt {
+        pub amount: Balance,
+        /// Account the tokens were burned from
+        pub account: AccountId,
+        /// Block the redemption happened in
+        pub block: BlockNumber,
+        /// Hash of the off-chain payout reference, as supplied by the owner
+        pub payout_reference_hash: Hash,
+    }
+
+    /// Spending limit an account imposed on itself, with a raise or removal waiting for its delay.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct SpendingLimit {
+        /// Maximum amount sent per window, `0` if there is no limit
+        pub amount_per_day: Balance,
+        /// Limit taking over once the timestamp has passed, `0` removing it
+        pub pending: Option<(Balance, Timestamp)>,
+    }
+
+    /// Cap on the total value all transfers may move within a time window.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct ThroughputCap {
+        /// Maximum value moved per window, `0` disables the cap
+        pub max_value_per_window: Balance,
+        /// Window length in milliseconds
+        pub window_ms: u64,
+    }
+
+    /// Tokens escrowed for whoever presents the preimage of a code hash.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Gift {
+        pub creator: AccountId,
+        pub amount: Balance,
+        /// Timestamp after which the gift can no longer be redeemed and the creator can reclaim it
+        pub expiry: Timestamp,
+        /// Whether the gift was redeemed or reclaimed
+        pub spent: bool,
+    }
+
+    /// Part of a payer's balance earmarked for a recipient, who may capture it until it expires.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Hold {
+        pub payer: AccountId,
+        /// Account that may capture the hold
+        pub to: AccountId,
+        pub value: Balance,
+        /// Timestamp after which the hold can no longer be captured and the payer can release it
+        pub expires_at: Timestamp,
+    }
+
+    /// Velocity rule limiting how many outgoing transfers an account may make within a time window,
+    /// and how much of its balance they may move.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct VelocityRule {
+        /// Maximum number of outgoing transfers per window, `0` disables the rule
+        pub max_outflows_per_window: u32,
+        /// Window length in milliseconds, also used as the duration of an automatic freeze
+        pub window_ms: u64,
+        /// Share in basis points of the balance held when the window opened that the window's
+        /// transfers must move beyond for the rule to trip, `0` to trip on the count alone
+        pub max_share_bps: Balance,
+    }
+
+    /// Outgoing transfers of an account within its current velocity window.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct OutflowWindow {
+        /// Timestamp the window opened at
+        pub start: Timestamp,
+        /// Number of outgoing transfers within the window
+        pub count: u32,
+        /// Tokens sent within the window
+        pub sent: Balance,
+        /// Balance of the account when the window opened
+        pub opening_balance: Balance,
+    }
+
+    /// Transfer-mining program rewarding senders of fee-paying transfers with points.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct PointsProgram {
+        /// Whether fee-paying transfers accrue points
+        pub active: bool,
+        /// A transfer of `value` accrues `value / divisor` points to its sender, `0` accrues none
+        pub divisor: Balance,
+        /// Whether claimed tokens are minted, or else paid from the contract's own balance
+        pub mint: bool,
+        /// Maximum number of points converted into tokens per epoch, across all accounts
+        pub epoch_cap: Balance,
+        /// Epoch length in milliseconds
+        pub epoch_ms: u64,
+    }
+
+    /// Tokens distributed pro rata to the holders at a snapshot.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Distribution {
+        /// Tokens escrowed for the distribution
+        pub total: Balance,
+        /// Tokens claimed so far
+        pub claimed: Balance,
+        /// Timestamp after which claims close and the unclaimed remainder can be swept
+        pub deadline: Timestamp,
+        /// Whether the unclaimed remainder was swept back to the owner
+        pub swept: bool,
+    }
+
+    /// Tokens escrowed for accounts to claim with a Merkle proof of their amount.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Airdrop {
+        /// Root over blake2 leaves of `(account, amount)`, with sorted-pair nodes
+        pub root: [u8; 32],
+        /// Tokens escrowed for the airdrop
+        pub total: Balance,
+        /// Tokens claimed so far
+        pub claimed: Balance,
+        /// Timestamp after which claims close and the unclaimed remainder can be swept
+        pub expiry: Timestamp,
+        /// Whether the unclaimed remainder was swept back to the owner
+        pub closed: bool,
+    }
+
+    /// Tokens escrowed for a beneficiary and unlocked linearly over time.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct VestingSchedule {
+        /// Tokens escrowed for the schedule
+        pub total: Balance,
+        /// Tokens claimed so far
+        pub claimed: Balance,
+        /// Timestamp the schedule starts unlocking from
+        pub start: Timestamp,
+        /// Time in milliseconds after `start` at which all tokens are unlocked
+        pub duration_ms: u64,
+        /// Time in milliseconds after `start` before which nothing is unlocked
+        pub cliff_ms: u64,
+    }
+
+    /// Side of a balance change screened by `ensure_compliant`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Direction {
+        /// Tokens leave the account
+        Debit,
+        /// Tokens arrive in the account
+        Credit,
+    }
+
+    /// Returns `a * b / c` rounded down, computed in 128 bits so 64-bit balances can't overflow.
+    ///
+    /// `b` must not exceed `c`, so the result fits in `Balance`.
+    #[allow(clippy::useless_conversion)] // `Balance` is `u128` unless `custom-environment` is selected
+    fn mul_div(a: Balance, b: Balance, c: Balance) -> Balance {
+        let product = u128::from(a).saturating_mul(u128::from(b));
+        Balance::try_from(product / u128::from(c)).unwrap_or(a)
+    }
+
+    /// Returns whether `signature` is a valid sr25519 signature of `message_hash` by `signer`,
+    /// as reported by the runtime's chain extension.
+    #[cfg(not(test))]
+    fn sr25519_verify(signer: AccountId, message_hash: [u8; 32], signature: [u8; 64]) -> bool {
+        env::chain_extension::ChainExtensionMethod::build(SR25519_VERIFY_FUNC_ID)
+            .input::<(AccountId, [u8; 32], [u8; 64])>()
+            .output::<bool>()
+            .ignore_error_code()
+            .call(&(signer, message_hash, signature))
+    }
+
+    /// Off-chain stand-in for the chain extension, as ink's test environment has no runtime serving it.
+    /// Accepts signatures made with `tests::mock_sign`.
+    #[cfg(test)]
+    fn sr25519_verify(signer: AccountId, message_hash: [u8; 32], signature: [u8; 64]) -> bool {
+        tests::mock_sr25519_verify(signer, message_hash, signature)
+    }
+
+    /// Returns the code hash of `account` if it is a contract, as reported by the runtime's chain extension.
+    #[cfg(not(test))]
+    fn code_hash_of(account: AccountId) -> Option<Hash> {
+        env::chain_extension::ChainExtensionMethod::build(CODE_HASH_FUNC_ID)
+            .input::<AccountId>()
+            .output::<Option<Hash>>()
+            .ignore_error_code()
+            .call(&account)
+    }
+
+    /// Off-chain stand-in for the chain extension, reporting the code hashes registered with
+    /// `tests::mock_contract`.
+    #[cfg(test)]
+    fn code_hash_of(account: AccountId) -> Option<Hash> {
+        tests::mock_code_hash(account)
+    }
+
+    /// Replaces the code of this contract with the code uploaded under `code_hash`, keeping its storage,
+    /// through the runtime's chain extension. Returns whether the code was replaced.
+    #[cfg(not(test))]
+    fn set_code_hash(code_hash: Hash) -> bool {
+        env::chain_extension::ChainExtensionMethod::build(SET_CODE_HASH_FUNC_ID)
+            .input::<Hash>()
+            .output::<bool>()
+            .ignore_error_code()
+            .call(&code_hash)
+    }
+
+    /// Off-chain stand-in for the chain extension, replacing the code if it was uploaded with
+    /// `tests::mock_upload`.
+    #[cfg(test)]
+    fn set_code_hash(code_hash: Hash) -> bool {
+        tests::mock_set_code_hash(code_hash)
+    }
+
+    /// Selector of the `on_entropy_received(from: AccountId, value: Balance, data: Vec<u8>) -> bool`
+    /// message `transfer_and_call` notifies contract recipients through, returning whether they accept.
+    #[cfg_attr(test, allow(dead_code))]
+    pub const ON_ENTROPY_RECEIVED_SELECTOR: [u8; 4] = [0x01, 0x00, 0xF9, 0x8C];
+
+    /// Calls `on_entropy_received` on `receiver`, returning whether it accepted the tokens. A failing call
+    /// counts as a rejection.
+    #[cfg(not(test))]
+    fn notify_received(receiver: AccountId, from: AccountId, value: Balance, data: Vec<u8>) -> bool {
+        use env::call::{
+            build_call,
+            utils::ReturnType,
+            ExecutionInput,
+            Selector,
+        };
+
+        build_call::<crate::EntropyEnvironment>()
+            .callee(receiver)
+            .gas_limit(0)
+            .exec_input(
+                ExecutionInput::new(Selector::new(ON_ENTROPY_RECEIVED_SELECTOR))
+                    .push_arg(from)
+                    .push_arg(value)
+                    .push_arg(data)
+            )
+            .returns::<ReturnType<bool>>()
+            .fire()
+            .unwrap_or(false)
+    }
+
+    /// Off-chain stand-in for the cross-contract call, as ink's test environment can't execute other
+    /// contracts. Answers as registered with `tests::mock_receiver`.
+    #[cfg(test)]
+    fn notify_received(receiver: AccountId, from: AccountId, value: Balance, data: Vec<u8>) -> bool {
+        tests::mock_notify_received(receiver, from, value, data)
+    }
+
+    /// Ends the call returning `error`, reverting all state changes the call made.
+    #[cfg(not(test))]
+    fn revert_with(error: Error) -> ! {
+        env::return_value::<Result<()>>(env::ReturnFlags::default().set_reverted(true), &Err(error))
+    }
+
+    /// Off-chain stand-in, as ink's test environment can't revert and `return_value` would end the test
+    /// binary: panics with `error` instead.
+    #[cfg(test)]
+    fn revert_with(error: Error) -> ! {
+        panic!("reverted with {:?}", error)
+    }
+
+    /// Selector of the standard `PSP22::transfer(to: AccountId, value: Balance, data: Vec<u8>)` message
+    /// `rescue_token` calls on foreign tokens.
+    #[cfg_attr(test, allow(dead_code))]
+    pub const PSP22_TRANSFER_SELECTOR: [u8; 4] = [0xDB, 0x20, 0xF9, 0xF5];
+
+    /// Calls `PSP22::transfer` on `token` to send `value` of the tokens the contract holds to `to`,
+    /// returning whether the token accepted it. A failing call counts as a refusal.
+    #[cfg(not(test))]
+    fn transfer_foreign_token(token: AccountId, to: AccountId, value: Balance) -> bool {
+        use env::call::{
+            build_call,
+            utils::ReturnType,
+            ExecutionInput,
+            Selector,
+        };
+
+        build_call::<crate::EntropyEnvironment>()
+            .callee(token)
+            .gas_limit(0)
+            .exec_input(
+                ExecutionInput::new(Selector::new(PSP22_TRANSFER_SELECTOR))
+                    .push_arg(to)
+                    .push_arg(value)
+                    .push_arg(Vec::<u8>::new())
+            )
+            .returns::<ReturnType<core::result::Result<(), PSP22Error>>>()
+            .fire()
+            .map_or(false, |result| result.is_ok())
+    }
+
+    /// Off-chain stand-in for the cross-contract call, moving balances of tokens registered with
+    /// `tests::mock_token`.
+    #[cfg(test)]
+    fn transfer_foreign_token(token: AccountId, to: AccountId, value: Balance) -> bool {
+        tests::mock_token_transfer(token, to, value)
+    }
+
+    /// Recoverable secp256k1 signature as produced by Ethereum wallets, encoded as its 65 bytes.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct EcdsaSignature {
+        /// The `r` and `s` values
+        pub rs: [u8; 64],
+        /// The recovery id `v`
+        pub v: u8,
+    }
+
+    /// Returns the Ethereum address of the secp256k1 key that made `signature` over `message_hash`, or
+    /// `None` if no key can be recovered, as reported by the runtime's chain extension.
+    #[cfg(not(test))]
+    fn ecdsa_recover_eth_address(signature: EcdsaSignature, message_hash: [u8; 32]) -> Option<[u8; 20]> {
+        env::chain_extension::ChainExtensionMethod::build(ECDSA_RECOVER_FUNC_ID)
+            .input::<(EcdsaSignature, [u8; 32])>()
+            .output::<Option<[u8; 20]>>()
+            .ignore_error_code()
+            .call(&(signature, message_hash))
+    }
+
+    /// Off-chain stand-in for the chain extension, recovering signatures made with `tests::mock_ecdsa_sign`.
+    #[cfg(test)]
+    fn ecdsa_recover_eth_address(signature: EcdsaSignature, message_hash: [u8; 32]) -> Option<[u8; 20]> {
+        tests::mock_ecdsa_recover(signature, message_hash)
+    }
+
+    /// Administrative operation the owner can sign off-chain and have executed by any relayer.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum AdminOp {
+        /// Add an account to blacklist for a reason
+        AddToBlackList(AccountId, String),
+        /// Remove an account from blacklist
+        RemoveFromBlackList(AccountId),
+        /// Freeze an account until the given timestamp
+        FreezeUntil(AccountId, Timestamp),
+        /// Unfreeze an account
+        Unfreeze(AccountId),
+        /// Set whether an account is private or not
+        SetPrivate(AccountId, bool),
+    }
+
     /// Defines the storage of your contract.
     /// Add new fields to the below struct in order
     /// to add new static storage fields to your contract.
@@ -28,8 +857,8 @@ mod entropy {
         decimals: u32,
 
         /// Additional params for use if contract level transaction fees ever became necessary
-        basis_points_rate: u128, // e.g: '5' means 0.0005 rate
-        maximum_fee: u128,  // e.g: '50_000_000' means maximal 50 ENT fee per trasaction
+        basis_points_rate: Balance, // e.g: '5' means 0.0005 rate
+        maximum_fee: Balance,  // e.g: '50_000_000' means maximal 50 ENT fee per trasaction
 
         owner: AccountId,
 
@@ -37,17 +866,293 @@ mod entropy {
         total_supply: Lazy<Balance>,
 
         /// Mapping from owner to number of owned token.
+        ///
+        /// Like the other storage maps, entries are loaded by key on first access, so reading one
+        /// balance doesn't pull in the others.
         balances: StorageHashMap<AccountId, Balance>,
 
         /// Mapping of the token amount which an account is allowed to withdraw
-        /// from another account.
-        allowances: StorageHashMap<(AccountId, AccountId), Balance>,
+        /// from another account, and until when.
+        allowances: StorageHashMap<(AccountId, AccountId), AllowanceEntry>,
+
+        /// Mapping of an account to its flags, absent while all of them are off
+        account_flags: StorageHashMap<AccountId, AccountFlags>,
+
+        /// Mapping of who last set an account's privacy
+        privacy_sources: StorageHashMap<AccountId, PrivacySource>,
+
+        /// Mapping of a blacklisted account to its blacklist entry
+        accounts_blacklisted: StorageHashMap<AccountId, BlacklistEntry>,
+
+        /// Currently blacklisted accounts, in no particular order
+        blacklisted: StorageVec<AccountId>,
+
+        /// Mapping of a blacklisted account to its position in `blacklisted`
+        blacklisted_index: StorageHashMap<AccountId, u32>,
+
+        /// Mapping of the timestamp until which an account is frozen
+        frozen_until: StorageHashMap<AccountId, Timestamp>,
+
+        /// Velocity rule applied to outgoing transfers
+        velocity_rule: VelocityRule,
+
+        /// Mapping of an account's outgoing transfers within its current velocity window
+        outflows: StorageHashMap<AccountId, OutflowWindow>,
+
+        /// Mapping of whether an account is exempt from the velocity rule
+        velocity_exempt: StorageHashMap<AccountId, bool>,
+
+        /// Id of the last executed transfer, `0` if there is none
+        last_tx_id: u64,
+
+        /// Mapping of the most recent transfer ids to their records
+        recent_transfers: StorageHashMap<u64, TransferRecord>,
+
+        /// Time in milliseconds after a transfer during which it can be reversed
+        dispute_window_ms: u64,
+
+        /// Whether transfers are open to everyone
+        trading_enabled: bool,
+
+        /// Mapping of whether an account may send tokens before trading is enabled
+        prelaunch_allowlist: StorageHashMap<AccountId, bool>,
+
+        /// Nonce the next signed admin operation must carry
+        admin_nonce: u64,
+
+        /// Mapping of an account's lifetime sent and received token volume
+        volumes: StorageHashMap<AccountId, (Balance, Balance)>,
+
+        /// Whether token movement is halted: transfers, issuance, redemption and destruction
+        paused: bool,
+
+        /// Largest fraction of total supply a single transfer may move, in basis points, `0` disables the breaker
+        circuit_breaker_bps: Balance,
+
+        /// Mapping of whether an account is a system account, exempt from the circuit breaker
+        system_accounts: StorageHashMap<AccountId, bool>,
+
+        /// Mapping of whether an account is protected from compliance actions
+        protected_accounts: StorageHashMap<AccountId, bool>,
+
+        /// Transfer-mining program configuration
+        points_program: PointsProgram,
+
+        /// Mapping of an account's unclaimed reward points, not part of the token supply
+        reward_points: StorageHashMap<AccountId, Balance>,
+
+        /// Tokens paid per claimed point, `0` if points can't be claimed
+        points_rate: Balance,
+
+        /// Start of the current conversion epoch and the number of points converted within it
+        points_epoch: (Timestamp, Balance),
+
+        /// Mapping of the nonce an account's next signed transfer must carry
+        nonces: StorageHashMap<AccountId, u64>,
+
+        /// Id of the last snapshot taken, `0` if there is none
+        snapshot_id: u32,
+
+        /// Mapping of an account and a snapshot id to the account's balance as of the snapshot,
+        /// recorded on its first change after the snapshot
+        balance_snapshots: StorageHashMap<(AccountId, u32), Balance>,
+
+        /// Mapping of a snapshot id to the total supply when it was taken
+        supply_snapshots: StorageHashMap<u32, Balance>,
+
+        /// Mapping of a snapshot id to the distribution to its holders
+        distributions: StorageHashMap<u32, Distribution>,
+
+        /// Mapping of whether an account claimed its share of a snapshot's distribution
+        distribution_claims: StorageHashMap<(u32, AccountId), bool>,
+
+        /// Time in milliseconds after funding during which a distribution can be claimed
+        distribution_window_ms: u64,
+
+        /// Mapping of a beneficiary to its vesting schedule
+        vesting_schedules: StorageHashMap<AccountId, VestingSchedule>,
+
+        /// Id of the last airdrop created, `0` if there is none
+        airdrop_count: u32,
+
+        /// Mapping of an airdrop id to the airdrop
+        airdrops: StorageHashMap<u32, Airdrop>,
 
-        /// Mapping of whether an account is private
-        accounts_private: StorageHashMap<AccountId, bool>,
+        /// Mapping of whether an account claimed its amount of an airdrop
+        airdrop_claims: StorageHashMap<(u32, AccountId), bool>,
 
-        /// Mapping of whether an account is blacklisted
-        accounts_blacklisted: StorageHashMap<AccountId, bool>
+        /// Tokens held in the contract's account for distributions, vesting schedules, airdrops, claimable transfers and gifts, not available to pay points claims
+        escrowed: Balance,
+
+        /// Mapping of an account's fee rebate, in basis points of the transfer fees it pays
+        fee_rebates: StorageHashMap<AccountId, Balance>,
+
+        /// Mapping of an account's accrued and unclaimed fee rebate
+        accrued_rebates: StorageHashMap<AccountId, Balance>,
+
+        /// Most recent owners in the order they took over, at most `MAX_OWNERSHIP_HISTORY`
+        ownership_history: Lazy<Vec<OwnerRecord>>,
+
+        /// Rounding applied to the fee computation
+        fee_rounding: FeeRounding,
+
+        /// Mapping of the token amount which an account is allowed to withdraw from another account
+        /// for transfers to a specific recipient only.
+        recipient_allowances: StorageHashMap<(AccountId, AccountId, AccountId), Balance>,
+
+        /// Id of the last claimable transfer, `0` if there is none
+        last_pending_id: u64,
+
+        /// Mapping of the pending claimable transfers by id
+        pending_transfers: StorageHashMap<u64, PendingTransfer>,
+
+        /// Headline token metrics, kept up to date at every mutation
+        stats: GlobalStats,
+
+        /// Mapping of whether an account is an auditor, allowed to read private accounts' data
+        auditors: StorageHashMap<AccountId, bool>,
+
+        /// Every account that ever held a balance, in order of first appearance, less accounts swept as dust
+        holders: StorageVec<AccountId>,
+
+        /// Accounts currently holding a nonzero balance, in no particular order
+        current_holders: StorageVec<AccountId>,
+
+        /// Mapping of a current holder to its position in `current_holders`
+        current_holders_index: StorageHashMap<AccountId, u32>,
+
+        /// Balances root commitment in progress, if any
+        pending_commitment: Option<Commitment>,
+
+        /// Number of finalized balances roots
+        balances_root_count: u32,
+
+        /// Mapping of the finalized balances roots by sequence number
+        balances_roots: StorageHashMap<u32, BalancesRoot>,
+
+        /// Whether accounts receiving tokens for the first time are made private
+        default_private: bool,
+
+        /// Mapping of whether contracts with a code hash are banned from calling the token
+        banned_code_hashes: StorageHashMap<Hash, bool>,
+
+        /// Largest holders sorted by descending balance, at most `top_holders_limit`
+        top_holders: Lazy<Vec<(AccountId, Balance)>>,
+
+        /// Number of accounts on the top holders leaderboard
+        top_holders_limit: u32,
+
+        /// Smallest balance on the leaderboard once it is full, `0` while it isn't
+        top_holders_floor: Balance,
+
+        /// Mapping of an account's self-locks, expired ones are pruned when it locks again
+        self_locks: StorageHashMap<AccountId, Vec<SelfLock>>,
+
+        /// Mapping of the Blake2x256 hash of a gift code to its gift, kept once spent
+        gifts: StorageHashMap<Hash, Gift>,
+
+        /// Id of the last hold created, `0` if there is none
+        hold_count: u64,
+
+        /// Mapping of a hold id to its hold, removed once captured or released
+        holds: StorageHashMap<u64, Hold>,
+
+        /// Mapping of an account to the part of its balance earmarked by its holds
+        on_hold: StorageHashMap<AccountId, Balance>,
+
+        /// Timestamp at which a timed pause lifts by itself, `None` if the pause is indefinite
+        paused_until: Option<Timestamp>,
+
+        /// How long an account must stay blacklisted before its funds can be destroyed, in milliseconds
+        destruction_delay_ms: Timestamp,
+
+
+        /// Mapping of an account to its latest appeal against its blacklisting
+        appeals: StorageHashMap<AccountId, Appeal>,
+
+        /// Native currency an appeal must deposit
+        appeal_bond: Balance,
+
+        /// How long a pending appeal blocks destroying the appellant's funds, in milliseconds
+        appeal_timeout_ms: Timestamp,
+
+        /// Mapping of an account to the blocks and timestamps its balance changed at and the balance from then on
+        balance_history: StorageHashMap<AccountId, Vec<(BlockNumber, Timestamp, Balance)>>,
+
+        /// Number of times each error was returned, indexed by error code
+        failure_counts: Lazy<Vec<u64>>,
+
+        /// Whether `approve` refuses to change a nonzero allowance to another nonzero value
+        strict_approvals: bool,
+
+        /// Mapping of an account to the Ethereum address whose key may sign permits for it
+        eth_addresses: StorageHashMap<AccountId, [u8; 20]>,
+
+        /// Mapping of a linked Ethereum address back to its account
+        eth_accounts: StorageHashMap<[u8; 20], AccountId>,
+
+        /// Announced dust sweep, cleared once executed through the whole holders index
+        dust_sweep: Option<DustSweep>,
+
+        /// Number of redemption receipts issued, the id of the latest one
+        redemption_receipt_count: u64,
+
+        /// Mapping of a receipt id, starting at `1`, to its redemption receipt
+        redemption_receipts: StorageHashMap<u64, RedemptionReceipt>,
+
+        /// How long received tokens stay unspendable, in milliseconds, `0` disables the holding period
+        min_holding_period_ms: Timestamp,
+
+        /// Mapping of an account to the timestamps and amounts of its recent receipts, oldest first
+        held_receipts: StorageHashMap<AccountId, Vec<(Timestamp, Balance)>>,
+
+        /// Mapping of an account to the spending limit it imposed on itself
+        spending_limits: StorageHashMap<AccountId, SpendingLimit>,
+
+        /// Mapping of an account's current spending window start and amount sent within it
+        spending_windows: StorageHashMap<AccountId, (Timestamp, Balance)>,
+
+        /// Transfer limit the owner imposes on accounts without their own, `0` if there is none
+        default_transfer_limit: Balance,
+
+        /// Mapping of an account to the transfer limit the owner imposed on it, `0` if it is unlimited
+        transfer_limits: StorageHashMap<AccountId, Balance>,
+
+        /// Mapping of an account's current transfer limit window start and amount sent within it
+        transfer_windows: StorageHashMap<AccountId, (Timestamp, Balance)>,
+
+        /// Contract-wide cap on the value transfers move per window
+        throughput_cap: ThroughputCap,
+
+        /// Current throughput window start and value moved within it
+        throughput_window: (Timestamp, Balance),
+
+        /// Contract trusted to call the `*_for` messages on behalf of the senders it authenticated
+        trusted_forwarder: Option<AccountId>,
+
+        /// Mapping of an account to the balance `transfer_keep_alive` and `transfer_all` leave on it
+        keep_alive_minimums: StorageHashMap<AccountId, Balance>,
+
+        /// Whether `transfer_and_call` is notifying a contract recipient, rejecting calls back into the contract
+        notifying_receiver: bool,
+
+        /// Account proposed as the next owner, pending its acceptance
+        pending_owner: Option<AccountId>,
+
+        /// Account credited with transfer fees and paying out fee rebates
+        fee_collector: AccountId,
+
+        /// Whether charging a fee also emits a `Transfer` event to the fee collector
+        fee_transfer_events: bool,
+
+        /// Hard cap on the total supply, fixed at construction, `None` if uncapped
+        max_supply: Option<Balance>,
+
+        /// Whether failing calls also emit a `TransactionFailed` event, fixed at construction
+        verbose_errors: bool,
+
+        /// Version of the contract logic that constructed the storage
+        version: u32
     }
 
     
@@ -55,9 +1160,9 @@ mod entropy {
     #[ink(event)]
     pub struct Params {
         #[ink(topic)]
-        basis_points_rate: u128,
+        basis_points_rate: Balance,
         #[ink(topic)]
-        maximum_fee: u128
+        maximum_fee: Balance
     }
 
     /// Event emitted when a token transfer occurs.
@@ -71,6 +1176,21 @@ mod entropy {
         value: Balance,
     }
 
+    /// Event emitted after the `Transfer` event of a `transfer_with_memo`, so that recipients such
+    /// as exchanges can match deposits by reference.
+    #[ink(event)]
+    pub struct TransferWithMemo {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        /// Blake2x256 hash of the memo
+        #[ink(topic)]
+        memo_hash: Hash,
+        memo: Vec<u8>
+    }
+
     /// Event emitted when an approval occurs that `spender` is allowed to withdraw
     /// up to the amount of `value` tokens from `owner`.
     #[ink(event)]
@@ -94,7 +1214,10 @@ mod entropy {
     #[ink(event)]
     pub struct Redeem {
         #[ink(topic)]
-        amount: Balance
+        amount: Balance,
+        /// Receipt issued by an owner `redeem`, `None` for other burns
+        #[ink(topic)]
+        receipt_id: Option<u64>
     }
 
     /// Event emitted when an account's privacy is updated
@@ -110,7 +1233,54 @@ mod entropy {
     #[ink(event)]
     pub struct AddedBlackList {
         #[ink(topic)]
-        account: AccountId
+        account: AccountId,
+        /// Blake2x256 hash of the reason the account is blacklisted for
+        #[ink(topic)]
+        reason_hash: Hash,
+        /// Earliest timestamp the account's funds can be destroyed at
+        destroyable_at: Timestamp
+    }
+
+    /// Event emitted when the quarantine before destroying blacklisted funds is updated
+    #[ink(event)]
+    pub struct DestructionDelayChanged {
+        delay_ms: Timestamp
+    }
+
+    /// Event emitted when the appeal bond or timeout is updated
+    #[ink(event)]
+    pub struct AppealTermsChanged {
+        bond: Balance,
+        timeout_ms: Timestamp
+    }
+
+    /// Event emitted when a blacklisted account appeals
+    #[ink(event)]
+    pub struct AppealLodged {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        statement_hash: Hash,
+        bond: Balance
+    }
+
+    /// Event emitted when an appeal is upheld or rejected
+    #[ink(event)]
+    pub struct AppealResolved {
+        #[ink(topic)]
+        account: AccountId,
+        upheld: bool
+    }
+
+    /// Event emitted when the owner rescues native balance or foreign tokens sent to the contract
+    #[ink(event)]
+    pub struct Rescued {
+        /// Rescued token, `None` for the native balance
+        #[ink(topic)]
+        token: Option<AccountId>,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance
     }
 
     /// Event emitted when an account is removed from blacklist
@@ -129,811 +1299,8641 @@ mod entropy {
         funds: Balance
     }
 
-    /// Event emitted when error occurs
+    /// Event emitted when an account is frozen until a timestamp
     #[ink(event)]
-    pub struct TransactionFailed {
+    pub struct Frozen {
+        #[ink(topic)]
+        account: AccountId,
         #[ink(topic)]
-        error: String
+        until: Timestamp
     }
 
-    /// Entropy error types.
-    #[derive(Debug, PartialEq, Eq, scale::Encode)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum Error {
-        /// Returned if not privileged.
-        PermissionDenied,
-        /// Returned if not enough balance to fulfill a request is available.
-        InsufficientBalance,
-        /// Returned if not enough allowance to fulfill a request is available.
-        InsufficientAllowance,
-        /// Returned if trying to transfer funds from a blacklisted account
-        AccountBlackListed,
-        /// Returned if trying to destropy funds of an account which is not blacklisted
-        AccountNotBlackListed
+    /// Event emitted when an account is unfrozen
+    #[ink(event)]
+    pub struct Unfrozen {
+        #[ink(topic)]
+        account: AccountId
     }
 
-    impl fmt::Display for Error {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            match *self {
-                Self::PermissionDenied => write!(f, "PermissionDenied"),
-                Self::InsufficientBalance => write!(f, "InsufficientBalance"),
-                Self::InsufficientAllowance => write!(f, "InsufficientAllowance"),
-                Self::AccountBlackListed => write!(f, "AccountBlackListed"),
-                Self::AccountNotBlackListed => write!(f, "AccountNotBlackListed")
-            }
-        }
+    /// Event emitted when the velocity rule is set
+    #[ink(event)]
+    pub struct VelocityRuleChanged {
+        #[ink(topic)]
+        rule: VelocityRule
     }
 
-    /// Entropy result type.
-    pub type Result<T> = core::result::Result<T, Error>;
+    /// Event emitted when an account's velocity rule exemption is updated
+    #[ink(event)]
+    pub struct VelocityExemption {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        exempt: bool
+    }
 
-    impl Entropy {
+    /// Event emitted when an account is automatically frozen for tripping the velocity rule
+    #[ink(event)]
+    pub struct AutoFrozen {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        rule: VelocityRule
+    }
 
-        /// Creates a new Entropy contract with the specified initial supply, name, symbol and decimals.
-        #[ink(constructor)]
-        pub fn construct(initial_supply: Balance, name: String, symbol: String, decimals: u32) -> Self {
-            env::debug_println(&format!("Entropy: Construct with initial_supply: 0x{:x}, name: {}, symbol: {}, decimals: 0x{:x}", initial_supply, &name, &symbol, decimals));
+    /// Event emitted when the dispute window is set
+    #[ink(event)]
+    pub struct DisputeWindowChanged {
+        #[ink(topic)]
+        window_ms: u64
+    }
 
-            let caller = Self::env().caller();
-            let mut balances = StorageHashMap::new();
-            balances.insert(caller, initial_supply);
-            let instance = Self {
-                total_supply: Lazy::new(initial_supply),
-                name: name.clone(),
-                symbol: symbol.clone(),
-                basis_points_rate: 0,
-                maximum_fee: 0,
-                owner: caller,
-                decimals,
-                balances,
-                allowances: StorageHashMap::new(),
-                accounts_private: StorageHashMap::new(),
-                accounts_blacklisted: StorageHashMap::new()
-            };
-            Self::env().emit_event(Transfer {
-                from: None,
-                to: Some(caller),
-                value: initial_supply,
-            });
-            instance
-        }
+    /// Event emitted when a transfer is reversed
+    #[ink(event)]
+    pub struct TransferReversed {
+        #[ink(topic)]
+        tx_id: u64,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        reason: String
+    }
 
-        /// Creates a new Entropy contract with the specified initial supply and default name, symbol and decimals.
-        #[ink(constructor)]
-        pub fn new(initial_supply: Balance) -> Self {
-            Entropy::construct(initial_supply, "Entropy Coin".into(), "ENT".into(), 6)
-        }
+    /// Event emitted when an account's balance and flags are migrated to a new account
+    #[ink(event)]
+    pub struct AccountRekeyed {
+        #[ink(topic)]
+        old: AccountId,
+        #[ink(topic)]
+        new: AccountId
+    }
 
-        /// Creates a new Entropy contract with default initial supply, name, symbol and decimals.
-        #[ink(constructor)]
-        pub fn default() -> Self {
-            Entropy::construct(1_000_000_000_000, "Entropy Coin".into(), "ENT".into(), 6)
-        }
+    /// Event emitted when trading is enabled
+    #[ink(event)]
+    pub struct TradingEnabled {
+        #[ink(topic)]
+        block: BlockNumber
+    }
 
-        /// Returns the token name.
-        #[ink(message)]
-        pub fn name(&self) -> String {
-            self.name.clone()
-        }
+    /// Event emitted when an account's pre-launch allowlist entry is updated
+    #[ink(event)]
+    pub struct PrelaunchAllowlist {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        allowed: bool
+    }
 
-        /// Returns the token symbol.
-        #[ink(message)]
-        pub fn symbol(&self) -> String {
-            self.symbol.clone()
-        }
+    /// Event emitted when a signed admin operation is executed
+    #[ink(event)]
+    pub struct SignedAdminOpExecuted {
+        #[ink(topic)]
+        nonce: u64,
+        #[ink(topic)]
+        relayer: AccountId
+    }
 
-        /// Returns the token decimals.
-        #[ink(message)]
-        pub fn decimals(&self) -> u32 {
-            self.decimals
-        }
+    /// Event emitted when the contract is paused by the owner
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        by: AccountId,
+        until: Option<Timestamp>
+    }
 
-        /// Returns contract level transaction fee basic points rate (*/10000)
-        #[ink(message)]
-        pub fn basis_points_rate(&self) -> u128 {
-            self.basis_points_rate
-        }
+    /// Event emitted when the contract is unpaused
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        by: AccountId
+    }
 
-        /// Returns contract level maximum fee per transaction
-        #[ink(message)]
-        pub fn maximum_fee(&self) -> u128 {
-            self.maximum_fee
-        }
+    /// Event emitted when the circuit breaker threshold is updated
+    #[ink(event)]
+    pub struct CircuitBreakerChanged {
+        #[ink(topic)]
+        bps: Balance
+    }
 
-        /// Set contract level transaction fee params
-        #[ink(message)]
-        pub fn set_params(&mut self, new_basic_points: u128, new_max_fee: u128) -> Result<()> {
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when an abnormally large transfer trips the circuit breaker and pauses the contract
+    #[ink(event)]
+    pub struct CircuitBreakerTripped {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance
+    }
 
-            self.basis_points_rate = if new_basic_points > 20 { 20 } else { new_basic_points };
-            self.maximum_fee = if new_max_fee > 50_000_000 { 50_000_000 } else { new_max_fee };
+    /// Event emitted when an account is designated as or removed from system accounts
+    #[ink(event)]
+    pub struct SystemAccount {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        system: bool
+    }
 
-            self.env().emit_event(Params {
-                basis_points_rate: self.basis_points_rate,
-                maximum_fee: self.maximum_fee
-            });
+    /// Event emitted when an account's protection from compliance actions is updated
+    #[ink(event)]
+    pub struct AccountProtection {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        protected: bool
+    }
 
-            Ok(())
-        }
+    /// Event emitted when the transfer-mining program is configured
+    #[ink(event)]
+    pub struct PointsProgramChanged {
+        #[ink(topic)]
+        program: PointsProgram
+    }
 
-        /// Returns the contract owner.
-        #[ink(message)]
-        pub fn owner(&self) -> AccountId {
-            self.owner
-        }
+    /// Event emitted when the rate at which points convert into tokens is set
+    #[ink(event)]
+    pub struct PointsConversion {
+        #[ink(topic)]
+        rate: Balance
+    }
 
-        /// Returns the total token supply.
-        #[ink(message)]
-        pub fn total_supply(&self) -> Balance {
-            *self.total_supply
-        }
+    /// Event emitted when reward points are claimed as tokens
+    #[ink(event)]
+    pub struct PointsClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        points: Balance,
+        value: Balance
+    }
 
-        /// Returns the account balance for the specified `owner`.
-        ///
-        /// Returns `0` if the account is non-existent.
-        #[ink(message)]
-        pub fn balance_of(&self, owner: AccountId) -> Balance {
-            self.balances.get(&owner).copied().unwrap_or(0)
-        }
+    /// Event emitted when a balance snapshot is taken
+    #[ink(event)]
+    pub struct Snapshot {
+        #[ink(topic)]
+        id: u32
+    }
 
-        /// Returns the amount which `spender` is still allowed to withdraw from `owner`.
-        ///
-        /// Returns `0` if no allowance has been set `0`.
-        #[ink(message)]
-        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
-            self.allowances.get(&(owner, spender)).copied().unwrap_or(0)
-        }
+    /// Event emitted when the claim window of new distributions is updated
+    #[ink(event)]
+    pub struct DistributionWindowChanged {
+        #[ink(topic)]
+        window_ms: u64
+    }
 
-        /// Transfer ownership to another account
-        #[ink(message)]
-        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when tokens are escrowed for distribution to the holders at a snapshot
+    #[ink(event)]
+    pub struct DistributionFunded {
+        #[ink(topic)]
+        snapshot_id: u32,
+        total: Balance,
+        deadline: Timestamp
+    }
 
-            if new_owner != AccountId::from([0x0; 32]) {
-                self.owner = new_owner.clone();
-            }
-            Ok(())
-        }
+    /// Event emitted when a holder claims its share of a distribution
+    #[ink(event)]
+    pub struct DistributionClaimed {
+        #[ink(topic)]
+        snapshot_id: u32,
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance
+    }
 
-        /// Transfers `value` amount of tokens from the caller's account to account `to`.
-        ///
-        /// On success a `Transfer` event is emitted.
-        ///
-        /// # Errors
-        ///
-        ///  Returns `AccountBlackListed` error if the caller's account is blacklisted.
-        /// 
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the caller's account balance.
-        /// 
-        #[ink(message)]
-        pub fn transfer(&mut self, to: AccountId, value: Balance, extra: Option<String>) -> Result<()> {
-            let from = self.env().caller();
+    /// Event emitted when the unclaimed remainder of a distribution is swept back to the owner
+    #[ink(event)]
+    pub struct DistributionSwept {
+        #[ink(topic)]
+        snapshot_id: u32,
+        value: Balance
+    }
 
-            let blacklisted = self.is_account_blacklisted(from);
-            if blacklisted {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::AccountBlackListed)
-                });
-                return Err(Error::AccountBlackListed);
-            }
+    /// Event emitted when tokens are escrowed under a vesting schedule
+    #[ink(event)]
+    pub struct VestingCreated {
+        #[ink(topic)]
+        beneficiary: AccountId,
+        total: Balance,
+        start: Timestamp,
+        duration_ms: u64,
+        cliff_ms: u64
+    }
 
-            self.transfer_from_to(from, to, value)
-        }
+    /// Event emitted when a beneficiary claims the unlocked tokens of its vesting schedule
+    #[ink(event)]
+    pub struct VestingClaimed {
+        #[ink(topic)]
+        beneficiary: AccountId,
+        value: Balance
+    }
 
-        /// Allows `spender` to withdraw from the caller's account multiple times, up to
-        /// the `value` amount.
-        ///
-        /// If this function is called again it overwrites the current allowance with `value`.
-        ///
-        /// An `Approval` event is emitted.
-        #[ink(message)]
-        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
-            let owner = self.env().caller();
-            self.allowances.insert((owner, spender), value);
-            self.env().emit_event(Approval {
-                owner,
-                spender,
-                value,
-            });
-            Ok(())
-        }
+    /// Event emitted when tokens are escrowed for an airdrop
+    #[ink(event)]
+    pub struct AirdropCreated {
+        #[ink(topic)]
+        id: u32,
+        root: [u8; 32],
+        total: Balance,
+        expiry: Timestamp
+    }
 
-        /// Transfers `value` tokens on the behalf of `from` to the account `to`.
-        ///
-        /// This can be used to allow a contract to transfer tokens on ones behalf and/or
-        /// to charge fees in sub-currencies, for example.
-        ///
-        /// On success a `Transfer` event is emitted.
-        ///
-        /// # Errors
-        ///
-        /// Returns `AccountBlackListed` error if the `from` account is blacklisted.
-        /// 
-        /// Returns `InsufficientAllowance` error if there are not enough tokens allowed
-        /// for the caller to withdraw from `from`.
-        ///
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the the account balance of `from`.
-        #[ink(message)]
-        pub fn transfer_from(
-            &mut self,
-            from: AccountId,
-            to: AccountId,
-            value: Balance,
-        ) -> Result<()> {
-            env::debug_println(&format!("Entropy: Trying to transfer 0x{:x} tokens from {:?} to {:?}", value, from, to));
+    /// Event emitted when an account claims its amount of an airdrop
+    #[ink(event)]
+    pub struct AirdropClaimed {
+        #[ink(topic)]
+        id: u32,
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance
+    }
 
-            let blacklisted = self.is_account_blacklisted(from);
-            if blacklisted {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::AccountBlackListed)
-                });
-                return Err(Error::AccountBlackListed);
-            }
+    /// Event emitted when the unclaimed remainder of an airdrop is swept back to the owner
+    #[ink(event)]
+    pub struct AirdropClosed {
+        #[ink(topic)]
+        id: u32,
+        value: Balance
+    }
 
-            let caller = self.env().caller();
-            let allowance = self.allowance(from, caller);
-            if allowance < value {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::InsufficientAllowance)
-                });
-                return Err(Error::InsufficientAllowance)
-            }
-            self.transfer_from_to(from, to, value)?;
-            self.allowances.insert((from, caller), allowance - value);
-            Ok(())
-        }
+    /// Event emitted when an account's fee rebate is set
+    #[ink(event)]
+    pub struct FeeRebateChanged {
+        #[ink(topic)]
+        account: AccountId,
+        rebate_bps: Balance
+    }
 
-        /// Transfers `value` amount of tokens from the caller's account to account `to`.
-        ///
-        /// On success a `Transfer` event is emitted.
-        ///
-        /// # Errors
-        ///
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the caller's account balance.
-        fn transfer_from_to(
-            &mut self,
-            from: AccountId,
-            to: AccountId,
-            value: Balance
-        ) -> Result<()> {
-            env::debug_println(&format!("Entropy: Transferring 0x{:x} tokens from {:?} to {:?}", value, from, to));
+    /// Event emitted when a fee rebate accrues to an account paying a transfer fee
+    #[ink(event)]
+    pub struct FeeRebateAccrued {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance
+    }
 
-            let from_balance = self.balance_of(from);
-            if from_balance < value {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::InsufficientBalance)
-                });
-                return Err(Error::InsufficientBalance)
-            }
+    /// Event emitted when an account claims its accrued fee rebate
+    #[ink(event)]
+    pub struct FeeRebateClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance
+    }
 
-            let mut fee = 0;
-            if self.basis_points_rate > 0 {
-                // let init_fee = value.checked_mul(Balance::from(self.basis_points_rate)).unwrap_or(Balance::from(0u128)).checked_div(Balance::from(10000u128)).unwrap_or(Balance::from(0u128));
-                let init_fee = value * self.basis_points_rate / 10000;
-                fee = if init_fee > self.maximum_fee { self.maximum_fee } else { init_fee };
-            }
-            let send_value = value - fee;
+    /// Event emitted after destroying the funds of a batch of blacklisted accounts
+    #[ink(event)]
+    pub struct DestroyedBlackFundsBatch {
+        /// Number of accounts whose funds were destroyed
+        destroyed: u32,
+        /// Number of accounts skipped as not blacklisted, protected or empty
+        skipped: u32,
+        funds: Balance
+    }
 
-            self.balances.insert(from, from_balance - value);
-            let to_balance = self.balance_of(to);
-            self.balances.insert(to, to_balance + send_value);
+    /// Event emitted when the fee rounding mode is updated
+    #[ink(event)]
+    pub struct FeeRoundingChanged {
+        #[ink(topic)]
+        rounding: FeeRounding
+    }
 
-            if fee > 0 {
-                let owner_balance = self.balance_of(self.owner);
-                self.balances.insert(self.owner, owner_balance + fee);
-                self.env().emit_event(Transfer {
-                    from: Some(from),
-                    to: Some(self.owner),
-                    value: fee
-                });
-            }
+    /// Event emitted when an approval scoped to transfers to `recipient` occurs
+    #[ink(event)]
+    pub struct RecipientApproval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        #[ink(topic)]
+        recipient: AccountId,
+        value: Balance
+    }
 
-            self.env().emit_event(Transfer {
-                from: Some(from),
-                to: Some(to),
-                value: send_value,
-            });
-            Ok(())
-        }
+    /// Event emitted when tokens are escrowed for a recipient to claim
+    #[ink(event)]
+    pub struct ClaimableTransferCreated {
+        #[ink(topic)]
+        id: u64,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        expiry: Timestamp
+    }
 
-        /// Issues `value` amount of tokens to contract owner's account. Only contract owner is allowed to call this function.
-        /// 
-        /// On success a `Issue` event is emitted.
-        /// 
-        /// # Errors
-        /// 
-        /// Returns `PermissionDenied` error if caller is not the owner.
-        #[ink(message)]
-        pub fn issue(&mut self, value: Balance) -> Result<()> {
-            env::debug_println(&format!("Entropy: Issuing 0x{:x} tokens to owner account", value));
+    /// Event emitted when the recipient of a claimable transfer claims it
+    #[ink(event)]
+    pub struct ClaimableTransferClaimed {
+        #[ink(topic)]
+        id: u64,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance
+    }
 
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when an unclaimed transfer is refunded to its sender
+    #[ink(event)]
+    pub struct ClaimableTransferRefunded {
+        #[ink(topic)]
+        id: u64,
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance
+    }
 
-            let balance = self.balance_of(self.owner);
-            self.balances.insert(self.owner, balance + value);
+    /// Event emitted when an account is granted the auditor role
+    #[ink(event)]
+    pub struct AuditorAdded {
+        #[ink(topic)]
+        account: AccountId
+    }
 
-            let total_supply = &mut self.total_supply;
-            let current_supply = Lazy::<Balance>::get(total_supply);
-            let new_supply = current_supply + value;
-            Lazy::<Balance>::set(total_supply, new_supply);
+    /// Event emitted when an account's auditor role is revoked
+    #[ink(event)]
+    pub struct AuditorRemoved {
+        #[ink(topic)]
+        account: AccountId
+    }
 
-            self.env().emit_event(Issue {
-                amount: value
-            });
+    /// Event emitted when a balances root is finalized
+    #[ink(event)]
+    pub struct BalancesRootCommitted {
+        #[ink(topic)]
+        id: u32,
+        root: [u8; 32],
+        block: BlockNumber
+    }
 
-            Ok(())
-        }
+    /// Event emitted when the default privacy of new accounts is updated
+    #[ink(event)]
+    pub struct DefaultPrivacy {
+        #[ink(topic)]
+        private: bool
+    }
 
-        /// Redeem `value` amount of tokens from contract owner's account. Only contract owner is allowed to call this function.
-        /// 
-        /// On success a `Redeem` event is emitted.
-        /// 
-        /// # Errors
-        /// 
-        /// Returns `PermissionDenied` error if caller is not the owner.
-        /// Returns `InsufficientBalance` error if owner's balance is insufficient.
-        #[ink(message)]
-        pub fn redeem(&mut self, value: Balance) -> Result<()> {
-            env::debug_println(&format!("Entropy: Redeeming 0x{:x} tokens from owner account", value));
+    /// Event emitted when contracts with a code hash are banned from calling the token
+    #[ink(event)]
+    pub struct CodeHashBanned {
+        #[ink(topic)]
+        code_hash: Hash
+    }
 
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when a code hash ban is lifted
+    #[ink(event)]
+    pub struct CodeHashUnbanned {
+        #[ink(topic)]
+        code_hash: Hash
+    }
 
-            let balance = self.balance_of(self.owner);
-            if balance < value {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::InsufficientBalance)
-                });
-                return Err(Error::InsufficientBalance);
-            }
+    /// Event emitted when the size of the top holders leaderboard is updated
+    #[ink(event)]
+    pub struct TopHoldersLimitChanged {
+        #[ink(topic)]
+        limit: u32
+    }
 
-            self.balances.insert(self.owner, balance - value);
+    /// Event emitted when part of an account's balance is locked, by itself or by a locked transfer
+    #[ink(event)]
+    pub struct TokensLocked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        until: Timestamp
+    }
 
-            let total_supply = &mut self.total_supply;
-            let current_supply = Lazy::<Balance>::get(total_supply);
-            let new_supply = current_supply - value;
-            Lazy::<Balance>::set(total_supply, new_supply);
+    /// Event emitted when matured locks are swept from an account
+    #[ink(event)]
+    pub struct TokensUnlocked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance
+    }
 
-            self.env().emit_event(Redeem {
-                amount: value
-            });
+    /// Event emitted when tokens are escrowed as a gift
+    #[ink(event)]
+    pub struct GiftCreated {
+        #[ink(topic)]
+        code_hash: Hash,
+        #[ink(topic)]
+        creator: AccountId,
+        amount: Balance,
+        expiry: Timestamp
+    }
 
-            Ok(())
-        }
+    /// Event emitted when a gift is redeemed with its code
+    #[ink(event)]
+    pub struct GiftRedeemed {
+        #[ink(topic)]
+        code_hash: Hash,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance
+    }
 
-        /// Set whether an account is private or not
-        /// 
-        /// On success a `Privacy` event is emitted.
-        /// 
-        /// # Errors
-        /// 
-        /// Returns `PermissionDenied` error if caller is not the owner.
-        #[ink(message)]
-        pub fn set_account_private(&mut self, account: AccountId, private: bool) -> Result<()> {
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when an expired gift is reclaimed by its creator
+    #[ink(event)]
+    pub struct GiftReclaimed {
+        #[ink(topic)]
+        code_hash: Hash,
+        #[ink(topic)]
+        creator: AccountId,
+        amount: Balance
+    }
 
-            self.accounts_private.insert(account, private);
+    /// Event emitted when part of a payer's balance is put on hold for a recipient
+    #[ink(event)]
+    pub struct HoldCreated {
+        #[ink(topic)]
+        id: u64,
+        #[ink(topic)]
+        payer: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        expires_at: Timestamp
+    }
 
-            self.env().emit_event(Privacy {
-                account,
-                private
-            });
+    /// Event emitted when a recipient captures a hold, preceded by the `Transfer` event
+    #[ink(event)]
+    pub struct HoldCaptured {
+        #[ink(topic)]
+        id: u64,
+        #[ink(topic)]
+        payer: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance
+    }
 
-            Ok(())
-        }
+    /// Event emitted when a hold is released, leaving its tokens spendable by the payer again
+    #[ink(event)]
+    pub struct HoldReleased {
+        #[ink(topic)]
+        id: u64,
+        #[ink(topic)]
+        payer: AccountId,
+        value: Balance
+    }
 
-        /// Returns whether an account is private
-        #[ink(message)]
-        pub fn is_account_private(&self, account: AccountId) -> bool {
-            self.accounts_private.get(&account).copied().unwrap_or(false)
-        }
+    /// Event emitted when an account's balance becomes nonzero, including again after it was emptied,
+    /// so the events track `GlobalStats::holder_count` increments
+    #[ink(event)]
+    pub struct NewHolder {
+        #[ink(topic)]
+        account: AccountId,
+        block: BlockNumber
+    }
 
-        /// Returns whether an account is blacklisted
-        #[ink(message)]
-        pub fn is_account_blacklisted(&self, account: AccountId) -> bool {
-            self.accounts_blacklisted.get(&account).copied().unwrap_or(false)
-        }
+    /// Event emitted when strict approvals are turned on or off
+    #[ink(event)]
+    pub struct StrictApprovalsChanged {
+        enabled: bool
+    }
 
-        /// Add an account to blacklist
-        /// 
-        /// On success an `AddedBlackList` event is emitted.
-        /// 
-        /// # Errors
-        /// 
-        /// Returns `PermissionDenied` error if caller is not the owner.
-        #[ink(message)]
-        pub fn add_account_to_blacklist(&mut self, account: AccountId) -> Result<()> {
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when an Ethereum address is linked to an account
+    #[ink(event)]
+    pub struct EthAddressLinked {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        eth_address: [u8; 20]
+    }
 
-            self.accounts_blacklisted.insert(account, true);
+    /// Event emitted when an Ethereum address is unlinked from an account
+    #[ink(event)]
+    pub struct EthAddressUnlinked {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        eth_address: [u8; 20]
+    }
 
-            self.env().emit_event(AddedBlackList {
-                account
-            });
+    /// Event emitted when a dust sweep is announced
+    #[ink(event)]
+    pub struct DustSweepAnnounced {
+        threshold: Balance,
+        execute_after: Timestamp,
+        burn: bool
+    }
 
-            Ok(())
-        }
+    /// Event emitted when a dust balance is swept
+    #[ink(event)]
+    pub struct DustSwept {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        burned: bool
+    }
 
-        /// Remove an account from blacklist
-        /// 
-        /// On success an `RemovedBlackList` event is emitted.
-        /// 
-        /// # Errors
-        /// 
-        /// Returns `PermissionDenied` error if caller is not the owner.
-        #[ink(message)]
-        pub fn remove_account_from_blacklist(&mut self, account: AccountId) -> Result<()> {
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when the minimum holding period is updated
+    #[ink(event)]
+    pub struct MinHoldingPeriodChanged {
+        period_ms: Timestamp
+    }
 
-            self.accounts_blacklisted.insert(account, false);
+    /// Event emitted when an account changes its own spending limit
+    #[ink(event)]
+    pub struct SpendingLimitChanged {
+        #[ink(topic)]
+        account: AccountId,
+        amount_per_day: Balance,
+        /// Timestamp the new limit takes effect at
+        effective_at: Timestamp
+    }
 
-            self.env().emit_event(RemovedBlackList {
-                account
-            });
+    /// Event emitted when the owner sets or clears the transfer limit of an account
+    #[ink(event)]
+    pub struct TransferLimitChanged {
+        #[ink(topic)]
+        account: AccountId,
+        /// New limit, `0` if unlimited, or `None` if the account falls back to the default limit
+        limit: Option<Balance>
+    }
 
-            Ok(())
-        }
+    /// Event emitted when the owner changes the default transfer limit
+    #[ink(event)]
+    pub struct DefaultTransferLimitChanged {
+        /// New default limit, `0` if unlimited
+        limit: Balance
+    }
 
-        /// Destroy funds of a blacklisted account
-        /// 
-        /// On success an `DestroyedBlackFunds` event is emitted.
-        /// 
-        /// # Errors
-        /// 
-        /// Returns `PermissionDenied` error if caller is not the owner, `AccountNotBlackListed` if the account is not blacklisted
-        #[ink(message)]
-        pub fn destroy_black_funds(&mut self, account: AccountId) -> Result<()> {
-            let caller = self.env().caller();
-            if caller != self.owner {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::PermissionDenied)
-                });
-                return Err(Error::PermissionDenied);
-            }
+    /// Event emitted when a transfer is blocked by the sender's own spending limit
+    #[ink(event)]
+    pub struct SpendingLimitExceeded {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        /// Amount the account could still send in the current window
+        remaining: Balance
+    }
 
-            let blacklisted = self.is_account_blacklisted(account);
-            if !blacklisted {
-                self.env().emit_event(TransactionFailed {
-                    error: format!("{:?}", Error::AccountNotBlackListed)
-                });
-                return Err(Error::AccountNotBlackListed);
-            }
+    /// Event emitted when the throughput cap is updated
+    #[ink(event)]
+    pub struct ThroughputCapChanged {
+        #[ink(topic)]
+        cap: ThroughputCap
+    }
 
-            let dirty_funds = self.balance_of(account);
-            self.balances.insert(account, 0);
+    /// Event emitted when the trusted forwarder is set or removed
+    #[ink(event)]
+    pub struct TrustedForwarderChanged {
+        #[ink(topic)]
+        forwarder: Option<AccountId>
+    }
 
-            let total_supply = &mut self.total_supply;
-            let current_supply = Lazy::<Balance>::get(total_supply);
-            let new_supply = current_supply - dirty_funds;
-            Lazy::<Balance>::set(total_supply, new_supply);
+    /// Event emitted when an account changes its keep-alive minimum
+    #[ink(event)]
+    pub struct KeepAliveMinimumChanged {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance
+    }
 
-            self.env().emit_event(DestroyedBlackFunds {
-                account,
-                funds: dirty_funds
-            });
+    /// Event emitted when a holder burns its own tokens
+    #[ink(event)]
+    pub struct Burn {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance
+    }
 
-            Ok(())
-        }
+    /// Event emitted when the pending owner accepts the ownership
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        old: AccountId,
+        #[ink(topic)]
+        new: AccountId
+    }
 
+    /// Event emitted when the owner renounces the ownership for good
+    #[ink(event)]
+    pub struct OwnershipRenounced {
+        #[ink(topic)]
+        previous: AccountId
     }
 
-    /// Unit tests
-    #[cfg(test)]
-    mod tests {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
-        use ink_env::{
-            hash::{
-                Blake2x256,
-                CryptoHash,
-                HashOutput,
-            },
-            Clear,
-        };
+    /// Event emitted when the contract's code is replaced
+    #[ink(event)]
+    pub struct ContractUpgraded {
+        #[ink(topic)]
+        old_code_hash: Hash,
+        #[ink(topic)]
+        new_code_hash: Hash
+    }
 
-        type Event = <Entropy as ::ink_lang::BaseEvent>::Type;
+    /// Event emitted when an account's fee exemption is updated
+    #[ink(event)]
+    pub struct FeeExemption {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        exempt: bool
+    }
 
-        use ink_lang as ink;
+    /// Event emitted when the fee collector is changed
+    #[ink(event)]
+    pub struct FeeCollectorChanged {
+        #[ink(topic)]
+        collector: AccountId
+    }
 
-        fn encoded_into_hash<T>(entity: &T) -> Hash
-            where T: scale::Encode
-        {
-            let mut result = Hash::clear();
-            let len_result = result.as_ref().len();
-            let encoded = entity.encode();
-            let len_encoded = encoded.len();
-            if len_encoded <= len_result {
-                result.as_mut()[..len_encoded].copy_from_slice(&encoded);
-                return result
+    /// Event emitted when a blacklisted account's funds are seized into a treasury account
+    #[ink(event)]
+    pub struct SeizedBlackFunds {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        treasury: AccountId,
+        funds: Balance
+    }
+
+    /// Event emitted when the owner moves an account's tokens with `force_transfer`
+    #[ink(event)]
+    pub struct ForcedTransfer {
+        #[ink(topic)]
+        operator: AccountId,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance
+    }
+
+    /// Event emitted when a transfer fee is charged, right before the transfer's own `Transfer` event
+    #[ink(event)]
+    pub struct FeeCharged {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        collector: AccountId,
+        fee: Balance
+    }
+
+    /// Event emitted when the failure counters are reset
+    #[ink(event)]
+    pub struct FailureStatsReset {
+        #[ink(topic)]
+        by: AccountId
+    }
+
+    /// Event emitted when error occurs, if the contract was constructed with `verbose_errors`
+    #[ink(event)]
+    pub struct TransactionFailed {
+        #[ink(topic)]
+        caller: AccountId,
+        /// Name of the message which failed
+        message: String,
+        #[ink(topic)]
+        error: Error
+    }
+
+    /// Entropy error types.
+    ///
+    /// A variant's error code, as reported by `failure_stats`, is its index in declaration order.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if not privileged.
+        PermissionDenied,
+        /// Returned if not enough balance to fulfill a request is available.
+        InsufficientBalance,
+        /// Returned if not enough allowance to fulfill a request is available.
+        InsufficientAllowance,
+        /// Returned if trying to transfer funds from a blacklisted account
+        AccountBlackListed,
+        /// Returned if trying to destropy funds of an account which is not blacklisted
+        AccountNotBlackListed,
+        /// Returned if trying to transfer funds from a frozen account
+        AccountFrozen,
+        /// Returned if a transfer id is unknown or no longer kept
+        UnknownTransfer,
+        /// Returned if trying to reverse a transfer after its dispute window
+        DisputeWindowClosed,
+        /// Returned if trying to reverse a transfer which is already reversed
+        TransferAlreadyReversed,
+        /// Returned if an account argument is not a valid target
+        InvalidAccount,
+        /// Returned if trying to transfer funds before trading is enabled
+        TradingNotEnabled,
+        /// Returned if a signature does not verify against the expected signer
+        InvalidSignature,
+        /// Returned if a signed operation is submitted after its deadline
+        SignatureExpired,
+        /// Returned if a signed operation does not carry the expected nonce
+        InvalidNonce,
+        /// Returned if trying to transfer funds while the contract is paused
+        ContractPaused,
+        /// Returned if a transfer moves more than the circuit breaker allows
+        CircuitBreakerTripped,
+        /// Returned if a compliance action targets a protected account
+        ProtectedAccount,
+        /// Returned if trying to claim points, a distribution share or a fee rebate without anything to claim
+        NothingToClaim,
+        /// Returned if the points conversion cap of the current epoch is exhausted
+        PointsCapExceeded,
+        /// Returned if a snapshot id is not one of the snapshots taken
+        UnknownSnapshot,
+        /// Returned if trying to fund a second distribution for a snapshot
+        DistributionExists,
+        /// Returned if no distribution was funded for a snapshot
+        UnknownDistribution,
+        /// Returned if trying to claim a distribution share twice
+        AlreadyClaimed,
+        /// Returned if trying to claim a distribution share after its deadline
+        ClaimWindowClosed,
+        /// Returned if trying to sweep a distribution before its deadline
+        ClaimWindowOpen,
+        /// Returned if a batch holds more than `MAX_BATCH_SIZE` entries
+        BatchTooLarge,
+        /// Returned if a balances root commitment step doesn't follow the previous one
+        InvalidCommitment,
+        /// Returned if the caller is a contract whose code hash is banned
+        CallerCodeBanned,
+        /// Returned if a self-lock has no amount or ends in the past
+        InvalidLock,
+        /// Returned if a gift has no amount, ends in the past or reuses a code hash
+        InvalidGift,
+        /// Returned if no gift was created under a code hash
+        UnknownGift,
+        /// Returned if trying to redeem or reclaim a gift which was already redeemed or reclaimed
+        GiftSpent,
+        /// Returned if trying to destroy funds of an account blacklisted for less than the destruction delay
+        QuarantineActive,
+        /// Returned if trying to destroy funds of an account whose appeal is pending
+        AppealPending,
+        /// Returned if an account appeals while its appeal is pending or after it was rejected
+        AppealExists,
+        /// Returned if trying to resolve an appeal which is not pending
+        NoPendingAppeal,
+        /// Returned if an appeal deposits less than the appeal bond
+        InsufficientBond,
+        /// Returned if an appeal bond can't be refunded or forfeited
+        BondTransferFailed,
+        /// Returned if trying to change a nonzero allowance to another nonzero value while strict approvals are on
+        NonZeroAllowanceExists,
+        /// Returned if trying to link an Ethereum address which is linked to another account
+        EthAddressLinked,
+        /// Returned if an account has no linked Ethereum address
+        EthAddressNotLinked,
+        /// Returned if a dust sweep has no threshold or notice, or none was announced
+        InvalidDustSweep,
+        /// Returned if trying to execute a dust sweep before its notice has passed
+        DustSweepNotDue,
+        /// Returned if a transfer would spend tokens received within the minimum holding period
+        HoldingPeriodActive,
+        /// Returned if a transfer would exceed the sender's own spending limit
+        SpendingLimitExceeded,
+        /// Returned if a transfer would move more than the throughput cap allows in the current window
+        ThroughputExceeded,
+        /// Returned if a `*_for` message is called by another account than the trusted forwarder
+        UntrustedForwarder,
+        /// Returned if a keep-alive transfer would leave the sender below its keep-alive minimum
+        WouldDropBelowMinimum,
+        /// Returned if an amount would exceed the range of `Balance`
+        Overflow,
+        /// Returned if an account tries to change its privacy while the owner forced it private
+        PrivacyForced,
+        /// Returned if issuing would raise the total supply above the max supply
+        SupplyCapExceeded,
+        /// Returned if a contract recipient of `transfer_and_call` rejects or fails its notification
+        ReceiverRejected,
+        /// Returned if trying to issue or redeem no tokens
+        ZeroAmount,
+        /// Returned if a transfer memo is longer than `MAX_MEMO_LEN` bytes
+        MemoTooLarge,
+        /// Returned if a vesting schedule has no amount or duration, its cliff exceeds its duration,
+        /// or its beneficiary already has one
+        InvalidVesting,
+        /// Returned if upgrading to the zero code hash or to code that was never uploaded
+        InvalidCodeHash,
+        /// Returned if a transfer would exceed the transfer limit the owner imposed on the sender
+        TransferLimitExceeded,
+        /// Returned if native balance or foreign tokens can't be rescued
+        RescueFailed,
+        /// Returned if renouncing the ownership without confirming it
+        RenounceNotConfirmed,
+        /// Returned if an airdrop has no tokens or its expiry has passed
+        InvalidAirdrop,
+        /// Returned if no airdrop was created under an id
+        UnknownAirdrop,
+        /// Returned if a Merkle proof doesn't lead to the expected root
+        InvalidProof,
+        /// Returned if a hold has no tokens or its expiry has passed
+        InvalidHold,
+        /// Returned if no unsettled hold exists under an id
+        UnknownHold,
+        /// Returned if a contract notified by `transfer_and_call` calls back into the contract to change balances
+        ReentrantCall
+    }
+
+    impl Error {
+        /// Returns the name of the error, without going through the formatting machinery.
+        pub fn as_str(&self) -> &'static str {
+            match *self {
+                Self::PermissionDenied => "PermissionDenied",
+                Self::InsufficientBalance => "InsufficientBalance",
+                Self::InsufficientAllowance => "InsufficientAllowance",
+                Self::AccountBlackListed => "AccountBlackListed",
+                Self::AccountNotBlackListed => "AccountNotBlackListed",
+                Self::AccountFrozen => "AccountFrozen",
+                Self::UnknownTransfer => "UnknownTransfer",
+                Self::DisputeWindowClosed => "DisputeWindowClosed",
+                Self::TransferAlreadyReversed => "TransferAlreadyReversed",
+                Self::InvalidAccount => "InvalidAccount",
+                Self::TradingNotEnabled => "TradingNotEnabled",
+                Self::InvalidSignature => "InvalidSignature",
+                Self::SignatureExpired => "SignatureExpired",
+                Self::InvalidNonce => "InvalidNonce",
+                Self::ContractPaused => "ContractPaused",
+                Self::CircuitBreakerTripped => "CircuitBreakerTripped",
+                Self::ProtectedAccount => "ProtectedAccount",
+                Self::NothingToClaim => "NothingToClaim",
+                Self::PointsCapExceeded => "PointsCapExceeded",
+                Self::UnknownSnapshot => "UnknownSnapshot",
+                Self::DistributionExists => "DistributionExists",
+                Self::UnknownDistribution => "UnknownDistribution",
+                Self::AlreadyClaimed => "AlreadyClaimed",
+                Self::ClaimWindowClosed => "ClaimWindowClosed",
+                Self::ClaimWindowOpen => "ClaimWindowOpen",
+                Self::BatchTooLarge => "BatchTooLarge",
+                Self::InvalidCommitment => "InvalidCommitment",
+                Self::CallerCodeBanned => "CallerCodeBanned",
+                Self::InvalidLock => "InvalidLock",
+                Self::InvalidGift => "InvalidGift",
+                Self::UnknownGift => "UnknownGift",
+                Self::GiftSpent => "GiftSpent",
+                Self::QuarantineActive => "QuarantineActive",
+                Self::AppealPending => "AppealPending",
+                Self::AppealExists => "AppealExists",
+                Self::NoPendingAppeal => "NoPendingAppeal",
+                Self::InsufficientBond => "InsufficientBond",
+                Self::BondTransferFailed => "BondTransferFailed",
+                Self::NonZeroAllowanceExists => "NonZeroAllowanceExists",
+                Self::EthAddressLinked => "EthAddressLinked",
+                Self::EthAddressNotLinked => "EthAddressNotLinked",
+                Self::InvalidDustSweep => "InvalidDustSweep",
+                Self::DustSweepNotDue => "DustSweepNotDue",
+                Self::HoldingPeriodActive => "HoldingPeriodActive",
+                Self::SpendingLimitExceeded => "SpendingLimitExceeded",
+                Self::ThroughputExceeded => "ThroughputExceeded",
+                Self::UntrustedForwarder => "UntrustedForwarder",
+                Self::WouldDropBelowMinimum => "WouldDropBelowMinimum",
+                Self::Overflow => "Overflow",
+                Self::PrivacyForced => "PrivacyForced",
+                Self::SupplyCapExceeded => "SupplyCapExceeded",
+                Self::ReceiverRejected => "ReceiverRejected",
+                Self::ZeroAmount => "ZeroAmount",
+                Self::MemoTooLarge => "MemoTooLarge",
+                Self::InvalidVesting => "InvalidVesting",
+                Self::InvalidCodeHash => "InvalidCodeHash",
+                Self::TransferLimitExceeded => "TransferLimitExceeded",
+                Self::RescueFailed => "RescueFailed",
+                Self::RenounceNotConfirmed => "RenounceNotConfirmed",
+                Self::InvalidAirdrop => "InvalidAirdrop",
+                Self::UnknownAirdrop => "UnknownAirdrop",
+                Self::InvalidProof => "InvalidProof",
+                Self::InvalidHold => "InvalidHold",
+                Self::UnknownHold => "UnknownHold",
+                Self::ReentrantCall => "ReentrantCall"
             }
-            let mut hash_output =
-                <<Blake2x256 as HashOutput>::Type as Default>::default();
-            <Blake2x256 as CryptoHash>::hash(&encoded, &mut hash_output);
-            let copy_len = core::cmp::min(hash_output.len(), len_result);
-            result.as_mut()[0..copy_len].copy_from_slice(&hash_output[0..copy_len]);
-            result
         }
+    }
 
-        fn assert_transfer_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_from: Option<AccountId>,
-            expected_to: Option<AccountId>,
-            expected_value: Balance,
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
-                .expect("encountered invalid contract event data buffer");
-            if let Event::Transfer(Transfer { from, to, value }) = decoded_event {
-                assert_eq!(from, expected_from, "encountered invalid Transfer.from");
-                assert_eq!(to, expected_to, "encountered invalid Transfer.to");
-                assert_eq!(value, expected_value, "encountered invalid Trasfer.value");
-            } else {
-                panic!("encountered unexpected event kind: expected a Transfer event")
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
+
+    /// Entropy result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Error type of the PSP22 standard extension messages.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        /// Any Entropy error without a standard counterpart, by name
+        Custom(String),
+        /// Returned if not enough balance to fulfill a request is available
+        InsufficientBalance,
+        /// Returned if not enough allowance to fulfill a request is available
+        InsufficientAllowance,
+        /// Returned if recipient's address is zero
+        ZeroRecipientAddress,
+        /// Returned if sender's address is zero
+        ZeroSenderAddress,
+        /// Returned if a safe transfer check fails
+        SafeTransferCheckFailed(String),
+    }
+
+    impl From<Error> for PSP22Error {
+        fn from(error: Error) -> Self {
+            match error {
+                Error::InsufficientBalance => Self::InsufficientBalance,
+                Error::InsufficientAllowance => Self::InsufficientAllowance,
+                error => Self::Custom(String::from(error.as_str()))
             }
+        }
+    }
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::Transfer",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::Transfer::from",
-                    value: &expected_from,
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::Transfer::to",
-                    value: &expected_to,
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::Transfer::value",
-                    value: &expected_value,
-                })
-            ];
-            for (n, (actual_topic, expected_topic)) in
-                event.topics.iter().zip(expected_topics).enumerate()
-            {
-                let topic = actual_topic
-                    .decode::<Hash>()
-                    .expect("encountered invalid topic encoding");
-                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
-            }
+    // Every message has an explicit selector: the PSP22 one where the standard defines the message,
+    // otherwise the first four bytes of the Blake2x256 hash of the message name, as ink! derives it.
+    impl Entropy {
+
+        /// Creates a new Entropy contract with the specified initial supply, name, symbol and decimals.
+        #[ink(constructor)]
+        pub fn construct(initial_supply: Balance, name: String, symbol: String, decimals: u32) -> Self {
+            Self::construct_with_config(initial_supply, name, symbol, decimals, ContractConfig::default())
         }
 
-        fn assert_issue_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_value: Balance,
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
-                .expect("encountered invalid contract event data buffer");
-            if let Event::Issue(Issue { amount }) = decoded_event {
-                assert_eq!(amount, expected_value, "encountered invalid Issue.amount");
-            } else {
-                panic!("encountered unexpected event kind: expected an Issue event")
-            }
+        /// Creates a new Entropy contract with the specified initial supply, name, symbol and decimals,
+        /// and the deployment options in `config`.
+        ///
+        /// # Panics
+        ///
+        /// Panics, reverting the deployment, if `initial_supply` exceeds `config.max_supply`.
+        #[ink(constructor)]
+        pub fn construct_with_config(initial_supply: Balance, name: String, symbol: String, decimals: u32, config: ContractConfig) -> Self {
+            debug_log!("Entropy: Construct with initial_supply: 0x{:x}, name: {}, symbol: {}, decimals: 0x{:x}, config: {:?}", initial_supply, &name, &symbol, decimals, config);
+            assert!(config.max_supply.map_or(true, |cap| initial_supply <= cap), "{}", Error::SupplyCapExceeded.as_str());
+            let ContractConfig { trading_enabled, fee_transfer_events, max_supply, verbose_errors, strict_approvals } = config;
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::Issue",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::Issue::amount",
-                    value: &expected_value,
-                })
-            ];
-            for (n, (actual_topic, expected_topic)) in
-                event.topics.iter().zip(expected_topics).enumerate()
-            {
-                let topic = actual_topic
-                    .decode::<Hash>()
-                    .expect("encountered invalid topic encoding");
-                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            let caller = Self::env().caller();
+            let mut balances = StorageHashMap::new();
+            balances.insert(caller, initial_supply);
+            let mut volumes = StorageHashMap::new();
+            volumes.insert(caller, (0, initial_supply));
+            let mut holders = StorageVec::new();
+            holders.push(caller);
+            let mut current_holders = StorageVec::new();
+            let mut current_holders_index = StorageHashMap::new();
+            if initial_supply > 0 {
+                current_holders_index.insert(caller, 0);
+                current_holders.push(caller);
+            }
+            let mut balance_history = StorageHashMap::new();
+            if initial_supply > 0 {
+                balance_history.insert(caller, vec![(Self::env().block_number(), Self::env().block_timestamp(), initial_supply)]);
             }
+            let instance = Self {
+                total_supply: Lazy::new(initial_supply),
+                name,
+                symbol,
+                basis_points_rate: 0,
+                maximum_fee: 0,
+                owner: caller,
+                decimals,
+                balances,
+                allowances: StorageHashMap::new(),
+                account_flags: StorageHashMap::new(),
+                privacy_sources: StorageHashMap::new(),
+                accounts_blacklisted: StorageHashMap::new(),
+                blacklisted: StorageVec::new(),
+                blacklisted_index: StorageHashMap::new(),
+                frozen_until: StorageHashMap::new(),
+                velocity_rule: VelocityRule::default(),
+                outflows: StorageHashMap::new(),
+                velocity_exempt: StorageHashMap::new(),
+                last_tx_id: 0,
+                recent_transfers: StorageHashMap::new(),
+                dispute_window_ms: DEFAULT_DISPUTE_WINDOW_MS,
+                trading_enabled,
+                prelaunch_allowlist: StorageHashMap::new(),
+                admin_nonce: 0,
+                volumes,
+                paused: false,
+                circuit_breaker_bps: 0,
+                system_accounts: StorageHashMap::new(),
+                protected_accounts: StorageHashMap::new(),
+                points_program: PointsProgram::default(),
+                reward_points: StorageHashMap::new(),
+                points_rate: 0,
+                points_epoch: (0, 0),
+                nonces: StorageHashMap::new(),
+                snapshot_id: 0,
+                balance_snapshots: StorageHashMap::new(),
+                supply_snapshots: StorageHashMap::new(),
+                distributions: StorageHashMap::new(),
+                distribution_claims: StorageHashMap::new(),
+                distribution_window_ms: DEFAULT_DISTRIBUTION_WINDOW_MS,
+                vesting_schedules: StorageHashMap::new(),
+                airdrop_count: 0,
+                airdrops: StorageHashMap::new(),
+                airdrop_claims: StorageHashMap::new(),
+                escrowed: 0,
+                fee_rebates: StorageHashMap::new(),
+                accrued_rebates: StorageHashMap::new(),
+                ownership_history: Lazy::new(vec![OwnerRecord {
+                    owner: caller,
+                    from_block: Self::env().block_number()
+                }]),
+                fee_rounding: FeeRounding::default(),
+                recipient_allowances: StorageHashMap::new(),
+                last_pending_id: 0,
+                pending_transfers: StorageHashMap::new(),
+                stats: GlobalStats {
+                    holder_count: if initial_supply > 0 { 1 } else { 0 },
+                    ..GlobalStats::default()
+                },
+                auditors: StorageHashMap::new(),
+                holders,
+                current_holders,
+                current_holders_index,
+                pending_commitment: None,
+                balances_root_count: 0,
+                balances_roots: StorageHashMap::new(),
+                default_private: false,
+                banned_code_hashes: StorageHashMap::new(),
+                top_holders: Lazy::new(if initial_supply > 0 { vec![(caller, initial_supply)] } else { Vec::new() }),
+                top_holders_limit: DEFAULT_TOP_HOLDERS,
+                top_holders_floor: 0,
+                self_locks: StorageHashMap::new(),
+                gifts: StorageHashMap::new(),
+                hold_count: 0,
+                holds: StorageHashMap::new(),
+                on_hold: StorageHashMap::new(),
+                paused_until: None,
+                destruction_delay_ms: 0,
+                appeals: StorageHashMap::new(),
+                appeal_bond: 0,
+                appeal_timeout_ms: DEFAULT_APPEAL_TIMEOUT_MS,
+                balance_history,
+                failure_counts: Lazy::new(Vec::new()),
+                strict_approvals,
+                eth_addresses: StorageHashMap::new(),
+                eth_accounts: StorageHashMap::new(),
+                dust_sweep: None,
+                redemption_receipt_count: 0,
+                redemption_receipts: StorageHashMap::new(),
+                min_holding_period_ms: 0,
+                held_receipts: StorageHashMap::new(),
+                spending_limits: StorageHashMap::new(),
+                spending_windows: StorageHashMap::new(),
+                default_transfer_limit: 0,
+                transfer_limits: StorageHashMap::new(),
+                transfer_windows: StorageHashMap::new(),
+                throughput_cap: Default::default(),
+                throughput_window: (0, 0),
+                trusted_forwarder: None,
+                keep_alive_minimums: StorageHashMap::new(),
+                notifying_receiver: false,
+                pending_owner: None,
+                fee_collector: caller,
+                fee_transfer_events,
+                max_supply,
+                verbose_errors,
+                version: CONTRACT_VERSION
+            };
+            Self::env().emit_event(Transfer {
+                from: None,
+                to: Some(caller),
+                value: initial_supply,
+            });
+            if initial_supply > 0 {
+                Self::env().emit_event(NewHolder {
+                    account: caller,
+                    block: Self::env().block_number()
+                });
+            }
+            instance
         }
 
-        fn assert_redeem_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_value: Balance,
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
-                .expect("encountered invalid contract event data buffer");
-            if let Event::Redeem(Redeem { amount }) = decoded_event {
-                assert_eq!(amount, expected_value, "encountered invalid Redeem.amount");
-            } else {
-                panic!("encountered unexpected event kind: expected a Redeem event")
-            }
+        /// Creates a new Entropy contract with the specified initial supply and default name, symbol and decimals.
+        #[ink(constructor)]
+        pub fn new(initial_supply: Balance) -> Self {
+            Entropy::construct(initial_supply, "Entropy Coin".into(), "ENT".into(), 6)
+        }
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::Redeem",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::Redeem::amount",
-                    value: &expected_value,
-                })
-            ];
-            for (n, (actual_topic, expected_topic)) in
-                event.topics.iter().zip(expected_topics).enumerate()
-            {
-                let topic = actual_topic
-                    .decode::<Hash>()
-                    .expect("encountered invalid topic encoding");
-                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
-            }
+        /// Creates a new Entropy contract with default initial supply, name, symbol and decimals.
+        #[ink(constructor)]
+        pub fn default() -> Self {
+            Entropy::construct(1_000_000_000_000, "Entropy Coin".into(), "ENT".into(), 6)
         }
 
-        fn assert_privacy_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_account: AccountId,
-            expected_private: bool,
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
-                .expect("encountered invalid contract event data buffer");
-            if let Event::Privacy(Privacy { account, private }) = decoded_event {
-                assert_eq!(account, expected_account, "encountered invalid Privacy.account");
-                assert_eq!(private, expected_private, "encountered invalid Privacy.private");
-            } else {
-                panic!("encountered unexpected event kind: expected a Privacy event")
-            }
+        /// Returns the token name.
+        #[ink(message, selector = "0x3ADAF70D")]
+        pub fn name(&self) -> String {
+            self.name.clone()
+        }
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::Privacy",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::Privacy::account",
-                    value: &expected_account,
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::Privacy::private",
-                    value: &expected_private,
-                })
-            ];
-            for (n, (actual_topic, expected_topic)) in
-                event.topics.iter().zip(expected_topics).enumerate()
-            {
-                let topic = actual_topic
-                    .decode::<Hash>()
-                    .expect("encountered invalid topic encoding");
-                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
-            }
+        /// Returns the token symbol.
+        #[ink(message, selector = "0x9BD1933E")]
+        pub fn symbol(&self) -> String {
+            self.symbol.clone()
         }
 
-        fn assert_added_blacklist_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_account: AccountId
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
-                .expect("encountered invalid contract event data buffer");
-            if let Event::AddedBlackList(AddedBlackList { account }) = decoded_event {
-                assert_eq!(account, expected_account, "encountered invalid AddedBlackList.account");
-            } else {
-                panic!("encountered unexpected event kind: expected a AddedBlackList event")
-            }
+        /// Returns the token decimals.
+        #[ink(message, selector = "0x81C09D87")]
+        pub fn decimals(&self) -> u32 {
+            self.decimals
+        }
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::AddedBlackList",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::AddedBlackList::account",
-                    value: &expected_account,
-                })
-            ];
-            for (n, (actual_topic, expected_topic)) in
-                event.topics.iter().zip(expected_topics).enumerate()
-            {
-                let topic = actual_topic
-                    .decode::<Hash>()
-                    .expect("encountered invalid topic encoding");
-                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
-            }
+        /// Returns contract level transaction fee basic points rate (*/10000)
+        #[ink(message, selector = "0x6A1D94FB")]
+        pub fn basis_points_rate(&self) -> Balance {
+            self.basis_points_rate
         }
 
-        fn assert_removed_blacklist_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_account: AccountId
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
-                .expect("encountered invalid contract event data buffer");
-            if let Event::RemovedBlackList(RemovedBlackList { account }) = decoded_event {
-                assert_eq!(account, expected_account, "encountered invalid RemovedBlackList.account");
-            } else {
-                panic!("encountered unexpected event kind: expected a RemovedBlackList event")
-            }
+        /// Returns contract level maximum fee per transaction
+        #[ink(message, selector = "0x876922B0")]
+        pub fn maximum_fee(&self) -> Balance {
+            self.maximum_fee
+        }
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::RemovedBlackList",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::RemovedBlackList::account",
-                    value: &expected_account,
-                })
-            ];
-            for (n, (actual_topic, expected_topic)) in
-                event.topics.iter().zip(expected_topics).enumerate()
-            {
-                let topic = actual_topic
-                    .decode::<Hash>()
-                    .expect("encountered invalid topic encoding");
-                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+        /// Returns contract level transaction fee params, including the rounding mode
+        #[ink(message, selector = "0xC1CAB920")]
+        pub fn get_params(&self) -> FeeParams {
+            FeeParams {
+                basis_points_rate: self.basis_points_rate,
+                maximum_fee: self.maximum_fee,
+                rounding: self.fee_rounding
             }
         }
 
-        fn assert_destroyed_black_funds_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_account: AccountId,
-            expected_funds: Balance
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
-                .expect("encountered invalid contract event data buffer");
-            if let Event::DestroyedBlackFunds(DestroyedBlackFunds { account, funds }) = decoded_event {
-                assert_eq!(account, expected_account, "encountered invalid DestroyedBlackFunds.account");
-                assert_eq!(funds, expected_funds, "encountered invalid DestroyedBlackFunds.funds");
-            } else {
-                panic!("encountered unexpected event kind: expected a DestroyedBlackFunds event")
+        /// Returns the fee charged on a transfer of `value` under the current fee params
+        #[ink(message, selector = "0xD60BC501")]
+        pub fn estimate_fee(&self, value: Balance) -> Balance {
+            self.compute_fee(value)
+        }
+
+        /// Returns the `(fee, amount_received)` of the caller transferring `value`, as charged on a
+        /// transfer to a recipient which isn't fee exempt
+        #[ink(message, selector = "0x18ACF154")]
+        pub fn calculate_fee(&self, value: Balance) -> (Balance, Balance) {
+            let fee = if self.is_fee_exempt(self.env().caller()) { 0 } else { self.compute_fee(value) };
+            (fee, value - fee)
+        }
+
+        /// Set the rounding applied to the fee computation
+        ///
+        /// On success a `FeeRoundingChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xA130EBCB")]
+        pub fn set_fee_rounding(&mut self, rounding: FeeRounding) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_fee_rounding", Error::PermissionDenied));
             }
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::DestroyedBlackFunds",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::DestroyedBlackFunds::account",
+            self.fee_rounding = rounding;
+
+            self.env().emit_event(FeeRoundingChanged {
+                rounding
+            });
+
+            Ok(())
+        }
+
+        /// Computes the fee on a transfer of `value`: `basis_points_rate` basis points of it, rounded
+        /// per `fee_rounding` and capped at `maximum_fee`.
+        ///
+        /// Exact for any `value`: only the remainder of `value` modulo 10000 is multiplied before
+        /// dividing, so no intermediate product overflows and rounding applies to the fraction only.
+        fn compute_fee(&self, value: Balance) -> Balance {
+            // Split `value` so the product can't overflow: `basis_points_rate` is at most 20
+            let whole = (value / 10000).saturating_mul(self.basis_points_rate);
+            let product = (value % 10000) * self.basis_points_rate;
+            let fee = whole.saturating_add(match self.fee_rounding {
+                FeeRounding::Down => product / 10000,
+                FeeRounding::Up => product / 10000 + if product % 10000 > 0 { 1 } else { 0 },
+                FeeRounding::HalfUp => product / 10000 + if product % 10000 >= 5000 { 1 } else { 0 },
+            });
+            if fee > self.maximum_fee { self.maximum_fee } else { fee }
+        }
+
+        /// Set contract level transaction fee params
+        #[ink(message, selector = "0x158C977C")]
+        pub fn set_params(&mut self, new_basic_points: Balance, new_max_fee: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_params", Error::PermissionDenied));
+            }
+
+            self.basis_points_rate = if new_basic_points > 20 { 20 } else { new_basic_points };
+            self.maximum_fee = if new_max_fee > 50_000_000 { 50_000_000 } else { new_max_fee };
+
+            self.env().emit_event(Params {
+                basis_points_rate: self.basis_points_rate,
+                maximum_fee: self.maximum_fee
+            });
+
+            Ok(())
+        }
+
+        /// Returns the account credited with transfer fees.
+        #[ink(message, selector = "0x04C1D059")]
+        pub fn fee_collector(&self) -> AccountId {
+            self.fee_collector
+        }
+
+        /// Set the account credited with transfer fees, which also pays out fee rebates
+        ///
+        /// On success a `FeeCollectorChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        ///
+        /// Returns `AccountBlackListed` error if `account` is blacklisted.
+        #[ink(message, selector = "0xC5B70D50")]
+        pub fn set_fee_collector(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_fee_collector", Error::PermissionDenied));
+            }
+
+            self.validate_account("set_fee_collector", account)?;
+
+            if self.is_account_blacklisted(account) {
+                return Err(self.fail("set_fee_collector", Error::AccountBlackListed));
+            }
+
+            self.fee_collector = account;
+
+            self.env().emit_event(FeeCollectorChanged {
+                collector: account
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether charging a fee also emits a `Transfer` event to the fee collector.
+        #[ink(message, selector = "0xA92D535C")]
+        pub fn fee_transfer_events(&self) -> bool {
+            self.fee_transfer_events
+        }
+
+        /// Returns the hard cap on the total supply, `None` if uncapped.
+        #[ink(message, selector = "0x98A4FB1D")]
+        pub fn max_supply(&self) -> Option<Balance> {
+            self.max_supply
+        }
+
+        /// Returns whether failing calls also emit a `TransactionFailed` event.
+        #[ink(message, selector = "0x0D3A729B")]
+        pub fn verbose_errors(&self) -> bool {
+            self.verbose_errors
+        }
+
+        /// Returns the version of the contract logic that constructed the storage.
+        #[ink(message, selector = "0xEC6D41E1")]
+        pub fn version(&self) -> u32 {
+            self.version
+        }
+
+        /// Returns what a token page shows in one call: name, symbol, decimals, total supply, owner,
+        /// fee params, whether transfers are paused and the contract version
+        #[ink(message, selector = "0x9DA4FD46")]
+        pub fn contract_info(&self) -> ContractInfo {
+            ContractInfo {
+                name: self.name(),
+                symbol: self.symbol(),
+                decimals: self.decimals(),
+                total_supply: self.total_supply(),
+                owner: self.owner(),
+                basis_points_rate: self.basis_points_rate(),
+                maximum_fee: self.maximum_fee(),
+                paused: self.paused(),
+                version: self.version()
+            }
+        }
+
+        /// Returns the contract owner, the zero account once the ownership is renounced.
+        #[ink(message, selector = "0xFEAEA4FA")]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// `PSP22::total_supply`: returns the total token supply.
+        #[ink(message, selector = "0x162DF8C2")]
+        pub fn total_supply(&self) -> Balance {
+            *self.total_supply
+        }
+
+        /// `PSP22::balance_of`: returns the account balance for the specified `owner`.
+        ///
+        /// Returns `0` if the account is non-existent, or if it is private and the caller is neither
+        /// the account itself, the owner nor an auditor.
+        #[ink(message, selector = "0x6568382F")]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            if !self.can_view(owner) {
+                return 0;
+            }
+            self.balance(owner)
+        }
+
+        /// Returns the `balance_of` each account, in the order given.
+        ///
+        /// # Errors
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_SIZE` accounts are passed.
+        #[ink(message, selector = "0x97D5F295")]
+        pub fn balances_of(&self, owners: Vec<AccountId>) -> Result<Vec<Balance>> {
+            if owners.len() > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+            Ok(owners.into_iter().map(|owner| self.balance_of(owner)).collect())
+        }
+
+        /// Returns the account balance of an account regardless of its privacy, or `None` if the
+        /// caller is not the owner.
+        #[ink(message, selector = "0x2803B56A")]
+        pub fn balance_of_unchecked(&self, account: AccountId) -> Option<Balance> {
+            if self.env().caller() != self.owner {
+                return None;
+            }
+            Some(self.balance(account))
+        }
+
+        /// Returns the account balance of an account.
+        fn balance(&self, account: AccountId) -> Balance {
+            self.balances.get(&account).copied().unwrap_or(0)
+        }
+
+        /// `PSP22::allowance`: returns the amount which `spender` is still allowed to withdraw from `owner`.
+        ///
+        /// Returns `0` if no allowance has been set `0`, or if `owner` is private and the caller is
+        /// neither `owner`, `spender`, the contract owner nor an auditor.
+        #[ink(message, selector = "0x4D47D921")]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            if !self.can_view(owner) && self.env().caller() != spender {
+                return 0;
+            }
+            self.allowance_value(owner, spender)
+        }
+
+        /// Returns the `allowance` of each `(owner, spender)` pair, in the order given.
+        ///
+        /// # Errors
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_SIZE` pairs are passed.
+        #[ink(message, selector = "0x3ED08818")]
+        pub fn allowances_of(&self, pairs: Vec<(AccountId, AccountId)>) -> Result<Vec<Balance>> {
+            if pairs.len() > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+            Ok(pairs.into_iter().map(|(owner, spender)| self.allowance(owner, spender)).collect())
+        }
+
+        /// Returns when the allowance of `spender` on `owner`'s account expires, or `None` if it
+        /// never does or has already expired.
+        ///
+        /// Returns `None` as well if `owner` is private and the caller is neither `owner`, `spender`,
+        /// the contract owner nor an auditor.
+        #[ink(message, selector = "0x91C01671")]
+        pub fn allowance_expiry(&self, owner: AccountId, spender: AccountId) -> Option<Timestamp> {
+            if !self.can_view(owner) && self.env().caller() != spender {
+                return None;
+            }
+            self.live_allowance(owner, spender).and_then(|entry| entry.expires_at)
+        }
+
+        /// Returns the amount which `spender` is still allowed to withdraw from `owner`.
+        fn allowance_value(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.live_allowance(owner, spender).map(|entry| entry.value).unwrap_or(0)
+        }
+
+        /// Returns the allowance of `spender` on `owner`'s account unless it has expired.
+        fn live_allowance(&self, owner: AccountId, spender: AccountId) -> Option<AllowanceEntry> {
+            let now = self.env().block_timestamp();
+            self.allowances.get(&(owner, spender))
+                .copied()
+                .filter(|entry| entry.expires_at.map_or(true, |expires_at| now <= expires_at))
+        }
+
+        /// Returns the account proposed as the next owner, if any.
+        #[ink(message, selector = "0x6E429D8D")]
+        pub fn pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Proposes `new_owner` as the next owner, replacing any previous proposal.
+        ///
+        /// The ownership only changes once `new_owner` calls `accept_ownership`, so a mistyped
+        /// account can't lock the privileged functions away.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `new_owner` is the zero account or the current owner.
+        #[ink(message, selector = "0x1F4B986A")]
+        pub fn propose_owner(&mut self, new_owner: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("propose_owner", Error::PermissionDenied));
+            }
+
+            self.validate_counterparty("propose_owner", caller, new_owner)?;
+
+            self.pending_owner = Some(new_owner);
+            Ok(())
+        }
+
+        /// Makes the caller the owner, completing the transfer proposed with `propose_owner`.
+        ///
+        /// On success an `OwnershipTransferred` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the pending owner.
+        #[ink(message, selector = "0xB55BE9F0")]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(self.fail("accept_ownership", Error::PermissionDenied));
+            }
+
+            let old = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+            self.record_owner(caller);
+            self.env().emit_event(OwnershipTransferred {
+                old,
+                new: caller
+            });
+            Ok(())
+        }
+
+        /// Give up the ownership for good, making the zero account the owner. `confirm` must be true.
+        ///
+        /// As nobody holds the key of the zero account, every owner-only function fails with
+        /// `PermissionDenied` from then on: tokens can no longer be issued or redeemed, fees and
+        /// compliance settings are fixed, and any pending ownership proposal is dropped.
+        ///
+        /// On success an `OwnershipRenounced` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `RenounceNotConfirmed` error if `confirm` is false.
+        #[ink(message, selector = "0x8C90065B")]
+        pub fn renounce_ownership(&mut self, confirm: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("renounce_ownership", Error::PermissionDenied));
+            }
+
+            if !confirm {
+                return Err(self.fail("renounce_ownership", Error::RenounceNotConfirmed));
+            }
+
+            self.owner = AccountId::default();
+            self.pending_owner = None;
+            self.record_owner(AccountId::default());
+            self.env().emit_event(OwnershipRenounced {
+                previous: caller
+            });
+            Ok(())
+        }
+
+        /// Replace the code of this contract with the code uploaded under `code_hash`, keeping all
+        /// balances and other storage. Only contract owner is allowed to call this function.
+        ///
+        /// On success a `ContractUpgraded` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidCodeHash` error if `code_hash` is the zero hash or the runtime has no code
+        /// uploaded under it.
+        #[ink(message, selector = "0x9852F7B0")]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("upgrade", Error::PermissionDenied));
+            }
+
+            if code_hash == Hash::default() {
+                return Err(self.fail("upgrade", Error::InvalidCodeHash));
+            }
+
+            let old_code_hash = code_hash_of(self.env().account_id()).unwrap_or_default();
+            if !set_code_hash(code_hash) {
+                return Err(self.fail("upgrade", Error::InvalidCodeHash));
+            }
+
+            self.env().emit_event(ContractUpgraded {
+                old_code_hash,
+                new_code_hash: code_hash
+            });
+            Ok(())
+        }
+
+        /// Returns the most recent owners in the order they took over, starting with the deployer
+        /// until more than `MAX_OWNERSHIP_HISTORY` ownership changes are recorded.
+        #[ink(message, selector = "0x1E87512C")]
+        pub fn ownership_history(&self) -> Vec<OwnerRecord> {
+            self.ownership_history.clone()
+        }
+
+        /// Returns the owner at the end of `block`, or `None` if it predates the kept history.
+        #[ink(message, selector = "0x0ACFAA84")]
+        pub fn owner_at_block(&self, block: BlockNumber) -> Option<AccountId> {
+            self.ownership_history.iter()
+                .rev()
+                .find(|record| record.from_block <= block)
+                .map(|record| record.owner)
+        }
+
+        /// Appends `owner` to the ownership history, dropping the oldest record once the history is full.
+        fn record_owner(&mut self, owner: AccountId) {
+            let from_block = self.env().block_number();
+            let history = &mut *self.ownership_history;
+            if history.len() >= MAX_OWNERSHIP_HISTORY {
+                history.remove(0);
+            }
+            history.push(OwnerRecord {
+                owner,
+                from_block
+            });
+        }
+
+        /// Transfers `value` amount of tokens from the caller's account to account `to`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's account or `to` is blacklisted.
+        ///
+        /// Returns `AccountFrozen` error if the caller's account is frozen.
+        ///
+        /// Returns `TradingNotEnabled` error if trading is not enabled yet and the caller
+        /// is neither the owner nor on the pre-launch allowlist.
+        /// 
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on
+        /// the caller's account balance.
+        /// 
+        #[ink(message, selector = "0x84A15DA1")]
+        pub fn transfer(&mut self, to: AccountId, value: Balance, extra: Option<String>) -> Result<()> {
+            self.ensure_caller_allowed("transfer")?;
+            let from = self.env().caller();
+            debug_log!("Entropy: Transfer 0x{:x} tokens to {:?} with extra: {:?}", value, to, extra);
+
+            self.send("transfer", from, to, value)
+        }
+
+        /// Transfers `value` tokens from `sender` to `to`, called by the trusted forwarder after it
+        /// authenticated `sender`.
+        ///
+        /// Behaves like `transfer` called by `sender`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UntrustedForwarder` error if the caller is not the trusted forwarder.
+        ///
+        /// Returns the errors of `transfer` otherwise.
+        #[ink(message, selector = "0xC32E3920")]
+        pub fn transfer_for(&mut self, sender: AccountId, to: AccountId, value: Balance, extra: Option<String>) -> Result<()> {
+            self.ensure_caller_allowed("transfer_for")?;
+            let from = self.forwarded_sender("transfer_for", sender)?;
+            debug_log!("Entropy: Forwarded transfer 0x{:x} tokens to {:?} with extra: {:?}", value, to, extra);
+
+            self.send("transfer_for", from, to, value)
+        }
+
+        /// Transfers `value` tokens from the caller's account to account `to`, keeping at least the
+        /// caller's keep-alive minimum on its account.
+        ///
+        /// Any transfer fee is part of `value`, so the caller's balance drops by exactly `value`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `WouldDropBelowMinimum` error if the caller's balance after the transfer would be
+        /// below its keep-alive minimum.
+        ///
+        /// Returns the errors of `transfer` otherwise.
+        #[ink(message, selector = "0xC28133F1")]
+        pub fn transfer_keep_alive(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("transfer_keep_alive")?;
+            let from = self.env().caller();
+
+            let balance = self.balance(from);
+            if value <= balance && balance - value < self.keep_alive_minimum_of(from) {
+                return Err(self.fail("transfer_keep_alive", Error::WouldDropBelowMinimum));
+            }
+
+            self.send("transfer_keep_alive", from, to, value)
+        }
+
+        /// Transfers all of the caller's tokens that it can spend to account `to`, leaving exactly its
+        /// keep-alive minimum if it has one. Returns the amount transferred, fee included.
+        ///
+        /// Tokens held by self-locks, holds or the minimum holding period stay on the caller's account.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns the errors of `transfer`.
+        #[ink(message, selector = "0xCDE552F9")]
+        pub fn transfer_all(&mut self, to: AccountId) -> Result<Balance> {
+            self.ensure_caller_allowed("transfer_all")?;
+            let from = self.env().caller();
+
+            let available = self.spendable_balance_of(from).saturating_sub(self.held_balance_of(from));
+            let above_minimum = self.balance(from).saturating_sub(self.keep_alive_minimum_of(from));
+            let value = core::cmp::min(available, above_minimum);
+
+            self.send("transfer_all", from, to, value)?;
+            Ok(value)
+        }
+
+        /// Returns the balance `transfer_keep_alive` and `transfer_all` leave on an account, `0` if none
+        #[ink(message, selector = "0xCF025E19")]
+        pub fn keep_alive_minimum_of(&self, account: AccountId) -> Balance {
+            self.keep_alive_minimums.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Set the balance `transfer_keep_alive` and `transfer_all` leave on the caller's account,
+        /// `0` clearing it
+        ///
+        /// Plain transfers, allowances and compliance actions ignore the minimum.
+        ///
+        /// On success a `KeepAliveMinimumChanged` event is emitted.
+        #[ink(message, selector = "0xB20946AF")]
+        pub fn set_keep_alive_minimum(&mut self, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if amount == 0 {
+                self.keep_alive_minimums.take(&caller);
+            } else {
+                self.keep_alive_minimums.insert(caller, amount);
+            }
+
+            self.env().emit_event(KeepAliveMinimumChanged {
+                account: caller,
+                amount
+            });
+
+            Ok(())
+        }
+
+        /// Transfers `value` amount of tokens from the caller's account to account `to`, then notifies
+        /// `to` if it is a contract by calling its `on_entropy_received(from, value, data)` message with
+        /// the amount it received, net of the transfer fee.
+        ///
+        /// While the recipient is notified, every call it makes back into the contract to change balances
+        /// fails with `ReentrantCall`. Plain `transfer` never calls the recipient.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns the errors of `transfer`.
+        ///
+        /// Reverts with `ReceiverRejected` error if `to` is a contract which rejects the tokens or fails,
+        /// undoing the transfer.
+        #[ink(message, selector = "0xACD10E50")]
+        pub fn transfer_and_call(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()> {
+            self.ensure_caller_allowed("transfer_and_call")?;
+            let from = self.env().caller();
+
+            if let Err(error) = self.validate_transfer(None, from, to, value) {
+                return Err(self.reject_transfer("transfer_and_call", from, to, value, error));
+            }
+
+            let received = value.saturating_sub(self.transfer_fee(from, to, value));
+            self.send("transfer_and_call", from, to, value)?;
+
+            if code_hash_of(to).is_some() {
+                self.notifying_receiver = true;
+                let accepted = notify_received(to, from, received, data);
+                self.notifying_receiver = false;
+                if !accepted {
+                    revert_with(self.fail("transfer_and_call", Error::ReceiverRejected));
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Transfers `value` amount of tokens from the caller's account to account `to`, tagged with
+        /// `memo`, e.g. the deposit reference of an exchange customer.
+        ///
+        /// On success a `Transfer` event is emitted, followed by a `TransferWithMemo` event carrying
+        /// the memo and its hash as a topic.
+        ///
+        /// # Errors
+        ///
+        /// Returns `MemoTooLarge` error if `memo` is longer than `MAX_MEMO_LEN` bytes.
+        ///
+        /// Returns the errors of `transfer` otherwise.
+        #[ink(message, selector = "0x3E0F2C20")]
+        pub fn transfer_with_memo(&mut self, to: AccountId, value: Balance, memo: Vec<u8>) -> Result<()> {
+            self.ensure_caller_allowed("transfer_with_memo")?;
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(self.fail("transfer_with_memo", Error::MemoTooLarge));
+            }
+            let from = self.env().caller();
+
+            self.send("transfer_with_memo", from, to, value)?;
+
+            let memo_hash = Hash::from(self.env().hash_bytes::<env::hash::Blake2x256>(&memo));
+            self.env().emit_event(TransferWithMemo {
+                from,
+                to,
+                value,
+                memo_hash,
+                memo
+            });
+
+            Ok(())
+        }
+
+        /// Transfers `value` tokens from the caller's account to `to`, locking what `to` receives
+        /// until `unlock_at`.
+        ///
+        /// The received tokens join `to`'s self-locks: they count towards its balance but can't be
+        /// spent until `unlock_at` has passed. The lock then expires on its own, and `claim_unlocked`
+        /// sweeps it.
+        ///
+        /// On success a `Transfer` event and a `TokensLocked` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidLock` error if `value` is zero or `unlock_at` has already passed.
+        ///
+        /// Returns the errors of `transfer` otherwise.
+        #[ink(message, selector = "0x46C66A27")]
+        pub fn transfer_locked(&mut self, to: AccountId, value: Balance, unlock_at: Timestamp) -> Result<()> {
+            self.ensure_caller_allowed("transfer_locked")?;
+            if value == 0 || unlock_at <= self.env().block_timestamp() {
+                return Err(self.fail("transfer_locked", Error::InvalidLock));
+            }
+            let from = self.env().caller();
+            let received = value - self.transfer_fee(from, to, value);
+
+            self.send("transfer_locked", from, to, value)?;
+            if received > 0 {
+                self.add_lock(to, received, unlock_at);
+            }
+
+            Ok(())
+        }
+
+        /// Runs the checks of `transfer` before transferring `value` tokens from `from` to `to`.
+        fn send(&mut self, message: &'static str, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            if let Err(error) = self.validate_transfer(None, from, to, value) {
+                return Err(self.reject_transfer(message, from, to, value, error));
+            }
+
+            if let Err(error) = self.transfer_from_to(from, to, value) {
+                return Err(self.reject_transfer(message, from, to, value, error));
+            }
+            Ok(())
+        }
+
+        /// Returns the nonce the next signed transfer of an account must carry
+        #[ink(message, selector = "0x32544995")]
+        pub fn nonce_of(&self, account: AccountId) -> u64 {
+            self.nonces.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Returns the nonce following `nonce`, checked to be the current nonce of `account`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidNonce` error if `nonce` is not the current nonce of `account`.
+        ///
+        /// Returns `Overflow` error if `nonce` is the last one, which can't be used.
+        fn check_nonce(&mut self, message: &'static str, account: AccountId, nonce: u64) -> Result<u64> {
+            if nonce != self.nonce_of(account) {
+                return Err(self.fail(message, Error::InvalidNonce));
+            }
+            self.or_overflow(message, nonce.checked_add(1))
+        }
+
+        /// Marks the current nonce of `account` as used by a signed action, `next_nonce` being the
+        /// one `check_nonce` returned for it.
+        ///
+        /// Signed transfers, permits and Ethereum address links share one nonce sequence per account,
+        /// so using a nonce for one voids every other signature made with it.
+        fn consume_nonce(&mut self, account: AccountId, next_nonce: u64) {
+            self.nonces.insert(account, next_nonce);
+        }
+
+        /// Returns the hash `from` signs to authorize a transfer with `transfer_with_signature`
+        ///
+        /// The hash commits to this contract's account so a signature can't be replayed on another deployment.
+        #[ink(message, selector = "0x9C1EA94D")]
+        pub fn transfer_hash(&self, from: AccountId, to: AccountId, value: Balance, deadline: Timestamp, nonce: u64) -> [u8; 32] {
+            self.env().hash_encoded::<env::hash::Blake2x256, _>(&(self.env().account_id(), from, to, value, deadline, nonce))
+        }
+
+        /// Transfers `value` tokens from `from` to `to` on behalf of any relayer, authorized by `from`'s signature
+        ///
+        /// `signature` is `from`'s sr25519 signature of `transfer_hash(from, to, value, deadline, nonce)`,
+        /// verified through the runtime's chain extension. On success the nonce of `from` is incremented
+        /// and a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `SignatureExpired` error if the block timestamp is past `deadline`.
+        ///
+        /// Returns `InvalidNonce` error if `nonce` is not the current nonce of `from`.
+        ///
+        /// Returns `InvalidSignature` error if `signature` is not `from`'s signature of the transfer.
+        ///
+        /// Returns any error `transfer` returns when called by `from`.
+        #[ink(message, selector = "0x24BAA7AC")]
+        pub fn transfer_with_signature(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            nonce: u64,
+            signature: [u8; 64],
+        ) -> Result<()> {
+            if self.env().block_timestamp() > deadline {
+                return Err(self.fail("transfer_with_signature", Error::SignatureExpired));
+            }
+
+            let next_nonce = self.check_nonce("transfer_with_signature", from, nonce)?;
+
+            if !sr25519_verify(from, self.transfer_hash(from, to, value, deadline, nonce), signature) {
+                return Err(self.fail("transfer_with_signature", Error::InvalidSignature));
+            }
+
+            self.send("transfer_with_signature", from, to, value)?;
+            self.consume_nonce(from, next_nonce);
+
+            Ok(())
+        }
+
+        /// Returns the hash `owner` signs to approve `spender` with `permit` or `permit_ecdsa`
+        ///
+        /// The hash is tagged as a permit, so a transfer signature can't be replayed as one, and commits
+        /// to this contract's account so a signature can't be replayed on another deployment.
+        #[ink(message, selector = "0xCFC862CA")]
+        pub fn permit_hash(&self, owner: AccountId, spender: AccountId, value: Balance, deadline: Timestamp, nonce: u64) -> [u8; 32] {
+            self.env().hash_encoded::<env::hash::Blake2x256, _>(&(PERMIT_DOMAIN, self.env().account_id(), owner, spender, value, deadline, nonce))
+        }
+
+        /// Sets the allowance of `spender` on `owner`'s account to `value` on behalf of any relayer,
+        /// authorized by `owner`'s sr25519 signature of `permit_hash(owner, spender, value, deadline, nonce)`
+        ///
+        /// Permits share `owner`'s nonce with signed transfers. On success the nonce is incremented
+        /// and an `Approval` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `SignatureExpired` error if the block timestamp is past `deadline`.
+        ///
+        /// Returns `InvalidNonce` error if `nonce` is not the current nonce of `owner`.
+        ///
+        /// Returns `InvalidSignature` error if `signature` is not `owner`'s signature of the permit.
+        ///
+        /// Returns `InvalidAccount` error if `spender` is the zero account or `owner`'s account.
+        #[ink(message, selector = "0x84AFF499")]
+        pub fn permit(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            nonce: u64,
+            signature: [u8; 64],
+        ) -> Result<()> {
+            let next_nonce = self.check_permit("permit", owner, deadline, nonce)?;
+
+            if !sr25519_verify(owner, self.permit_hash(owner, spender, value, deadline, nonce), signature) {
+                return Err(self.fail("permit", Error::InvalidSignature));
+            }
+
+            self.execute_permit("permit", owner, spender, value, next_nonce)
+        }
+
+        /// Sets the allowance of `spender` on `owner`'s account to `value` on behalf of any relayer,
+        /// authorized by a secp256k1 signature of `permit_hash(owner, spender, value, deadline, nonce)`
+        /// made with the Ethereum key linked to `owner`
+        ///
+        /// Permits share `owner`'s nonce with signed transfers. On success the nonce is incremented
+        /// and an `Approval` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `SignatureExpired` error if the block timestamp is past `deadline`.
+        ///
+        /// Returns `InvalidNonce` error if `nonce` is not the current nonce of `owner`.
+        ///
+        /// Returns `EthAddressNotLinked` error if `owner` has no linked Ethereum address.
+        ///
+        /// Returns `InvalidSignature` error if `signature` wasn't made by the linked key.
+        ///
+        /// Returns `InvalidAccount` error if `spender` is the zero account or `owner`'s account.
+        #[ink(message, selector = "0x31F0F1F2")]
+        pub fn permit_ecdsa(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            nonce: u64,
+            signature: EcdsaSignature,
+        ) -> Result<()> {
+            let next_nonce = self.check_permit("permit_ecdsa", owner, deadline, nonce)?;
+
+            let eth_address = match self.eth_address_of(owner) {
+                Some(eth_address) => eth_address,
+                None => return Err(self.fail("permit_ecdsa", Error::EthAddressNotLinked))
+            };
+            if ecdsa_recover_eth_address(signature, self.permit_hash(owner, spender, value, deadline, nonce)) != Some(eth_address) {
+                return Err(self.fail("permit_ecdsa", Error::InvalidSignature));
+            }
+
+            self.execute_permit("permit_ecdsa", owner, spender, value, next_nonce)
+        }
+
+        /// Checks the deadline and nonce of a permit by `owner`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `SignatureExpired` error if the block timestamp is past `deadline`.
+        ///
+        /// Returns `InvalidNonce` error if `nonce` is not the current nonce of `owner`, or `Overflow`
+        /// error if it is the last one.
+        fn check_permit(&mut self, message: &'static str, owner: AccountId, deadline: Timestamp, nonce: u64) -> Result<u64> {
+            if self.env().block_timestamp() > deadline {
+                return Err(self.fail(message, Error::SignatureExpired));
+            }
+
+            self.check_nonce(message, owner, nonce)
+        }
+
+        /// Sets the allowance of a verified permit and consumes its nonce.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidAccount` error if `spender` is the zero account or `owner`'s account.
+        ///
+        /// Returns `AccountFrozen` error if `owner`'s account is frozen.
+        fn execute_permit(&mut self, message: &'static str, owner: AccountId, spender: AccountId, value: Balance, next_nonce: u64) -> Result<()> {
+            self.validate_counterparty(message, owner, spender)?;
+            self.ensure_not_frozen(message, owner)?;
+            self.set_allowance_until(owner, spender, value, None);
+            self.consume_nonce(owner, next_nonce);
+            Ok(())
+        }
+
+        /// Returns the Ethereum address linked to an account, or `None` if there is none
+        #[ink(message, selector = "0x7044B408")]
+        pub fn eth_address_of(&self, account: AccountId) -> Option<[u8; 20]> {
+            self.eth_addresses.get(&account).copied()
+        }
+
+        /// Returns the account an Ethereum address is linked to, or `None` if there is none
+        #[ink(message, selector = "0xA410D279")]
+        pub fn account_of_eth_address(&self, eth_address: [u8; 20]) -> Option<AccountId> {
+            self.eth_accounts.get(&eth_address).copied()
+        }
+
+        /// Returns the hash the key of `eth_address` signs to prove it controls the address when
+        /// linking it to `account` with the account's current nonce
+        #[ink(message, selector = "0x9E9EEF63")]
+        pub fn link_eth_hash(&self, account: AccountId, eth_address: [u8; 20], nonce: u64) -> [u8; 32] {
+            self.env().hash_encoded::<env::hash::Blake2x256, _>(&(LINK_ETH_DOMAIN, self.env().account_id(), account, eth_address, nonce))
+        }
+
+        /// Link an Ethereum address to the caller's account, letting its key sign permits for the account
+        ///
+        /// `proof_signature` is the address's secp256k1 signature of `link_eth_hash(caller, eth_address, nonce)`
+        /// with the caller's current nonce, which is then incremented. A previously linked address is replaced.
+        ///
+        /// On success an `EthAddressLinked` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `EthAddressLinked` error if `eth_address` is linked to another account.
+        ///
+        /// Returns `InvalidSignature` error if `proof_signature` wasn't made by the key of `eth_address`.
+        #[ink(message, selector = "0x6C33B355")]
+        pub fn link_eth_address(&mut self, eth_address: [u8; 20], proof_signature: EcdsaSignature) -> Result<()> {
+            let caller = self.env().caller();
+
+            if self.account_of_eth_address(eth_address).map_or(false, |account| account != caller) {
+                return Err(self.fail("link_eth_address", Error::EthAddressLinked));
+            }
+
+            let nonce = self.nonce_of(caller);
+            let next_nonce = self.check_nonce("link_eth_address", caller, nonce)?;
+            if ecdsa_recover_eth_address(proof_signature, self.link_eth_hash(caller, eth_address, nonce)) != Some(eth_address) {
+                return Err(self.fail("link_eth_address", Error::InvalidSignature));
+            }
+
+            if let Some(previous) = self.eth_addresses.insert(caller, eth_address) {
+                self.eth_accounts.take(&previous);
+            }
+            self.eth_accounts.insert(eth_address, caller);
+            self.consume_nonce(caller, next_nonce);
+
+            self.env().emit_event(EthAddressLinked {
+                account: caller,
+                eth_address
+            });
+
+            Ok(())
+        }
+
+        /// Unlink the Ethereum address linked to the caller's account, so its key can no longer sign permits
+        ///
+        /// On success an `EthAddressUnlinked` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `EthAddressNotLinked` error if the caller's account has no linked Ethereum address.
+        #[ink(message, selector = "0x395945A4")]
+        pub fn unlink_eth_address(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+
+            let eth_address = match self.eth_addresses.take(&caller) {
+                Some(eth_address) => eth_address,
+                None => return Err(self.fail("unlink_eth_address", Error::EthAddressNotLinked))
+            };
+            self.eth_accounts.take(&eth_address);
+
+            self.env().emit_event(EthAddressUnlinked {
+                account: caller,
+                eth_address
+            });
+
+            Ok(())
+        }
+
+        /// Returns a pending claimable transfer, or `None` if the id is unknown, claimed or refunded
+        #[ink(message, selector = "0x0CB4BD02")]
+        pub fn pending_transfer(&self, id: u64) -> Option<PendingTransfer> {
+            self.pending_transfers.get(&id).copied()
+        }
+
+        /// Moves `value` tokens from the caller's account into escrow for `to`, who must claim them
+        /// with `claim_incoming` within `timeout_ms`, returning the id of the pending transfer.
+        ///
+        /// Once the timeout passes unclaimed, anyone can return the tokens to the caller with
+        /// `refund_unclaimed`. The fee and the recipient's compliance screening apply when the
+        /// transfer is claimed.
+        ///
+        /// On success a `ClaimableTransferCreated` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account or the caller's account.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the caller's account fails compliance screening.
+        ///
+        /// Returns `TradingNotEnabled` error if trading is not enabled yet and the caller
+        /// is neither the owner nor on the pre-launch allowlist.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on
+        /// the caller's account balance.
+        #[ink(message, selector = "0xA968A677")]
+        pub fn transfer_claimable(&mut self, to: AccountId, value: Balance, timeout_ms: u64) -> Result<u64> {
+            self.ensure_caller_allowed("transfer_claimable")?;
+            let from = self.env().caller();
+
+            self.ensure_not_paused("transfer_claimable")?;
+            self.validate_counterparty("transfer_claimable", from, to)?;
+            self.ensure_compliant("transfer_claimable", from, Direction::Debit)?;
+
+            if !self.is_trading_allowed(from) {
+                return Err(self.fail("transfer_claimable", Error::TradingNotEnabled));
+            }
+
+            if self.spendable_balance_of(from) < value {
+                return Err(self.fail("transfer_claimable", Error::InsufficientBalance));
+            }
+
+            let escrowed = self.or_overflow("transfer_claimable", self.escrowed.checked_add(value))?;
+            self.move_balance("transfer_claimable", from, self.env().account_id(), value)?;
+            self.escrowed = escrowed;
+
+            let id = self.last_pending_id + 1;
+            self.last_pending_id = id;
+            let expiry = self.env().block_timestamp().saturating_add(timeout_ms);
+            self.pending_transfers.insert(id, PendingTransfer {
+                from,
+                to,
+                value,
+                expiry
+            });
+
+            self.env().emit_event(ClaimableTransferCreated {
+                id,
+                from,
+                to,
+                value,
+                expiry
+            });
+
+            Ok(id)
+        }
+
+        /// Claim a pending transfer to the caller before its timeout, charging the transfer fee
+        ///
+        /// The transfer is assigned the next transaction id and recorded for reversal like any other.
+        ///
+        /// On success `Transfer` events and a `ClaimableTransferClaimed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownTransfer` error if the id is unknown, claimed or refunded.
+        ///
+        /// Returns `PermissionDenied` error if the caller is not the recipient.
+        ///
+        /// Returns `ClaimWindowClosed` error if the timeout has passed.
+        ///
+        /// Returns `AccountBlackListed` error if the sender's or the caller's account is blacklisted, or
+        /// `AccountFrozen` error if the sender's account is frozen.
+        #[ink(message, selector = "0xAE41CA16")]
+        pub fn claim_incoming(&mut self, id: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("claim_incoming")?;
+
+            let pending = match self.pending_transfer(id) {
+                Some(pending) => pending,
+                None => {
+                    return Err(self.fail("claim_incoming", Error::UnknownTransfer));
+                }
+            };
+
+            if caller != pending.to {
+                return Err(self.fail("claim_incoming", Error::PermissionDenied));
+            }
+
+            if self.env().block_timestamp() > pending.expiry {
+                return Err(self.fail("claim_incoming", Error::ClaimWindowClosed));
+            }
+
+            self.ensure_compliant("claim_incoming", pending.to, Direction::Credit)?;
+            self.ensure_compliant("claim_incoming", pending.from, Direction::Debit)?;
+
+            let escrow = self.env().account_id();
+            let fee = self.transfer_fee(pending.from, pending.to, pending.value);
+            let escrowed = self.or_overflow("claim_incoming", self.escrowed.checked_sub(pending.value))?;
+            if self.credit_overflows(escrow, pending.to, pending.value, fee) {
+                return Err(self.fail("claim_incoming", Error::Overflow));
+            }
+
+            self.move_balance("claim_incoming", escrow, pending.to, pending.value - fee)?;
+            if fee > 0 {
+                self.move_balance("claim_incoming", escrow, self.fee_collector, fee)?;
+                self.accrue_points(pending.from, pending.value);
+                self.accrue_rebate(pending.from, fee);
+            }
+            self.pending_transfers.take(&id);
+            self.escrowed = escrowed;
+            self.record_transfer(pending.from, pending.to, pending.value - fee, fee);
+
+            self.env().emit_event(ClaimableTransferClaimed {
+                id,
+                to: pending.to,
+                value: pending.value - fee
+            });
+
+            Ok(())
+        }
+
+        /// Return the tokens of a pending transfer to its sender once its timeout has passed unclaimed
+        ///
+        /// Anyone can call this function; the tokens always go back to the sender.
+        ///
+        /// On success a `Transfer` event and a `ClaimableTransferRefunded` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UnknownTransfer` error if the id is unknown, claimed or refunded.
+        ///
+        /// Returns `ClaimWindowOpen` error if the timeout has not passed yet.
+        ///
+        /// Returns `AccountBlackListed` error if the sender's account is blacklisted, in which case the
+        /// owner may move the tokens into a treasury with `seize_unclaimed_transfer`.
+        #[ink(message, selector = "0xECB5C77C")]
+        pub fn refund_unclaimed(&mut self, id: u64) -> Result<()> {
+            let pending = match self.pending_transfer(id) {
+                Some(pending) => pending,
+                None => {
+                    return Err(self.fail("refund_unclaimed", Error::UnknownTransfer));
+                }
+            };
+
+            if self.env().block_timestamp() <= pending.expiry {
+                return Err(self.fail("refund_unclaimed", Error::ClaimWindowOpen));
+            }
+
+            self.ensure_compliant("refund_unclaimed", pending.from, Direction::Credit)?;
+
+            let escrowed = self.or_overflow("refund_unclaimed", self.escrowed.checked_sub(pending.value))?;
+            self.move_balance("refund_unclaimed", self.env().account_id(), pending.from, pending.value)?;
+            self.escrowed = escrowed;
+            self.pending_transfers.take(&id);
+
+            self.env().emit_event(ClaimableTransferRefunded {
+                id,
+                from: pending.from,
+                value: pending.value
+            });
+
+            Ok(())
+        }
+
+        /// Returns the gift escrowed under a code hash, or `None` if there is none
+        #[ink(message, selector = "0x5955341C")]
+        pub fn gift(&self, code_hash: Hash) -> Option<Gift> {
+            self.gifts.get(&code_hash).copied()
+        }
+
+        /// Escrow `amount` of the caller's tokens as a gift redeemable until `expiry` by whoever
+        /// presents the code whose Blake2x256 hash is `code_hash`.
+        ///
+        /// On success a `Transfer` event and a `GiftCreated` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the caller's account fails compliance screening.
+        ///
+        /// Returns `TradingNotEnabled` error if trading is not enabled yet and the caller
+        /// is neither the owner nor on the pre-launch allowlist.
+        ///
+        /// Returns `InvalidGift` error if `amount` is zero, `expiry` has already passed or a gift
+        /// was ever created under `code_hash`.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on
+        /// the caller's account balance.
+        #[ink(message, selector = "0x0B4DE74D")]
+        pub fn create_gift(&mut self, code_hash: Hash, amount: Balance, expiry: Timestamp) -> Result<()> {
+            self.ensure_caller_allowed("create_gift")?;
+            let creator = self.env().caller();
+
+            self.ensure_not_paused("create_gift")?;
+            self.ensure_compliant("create_gift", creator, Direction::Debit)?;
+
+            if !self.is_trading_allowed(creator) {
+                return Err(self.fail("create_gift", Error::TradingNotEnabled));
+            }
+
+            if amount == 0 || expiry <= self.env().block_timestamp() || self.gifts.contains_key(&code_hash) {
+                return Err(self.fail("create_gift", Error::InvalidGift));
+            }
+
+            if self.spendable_balance_of(creator) < amount {
+                return Err(self.fail("create_gift", Error::InsufficientBalance));
+            }
+
+            let escrowed = self.or_overflow("create_gift", self.escrowed.checked_add(amount))?;
+            self.move_balance("create_gift", creator, self.env().account_id(), amount)?;
+            self.escrowed = escrowed;
+            self.gifts.insert(code_hash, Gift {
+                creator,
+                amount,
+                expiry,
+                spent: false
+            });
+
+            self.env().emit_event(GiftCreated {
+                code_hash,
+                creator,
+                amount,
+                expiry
+            });
+
+            Ok(())
+        }
+
+        /// Redeem the gift whose code is `code_preimage`, paying its tokens to the caller
+        ///
+        /// The code is revealed by the call, so it should only be submitted by its holder's own account.
+        ///
+        /// On success a `Transfer` event and a `GiftRedeemed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownGift` error if no gift was created under the code's hash.
+        ///
+        /// Returns `GiftSpent` error if the gift was already redeemed or reclaimed.
+        ///
+        /// Returns `ClaimWindowClosed` error if the gift has expired.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's account is blacklisted.
+        #[ink(message, selector = "0xE29C8E5E")]
+        pub fn redeem_gift(&mut self, code_preimage: Vec<u8>) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("redeem_gift")?;
+
+            let code_hash = Hash::from(self.env().hash_bytes::<env::hash::Blake2x256>(&code_preimage));
+            let mut gift = self.spendable_gift("redeem_gift", code_hash)?;
+
+            if self.env().block_timestamp() > gift.expiry {
+                return Err(self.fail("redeem_gift", Error::ClaimWindowClosed));
+            }
+
+            self.ensure_compliant("redeem_gift", caller, Direction::Credit)?;
+
+            let escrowed = self.or_overflow("redeem_gift", self.escrowed.checked_sub(gift.amount))?;
+            self.move_balance("redeem_gift", self.env().account_id(), caller, gift.amount)?;
+            self.escrowed = escrowed;
+            gift.spent = true;
+            self.gifts.insert(code_hash, gift);
+
+            self.env().emit_event(GiftRedeemed {
+                code_hash,
+                account: caller,
+                amount: gift.amount
+            });
+
+            Ok(())
+        }
+
+        /// Return the tokens of an expired, unredeemed gift to its creator, who must be the caller
+        ///
+        /// On success a `Transfer` event and a `GiftReclaimed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UnknownGift` error if no gift was created under `code_hash`.
+        ///
+        /// Returns `GiftSpent` error if the gift was already redeemed or reclaimed.
+        ///
+        /// Returns `PermissionDenied` error if the caller is not the gift's creator.
+        ///
+        /// Returns `ClaimWindowOpen` error if the gift has not expired yet.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's account is blacklisted, in which case the
+        /// owner may move the tokens into a treasury with `seize_expired_gift`.
+        #[ink(message, selector = "0x9F411E7A")]
+        pub fn reclaim_expired_gift(&mut self, code_hash: Hash) -> Result<()> {
+            let caller = self.env().caller();
+
+            let mut gift = self.spendable_gift("reclaim_expired_gift", code_hash)?;
+
+            if caller != gift.creator {
+                return Err(self.fail("reclaim_expired_gift", Error::PermissionDenied));
+            }
+
+            if self.env().block_timestamp() <= gift.expiry {
+                return Err(self.fail("reclaim_expired_gift", Error::ClaimWindowOpen));
+            }
+
+            self.ensure_compliant("reclaim_expired_gift", caller, Direction::Credit)?;
+
+            let escrowed = self.or_overflow("reclaim_expired_gift", self.escrowed.checked_sub(gift.amount))?;
+            self.move_balance("reclaim_expired_gift", self.env().account_id(), caller, gift.amount)?;
+            self.escrowed = escrowed;
+            gift.spent = true;
+            self.gifts.insert(code_hash, gift);
+
+            self.env().emit_event(GiftReclaimed {
+                code_hash,
+                creator: caller,
+                amount: gift.amount
+            });
+
+            Ok(())
+        }
+
+        /// Returns the unspent gift escrowed under `code_hash`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UnknownGift` error if there is none, or `GiftSpent` error if it was spent.
+        fn spendable_gift(&mut self, message: &'static str, code_hash: Hash) -> Result<Gift> {
+            let gift = match self.gift(code_hash) {
+                Some(gift) => gift,
+                None => {
+                    return Err(self.fail(message, Error::UnknownGift));
+                }
+            };
+            if gift.spent {
+                return Err(self.fail(message, Error::GiftSpent));
+            }
+            Ok(gift)
+        }
+
+        /// Returns the hold created under `hold_id`, `None` once it was captured or released
+        #[ink(message, selector = "0x38F09F5B")]
+        pub fn hold(&self, hold_id: u64) -> Option<Hold> {
+            self.holds.get(&hold_id).copied()
+        }
+
+        /// Returns the part of an account's balance earmarked by its unsettled holds
+        #[ink(message, selector = "0x68CCE803")]
+        pub fn on_hold_balance_of(&self, account: AccountId) -> Balance {
+            self.on_hold.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Put `value` of the caller's tokens on hold for `to` until `expires_at`, returning the id
+        /// of the hold.
+        ///
+        /// The tokens stay on the caller's account and count towards its balance, but can't be spent
+        /// until `to` captures the hold or it is released.
+        ///
+        /// On success a `HoldCreated` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the caller's account fails compliance screening.
+        ///
+        /// Returns `TradingNotEnabled` error if trading is not enabled yet and the caller
+        /// is neither the owner nor on the pre-launch allowlist.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account or the caller's account.
+        ///
+        /// Returns `InvalidHold` error if `value` is zero or `expires_at` has already passed.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on
+        /// the caller's account balance.
+        #[ink(message, selector = "0xF2C853AC")]
+        pub fn create_hold(&mut self, to: AccountId, value: Balance, expires_at: Timestamp) -> Result<u64> {
+            self.ensure_caller_allowed("create_hold")?;
+            let payer = self.env().caller();
+
+            self.ensure_not_paused("create_hold")?;
+            self.ensure_compliant("create_hold", payer, Direction::Debit)?;
+
+            if !self.is_trading_allowed(payer) {
+                return Err(self.fail("create_hold", Error::TradingNotEnabled));
+            }
+
+            self.validate_counterparty("create_hold", payer, to)?;
+
+            if value == 0 || expires_at <= self.env().block_timestamp() {
+                return Err(self.fail("create_hold", Error::InvalidHold));
+            }
+
+            if self.spendable_balance_of(payer) < value {
+                return Err(self.fail("create_hold", Error::InsufficientBalance));
+            }
+
+            let hold_id = self.or_overflow("create_hold", self.hold_count.checked_add(1))?;
+            let on_hold = self.or_overflow("create_hold", self.on_hold_balance_of(payer).checked_add(value))?;
+            self.hold_count = hold_id;
+            self.holds.insert(hold_id, Hold {
+                payer,
+                to,
+                value,
+                expires_at
+            });
+            self.on_hold.insert(payer, on_hold);
+
+            self.env().emit_event(HoldCreated {
+                id: hold_id,
+                payer,
+                to,
+                value,
+                expires_at
+            });
+
+            Ok(hold_id)
+        }
+
+        /// Capture the hold `hold_id`, moving its tokens from the payer to the caller, who must be its
+        /// recipient. No fee is charged.
+        ///
+        /// On success a `Transfer` event and a `HoldCaptured` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownHold` error if no unsettled hold exists under `hold_id`.
+        ///
+        /// Returns `PermissionDenied` error if the caller is not the hold's recipient.
+        ///
+        /// Returns `ClaimWindowClosed` error if the hold has expired.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the payer's or the caller's account
+        /// fails compliance screening.
+        ///
+        /// Returns `InsufficientBalance` error if the payer's balance no longer covers the hold.
+        #[ink(message, selector = "0xB6EFDCA7")]
+        pub fn capture_hold(&mut self, hold_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("capture_hold")?;
+
+            let hold = self.unsettled_hold("capture_hold", hold_id)?;
+
+            if caller != hold.to {
+                return Err(self.fail("capture_hold", Error::PermissionDenied));
+            }
+
+            if self.env().block_timestamp() > hold.expires_at {
+                return Err(self.fail("capture_hold", Error::ClaimWindowClosed));
+            }
+
+            self.ensure_compliant("capture_hold", hold.payer, Direction::Debit)?;
+            self.ensure_compliant("capture_hold", caller, Direction::Credit)?;
+
+            if self.balance(hold.payer) < hold.value {
+                return Err(self.fail("capture_hold", Error::InsufficientBalance));
+            }
+
+            self.move_balance("capture_hold", hold.payer, caller, hold.value)?;
+            self.settle_hold(hold_id, hold);
+
+            self.env().emit_event(HoldCaptured {
+                id: hold_id,
+                payer: hold.payer,
+                to: caller,
+                value: hold.value
+            });
+
+            Ok(())
+        }
+
+        /// Release the hold `hold_id`, leaving its tokens spendable by the payer again
+        ///
+        /// The recipient may release a hold at any time, the payer only once it has expired.
+        ///
+        /// On success a `HoldReleased` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UnknownHold` error if no unsettled hold exists under `hold_id`.
+        ///
+        /// Returns `PermissionDenied` error if the caller is neither the hold's payer nor its recipient.
+        ///
+        /// Returns `ClaimWindowOpen` error if the caller is the payer and the hold has not expired yet.
+        #[ink(message, selector = "0xB4643B60")]
+        pub fn release_hold(&mut self, hold_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            let hold = self.unsettled_hold("release_hold", hold_id)?;
+
+            if caller != hold.to && caller != hold.payer {
+                return Err(self.fail("release_hold", Error::PermissionDenied));
+            }
+
+            if caller != hold.to && self.env().block_timestamp() <= hold.expires_at {
+                return Err(self.fail("release_hold", Error::ClaimWindowOpen));
+            }
+
+            self.settle_hold(hold_id, hold);
+
+            self.env().emit_event(HoldReleased {
+                id: hold_id,
+                payer: hold.payer,
+                value: hold.value
+            });
+
+            Ok(())
+        }
+
+        /// Returns the unsettled hold created under `hold_id`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UnknownHold` error if there is none.
+        fn unsettled_hold(&mut self, message: &'static str, hold_id: u64) -> Result<Hold> {
+            match self.hold(hold_id) {
+                Some(hold) => Ok(hold),
+                None => Err(self.fail(message, Error::UnknownHold))
+            }
+        }
+
+        /// Removes a captured or released hold, no longer earmarking its tokens on the payer's account.
+        fn settle_hold(&mut self, hold_id: u64, hold: Hold) {
+            self.holds.take(&hold_id);
+            let on_hold = self.on_hold_balance_of(hold.payer).saturating_sub(hold.value);
+            if on_hold == 0 {
+                self.on_hold.take(&hold.payer);
+            } else {
+                self.on_hold.insert(hold.payer, on_hold);
+            }
+        }
+
+        /// Allows `spender` to withdraw from the caller's account multiple times, up to
+        /// the `value` amount.
+        ///
+        /// If this function is called again it overwrites the current allowance with `value`.
+        /// While strict approvals are on, a nonzero allowance must be set to zero first.
+        ///
+        /// An `Approval` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `InvalidAccount` error if `spender` is the zero account or the caller's account.
+        ///
+        /// Returns `AccountFrozen` error if the caller's account is frozen.
+        ///
+        /// Returns `NonZeroAllowanceExists` error if strict approvals are on and both `value` and
+        /// the current allowance are nonzero.
+        #[ink(message, selector = "0x681266A0")]
+        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("approve")?;
+            let owner = self.env().caller();
+            self.approve_from("approve", owner, spender, value, None)
+        }
+
+        /// Allows `spender` to withdraw from `sender`'s account up to the `value` amount, called by the
+        /// trusted forwarder after it authenticated `sender`.
+        ///
+        /// Behaves like `approve` called by `sender`.
+        ///
+        /// An `Approval` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UntrustedForwarder` error if the caller is not the trusted forwarder.
+        ///
+        /// Returns the errors of `approve` otherwise.
+        #[ink(message, selector = "0x3D209FD5")]
+        pub fn approve_for(&mut self, sender: AccountId, spender: AccountId, value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("approve_for")?;
+            let owner = self.forwarded_sender("approve_for", sender)?;
+            self.approve_from("approve_for", owner, spender, value, None)
+        }
+
+        /// Allows `spender` to withdraw from the caller's account up to the `value` amount until
+        /// `expires_at`, after which the allowance counts as zero.
+        ///
+        /// Behaves like `approve` otherwise.
+        ///
+        /// An `Approval` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns the errors of `approve`.
+        #[ink(message, selector = "0xDD0FCF68")]
+        pub fn approve_with_expiry(&mut self, spender: AccountId, value: Balance, expires_at: Timestamp) -> Result<()> {
+            self.ensure_caller_allowed("approve_with_expiry")?;
+            let owner = self.env().caller();
+            self.approve_from("approve_with_expiry", owner, spender, value, Some(expires_at))
+        }
+
+        /// Runs the checks of `approve` before setting the allowance of `spender` on `owner`'s account.
+        fn approve_from(&mut self, message: &'static str, owner: AccountId, spender: AccountId, value: Balance, expires_at: Option<Timestamp>) -> Result<()> {
+            self.validate_counterparty(message, owner, spender)?;
+            self.ensure_not_frozen(message, owner)?;
+            if self.strict_approvals && value > 0 && self.allowance_value(owner, spender) > 0 {
+                return Err(self.fail(message, Error::NonZeroAllowanceExists));
+            }
+            self.set_allowance_until(owner, spender, value, expires_at);
+            Ok(())
+        }
+
+        /// Raises the allowance of `spender` on the caller's account by `delta_value`, regardless
+        /// of strict approvals.
+        ///
+        /// Unlike `approve`, this can't be front-run by the spender to spend both the old and the
+        /// new allowance. The allowance saturates at `Balance::MAX`.
+        ///
+        /// An `Approval` event carrying the new allowance is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `InvalidAccount` error if `spender` is the zero account or the caller's account.
+        ///
+        /// Returns `AccountFrozen` error if the caller's account is frozen.
+        #[ink(message, selector = "0xF551D422")]
+        pub fn increase_allowance(&mut self, spender: AccountId, delta_value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("increase_allowance")?;
+            let owner = self.env().caller();
+            self.validate_counterparty("increase_allowance", owner, spender)?;
+            self.ensure_not_frozen("increase_allowance", owner)?;
+            let allowance = self.allowance_value(owner, spender);
+            self.set_allowance(owner, spender, allowance.saturating_add(delta_value));
+            Ok(())
+        }
+
+        /// Lowers the allowance of `spender` on the caller's account by `delta_value`, regardless
+        /// of strict approvals.
+        ///
+        /// If the spender already spent part of the allowance, lowering it by more than what is left
+        /// fails rather than zeroing it, so the caller notices.
+        ///
+        /// An `Approval` event carrying the new allowance is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `InvalidAccount` error if `spender` is the zero account or the caller's account.
+        ///
+        /// Returns `InsufficientAllowance` error if the allowance is less than `delta_value`.
+        #[ink(message, selector = "0xF998EBD9")]
+        pub fn decrease_allowance(&mut self, spender: AccountId, delta_value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("decrease_allowance")?;
+            let owner = self.env().caller();
+            self.validate_counterparty("decrease_allowance", owner, spender)?;
+            let allowance = self.allowance_value(owner, spender);
+            if allowance < delta_value {
+                return Err(self.fail("decrease_allowance", Error::InsufficientAllowance));
+            }
+            self.set_allowance(owner, spender, allowance - delta_value);
+            Ok(())
+        }
+
+        /// Sets the allowance of `spender` on `owner`'s account, keeping the expiry of a live
+        /// allowance, and emits an `Approval` event.
+        fn set_allowance(&mut self, owner: AccountId, spender: AccountId, value: Balance) {
+            let expires_at = self.live_allowance(owner, spender).and_then(|entry| entry.expires_at);
+            self.set_allowance_until(owner, spender, value, expires_at);
+        }
+
+        /// Sets the allowance of `spender` on `owner`'s account to expire at `expires_at`
+        /// and emits an `Approval` event.
+        fn set_allowance_until(&mut self, owner: AccountId, spender: AccountId, value: Balance, expires_at: Option<Timestamp>) {
+            self.write_allowance(owner, spender, value, expires_at);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                value,
+            });
+        }
+
+        /// Lowers the allowance of `spender` on `owner`'s account to what is left after a spend,
+        /// keeping its expiry, without emitting an event.
+        fn spend_allowance(&mut self, owner: AccountId, spender: AccountId, value: Balance) {
+            let expires_at = self.live_allowance(owner, spender).and_then(|entry| entry.expires_at);
+            self.write_allowance(owner, spender, value, expires_at);
+        }
+
+        /// Stores the allowance of `spender` on `owner`'s account, removing its entry once it is zero.
+        fn write_allowance(&mut self, owner: AccountId, spender: AccountId, value: Balance, expires_at: Option<Timestamp>) {
+            if value == 0 {
+                self.allowances.take(&(owner, spender));
+            } else {
+                self.allowances.insert((owner, spender), AllowanceEntry { value, expires_at });
+            }
+        }
+
+        /// Stores the allowance of `spender` for transfers from `owner` to `recipient`, removing its
+        /// entry once it is zero.
+        fn write_recipient_allowance(&mut self, owner: AccountId, spender: AccountId, recipient: AccountId, value: Balance) {
+            if value == 0 {
+                self.recipient_allowances.take(&(owner, spender, recipient));
+            } else {
+                self.recipient_allowances.insert((owner, spender, recipient), value);
+            }
+        }
+
+        /// Returns whether `approve` refuses to change a nonzero allowance to another nonzero value
+        #[ink(message, selector = "0x3E81E536")]
+        pub fn strict_approvals(&self) -> bool {
+            self.strict_approvals
+        }
+
+        /// Set whether `approve` refuses to change a nonzero allowance to another nonzero value,
+        /// forcing holders to zero an allowance before changing it
+        ///
+        /// On success a `StrictApprovalsChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xBD4761AF")]
+        pub fn set_strict_approvals(&mut self, enabled: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_strict_approvals", Error::PermissionDenied));
+            }
+
+            self.strict_approvals = enabled;
+
+            self.env().emit_event(StrictApprovalsChanged {
+                enabled
+            });
+
+            Ok(())
+        }
+
+        /// Returns the amount which `spender` is still allowed to withdraw from `owner` for
+        /// transfers to `recipient` only.
+        ///
+        /// Returns `0` if no such allowance has been set.
+        #[ink(message, selector = "0x0861A8D8")]
+        pub fn allowance_for_recipient(&self, owner: AccountId, spender: AccountId, recipient: AccountId) -> Balance {
+            self.recipient_allowances.get(&(owner, spender, recipient)).copied().unwrap_or(0)
+        }
+
+        /// Allows `spender` to withdraw from the caller's account multiple times, up to the `value`
+        /// amount, but only for transfers to `recipient`.
+        ///
+        /// If this function is called again it overwrites the current allowance for `recipient` with
+        /// `value`. `transfer_from` consumes this allowance in preference to the general one.
+        ///
+        /// A `RecipientApproval` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `InvalidAccount` error if `spender` is the zero account or the caller's account,
+        /// or if `recipient` is the zero account.
+        #[ink(message, selector = "0x8DD00336")]
+        pub fn approve_for_recipient(&mut self, spender: AccountId, recipient: AccountId, value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("approve_for_recipient")?;
+            let owner = self.env().caller();
+            self.validate_counterparty("approve_for_recipient", owner, spender)?;
+            self.validate_account("approve_for_recipient", recipient)?;
+            self.write_recipient_allowance(owner, spender, recipient, value);
+            self.env().emit_event(RecipientApproval {
+                owner,
+                spender,
+                recipient,
+                value
+            });
+            Ok(())
+        }
+
+        /// Transfers `value` tokens on the behalf of `from` to the account `to`.
+        ///
+        /// This can be used to allow a contract to transfer tokens on ones behalf and/or
+        /// to charge fees in sub-currencies, for example.
+        ///
+        /// An allowance of `Balance::MAX` is infinite: it is left untouched instead of being
+        /// decremented by `value`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account. Unlike `transfer`,
+        /// `to` may be the caller, so a spender can pull funds to itself.
+        ///
+        /// Returns `AccountBlackListed` error if the `from` or `to` account is blacklisted.
+        ///
+        /// Returns `AccountFrozen` error if the `from` account is frozen.
+        ///
+        /// Returns `TradingNotEnabled` error if trading is not enabled yet and the `from`
+        /// account is neither the owner nor on the pre-launch allowlist.
+        /// 
+        /// Returns `InsufficientAllowance` error if neither the caller's allowance for transfers
+        /// from `from` to `to` nor its general allowance covers `value`. An expired allowance covers nothing.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on
+        /// the the account balance of `from`.
+        #[ink(message, selector = "0x0B396F18")]
+        pub fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            self.ensure_caller_allowed("transfer_from")?;
+            debug_log!("Entropy: Trying to transfer 0x{:x} tokens from {:?} to {:?}", value, from, to);
+
+            let caller = self.env().caller();
+            if let Err(error) = self.validate_transfer(Some(caller), from, to, value) {
+                return Err(self.reject_transfer("transfer_from", from, to, value, error));
+            }
+
+            let recipient_allowance = self.allowance_for_recipient(from, caller, to);
+            let allowance = self.allowance_value(from, caller);
+            if let Err(error) = self.transfer_from_to(from, to, value) {
+                return Err(self.reject_transfer("transfer_from", from, to, value, error));
+            }
+            if recipient_allowance >= value {
+                self.write_recipient_allowance(from, caller, to, recipient_allowance - value);
+            } else if allowance != Balance::MAX {
+                self.spend_allowance(from, caller, allowance - value);
+            }
+            Ok(())
+        }
+
+        /// Returns whether `from` can transfer `value` tokens to `to` right now, or the error `transfer`
+        /// would fail with
+        ///
+        /// Runs the same checks as the transfer itself without changing any state, so wallets can show
+        /// why a transfer would fail before submitting it. Whether the caller of the transfer is a
+        /// contract with a banned code hash is not checked.
+        #[ink(message, selector = "0xDD54551B")]
+        pub fn can_transfer(&self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            self.validate_transfer(None, from, to, value)
+        }
+
+        /// Returns whether `spender` can transfer `value` tokens from `from` to `to` on its allowance
+        /// right now, or the error `transfer_from` would fail with
+        ///
+        /// Behaves like `can_transfer`, also checking the allowance.
+        #[ink(message, selector = "0xA206EAB2")]
+        pub fn can_transfer_from(&self, spender: AccountId, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            self.validate_transfer(Some(spender), from, to, value)
+        }
+
+        /// Runs every check of a transfer of `value` tokens from `from` to `to`, on the allowance of
+        /// `spender` if it is given, without changing any state.
+        ///
+        /// The transfers and `can_transfer` share these checks, so a dry run can't disagree with the
+        /// transfer it stands for. Transfers hand a failure to `reject_transfer`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ReentrantCall` error while `transfer_and_call` notifies a contract recipient.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account.
+        ///
+        /// Returns `AccountBlackListed` error if the `from` or `to` account is blacklisted.
+        ///
+        /// Returns `AccountFrozen` error if the `from` account is frozen, or the transfer trips the velocity rule.
+        ///
+        /// Returns `TradingNotEnabled` error if trading is not enabled yet and the `from` account
+        /// is neither the owner nor on the pre-launch allowlist.
+        ///
+        /// Returns `InsufficientAllowance` error if neither the allowance of `spender` for transfers
+        /// from `from` to `to` nor its general allowance covers `value`.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on `from`'s account.
+        ///
+        /// Returns `HoldingPeriodActive` error if the transfer would spend tokens the `from` account
+        /// received within the minimum holding period.
+        ///
+        /// Returns `Overflow` error if crediting `to` or the fee to the fee collector would overflow.
+        ///
+        /// Returns `SpendingLimitExceeded` error if the transfer would exceed the spending limit
+        /// the `from` account imposed on itself.
+        ///
+        /// Returns `TransferLimitExceeded` error if the transfer would exceed the transfer limit
+        /// the owner imposed on the `from` account.
+        ///
+        /// Returns `CircuitBreakerTripped` error if the transfer moves more than `circuit_breaker_bps`
+        /// of total supply.
+        ///
+        /// Returns `ThroughputExceeded` error if the transfer would exceed the throughput cap.
+        fn validate_transfer(&self, spender: Option<AccountId>, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            if self.notifying_receiver {
+                return Err(Error::ReentrantCall);
+            }
+            if self.paused() {
+                return Err(Error::ContractPaused);
+            }
+            if to == AccountId::default() {
+                return Err(Error::InvalidAccount);
+            }
+            if self.is_account_blacklisted(to) || self.is_account_blacklisted(from) {
+                return Err(Error::AccountBlackListed);
+            }
+            if self.is_account_frozen(from) {
+                return Err(Error::AccountFrozen);
+            }
+            if !self.is_trading_allowed(from) {
+                return Err(Error::TradingNotEnabled);
+            }
+
+            if let Some(spender) = spender {
+                if self.allowance_for_recipient(from, spender, to) < value && self.allowance_value(from, spender) < value {
+                    return Err(Error::InsufficientAllowance);
+                }
+            }
+
+            let spendable = self.spendable_balance_of(from);
+            if spendable < value {
+                return Err(Error::InsufficientBalance);
+            }
+            if spendable.saturating_sub(self.held_balance_of(from)) < value {
+                return Err(Error::HoldingPeriodActive);
+            }
+
+            if self.credit_overflows(from, to, value, self.transfer_fee(from, to, value)) {
+                return Err(Error::Overflow);
+            }
+
+            if self.remaining_spending_of(from).map_or(false, |remaining| value > remaining) {
+                return Err(Error::SpendingLimitExceeded);
+            }
+            if self.remaining_limit_of(from).map_or(false, |remaining| value > remaining) {
+                return Err(Error::TransferLimitExceeded);
+            }
+            if self.trips_circuit_breaker(from, value) {
+                return Err(Error::CircuitBreakerTripped);
+            }
+            if self.throughput_window_for(from).map_or(false, |(_, moved)| moved.saturating_add(value) > self.throughput_cap.max_value_per_window) {
+                return Err(Error::ThroughputExceeded);
+            }
+            if self.outflow_window_of(from).map_or(false, |window| self.trips_velocity_rule(window, value)) {
+                return Err(Error::AccountFrozen);
+            }
+            Ok(())
+        }
+
+        /// Returns whether moving `value` from `from` to `to`, less a `fee` paid to the fee collector,
+        /// would overflow the balance of `to` or of the fee collector. A fee collector receiving the
+        /// transfer is credited the fee on top of it.
+        fn credit_overflows(&self, from: AccountId, to: AccountId, value: Balance, fee: Balance) -> bool {
+            let credited = if to == self.fee_collector { value } else { value - fee };
+            let to_overflows = to != from && self.balance(to).checked_add(credited).is_none();
+            let fee_overflows = fee > 0
+                && self.fee_collector != from
+                && self.fee_collector != to
+                && self.balance(self.fee_collector).checked_add(fee).is_none();
+            to_overflows || fee_overflows
+        }
+
+        /// Counts a transfer `validate_transfer` rejected with `error` as failed, returning the error.
+        ///
+        /// A transfer tripping a safety rule first sets it off: exceeding the spending limit emits a
+        /// `SpendingLimitExceeded` event, the circuit breaker pauses the contract and emits a
+        /// `CircuitBreakerTripped` event, and the velocity rule freezes `from` for one window length
+        /// and emits an `AutoFrozen` event.
+        fn reject_transfer(&mut self, message: &'static str, from: AccountId, to: AccountId, value: Balance, error: Error) -> Error {
+            match error {
+                Error::SpendingLimitExceeded => {
+                    self.env().emit_event(SpendingLimitExceeded {
+                        account: from,
+                        value,
+                        remaining: self.remaining_spending_of(from).unwrap_or(0)
+                    });
+                }
+                Error::CircuitBreakerTripped => {
+                    self.paused = true;
+                    self.paused_until = None;
+                    self.env().emit_event(CircuitBreakerTripped {
+                        from,
+                        to,
+                        value
+                    });
+                }
+                // `from` is only rejected as frozen without being frozen when the velocity rule trips
+                Error::AccountFrozen if !self.is_account_frozen(from) => {
+                    let rule = self.velocity_rule;
+                    let now = self.env().block_timestamp();
+                    self.frozen_until.insert(from, now.saturating_add(rule.window_ms));
+                    self.update_flags(from, |flags| flags.frozen = true);
+                    self.outflows.take(&from);
+                    self.env().emit_event(AutoFrozen {
+                        account: from,
+                        rule
+                    });
+                }
+                _ => {}
+            }
+            self.fail(message, error)
+        }
+
+        /// Transfers `value` amount of tokens from the `from` account to account `to`.
+        ///
+        /// The transfer is assigned the next transaction id and recorded for reversal, and counted
+        /// against the limits and rules it is subject to. Callers check it with `validate_transfer` first.
+        ///
+        /// On success a `Transfer` event is emitted. If a fee is charged, a `FeeCharged` event is
+        /// always emitted before it (preceded by a fee `Transfer` event if `fee_transfer_events` is set).
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if `from` holds less than `value`, or `Overflow` error
+        /// if crediting `to` or the fee collector would overflow, before changing anything.
+        fn transfer_from_to(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance
+        ) -> Result<()> {
+            debug_log!("Entropy: Transferring 0x{:x} tokens from {:?} to {:?}", value, from, to);
+
+            let fee = self.transfer_fee(from, to, value);
+            let send_value = value - fee;
+
+            // Every balance is worked out before any is written, each from the one written before it
+            // when accounts coincide
+            let from_balance = self.balance(from).checked_sub(value).ok_or(Error::InsufficientBalance)?;
+            let to_balance = if to == from { from_balance } else { self.balance(to) };
+            let to_balance = to_balance.checked_add(send_value).ok_or(Error::Overflow)?;
+            let collector_balance = if self.fee_collector == to {
+                to_balance
+            } else if self.fee_collector == from {
+                from_balance
+            } else {
+                self.balance(self.fee_collector)
+            };
+            let collector_balance = collector_balance.checked_add(fee).ok_or(Error::Overflow)?;
+
+            self.record_spending(from, value);
+            self.record_limited_transfer(from, value);
+            self.record_throughput(from, value);
+            self.record_outflow(from, value);
+
+            if fee > 0 {
+                self.accrue_points(from, value);
+                self.accrue_rebate(from, fee);
+            }
+
+            self.set_balance(from, from_balance);
+            self.set_balance(to, to_balance);
+            self.record_volume(Some(from), Some(to), value, send_value);
+            self.record_held_receipt(to, send_value);
+
+            if fee > 0 {
+                self.set_balance(self.fee_collector, collector_balance);
+                if self.fee_transfer_events {
+                    self.env().emit_event(Transfer {
+                        from: Some(from),
+                        to: Some(self.fee_collector),
+                        value: fee
+                    });
+                }
+                self.env().emit_event(FeeCharged {
+                    from,
+                    collector: self.fee_collector,
+                    fee
+                });
+            }
+
+            self.record_transfer(from, to, send_value, fee);
+
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value: send_value,
+            });
+
+            Ok(())
+        }
+
+        /// Assigns the next transaction id to a transfer and records it, dropping the oldest record
+        /// once more than `MAX_RECENT_TRANSFERS` are kept. The transfer is also counted in the global stats.
+        fn record_transfer(&mut self, from: AccountId, to: AccountId, net: Balance, fee: Balance) {
+            self.stats.total_transfers = self.stats.total_transfers.saturating_add(1);
+            self.stats.total_volume = self.stats.total_volume.saturating_add(net).saturating_add(fee);
+            self.stats.total_fees_collected = self.stats.total_fees_collected.saturating_add(fee);
+
+            let tx_id = self.last_tx_id + 1;
+            self.last_tx_id = tx_id;
+            self.recent_transfers.insert(tx_id, TransferRecord {
+                from,
+                to,
+                net,
+                fee,
+                block: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                reversed: false
+            });
+            if tx_id > MAX_RECENT_TRANSFERS {
+                self.recent_transfers.take(&(tx_id - MAX_RECENT_TRANSFERS));
+            }
+        }
+
+        /// Adds `sent` to the sent volume of `from` and `received` to the received volume of `to`,
+        /// where `None` stands for tokens being issued or burned. Both saturate at `Balance::MAX`.
+        fn record_volume(&mut self, from: Option<AccountId>, to: Option<AccountId>, sent: Balance, received: Balance) {
+            if let Some(from) = from {
+                let entry = self.volumes.entry(from).or_insert((0, 0));
+                entry.0 = entry.0.saturating_add(sent);
+            }
+            if let Some(to) = to {
+                let entry = self.volumes.entry(to).or_insert((0, 0));
+                entry.1 = entry.1.saturating_add(received);
+            }
+        }
+
+        /// Returns the lifetime `(sent, received)` token volume of an account
+        ///
+        /// Issued tokens count as received by the owner, and redeemed or destroyed tokens as sent by
+        /// their holder. A transfer's fee counts toward the sender's sent volume but not toward the
+        /// recipient's received volume, nor the owner's who collects it: it is not part of what the
+        /// recipient was sent, and the owner took no part in the transfer.
+        #[ink(message, selector = "0xA578F509")]
+        pub fn volume_of(&self, account: AccountId) -> (Balance, Balance) {
+            self.volumes.get(&account).copied().unwrap_or((0, 0))
+        }
+
+        /// Returns headline token metrics in one call
+        #[ink(message, selector = "0x16C3E1D5")]
+        pub fn global_stats(&self) -> GlobalStats {
+            GlobalStats {
+                current_supply: self.total_supply(),
+                ..self.stats
+            }
+        }
+
+        /// Returns the id of the last executed transfer, `0` if there is none
+        #[ink(message, selector = "0x801BC9FC")]
+        pub fn last_tx_id(&self) -> u64 {
+            self.last_tx_id
+        }
+
+        /// Returns the record of a recent transfer, or `None` if the id is unknown or no longer kept
+        #[ink(message, selector = "0xB795FBB3")]
+        pub fn transfer_record(&self, tx_id: u64) -> Option<TransferRecord> {
+            self.recent_transfers.get(&tx_id).copied()
+        }
+
+        /// Returns the time in milliseconds after a transfer during which it can be reversed
+        #[ink(message, selector = "0x523F0607")]
+        pub fn dispute_window(&self) -> u64 {
+            self.dispute_window_ms
+        }
+
+        /// Set the time in milliseconds after a transfer during which it can be reversed
+        ///
+        /// On success a `DisputeWindowChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xF5BFBED2")]
+        pub fn set_dispute_window(&mut self, window_ms: u64) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_dispute_window", Error::PermissionDenied));
+            }
+
+            self.dispute_window_ms = window_ms;
+
+            self.env().emit_event(DisputeWindowChanged {
+                window_ms
+            });
+
+            Ok(())
+        }
+
+        /// Reverse a recent transfer by moving its net amount back from the recipient to the sender.
+        /// The fee charged on the original transfer is not refunded. The recipient is debited even if
+        /// blacklisted or frozen, as the reversal claws back disputed funds.
+        ///
+        /// On success a `Transfer` event and a `TransferReversed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `UnknownTransfer` error if `tx_id` is unknown or no longer kept.
+        ///
+        /// Returns `TransferAlreadyReversed` error if the transfer was already reversed.
+        ///
+        /// Returns `DisputeWindowClosed` error if the dispute window of the transfer has passed.
+        ///
+        /// Returns `AccountBlackListed` error if the sender is blacklisted.
+        ///
+        /// Returns `AccountFrozen` error if the sender is frozen.
+        ///
+        /// Returns `InsufficientBalance` error if the recipient's balance not held by self-locks or holds
+        /// no longer covers the net amount.
+        ///
+        /// Returns `Overflow` error if crediting the sender would overflow.
+        #[ink(message, selector = "0xFA41E618")]
+        pub fn reverse_transfer(&mut self, tx_id: u64, reason: String) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("reverse_transfer", Error::PermissionDenied));
+            }
+
+            let mut record = match self.transfer_record(tx_id) {
+                Some(record) => record,
+                None => {
+                    return Err(self.fail("reverse_transfer", Error::UnknownTransfer));
+                }
+            };
+
+            if record.reversed {
+                return Err(self.fail("reverse_transfer", Error::TransferAlreadyReversed));
+            }
+
+            let now = self.env().block_timestamp();
+            if now.saturating_sub(record.timestamp) > self.dispute_window_ms {
+                return Err(self.fail("reverse_transfer", Error::DisputeWindowClosed));
+            }
+
+            self.ensure_compliant("reverse_transfer", record.from, Direction::Credit)?;
+
+            if self.spendable_balance_of(record.to) < record.net {
+                return Err(self.fail("reverse_transfer", Error::InsufficientBalance));
+            }
+
+            self.move_balance("reverse_transfer", record.to, record.from, record.net)?;
+
+            record.reversed = true;
+            self.recent_transfers.insert(tx_id, record);
+
+            self.env().emit_event(TransferReversed {
+                tx_id,
+                from: record.from,
+                to: record.to,
+                value: record.net,
+                reason
+            });
+
+            Ok(())
+        }
+
+        /// Issues `value` amount of tokens to contract owner's account. Only contract owner is allowed to call this function.
+        /// 
+        /// Same as `issue_to` the owner's account.
+        /// 
+        /// On success a `Issue` event and a `Transfer` event from no account are emitted.
+        /// 
+        /// # Errors
+        /// 
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `ZeroAmount` error if `value` is zero.
+        ///
+        /// Returns `AccountBlackListed` error if the owner's account is blacklisted.
+        ///
+        /// Returns `Overflow` error if the total supply would overflow.
+        ///
+        /// Returns `SupplyCapExceeded` error if the total supply would exceed the max supply.
+        #[ink(message, selector = "0xC392BA4D")]
+        pub fn issue(&mut self, value: Balance) -> Result<()> {
+            self.issue_to(self.owner, value)
+        }
+
+        /// Issues `value` amount of tokens directly to account `to`. Only contract owner is allowed to call this function.
+        ///
+        /// Unlike issuing to the owner and transferring, no transfer fee is charged.
+        ///
+        /// On success a `Issue` event and a `Transfer` event from no account are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account.
+        ///
+        /// Returns `ZeroAmount` error if `value` is zero.
+        ///
+        /// Returns `AccountBlackListed` error if `to` is blacklisted.
+        ///
+        /// Returns `Overflow` error if the total supply would overflow.
+        ///
+        /// Returns `SupplyCapExceeded` error if the total supply would exceed the max supply.
+        #[ink(message, selector = "0x83F69EF2")]
+        pub fn issue_to(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            debug_log!("Entropy: Issuing 0x{:x} tokens to {:?}", value, to);
+
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("issue_to", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("issue_to")?;
+
+            self.validate_account("issue_to", to)?;
+
+            if value == 0 {
+                return Err(self.fail("issue_to", Error::ZeroAmount));
+            }
+
+            self.mint_to("issue_to", to, value)
+        }
+
+        /// Issues `value` tokens to `account`, shared by `issue_to` and `PSP22Mintable::mint`.
+        fn mint_to(&mut self, message: &'static str, account: AccountId, value: Balance) -> Result<()> {
+            self.ensure_compliant(message, account, Direction::Credit)?;
+
+            let (new_balance, new_supply) = match (self.balance(account).checked_add(value), self.total_supply().checked_add(value)) {
+                (Some(new_balance), Some(new_supply)) => (new_balance, new_supply),
+                _ => return Err(self.fail(message, Error::Overflow))
+            };
+            self.ensure_within_supply_cap(message, new_supply)?;
+
+            self.set_balance(account, new_balance);
+            self.record_volume(None, Some(account), value, value);
+            Lazy::<Balance>::set(&mut self.total_supply, new_supply);
+
+            self.env().emit_event(Issue {
+                amount: value
+            });
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(account),
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Returns `SupplyCapExceeded` error if `new_supply` is above the max supply.
+        fn ensure_within_supply_cap(&mut self, message: &'static str, new_supply: Balance) -> Result<()> {
+            match self.max_supply {
+                Some(cap) if new_supply > cap => Err(self.fail(message, Error::SupplyCapExceeded)),
+                _ => Ok(())
+            }
+        }
+
+        /// Redeem `value` amount of tokens from contract owner's account. Only contract owner is allowed to call this function.
+        ///
+        /// `payout_reference_hash` is the hash of the off-chain reference of the corresponding fiat payout.
+        /// It is stored in a redemption receipt whose id, sequential from `1`, is returned.
+        /// 
+        /// On success a `Redeem` event is emitted.
+        /// 
+        /// # Errors
+        /// 
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        /// Returns `ContractPaused` error if the contract is paused.
+        /// Returns `ZeroAmount` error if `value` is zero.
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the owner's account fails compliance screening.
+        /// Returns `InsufficientBalance` error if owner's balance is insufficient.
+        #[ink(message, selector = "0xEC3E9290")]
+        pub fn redeem(&mut self, value: Balance, payout_reference_hash: Hash) -> Result<u64> {
+            debug_log!("Entropy: Redeeming 0x{:x} tokens from owner account", value);
+
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("redeem", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("redeem")?;
+
+            if value == 0 {
+                return Err(self.fail("redeem", Error::ZeroAmount));
+            }
+
+            let receipt_id = self.redemption_receipt_count + 1;
+            self.burn_from_account("redeem", self.owner, value, Some(receipt_id))?;
+
+            self.redemption_receipt_count = receipt_id;
+            self.redemption_receipts.insert(receipt_id, RedemptionReceipt {
+                amount: value,
+                account: self.owner,
+                block: self.env().block_number(),
+                payout_reference_hash
+            });
+
+            Ok(receipt_id)
+        }
+
+        /// Returns the redemption receipt with id `id`, starting at `1`
+        #[ink(message, selector = "0x0DAF5E43")]
+        pub fn redemption_receipt(&self, id: u64) -> Option<RedemptionReceipt> {
+            self.redemption_receipts.get(&id).copied()
+        }
+
+        /// Returns up to `limit` redemption receipts with their ids, starting at id `start`,
+        /// for paging through all receipts in issuance order.
+        ///
+        /// At most `MAX_BATCH_SIZE` receipts are returned per call.
+        #[ink(message, selector = "0xF8E41D02")]
+        pub fn redemption_receipts(&self, start: u64, limit: u32) -> Vec<(u64, RedemptionReceipt)> {
+            let limit = core::cmp::min(limit as usize, MAX_BATCH_SIZE) as u64;
+            let start = core::cmp::max(start, 1);
+            let end = core::cmp::min(self.redemption_receipt_count, start.saturating_add(limit).saturating_sub(1));
+            (start..=end)
+                .filter_map(|id| self.redemption_receipts.get(&id).map(|receipt| (id, *receipt)))
+                .collect()
+        }
+
+        /// Redeems `value` tokens from `account`, shared by `redeem` and `PSP22Burnable::burn`.
+        fn burn_from_account(&mut self, message: &'static str, account: AccountId, value: Balance, receipt_id: Option<u64>) -> Result<()> {
+            self.ensure_compliant(message, account, Direction::Debit)?;
+
+            if self.spendable_balance_of(account) < value {
+                return Err(self.fail(message, Error::InsufficientBalance));
+            }
+
+            self.burn_balance(account, value, receipt_id);
+
+            Ok(())
+        }
+
+        /// Destroy `value` tokens from the caller's account, reducing the total supply
+        ///
+        /// Unlike `redeem`, any holder can burn its own tokens, and no redemption receipt is issued.
+        /// Same as `PSP22Burnable::burn` with the caller's account, which takes the account as well.
+        ///
+        /// On success a `Transfer` event to no account and a `Burn` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the caller's account fails compliance screening.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on the caller's account.
+        #[ink(message, selector = "0xA6A7F3BD")]
+        pub fn burn_my_tokens(&mut self, value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("burn_my_tokens")?;
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("burn_my_tokens")?;
+            self.ensure_compliant("burn_my_tokens", caller, Direction::Debit)?;
+
+            self.burn_tokens("burn_my_tokens", caller, value)
+        }
+
+        /// Destroy `value` tokens from the `from` account, spending the caller's allowance on it
+        ///
+        /// This lets a contract approved to spend tokens burn them, for example an escrow.
+        ///
+        /// On success a `Transfer` event to no account, a `Burn` event and an `Approval` event
+        /// carrying the remaining allowance are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller is a contract whose code hash is banned.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the `from` account fails compliance screening.
+        ///
+        /// Returns `InsufficientAllowance` error if the caller's allowance on `from` is less than `value`.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on the `from` account.
+        #[ink(message, selector = "0x27212BBB")]
+        pub fn burn_from(&mut self, from: AccountId, value: Balance) -> Result<()> {
+            self.ensure_caller_allowed("burn_from")?;
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("burn_from")?;
+            self.ensure_compliant("burn_from", from, Direction::Debit)?;
+
+            let allowance = self.allowance_value(from, caller);
+            if allowance < value {
+                return Err(self.fail("burn_from", Error::InsufficientAllowance));
+            }
+            self.burn_tokens("burn_from", from, value)?;
+            self.set_allowance(from, caller, allowance - value);
+            Ok(())
+        }
+
+        /// Burns `value` unlocked tokens of `account`, emitting a `Transfer` to no account and a `Burn` event.
+        /// Callers screen the account with `ensure_compliant` first.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on `account`.
+        fn burn_tokens(&mut self, message: &'static str, account: AccountId, value: Balance) -> Result<()> {
+            if self.spendable_balance_of(account) < value {
+                return Err(self.fail(message, Error::InsufficientBalance));
+            }
+
+            self.reduce_supply(account, value);
+
+            self.env().emit_event(Transfer {
+                from: Some(account),
+                to: None,
+                value
+            });
+            self.env().emit_event(Burn {
+                account,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Burns `value` tokens of `account` without checks and emits a `Redeem` event carrying `receipt_id`.
+        fn burn_balance(&mut self, account: AccountId, value: Balance, receipt_id: Option<u64>) {
+            self.reduce_supply(account, value);
+
+            self.env().emit_event(Redeem {
+                amount: value,
+                receipt_id
+            });
+        }
+
+        /// Removes `value` tokens of `account` from its balance and the total supply, without checks or events.
+        fn reduce_supply(&mut self, account: AccountId, value: Balance) {
+            let balance = self.balance(account);
+            self.set_balance(account, balance - value);
+            self.record_volume(Some(account), None, value, value);
+            self.stats.total_burned = self.stats.total_burned.saturating_add(value);
+
+            let total_supply = &mut self.total_supply;
+            let current_supply = Lazy::<Balance>::get(total_supply);
+            let new_supply = current_supply - value;
+            Lazy::<Balance>::set(total_supply, new_supply);
+        }
+
+        /// `PSP22Mintable::mint`: issues `amount` tokens to `account`. Only contract owner is allowed to call this function.
+        ///
+        /// Behaves like `issue` with any recipient, errors are mapped to `PSP22Error`.
+        ///
+        /// On success a `Issue` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Custom("PermissionDenied")` error if caller is not the owner.
+        ///
+        /// Returns `ZeroRecipientAddress` error if `account` is the zero account.
+        ///
+        /// Returns `Custom("AccountBlackListed")` or `Custom("AccountFrozen")` error if `account` fails compliance screening.
+        ///
+        /// Returns `Custom("SupplyCapExceeded")` error if the total supply would exceed the max supply.
+        #[ink(message, selector = "0xFC3C75D4")]
+        pub fn mint(&mut self, account: AccountId, amount: Balance) -> core::result::Result<(), PSP22Error> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("mint", Error::PermissionDenied).into());
+            }
+
+            self.validate_account("mint", account).map_err(|_| PSP22Error::ZeroRecipientAddress)?;
+
+            Ok(self.mint_to("mint", account, amount)?)
+        }
+
+        /// `PSP22Burnable::burn`: destroys `amount` tokens from `account`, which must be the caller
+        /// or have allowed the caller to spend at least `amount`. The allowance is spent.
+        ///
+        /// Burning from the caller's own account behaves like `burn_my_tokens`, and a `Transfer` event
+        /// to no account and a `Burn` event are emitted. Burning from another account behaves like
+        /// `redeem` with any account, and a `Redeem` event is emitted. Errors are mapped to `PSP22Error`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Custom("ContractPaused")` error if the contract is paused.
+        ///
+        /// Returns `ZeroSenderAddress` error if `account` is the zero account.
+        ///
+        /// Returns `InsufficientAllowance` error if the caller is not `account` and its allowance is too low.
+        ///
+        /// Returns `Custom("AccountBlackListed")` or `Custom("AccountFrozen")` error if `account` fails compliance screening.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on `account`.
+        #[ink(message, selector = "0x7A9DA510")]
+        pub fn burn(&mut self, account: AccountId, amount: Balance) -> core::result::Result<(), PSP22Error> {
+            self.ensure_caller_allowed("burn")?;
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("burn")?;
+            self.validate_account("burn", account).map_err(|_| PSP22Error::ZeroSenderAddress)?;
+
+            if caller == account {
+                self.ensure_compliant("burn", account, Direction::Debit)?;
+                return Ok(self.burn_tokens("burn", account, amount)?);
+            }
+
+            let allowance = self.allowance_value(account, caller);
+            if allowance < amount {
+                return Err(self.fail("burn", Error::InsufficientAllowance).into());
+            }
+            self.burn_from_account("burn", account, amount, None)?;
+            self.spend_allowance(account, caller, allowance - amount);
+            Ok(())
+        }
+
+        /// `PSP22Metadata::token_name`: returns the token name.
+        #[ink(message, selector = "0x3D261BD4")]
+        pub fn token_name(&self) -> Option<String> {
+            Some(self.name.clone())
+        }
+
+        /// `PSP22Metadata::token_symbol`: returns the token symbol.
+        #[ink(message, selector = "0x34205BE5")]
+        pub fn token_symbol(&self) -> Option<String> {
+            Some(self.symbol.clone())
+        }
+
+        /// `PSP22Metadata::token_decimals`: returns the token decimals, saturated to `u8::MAX`.
+        #[ink(message, selector = "0x7271B782")]
+        pub fn token_decimals(&self) -> u8 {
+            u8::try_from(self.decimals).unwrap_or(u8::MAX)
+        }
+
+        /// `PSP22::transfer`: transfers `value` tokens from the caller's account to `to`. `_data` is ignored.
+        ///
+        /// Behaves like `transfer`, errors are mapped to `PSP22Error`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroRecipientAddress` error if `to` is the zero account.
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough unlocked tokens on the caller's account.
+        ///
+        /// Returns the other errors of `transfer` as `Custom` errors.
+        #[ink(message, selector = "0xDB20F9F5")]
+        pub fn psp22_transfer(&mut self, to: AccountId, value: Balance, _data: Vec<u8>) -> core::result::Result<(), PSP22Error> {
+            self.validate_account("psp22_transfer", to).map_err(|_| PSP22Error::ZeroRecipientAddress)?;
+            Ok(self.transfer(to, value, None)?)
+        }
+
+        /// `PSP22::transfer_from`: transfers `value` tokens from `from` to `to` on the caller's allowance. `_data` is ignored.
+        ///
+        /// Behaves like `transfer_from`, errors are mapped to `PSP22Error`.
+        ///
+        /// On success a `Transfer` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroSenderAddress` error if `from` is the zero account.
+        ///
+        /// Returns `ZeroRecipientAddress` error if `to` is the zero account.
+        ///
+        /// Returns `InsufficientAllowance` or `InsufficientBalance` error if the allowance or the balance of `from` is too low.
+        ///
+        /// Returns the other errors of `transfer_from` as `Custom` errors.
+        #[ink(message, selector = "0x54B3C76E")]
+        pub fn psp22_transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance, _data: Vec<u8>) -> core::result::Result<(), PSP22Error> {
+            self.validate_account("psp22_transfer_from", from).map_err(|_| PSP22Error::ZeroSenderAddress)?;
+            self.validate_account("psp22_transfer_from", to).map_err(|_| PSP22Error::ZeroRecipientAddress)?;
+            Ok(self.transfer_from(from, to, value)?)
+        }
+
+        /// `PSP22::approve`: allows `spender` to withdraw up to `value` tokens from the caller's account.
+        ///
+        /// Behaves like `approve`, errors are mapped to `PSP22Error`.
+        ///
+        /// An `Approval` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroRecipientAddress` error if `spender` is the zero account.
+        ///
+        /// Returns the other errors of `approve` as `Custom` errors.
+        #[ink(message, selector = "0xB20F1BBD")]
+        pub fn psp22_approve(&mut self, spender: AccountId, value: Balance) -> core::result::Result<(), PSP22Error> {
+            self.validate_account("psp22_approve", spender).map_err(|_| PSP22Error::ZeroRecipientAddress)?;
+            Ok(self.approve(spender, value)?)
+        }
+
+        /// `PSP22::increase_allowance`: raises the allowance of `spender` on the caller's account by `delta_value`.
+        ///
+        /// Behaves like `increase_allowance`, errors are mapped to `PSP22Error`.
+        ///
+        /// An `Approval` event carrying the new allowance is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroRecipientAddress` error if `spender` is the zero account.
+        ///
+        /// Returns the other errors of `increase_allowance` as `Custom` errors.
+        #[ink(message, selector = "0x96D6B57A")]
+        pub fn psp22_increase_allowance(&mut self, spender: AccountId, delta_value: Balance) -> core::result::Result<(), PSP22Error> {
+            self.validate_account("psp22_increase_allowance", spender).map_err(|_| PSP22Error::ZeroRecipientAddress)?;
+            Ok(self.increase_allowance(spender, delta_value)?)
+        }
+
+        /// `PSP22::decrease_allowance`: lowers the allowance of `spender` on the caller's account by `delta_value`.
+        ///
+        /// Behaves like `decrease_allowance`, errors are mapped to `PSP22Error`.
+        ///
+        /// An `Approval` event carrying the new allowance is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroRecipientAddress` error if `spender` is the zero account.
+        ///
+        /// Returns `InsufficientAllowance` error if the allowance is less than `delta_value`.
+        ///
+        /// Returns the other errors of `decrease_allowance` as `Custom` errors.
+        #[ink(message, selector = "0xFECB57D5")]
+        pub fn psp22_decrease_allowance(&mut self, spender: AccountId, delta_value: Balance) -> core::result::Result<(), PSP22Error> {
+            self.validate_account("psp22_decrease_allowance", spender).map_err(|_| PSP22Error::ZeroRecipientAddress)?;
+            Ok(self.decrease_allowance(spender, delta_value)?)
+        }
+
+        /// Set whether an account is private or not, overriding the account's own choice
+        ///
+        /// Making an account private this way is forced: the account can't change its privacy until
+        /// the owner makes it public again.
+        /// 
+        /// On success a `Privacy` event is emitted.
+        /// 
+        /// # Errors
+        /// 
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account or the owner's account.
+        #[ink(message, selector = "0xD7641771")]
+        pub fn set_account_private(&mut self, account: AccountId, private: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_account_private", Error::PermissionDenied));
+            }
+
+            self.set_private("set_account_private", account, private)
+        }
+
+        /// Marks `account` private or public on behalf of the owner.
+        fn set_private(&mut self, message: &'static str, account: AccountId, private: bool) -> Result<()> {
+            self.validate_counterparty(message, self.owner, account)?;
+
+            self.update_flags(account, |flags| flags.private = private);
+            self.privacy_sources.insert(account, PrivacySource::Owner);
+
+            self.env().emit_event(Privacy {
+                account,
+                private
+            });
+
+            Ok(())
+        }
+
+        /// Set whether the caller's account is private or not
+        ///
+        /// On success a `Privacy` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidAccount` error if the caller is the owner.
+        ///
+        /// Returns `PrivacyForced` error if the owner forced the account private.
+        #[ink(message, selector = "0xD21AD3A7")]
+        pub fn set_my_privacy(&mut self, private: bool) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.validate_counterparty("set_my_privacy", self.owner, caller)?;
+
+            if self.is_account_private(caller) && self.privacy_source_of(caller) == Some(PrivacySource::Owner) {
+                return Err(self.fail("set_my_privacy", Error::PrivacyForced));
+            }
+
+            self.update_flags(caller, |flags| flags.private = private);
+            self.privacy_sources.insert(caller, PrivacySource::User);
+
+            self.env().emit_event(Privacy {
+                account: caller,
+                private
+            });
+
+            Ok(())
+        }
+
+        /// Returns who set an account's privacy, or `None` if it was never set
+        #[ink(message, selector = "0xAF5171DA")]
+        pub fn privacy_source_of(&self, account: AccountId) -> Option<PrivacySource> {
+            self.privacy_sources.get(&account).copied()
+        }
+
+        /// Returns whether accounts receiving tokens for the first time are made private
+        #[ink(message, selector = "0xF8BE4E1C")]
+        pub fn default_private_for_new_accounts(&self) -> bool {
+            self.default_private
+        }
+
+        /// Set whether accounts receiving tokens for the first time are made private
+        ///
+        /// Accounts that already received tokens or whose privacy was set explicitly are untouched.
+        ///
+        /// On success a `DefaultPrivacy` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xFC2802D7")]
+        pub fn set_default_private_for_new_accounts(&mut self, private: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_default_private_for_new_accounts", Error::PermissionDenied));
+            }
+
+            self.default_private = private;
+
+            self.env().emit_event(DefaultPrivacy {
+                private
+            });
+
+            Ok(())
+        }
+
+        /// Returns the flags of `account`, all off if none was ever set.
+        fn flags(&self, account: AccountId) -> AccountFlags {
+            self.account_flags.get(&account).copied().unwrap_or_default()
+        }
+
+        /// Updates the flags of `account` with `update`, dropping its entry once all flags are off.
+        fn update_flags(&mut self, account: AccountId, update: impl FnOnce(&mut AccountFlags)) {
+            let mut flags = self.flags(account);
+            update(&mut flags);
+            if flags == AccountFlags::default() {
+                self.account_flags.take(&account);
+            } else {
+                self.account_flags.insert(account, flags);
+            }
+        }
+
+        /// Returns whether an account is private
+        #[ink(message, selector = "0xAF9F1F7B")]
+        pub fn is_account_private(&self, account: AccountId) -> bool {
+            self.flags(account).private
+        }
+
+        /// Returns the balance of an account, or `None` if it is private and the caller is neither
+        /// the account itself, the owner nor an auditor.
+        ///
+        /// Unlike `balance_of`, tells a hidden balance apart from an empty one.
+        #[ink(message, selector = "0x2659FFFD")]
+        pub fn private_balance_of(&self, account: AccountId) -> Option<Balance> {
+            if !self.can_view(account) {
+                return None;
+            }
+            Some(self.balance(account))
+        }
+
+        /// Returns the amount which `spender` is still allowed to withdraw from `owner`, or `None` if
+        /// `owner` is private and the caller is neither `owner`, `spender`, the contract owner nor an auditor.
+        #[ink(message, selector = "0x0CF28FE0")]
+        pub fn private_allowance(&self, owner: AccountId, spender: AccountId) -> Option<Balance> {
+            if !self.can_view(owner) && self.env().caller() != spender {
+                return None;
+            }
+            Some(self.allowance_value(owner, spender))
+        }
+
+        /// Returns the compliance and activity details of an account, or `None` if it is private and
+        /// the caller is neither the account itself, the owner nor an auditor.
+        #[ink(message, selector = "0x846B0A1E")]
+        pub fn account_info(&self, account: AccountId) -> Option<AccountInfo> {
+            if !self.can_view(account) {
+                return None;
+            }
+            let (sent, received) = self.volume_of(account);
+            Some(AccountInfo {
+                balance: self.balance(account),
+                sent,
+                received,
+                private: self.is_account_private(account),
+                blacklisted: self.is_account_blacklisted(account),
+                frozen_until: self.frozen_until(account)
+            })
+        }
+
+        /// Returns the balance and compliance flags of an account in one call.
+        ///
+        /// The balance is `0` if the account is private and the caller is neither the account itself,
+        /// the owner nor an auditor, like `balance_of`, while the flags are public.
+        #[ink(message, selector = "0x8DBEBAFC")]
+        pub fn account_status(&self, account: AccountId) -> AccountStatus {
+            AccountStatus {
+                balance: self.balance_of(account),
+                private: self.is_account_private(account),
+                blacklisted: self.is_account_blacklisted(account),
+                fee_exempt: self.is_fee_exempt(account),
+                frozen: self.is_account_frozen(account)
+            }
+        }
+
+        /// Returns the `account_status` of each account, in the order given.
+        ///
+        /// # Errors
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_SIZE` accounts are passed.
+        #[ink(message, selector = "0xEFCB9239")]
+        pub fn accounts_status(&self, accounts: Vec<AccountId>) -> Result<Vec<AccountStatus>> {
+            if accounts.len() > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+            Ok(accounts.into_iter().map(|account| self.account_status(account)).collect())
+        }
+
+        /// Returns whether the caller may read the balance, allowances and other data of `account`.
+        fn can_view(&self, account: AccountId) -> bool {
+            let caller = self.env().caller();
+            !self.is_account_private(account) || caller == account || caller == self.owner || self.is_auditor(caller)
+        }
+
+        /// Returns whether an account is an auditor
+        #[ink(message, selector = "0xD4B2247F")]
+        pub fn is_auditor(&self, account: AccountId) -> bool {
+            self.auditors.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Grant an account the auditor role, letting it read private accounts' data through the
+        /// privacy-aware views. Auditors gain no write capability.
+        ///
+        /// On success an `AuditorAdded` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account or the owner's account.
+        #[ink(message, selector = "0x7EF528AA")]
+        pub fn add_auditor(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("add_auditor", Error::PermissionDenied));
+            }
+
+            self.validate_counterparty("add_auditor", caller, account)?;
+
+            self.auditors.insert(account, true);
+
+            self.env().emit_event(AuditorAdded {
+                account
+            });
+
+            Ok(())
+        }
+
+        /// Revoke an account's auditor role
+        ///
+        /// On success an `AuditorRemoved` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x36A3ACC4")]
+        pub fn remove_auditor(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("remove_auditor", Error::PermissionDenied));
+            }
+
+            self.auditors.take(&account);
+
+            self.env().emit_event(AuditorRemoved {
+                account
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether an account is blacklisted
+        #[ink(message, selector = "0x5FADA0D2")]
+        pub fn is_account_blacklisted(&self, account: AccountId) -> bool {
+            self.flags(account).blacklisted
+        }
+
+        /// Returns up to `limit` blacklisted accounts from position `offset` of the blacklisted accounts list.
+        ///
+        /// At most `MAX_BATCH_SIZE` accounts are returned per call. Removing an account moves the last
+        /// one into its position, so the list should be paged through without removals in between.
+        #[ink(message, selector = "0xB764E455")]
+        pub fn blacklisted_accounts(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            let limit = core::cmp::min(limit as usize, MAX_BATCH_SIZE) as u32;
+            let end = core::cmp::min(self.blacklisted.len(), offset.saturating_add(limit));
+            (offset..end)
+                .filter_map(|index| self.blacklisted.get(index).copied())
+                .collect()
+        }
+
+        /// Returns the number of blacklisted accounts
+        #[ink(message, selector = "0x1ED99B98")]
+        pub fn blacklisted_count(&self) -> u32 {
+            self.blacklisted.len()
+        }
+
+        /// Returns why and since when an account is blacklisted, `None` if it isn't
+        #[ink(message, selector = "0x60D426AE")]
+        pub fn blacklist_entry(&self, account: AccountId) -> Option<BlacklistEntry> {
+            self.accounts_blacklisted.get(&account).cloned()
+        }
+
+        /// Add an account to blacklist for `reason`
+        ///
+        /// Blacklisting an account again replaces its reason but keeps its `since` timestamp.
+        /// 
+        /// On success an `AddedBlackList` event is emitted.
+        /// 
+        /// # Errors
+        /// 
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ProtectedAccount` error if `account` is protected, which includes the owner's account.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0x9AC6F78A")]
+        pub fn add_account_to_blacklist(&mut self, account: AccountId, reason: String) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("add_account_to_blacklist", Error::PermissionDenied));
+            }
+
+            self.blacklist("add_account_to_blacklist", account, reason)
+        }
+
+        /// Adds `account` to blacklist for `reason` on behalf of the owner.
+        ///
+        /// The quarantine clock starts when the account is first blacklisted and blacklisting it
+        /// again doesn't restart it.
+        fn blacklist(&mut self, message: &'static str, account: AccountId, reason: String) -> Result<()> {
+            self.ensure_unprotected(message, account)?;
+            self.validate_counterparty(message, self.owner, account)?;
+
+            let since = match self.accounts_blacklisted.get(&account) {
+                Some(entry) => entry.since,
+                None => {
+                    self.blacklisted_index.insert(account, self.blacklisted.len());
+                    self.blacklisted.push(account);
+                    self.env().block_timestamp()
+                }
+            };
+            let reason_hash = Hash::from(self.env().hash_bytes::<env::hash::Blake2x256>(reason.as_bytes()));
+            self.accounts_blacklisted.insert(account, BlacklistEntry {
+                since,
+                reason
+            });
+            self.update_flags(account, |flags| flags.blacklisted = true);
+
+            self.env().emit_event(AddedBlackList {
+                account,
+                reason_hash,
+                destroyable_at: self.destroyable_at(account)
+            });
+
+            Ok(())
+        }
+
+        /// Remove an account from blacklist
+        /// 
+        /// On success an `RemovedBlackList` event is emitted.
+        /// 
+        /// # Errors
+        /// 
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xD089F91C")]
+        pub fn remove_account_from_blacklist(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("remove_account_from_blacklist", Error::PermissionDenied));
+            }
+
+            self.unblacklist(account);
+
+            Ok(())
+        }
+
+        /// Add several accounts to blacklist for the same `reason`. Only contract owner is allowed to call this function.
+        ///
+        /// Every account is checked before any is blacklisted, so the batch applies entirely or not at all.
+        /// Accounts listed more than once are blacklisted once, and an empty batch does nothing.
+        ///
+        /// On success an `AddedBlackList` event is emitted for each distinct account.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_SIZE` accounts are passed.
+        ///
+        /// Returns `ProtectedAccount` error if any account is protected, which includes the owner's account.
+        ///
+        /// Returns `InvalidAccount` error if any account is the zero account.
+        #[ink(message, selector = "0xB26FB718")]
+        pub fn add_accounts_to_blacklist(&mut self, accounts: Vec<AccountId>, reason: String) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("add_accounts_to_blacklist", Error::PermissionDenied));
+            }
+
+            let accounts = self.distinct_batch("add_accounts_to_blacklist", accounts)?;
+            for account in accounts.iter() {
+                self.ensure_unprotected("add_accounts_to_blacklist", *account)?;
+                self.validate_counterparty("add_accounts_to_blacklist", self.owner, *account)?;
+            }
+            for account in accounts {
+                self.blacklist("add_accounts_to_blacklist", account, reason.clone())?;
+            }
+
+            Ok(())
+        }
+
+        /// Remove several accounts from blacklist. Only contract owner is allowed to call this function.
+        ///
+        /// Accounts listed more than once are removed once, and an empty batch does nothing.
+        ///
+        /// On success a `RemovedBlackList` event is emitted for each distinct account.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_SIZE` accounts are passed.
+        #[ink(message, selector = "0x7BBA426B")]
+        pub fn remove_accounts_from_blacklist(&mut self, accounts: Vec<AccountId>) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("remove_accounts_from_blacklist", Error::PermissionDenied));
+            }
+
+            for account in self.distinct_batch("remove_accounts_from_blacklist", accounts)? {
+                self.unblacklist(account);
+            }
+
+            Ok(())
+        }
+
+        /// Returns the distinct accounts of a batch in their first-seen order.
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_SIZE` accounts are passed.
+        fn distinct_batch(&mut self, message: &'static str, accounts: Vec<AccountId>) -> Result<Vec<AccountId>> {
+            if accounts.len() > MAX_BATCH_SIZE {
+                return Err(self.fail(message, Error::BatchTooLarge));
+            }
+
+            let mut distinct = Vec::with_capacity(accounts.len());
+            for account in accounts {
+                if !distinct.contains(&account) {
+                    distinct.push(account);
+                }
+            }
+            Ok(distinct)
+        }
+
+        /// Removes `account` from blacklist.
+        ///
+        /// The last account of the blacklisted accounts list is moved into its position.
+        fn unblacklist(&mut self, account: AccountId) {
+            self.accounts_blacklisted.take(&account);
+            self.update_flags(account, |flags| flags.blacklisted = false);
+            if let Some(index) = self.blacklisted_index.take(&account) {
+                self.blacklisted.swap_remove_drop(index);
+                if let Some(moved) = self.blacklisted.get(index).copied() {
+                    self.blacklisted_index.insert(moved, index);
+                }
+            }
+
+            self.env().emit_event(RemovedBlackList {
+                account
+            });
+        }
+
+        /// Destroy funds of a blacklisted account
+        ///
+        /// The account's self-locks, held receipts and the holds it pays are released.
+        /// 
+        /// On success a `Transfer` event to no account and a `DestroyedBlackFunds` event are emitted,
+        /// preceded by a `HoldReleased` event per released hold.
+        /// 
+        /// # Errors
+        /// 
+        /// Returns `PermissionDenied` error if caller is not the owner, `AccountNotBlackListed` if the account is not blacklisted
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `ProtectedAccount` error if `account` is protected, e.g. it was blacklisted before being protected.
+        ///
+        /// Returns `QuarantineActive` error if `account` has been blacklisted for less than the destruction delay.
+        ///
+        /// Returns `AppealPending` error if `account` has a pending appeal lodged less than the appeal timeout ago.
+        #[ink(message, selector = "0x83D2C2E0")]
+        pub fn destroy_black_funds(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("destroy_black_funds", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("destroy_black_funds")?;
+
+            self.ensure_destroyable("destroy_black_funds", account)?;
+
+            self.destroy(account, self.balance(account));
+
+            Ok(())
+        }
+
+        /// Destroy `amount` of the funds of a blacklisted account, leaving the rest of its balance
+        ///
+        /// On success a `Transfer` event to no account and a `DestroyedBlackFunds` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns the same errors as `destroy_black_funds`.
+        ///
+        /// Returns `InsufficientBalance` error if `account` holds less than `amount`.
+        #[ink(message, selector = "0xCA0F5C43")]
+        pub fn destroy_black_funds_partial(&mut self, account: AccountId, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("destroy_black_funds_partial", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("destroy_black_funds_partial")?;
+
+            self.ensure_destroyable("destroy_black_funds_partial", account)?;
+
+            if self.balance(account) < amount {
+                return Err(self.fail("destroy_black_funds_partial", Error::InsufficientBalance));
+            }
+
+            self.destroy(account, amount);
+
+            Ok(())
+        }
+
+        /// Seize the funds of a blacklisted account into `treasury`, which holds them in escrow
+        ///
+        /// Unlike `destroy_black_funds` the total supply is untouched. The same checks apply, and the
+        /// account's self-locks, held receipts and the holds it pays are released likewise.
+        ///
+        /// On success a `Transfer` event, a `HoldReleased` event per released hold and a `SeizedBlackFunds`
+        /// event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns the same errors as `destroy_black_funds`.
+        ///
+        /// Returns `InvalidAccount` error if `treasury` is the zero account or `account` itself.
+        ///
+        /// Returns `AccountBlackListed` error if `treasury` is blacklisted.
+        #[ink(message, selector = "0x1C2EEBC1")]
+        pub fn seize_black_funds(&mut self, account: AccountId, treasury: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("seize_black_funds", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("seize_black_funds")?;
+
+            self.ensure_seizable("seize_black_funds", account, treasury)?;
+
+            let funds = self.balance(account);
+            self.move_balance("seize_black_funds", account, treasury, funds)?;
+            self.release_encumbrances(account);
+
+            self.env().emit_event(SeizedBlackFunds {
+                account,
+                treasury,
+                funds
+            });
+
+            Ok(())
+        }
+
+        /// Seize the tokens of an expired, unclaimed pending transfer whose sender was blacklisted since
+        /// into `treasury`, as they can no longer be refunded to the sender.
+        ///
+        /// On success a `Transfer` event and a `SeizedBlackFunds` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns the same errors as `seize_black_funds` for the sender.
+        ///
+        /// Returns `UnknownTransfer` error if the id is unknown, claimed or refunded.
+        ///
+        /// Returns `ClaimWindowOpen` error if the timeout has not passed yet.
+        #[ink(message, selector = "0xB54F7B91")]
+        pub fn seize_unclaimed_transfer(&mut self, id: u64, treasury: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("seize_unclaimed_transfer", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("seize_unclaimed_transfer")?;
+
+            let pending = match self.pending_transfer(id) {
+                Some(pending) => pending,
+                None => {
+                    return Err(self.fail("seize_unclaimed_transfer", Error::UnknownTransfer));
+                }
+            };
+
+            if self.env().block_timestamp() <= pending.expiry {
+                return Err(self.fail("seize_unclaimed_transfer", Error::ClaimWindowOpen));
+            }
+
+            self.seize_escrow("seize_unclaimed_transfer", pending.from, treasury, pending.value)?;
+            self.pending_transfers.take(&id);
+
+            Ok(())
+        }
+
+        /// Seize the tokens of an expired, unredeemed gift whose creator was blacklisted since into
+        /// `treasury`, as they can no longer be reclaimed by the creator.
+        ///
+        /// On success a `Transfer` event and a `SeizedBlackFunds` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns the same errors as `seize_black_funds` for the creator.
+        ///
+        /// Returns `UnknownGift` error if no gift was created under `code_hash`.
+        ///
+        /// Returns `GiftSpent` error if the gift was already redeemed, reclaimed or seized.
+        ///
+        /// Returns `ClaimWindowOpen` error if the gift has not expired yet.
+        #[ink(message, selector = "0xE92B48AD")]
+        pub fn seize_expired_gift(&mut self, code_hash: Hash, treasury: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("seize_expired_gift", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("seize_expired_gift")?;
+
+            let mut gift = self.spendable_gift("seize_expired_gift", code_hash)?;
+
+            if self.env().block_timestamp() <= gift.expiry {
+                return Err(self.fail("seize_expired_gift", Error::ClaimWindowOpen));
+            }
+
+            self.seize_escrow("seize_expired_gift", gift.creator, treasury, gift.amount)?;
+            gift.spent = true;
+            self.gifts.insert(code_hash, gift);
+
+            Ok(())
+        }
+
+        /// Checks that the funds of `account` may be seized into `treasury`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidAccount` error if `treasury` is the zero account or `account` itself.
+        ///
+        /// Returns `AccountBlackListed` error if `treasury` is blacklisted.
+        ///
+        /// Returns the errors of `ensure_destroyable` for `account`.
+        fn ensure_seizable(&mut self, message: &'static str, account: AccountId, treasury: AccountId) -> Result<()> {
+            self.validate_counterparty(message, account, treasury)?;
+
+            if self.is_account_blacklisted(treasury) {
+                return Err(self.fail(message, Error::AccountBlackListed));
+            }
+
+            self.ensure_destroyable(message, account)
+        }
+
+        /// Moves `funds` escrowed on behalf of the blacklisted `account` into `treasury`.
+        fn seize_escrow(&mut self, message: &'static str, account: AccountId, treasury: AccountId, funds: Balance) -> Result<()> {
+            self.ensure_seizable(message, account, treasury)?;
+
+            let escrowed = self.or_overflow(message, self.escrowed.checked_sub(funds))?;
+            self.move_balance(message, self.env().account_id(), treasury, funds)?;
+            self.escrowed = escrowed;
+
+            self.env().emit_event(SeizedBlackFunds {
+                account,
+                treasury,
+                funds
+            });
+
+            Ok(())
+        }
+
+        /// Move `value` tokens from `from` to `to` without `from`'s approval, e.g. to recover the balance
+        /// of an account whose keys were lost once the legal recovery process completed
+        ///
+        /// No transfer fee is charged, and `from` may be frozen. Locked tokens stay where they are.
+        ///
+        /// On success a `Transfer` event and a `ForcedTransfer` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `ProtectedAccount` error if `from` is protected, which includes the owner's account.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account or `from` itself.
+        ///
+        /// Returns `AccountBlackListed` error if `from` or `to` is blacklisted.
+        ///
+        /// Returns `InsufficientBalance` error if `from` has less than `value` unlocked tokens.
+        #[ink(message, selector = "0x66837D18")]
+        pub fn force_transfer(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("force_transfer", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("force_transfer")?;
+
+            self.ensure_unprotected("force_transfer", from)?;
+            self.validate_counterparty("force_transfer", from, to)?;
+            self.ensure_compliant("force_transfer", to, Direction::Credit)?;
+            if self.is_account_blacklisted(from) {
+                return Err(self.fail("force_transfer", Error::AccountBlackListed));
+            }
+
+            if self.spendable_balance_of(from) < value {
+                return Err(self.fail("force_transfer", Error::InsufficientBalance));
+            }
+
+            self.move_balance("force_transfer", from, to, value)?;
+
+            self.env().emit_event(ForcedTransfer {
+                operator: caller,
+                from,
+                to,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Checks that the funds of `account` can be destroyed: it is blacklisted, unprotected, out of
+        /// quarantine and not appealing.
+        fn ensure_destroyable(&mut self, message: &'static str, account: AccountId) -> Result<()> {
+            self.ensure_unprotected(message, account)?;
+
+            let blacklisted = self.is_account_blacklisted(account);
+            if !blacklisted {
+                return Err(self.fail(message, Error::AccountNotBlackListed));
+            }
+
+            if self.env().block_timestamp() < self.destroyable_at(account) {
+                return Err(self.fail(message, Error::QuarantineActive));
+            }
+
+            if self.is_appeal_pending(account, self.env().block_timestamp()) {
+                return Err(self.fail(message, Error::AppealPending));
+            }
+
+            Ok(())
+        }
+
+        /// Destroy the funds of several blacklisted accounts. Only contract owner is allowed to call this function.
+        ///
+        /// Accounts that are not blacklisted, still quarantined or appealing, protected or hold no
+        /// funds are skipped rather than aborting the batch, and returned.
+        ///
+        /// On success a `Transfer` event to no account and a `DestroyedBlackFunds` event are emitted for
+        /// each account whose funds are destroyed, followed by a `DestroyedBlackFundsBatch` event.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_SIZE` accounts are passed.
+        #[ink(message, selector = "0xABB363B1")]
+        pub fn destroy_black_funds_batch(&mut self, accounts: Vec<AccountId>) -> Result<Vec<AccountId>> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("destroy_black_funds_batch", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("destroy_black_funds_batch")?;
+
+            if accounts.len() > MAX_BATCH_SIZE {
+                return Err(self.fail("destroy_black_funds_batch", Error::BatchTooLarge));
+            }
+
+            let now = self.env().block_timestamp();
+            let mut skipped = Vec::new();
+            let mut destroyed = 0;
+            let mut funds: Balance = 0;
+            for account in accounts {
+                if !self.is_account_blacklisted(account)
+                    || now < self.destroyable_at(account)
+                    || self.is_appeal_pending(account, now)
+                    || self.is_protected_account(account)
+                    || self.balance(account) == 0
+                {
+                    skipped.push(account);
+                    continue;
+                }
+                funds += self.destroy(account, self.balance(account));
+                destroyed += 1;
+            }
+
+            self.env().emit_event(DestroyedBlackFundsBatch {
+                destroyed,
+                skipped: skipped.len() as u32,
+                funds
+            });
+
+            Ok(skipped)
+        }
+
+        /// Returns how long an account must stay blacklisted before its funds can be destroyed, in milliseconds
+        #[ink(message, selector = "0x6B3F2CE8")]
+        pub fn destruction_delay(&self) -> Timestamp {
+            self.destruction_delay_ms
+        }
+
+        /// Returns the earliest timestamp a blacklisted account's funds can be destroyed at
+        #[ink(message, selector = "0x3DE9FEAA")]
+        pub fn destroyable_at(&self, account: AccountId) -> Timestamp {
+            let since = self.accounts_blacklisted.get(&account).map(|entry| entry.since).unwrap_or(0);
+            since.saturating_add(self.destruction_delay_ms)
+        }
+
+        /// Set how long an account must stay blacklisted before its funds can be destroyed, in milliseconds
+        ///
+        /// The delay applies to accounts already blacklisted, counted from when they were blacklisted.
+        ///
+        /// On success a `DestructionDelayChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x64C28DDC")]
+        pub fn set_destruction_delay(&mut self, delay_ms: Timestamp) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_destruction_delay", Error::PermissionDenied));
+            }
+
+            self.destruction_delay_ms = delay_ms;
+
+            self.env().emit_event(DestructionDelayChanged {
+                delay_ms
+            });
+
+            Ok(())
+        }
+
+        /// Returns the native bond an appeal must carry and how long a pending appeal blocks
+        /// destroying the account's funds, in milliseconds
+        #[ink(message, selector = "0x6C3EE075")]
+        pub fn appeal_terms(&self) -> (Balance, Timestamp) {
+            (self.appeal_bond, self.appeal_timeout_ms)
+        }
+
+        /// Returns the latest appeal lodged by an account, or `None` if there is none
+        #[ink(message, selector = "0x4FACCF47")]
+        pub fn appeal_of(&self, account: AccountId) -> Option<Appeal> {
+            self.appeals.get(&account).copied()
+        }
+
+        /// Set the native bond an appeal must carry and how long a pending appeal blocks destroying
+        /// the account's funds, in milliseconds
+        ///
+        /// On success an `AppealTermsChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x96EC8306")]
+        pub fn set_appeal_terms(&mut self, bond: Balance, timeout_ms: Timestamp) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_appeal_terms", Error::PermissionDenied));
+            }
+
+            self.appeal_bond = bond;
+            self.appeal_timeout_ms = timeout_ms;
+
+            self.env().emit_event(AppealTermsChanged {
+                bond,
+                timeout_ms
+            });
+
+            Ok(())
+        }
+
+        /// Appeal the caller's blacklisting with the hash of a statement published off-chain,
+        /// transferring at least the appeal bond in native currency along with the call.
+        ///
+        /// An account may appeal once, or again if its previous appeal was upheld and it was
+        /// blacklisted anew. The funds of an account with a pending appeal can't be destroyed
+        /// until the appeal is resolved or the appeal timeout passes.
+        ///
+        /// On success an `AppealLodged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `AccountNotBlackListed` error if the caller's account is not blacklisted.
+        ///
+        /// Returns `AppealExists` error if the caller's appeal is pending or was rejected.
+        ///
+        /// Returns `InsufficientBond` error if less than the appeal bond was transferred.
+        #[ink(message, payable, selector = "0xB5B9A423")]
+        pub fn lodge_appeal(&mut self, statement_hash: Hash) -> Result<()> {
+            let caller = self.env().caller();
+
+            if !self.is_account_blacklisted(caller) {
+                return Err(self.fail("lodge_appeal", Error::AccountNotBlackListed));
+            }
+
+            if self.appeal_of(caller).map_or(false, |appeal| appeal.status != AppealStatus::Upheld) {
+                return Err(self.fail("lodge_appeal", Error::AppealExists));
+            }
+
+            let bond = self.env().transferred_balance();
+            if bond < self.appeal_bond {
+                return Err(self.fail("lodge_appeal", Error::InsufficientBond));
+            }
+
+            self.appeals.insert(caller, Appeal {
+                statement_hash,
+                bond,
+                lodged_at: self.env().block_timestamp(),
+                status: AppealStatus::Pending
+            });
+
+            self.env().emit_event(AppealLodged {
+                account: caller,
+                statement_hash,
+                bond
+            });
+
+            Ok(())
+        }
+
+        /// Resolve an account's pending appeal. Upholding it removes the account from blacklist and
+        /// refunds its bond, rejecting it forfeits the bond to the owner.
+        ///
+        /// On success an `AppealResolved` event is emitted, preceded by a `RemovedBlackList` event
+        /// if the appeal is upheld.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `NoPendingAppeal` error if `account` has no pending appeal.
+        ///
+        /// Returns `BondTransferFailed` error if the bond can't be paid out.
+        #[ink(message, selector = "0xE88806F6")]
+        pub fn resolve_appeal(&mut self, account: AccountId, uphold: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("resolve_appeal", Error::PermissionDenied));
+            }
+
+            let mut appeal = match self.appeal_of(account) {
+                Some(appeal) if appeal.status == AppealStatus::Pending => appeal,
+                _ => {
+                    return Err(self.fail("resolve_appeal", Error::NoPendingAppeal));
+                }
+            };
+
+            let payee = if uphold { account } else { self.owner };
+            if appeal.bond > 0 && self.env().transfer(payee, appeal.bond).is_err() {
+                return Err(self.fail("resolve_appeal", Error::BondTransferFailed));
+            }
+
+            appeal.status = if uphold { AppealStatus::Upheld } else { AppealStatus::Rejected };
+            self.appeals.insert(account, appeal);
+            if uphold {
+                self.unblacklist(account);
+            }
+
+            self.env().emit_event(AppealResolved {
+                account,
+                upheld: uphold
+            });
+
+            Ok(())
+        }
+
+        /// Send `amount` of the native balance sent to the contract to `to`. Bonds of pending appeals
+        /// can't be rescued, as they are owed to the appellants or the owner.
+        ///
+        /// On success a `Rescued` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `to` is the zero account.
+        ///
+        /// Returns `InsufficientBalance` error if `amount` exceeds the native balance besides appeal bonds.
+        ///
+        /// Returns `RescueFailed` error if the runtime refuses the transfer.
+        #[ink(message, selector = "0x82F9DED6")]
+        pub fn rescue_native(&mut self, to: AccountId, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("rescue_native", Error::PermissionDenied));
+            }
+
+            self.validate_account("rescue_native", to)?;
+
+            let bonds: Balance = self.appeals.values()
+                .filter(|appeal| appeal.status == AppealStatus::Pending)
+                .fold(0, |bonds, appeal| bonds.saturating_add(appeal.bond));
+            if amount > self.env().balance().saturating_sub(bonds) {
+                return Err(self.fail("rescue_native", Error::InsufficientBalance));
+            }
+
+            if self.env().transfer(to, amount).is_err() {
+                return Err(self.fail("rescue_native", Error::RescueFailed));
+            }
+
+            self.env().emit_event(Rescued {
+                token: None,
+                to,
+                amount
+            });
+
+            Ok(())
+        }
+
+        /// Send `amount` of the PSP22 `token` held by the contract to `to`, through the token's
+        /// `PSP22::transfer` message.
+        ///
+        /// On success a `Rescued` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `to` or `token` is the zero account, or `token` is
+        /// Entropy itself, whose tokens held by the contract are escrowed.
+        ///
+        /// Returns `RescueFailed` error if the token call fails or returns an error.
+        #[ink(message, selector = "0x85FE8CFE")]
+        pub fn rescue_token(&mut self, token: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("rescue_token", Error::PermissionDenied));
+            }
+
+            self.validate_account("rescue_token", to)?;
+            self.validate_counterparty("rescue_token", self.env().account_id(), token)?;
+
+            if !transfer_foreign_token(token, to, amount) {
+                return Err(self.fail("rescue_token", Error::RescueFailed));
+            }
+
+            self.env().emit_event(Rescued {
+                token: Some(token),
+                to,
+                amount
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether `account` has a pending appeal lodged less than the appeal timeout before `now`.
+        fn is_appeal_pending(&self, account: AccountId, now: Timestamp) -> bool {
+            self.appeal_of(account).map_or(false, |appeal| {
+                appeal.status == AppealStatus::Pending && now < appeal.lodged_at.saturating_add(self.appeal_timeout_ms)
+            })
+        }
+
+        /// Burns `dirty_funds` of the balance of `account`, returning the destroyed amount.
+        fn destroy(&mut self, account: AccountId, dirty_funds: Balance) -> Balance {
+            let balance = self.balance(account);
+            if dirty_funds == balance {
+                self.release_encumbrances(account);
+            }
+            self.set_balance(account, balance - dirty_funds);
+            self.record_volume(Some(account), None, dirty_funds, dirty_funds);
+            self.stats.total_destroyed_black_funds = self.stats.total_destroyed_black_funds.saturating_add(dirty_funds);
+
+            let total_supply = &mut self.total_supply;
+            let current_supply = Lazy::<Balance>::get(total_supply);
+            let new_supply = current_supply - dirty_funds;
+            Lazy::<Balance>::set(total_supply, new_supply);
+
+            self.env().emit_event(Transfer {
+                from: Some(account),
+                to: None,
+                value: dirty_funds
+            });
+            self.env().emit_event(DestroyedBlackFunds {
+                account,
+                funds: dirty_funds
+            });
+
+            dirty_funds
+        }
+
+        /// Releases everything earmarking the balance of `account` once it was seized or destroyed as a
+        /// whole: its self-locks, its held receipts and the holds it pays, each of which emits a
+        /// `HoldReleased` event.
+        fn release_encumbrances(&mut self, account: AccountId) {
+            self.self_locks.take(&account);
+            self.held_receipts.take(&account);
+            let holds: Vec<(u64, Hold)> = self.holds.iter()
+                .filter(|(_, hold)| hold.payer == account)
+                .map(|(id, hold)| (*id, *hold))
+                .collect();
+            for (hold_id, hold) in holds {
+                self.settle_hold(hold_id, hold);
+                self.env().emit_event(HoldReleased {
+                    id: hold_id,
+                    payer: hold.payer,
+                    value: hold.value
+                });
+            }
+        }
+
+        /// Returns the caller's self-locks that haven't expired
+        #[ink(message, selector = "0xCDD6FA40")]
+        pub fn my_locks(&self) -> Vec<SelfLock> {
+            self.active_locks(self.env().caller())
+        }
+
+        /// Returns the amount of an account's balance held by unexpired self-locks
+        #[ink(message, selector = "0xA3B7D8EB")]
+        pub fn locked_balance_of(&self, account: AccountId) -> Balance {
+            self.active_locks(account).iter().fold(0, |locked: Balance, lock| locked.saturating_add(lock.amount))
+        }
+
+        /// Returns the part of an account's balance not held by self-locks or holds
+        #[ink(message, selector = "0x57392061")]
+        pub fn spendable_balance_of(&self, account: AccountId) -> Balance {
+            self.balance(account)
+                .saturating_sub(self.locked_balance_of(account))
+                .saturating_sub(self.on_hold_balance_of(account))
+        }
+
+        /// Returns how long received tokens stay unspendable, in milliseconds, `0` if the holding period is off
+        #[ink(message, selector = "0x0D12BCF4")]
+        pub fn min_holding_period(&self) -> Timestamp {
+            self.min_holding_period_ms
+        }
+
+        /// Set how long tokens received by transfer stay unspendable, in milliseconds, `0` turns the holding period off
+        ///
+        /// The period applies to receipts already tracked, counted from when they were received.
+        /// Receipts are only tracked while the period is on.
+        ///
+        /// On success a `MinHoldingPeriodChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x22BD526B")]
+        pub fn set_min_holding_period(&mut self, period_ms: Timestamp) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_min_holding_period", Error::PermissionDenied));
+            }
+
+            self.min_holding_period_ms = period_ms;
+
+            self.env().emit_event(MinHoldingPeriodChanged {
+                period_ms
+            });
+
+            Ok(())
+        }
+
+        /// Returns the amount of an account's balance received within the minimum holding period
+        ///
+        /// The owner and system accounts hold nothing back.
+        #[ink(message, selector = "0xD013403D")]
+        pub fn held_balance_of(&self, account: AccountId) -> Balance {
+            if self.is_holding_exempt(account) {
+                return 0;
+            }
+            let now = self.env().block_timestamp();
+            let period = self.min_holding_period_ms;
+            self.held_receipts.get(&account).map_or(0, |receipts| {
+                receipts.iter()
+                    .filter(|(received_at, _)| received_at.saturating_add(period) > now)
+                    .fold(0, |held: Balance, (_, amount)| held.saturating_add(*amount))
+            })
+        }
+
+        /// Returns whether an account is exempt from the minimum holding period
+        fn is_holding_exempt(&self, account: AccountId) -> bool {
+            self.min_holding_period_ms == 0 || account == self.owner || self.is_system_account(account)
+        }
+
+        /// Tracks `amount` received by `account` for the minimum holding period, dropping receipts
+        /// whose period has passed. Once `MAX_HELD_RECEIPTS` are tracked, the amount is merged into the
+        /// latest receipt, which is restarted so nothing is released early.
+        fn record_held_receipt(&mut self, account: AccountId, amount: Balance) {
+            if amount == 0 || self.is_holding_exempt(account) {
+                return;
+            }
+            let now = self.env().block_timestamp();
+            let period = self.min_holding_period_ms;
+            let receipts = self.held_receipts.entry(account).or_insert_with(Vec::new);
+            receipts.retain(|(received_at, _)| received_at.saturating_add(period) > now);
+            if receipts.len() < MAX_HELD_RECEIPTS {
+                receipts.push((now, amount));
+                return;
+            }
+            if let Some(latest) = receipts.last_mut() {
+                *latest = (now, latest.1.saturating_add(amount));
+            }
+        }
+
+        /// Returns the spending limit an account imposed on itself, with a due raise or removal applied
+        #[ink(message, selector = "0x09B14C07")]
+        pub fn spending_limit_of(&self, account: AccountId) -> SpendingLimit {
+            let limit = self.spending_limits.get(&account).copied().unwrap_or_default();
+            match limit.pending {
+                Some((amount_per_day, effective_at)) if effective_at <= self.env().block_timestamp() => SpendingLimit {
+                    amount_per_day,
+                    pending: None
+                },
+                _ => limit
+            }
+        }
+
+        /// Limit how much the caller can send per 24 hours, `0` removing the limit
+        ///
+        /// Lowering the limit takes effect at once, while raising or removing it only takes effect
+        /// after `SPENDING_LIMIT_DELAY_MS`, so whoever steals the caller's key can't lift it. Setting
+        /// a limit also cancels a raise or removal still waiting for its delay. The limit applies on
+        /// top of any rule imposed by the owner.
+        ///
+        /// On success a `SpendingLimitChanged` event is emitted.
+        #[ink(message, selector = "0x6F03682E")]
+        pub fn set_my_spending_limit(&mut self, amount_per_day: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+            let current = self.spending_limit_of(caller).amount_per_day;
+
+            let lowered = amount_per_day != 0 && (current == 0 || amount_per_day <= current);
+            let (limit, effective_at) = if lowered {
+                (SpendingLimit { amount_per_day, pending: None }, now)
+            } else {
+                let effective_at = now.saturating_add(SPENDING_LIMIT_DELAY_MS);
+                (SpendingLimit { amount_per_day: current, pending: Some((amount_per_day, effective_at)) }, effective_at)
+            };
+            self.spending_limits.insert(caller, limit);
+
+            self.env().emit_event(SpendingLimitChanged {
+                account: caller,
+                amount_per_day,
+                effective_at
+            });
+
+            Ok(())
+        }
+
+        /// Returns the start of `account`'s current spending window and the amount sent within it, the
+        /// window being started by the first transfer sent after the previous window ended.
+        fn spending_window_of(&self, account: AccountId) -> (Timestamp, Balance) {
+            let now = self.env().block_timestamp();
+            match self.spending_windows.get(&account).copied() {
+                Some((start, spent)) if now < start.saturating_add(SPENDING_LIMIT_WINDOW_MS) => (start, spent),
+                _ => (now, 0)
+            }
+        }
+
+        /// Returns how much `account` can still send within its own spending limit, or `None` if it has none.
+        fn remaining_spending_of(&self, account: AccountId) -> Option<Balance> {
+            let limit = self.spending_limit_of(account).amount_per_day;
+            if limit == 0 {
+                return None;
+            }
+            Some(limit.saturating_sub(self.spending_window_of(account).1))
+        }
+
+        /// Counts `value` sent by `account` against its own spending limit.
+        fn record_spending(&mut self, account: AccountId, value: Balance) {
+            if self.spending_limit_of(account).amount_per_day == 0 {
+                return;
+            }
+            let (window_start, spent) = self.spending_window_of(account);
+            self.spending_windows.insert(account, (window_start, spent.saturating_add(value)));
+        }
+
+        /// Returns the default transfer limit per 24 hours imposed on accounts without their own, `0` if there is none
+        #[ink(message, selector = "0x40C9BBC1")]
+        pub fn default_transfer_limit(&self) -> Balance {
+            self.default_transfer_limit
+        }
+
+        /// Set the transfer limit per 24 hours of accounts without their own, `0` removing it
+        ///
+        /// Transfers from the owner or a system account are exempt.
+        ///
+        /// On success a `DefaultTransferLimitChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x580C1F87")]
+        pub fn set_default_transfer_limit(&mut self, limit: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_default_transfer_limit", Error::PermissionDenied));
+            }
+
+            self.default_transfer_limit = limit;
+
+            self.env().emit_event(DefaultTransferLimitChanged {
+                limit
+            });
+
+            Ok(())
+        }
+
+        /// Set how much `account` can send per 24 hours, overriding the default transfer limit
+        ///
+        /// `Some(0)` leaves the account unlimited whatever the default, while `None` makes it fall
+        /// back to the default. The limit applies on top of the account's own spending limit.
+        ///
+        /// On success a `TransferLimitChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0xC0A0F030")]
+        pub fn set_transfer_limit(&mut self, account: AccountId, limit: Option<Balance>) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_transfer_limit", Error::PermissionDenied));
+            }
+
+            self.validate_account("set_transfer_limit", account)?;
+
+            match limit {
+                Some(limit) => self.transfer_limits.insert(account, limit),
+                None => self.transfer_limits.take(&account)
+            };
+
+            self.env().emit_event(TransferLimitChanged {
+                account,
+                limit
+            });
+
+            Ok(())
+        }
+
+        /// Returns how much an account can send per 24 hours, `0` if it is unlimited
+        #[ink(message, selector = "0xF12F7D30")]
+        pub fn transfer_limit_of(&self, account: AccountId) -> Balance {
+            if account == self.owner || self.is_system_account(account) {
+                return 0;
+            }
+            self.transfer_limits.get(&account).copied().unwrap_or(self.default_transfer_limit)
+        }
+
+        /// Returns how much an account can still send in its current transfer limit window, or
+        /// `None` if it is unlimited
+        #[ink(message, selector = "0xBB9B8705")]
+        pub fn remaining_limit_of(&self, account: AccountId) -> Option<Balance> {
+            let limit = self.transfer_limit_of(account);
+            if limit == 0 {
+                return None;
+            }
+            Some(limit.saturating_sub(self.transfer_window_of(account).1))
+        }
+
+        /// Returns the start of `account`'s current transfer limit window and the amount sent within
+        /// it, starting a new window once more than `TRANSFER_LIMIT_WINDOW_MS` passed since the last one began.
+        fn transfer_window_of(&self, account: AccountId) -> (Timestamp, Balance) {
+            let now = self.env().block_timestamp();
+            match self.transfer_windows.get(&account).copied() {
+                Some((start, spent)) if now.saturating_sub(start) <= TRANSFER_LIMIT_WINDOW_MS => (start, spent),
+                _ => (now, 0)
+            }
+        }
+
+        /// Counts `value` sent by `account` against the transfer limit the owner imposed on it.
+        fn record_limited_transfer(&mut self, account: AccountId, value: Balance) {
+            if self.transfer_limit_of(account) == 0 {
+                return;
+            }
+            let (window_start, spent) = self.transfer_window_of(account);
+            self.transfer_windows.insert(account, (window_start, spent.saturating_add(value)));
+        }
+
+        /// Lock `amount` of the caller's spendable balance until `until`, as a commitment device
+        ///
+        /// Nobody can lift a self-lock early, neither the caller nor the owner; it expires on its own
+        /// once `until` has passed. Only compliance actions override it: destroying a blacklisted
+        /// account's funds and reversing a transfer.
+        ///
+        /// On success a `TokensLocked` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidLock` error if `amount` is zero or `until` has already passed.
+        ///
+        /// Returns `InsufficientBalance` error if the caller's spendable balance is below `amount`.
+        #[ink(message, selector = "0x3D19FC04")]
+        pub fn lock_my_tokens(&mut self, amount: Balance, until: Timestamp) -> Result<()> {
+            let caller = self.env().caller();
+
+            if amount == 0 || until <= self.env().block_timestamp() {
+                return Err(self.fail("lock_my_tokens", Error::InvalidLock));
+            }
+
+            if self.spendable_balance_of(caller) < amount {
+                return Err(self.fail("lock_my_tokens", Error::InsufficientBalance));
+            }
+
+            self.add_lock(caller, amount, until);
+            Ok(())
+        }
+
+        /// Locks `amount` of `account`'s balance until `until`, sweeping its matured locks, and emits
+        /// a `TokensLocked` event.
+        fn add_lock(&mut self, account: AccountId, amount: Balance, until: Timestamp) {
+            self.sweep_matured_locks(account);
+            let mut locks = self.active_locks(account);
+            locks.push(SelfLock {
+                amount,
+                until
+            });
+            self.self_locks.insert(account, locks);
+
+            self.env().emit_event(TokensLocked {
+                account,
+                amount,
+                until
+            });
+        }
+
+        /// Sweep the caller's matured locks, whose tokens are already spendable, returning the
+        /// amount they held
+        ///
+        /// On success a `TokensUnlocked` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `NothingToClaim` error if none of the caller's locks has matured.
+        #[ink(message, selector = "0x3E96A76E")]
+        pub fn claim_unlocked(&mut self) -> Result<Balance> {
+            let caller = self.env().caller();
+            match self.sweep_matured_locks(caller) {
+                0 => Err(self.fail("claim_unlocked", Error::NothingToClaim)),
+                amount => Ok(amount)
+            }
+        }
+
+        /// Removes the expired self-locks of `account`, returning the amount they held. Emits a
+        /// `TokensUnlocked` event if it is nonzero.
+        fn sweep_matured_locks(&mut self, account: AccountId) -> Balance {
+            let now = self.env().block_timestamp();
+            let (matured, active): (Vec<SelfLock>, Vec<SelfLock>) = match self.self_locks.get(&account) {
+                Some(locks) => locks.iter().partition(|lock| lock.until < now),
+                None => return 0
+            };
+            if matured.is_empty() {
+                return 0;
+            }
+
+            let matured = matured.iter().fold(0, |total: Balance, lock| total.saturating_add(lock.amount));
+            if active.is_empty() {
+                self.self_locks.take(&account);
+            } else {
+                self.self_locks.insert(account, active);
+            }
+            if matured > 0 {
+                self.env().emit_event(TokensUnlocked {
+                    account,
+                    amount: matured
+                });
+            }
+            matured
+        }
+
+        /// Returns the self-locks of `account` that haven't expired. A lock still holds at its `until`.
+        fn active_locks(&self, account: AccountId) -> Vec<SelfLock> {
+            let now = self.env().block_timestamp();
+            self.self_locks.get(&account)
+                .map(|locks| locks.iter().filter(|lock| lock.until >= now).copied().collect())
+                .unwrap_or_default()
+        }
+
+        /// Returns whether an account is currently frozen
+        #[ink(message, selector = "0xC3488348")]
+        pub fn is_account_frozen(&self, account: AccountId) -> bool {
+            self.frozen_until(account).is_some()
+        }
+
+        /// Returns the timestamp until which an account is frozen, or `None` if it is not frozen
+        #[ink(message, selector = "0xA499F01F")]
+        pub fn frozen_until(&self, account: AccountId) -> Option<Timestamp> {
+            if !self.flags(account).frozen {
+                return None;
+            }
+            let now = self.env().block_timestamp();
+            self.frozen_until.get(&account).copied().filter(|until| *until > now)
+        }
+
+        /// Freeze an account until it is unfrozen, keeping it from sending or approving tokens while
+        /// it can still receive them
+        ///
+        /// On success a `Frozen` event is emitted, with `until` at `Timestamp::MAX`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ProtectedAccount` error if `account` is protected, which includes the owner's account.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0x6C44B1A2")]
+        pub fn freeze_account(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("freeze_account", Error::PermissionDenied));
+            }
+
+            self.freeze("freeze_account", account, Timestamp::MAX)
+        }
+
+        /// Freeze an account until the timestamp `until`, after which it is unfrozen automatically
+        ///
+        /// On success a `Frozen` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ProtectedAccount` error if `account` is protected, which includes the owner's account.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0xCD70B90E")]
+        pub fn freeze_account_until(&mut self, account: AccountId, until: Timestamp) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("freeze_account_until", Error::PermissionDenied));
+            }
+
+            self.freeze("freeze_account_until", account, until)
+        }
+
+        /// Freezes `account` until `until` on behalf of the owner.
+        fn freeze(&mut self, message: &'static str, account: AccountId, until: Timestamp) -> Result<()> {
+            self.ensure_unprotected(message, account)?;
+            self.validate_counterparty(message, self.owner, account)?;
+
+            self.frozen_until.insert(account, until);
+            self.update_flags(account, |flags| flags.frozen = true);
+
+            self.env().emit_event(Frozen {
+                account,
+                until
+            });
+
+            Ok(())
+        }
+
+        /// Unfreeze an account before its freeze expires, and reset its velocity window
+        ///
+        /// On success an `Unfrozen` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x54E8435B")]
+        pub fn unfreeze_account(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("unfreeze_account", Error::PermissionDenied));
+            }
+
+            self.unfreeze(account);
+
+            Ok(())
+        }
+
+        /// Unfreezes `account` and resets its velocity window.
+        fn unfreeze(&mut self, account: AccountId) {
+            self.frozen_until.take(&account);
+            self.update_flags(account, |flags| flags.frozen = false);
+            self.outflows.take(&account);
+
+            self.env().emit_event(Unfrozen {
+                account
+            });
+        }
+
+        /// Returns the velocity rule applied to outgoing transfers
+        #[ink(message, selector = "0xA07EE37C")]
+        pub fn velocity_rule(&self) -> VelocityRule {
+            self.velocity_rule
+        }
+
+        /// Set the velocity rule: an account making more than `max_outflows_per_window` outgoing
+        /// transfers within `window_ms` milliseconds, which together move more than `max_share_bps`
+        /// basis points of its balance when the window opened, is frozen for `window_ms` milliseconds.
+        /// Setting either of the first two params to `0` disables the rule, and `max_share_bps`
+        /// to `0` has it trip on the count alone. `max_share_bps` is capped at 10000.
+        ///
+        /// On success a `VelocityRuleChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xC0CCD225")]
+        pub fn set_velocity_rule(&mut self, max_outflows_per_window: u32, window_ms: u64, max_share_bps: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_velocity_rule", Error::PermissionDenied));
+            }
+
+            self.velocity_rule = VelocityRule {
+                max_outflows_per_window,
+                window_ms,
+                max_share_bps: if max_share_bps > 10000 { 10000 } else { max_share_bps }
+            };
+
+            self.env().emit_event(VelocityRuleChanged {
+                rule: self.velocity_rule
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether an account is exempt from the velocity rule
+        #[ink(message, selector = "0x44723156")]
+        pub fn is_velocity_exempt(&self, account: AccountId) -> bool {
+            self.velocity_exempt.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Set whether an account is exempt from the velocity rule
+        ///
+        /// On success a `VelocityExemption` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0x0165248B")]
+        pub fn set_velocity_exempt(&mut self, account: AccountId, exempt: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_velocity_exempt", Error::PermissionDenied));
+            }
+
+            self.validate_account("set_velocity_exempt", account)?;
+
+            if exempt {
+                self.velocity_exempt.insert(account, true);
+            } else {
+                self.velocity_exempt.take(&account);
+            }
+
+            self.env().emit_event(VelocityExemption {
+                account,
+                exempt
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether an account is exempt from transfer fees
+        #[ink(message, selector = "0x77D423C1")]
+        pub fn is_fee_exempt(&self, account: AccountId) -> bool {
+            self.flags(account).fee_exempt
+        }
+
+        /// Set whether an account is exempt from transfer fees, sparing any transfer from or to it
+        ///
+        /// On success a `FeeExemption` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0x350B81DC")]
+        pub fn set_fee_exempt(&mut self, account: AccountId, exempt: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_fee_exempt", Error::PermissionDenied));
+            }
+
+            self.validate_account("set_fee_exempt", account)?;
+
+            self.update_flags(account, |flags| flags.fee_exempt = exempt);
+
+            self.env().emit_event(FeeExemption {
+                account,
+                exempt
+            });
+
+            Ok(())
+        }
+
+        /// Returns the fee charged on a transfer of `value` from `from` to `to`, which is zero if
+        /// either account is fee exempt.
+        fn transfer_fee(&self, from: AccountId, to: AccountId, value: Balance) -> Balance {
+            if self.is_fee_exempt(from) || self.is_fee_exempt(to) {
+                return 0;
+            }
+            self.compute_fee(value)
+        }
+
+        /// Returns `account`'s current velocity window with the outgoing transfers recorded within it,
+        /// or `None` if the velocity rule doesn't apply to it. A window opening now holds no transfers.
+        fn outflow_window_of(&self, account: AccountId) -> Option<OutflowWindow> {
+            let rule = self.velocity_rule;
+            if rule.max_outflows_per_window == 0 || rule.window_ms == 0 || self.is_velocity_exempt(account) {
+                return None;
+            }
+
+            let now = self.env().block_timestamp();
+            Some(match self.outflows.get(&account).copied() {
+                Some(window) if now < window.start.saturating_add(rule.window_ms) => window,
+                _ => OutflowWindow {
+                    start: now,
+                    count: 0,
+                    sent: 0,
+                    opening_balance: self.balance(account)
+                }
+            })
+        }
+
+        /// Returns whether one more outgoing transfer of `value` within `window` trips the velocity
+        /// rule, exceeding both its transfer count and its share of the opening balance.
+        fn trips_velocity_rule(&self, window: OutflowWindow, value: Balance) -> bool {
+            let rule = self.velocity_rule;
+            window.count >= rule.max_outflows_per_window
+                && window.sent.saturating_add(value) > mul_div(window.opening_balance, rule.max_share_bps, 10000)
+        }
+
+        /// Records an outgoing transfer of `value` from `account` against the velocity rule.
+        fn record_outflow(&mut self, account: AccountId, value: Balance) {
+            if let Some(window) = self.outflow_window_of(account) {
+                self.outflows.insert(account, OutflowWindow {
+                    count: window.count.saturating_add(1),
+                    sent: window.sent.saturating_add(value),
+                    ..window
+                });
+            }
+        }
+
+        /// Migrate the caller's full balance, privacy flag and velocity rule exemption to `new_account`,
+        /// and reset all allowances the caller granted.
+        ///
+        /// On success an `Approval` event with zero value is emitted for each reset allowance,
+        /// followed by a `Transfer` event for the balance and an `AccountRekeyed` event.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidAccount` error if `new_account` is the zero account or the caller's account.
+        ///
+        /// Returns `AccountBlackListed` error if either account is blacklisted.
+        ///
+        /// Returns `AccountFrozen` error if the old account is frozen.
+        #[ink(message, selector = "0x36830082")]
+        pub fn rekey_to(&mut self, new_account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.rekey("rekey_to", caller, new_account)
+        }
+
+        /// Migrate the full balance, privacy flag and velocity rule exemption of `account` to `new_account`,
+        /// and reset all allowances `account` granted. Only contract owner is allowed to call this function.
+        ///
+        /// On success an `Approval` event with zero value is emitted for each reset allowance,
+        /// followed by a `Transfer` event for the balance and an `AccountRekeyed` event.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ProtectedAccount` error if `account` is protected, which includes the owner's account.
+        ///
+        /// Returns `InvalidAccount` error if `new_account` is the zero account or `account`.
+        ///
+        /// Returns `AccountBlackListed` error if either account is blacklisted.
+        ///
+        /// Returns `AccountFrozen` error if `account` is frozen.
+        #[ink(message, selector = "0x53301C20")]
+        pub fn rekey_account(&mut self, account: AccountId, new_account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("rekey_account", Error::PermissionDenied));
+            }
+
+            self.ensure_unprotected("rekey_account", account)?;
+            self.rekey("rekey_account", account, new_account)
+        }
+
+        /// Moves the balance, self-locks, holds and flags of `old` to `new` without fees and resets allowances granted by `old`.
+        fn rekey(&mut self, message: &'static str, old: AccountId, new: AccountId) -> Result<()> {
+            self.ensure_compliant(message, new, Direction::Credit)?;
+            if old == new {
+                return Err(self.fail(message, Error::InvalidAccount));
+            }
+            self.ensure_compliant(message, old, Direction::Debit)?;
+
+            let spenders: Vec<AccountId> = self.allowances.iter()
+                .filter(|((owner, _), entry)| *owner == old && entry.value > 0)
+                .map(|((_, spender), _)| *spender)
+                .collect();
+            for spender in spenders {
+                self.allowances.take(&(old, spender));
+                self.env().emit_event(Approval {
+                    owner: old,
+                    spender,
+                    value: 0,
+                });
+            }
+            let scoped: Vec<(AccountId, AccountId)> = self.recipient_allowances.iter()
+                .filter(|((owner, _, _), value)| *owner == old && **value > 0)
+                .map(|((_, spender, recipient), _)| (*spender, *recipient))
+                .collect();
+            for (spender, recipient) in scoped {
+                self.recipient_allowances.take(&(old, spender, recipient));
+                self.env().emit_event(RecipientApproval {
+                    owner: old,
+                    spender,
+                    recipient,
+                    value: 0
+                });
+            }
+
+            let source = self.privacy_sources.take(&old);
+            if self.is_account_private(old) {
+                self.update_flags(old, |flags| flags.private = false);
+                self.update_flags(new, |flags| flags.private = true);
+                if let Some(source) = source {
+                    self.privacy_sources.insert(new, source);
+                }
+            }
+            if self.velocity_exempt.take(&old).unwrap_or(false) {
+                self.velocity_exempt.insert(new, true);
+            }
+            if let Some(mut locks) = self.self_locks.take(&old) {
+                locks.extend(self.self_locks.take(&new).unwrap_or_default());
+                self.self_locks.insert(new, locks);
+            }
+            let holds: Vec<(u64, Hold)> = self.holds.iter()
+                .filter(|(_, hold)| hold.payer == old || hold.to == old)
+                .map(|(id, hold)| (*id, *hold))
+                .collect();
+            for (id, mut hold) in holds {
+                if hold.payer == old {
+                    hold.payer = new;
+                }
+                if hold.to == old {
+                    hold.to = new;
+                }
+                self.holds.insert(id, hold);
+            }
+            if let Some(on_hold) = self.on_hold.take(&old) {
+                let new_on_hold = self.on_hold_balance_of(new);
+                self.on_hold.insert(new, new_on_hold + on_hold);
+            }
+
+            let balance = self.balance(old);
+            if balance > 0 {
+                self.set_balance(old, 0);
+                let new_balance = self.balance(new);
+                self.set_balance(new, new_balance + balance);
+                self.record_volume(Some(old), Some(new), balance, balance);
+                self.env().emit_event(Transfer {
+                    from: Some(old),
+                    to: Some(new),
+                    value: balance,
+                });
+            }
+
+            self.env().emit_event(AccountRekeyed {
+                old,
+                new
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether trading is enabled
+        #[ink(message, selector = "0x87B6473B")]
+        pub fn trading_enabled(&self) -> bool {
+            self.trading_enabled
+        }
+
+        /// Enable trading for everyone. Trading cannot be disabled again once enabled.
+        ///
+        /// On success a `TradingEnabled` event is emitted the first time trading is enabled.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x7F12C9C9")]
+        pub fn enable_trading(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("enable_trading", Error::PermissionDenied));
+            }
+
+            if !self.trading_enabled {
+                self.trading_enabled = true;
+                self.env().emit_event(TradingEnabled {
+                    block: self.env().block_number()
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Returns whether an account may send tokens before trading is enabled
+        #[ink(message, selector = "0x5EFF199F")]
+        pub fn is_prelaunch_allowed(&self, account: AccountId) -> bool {
+            self.prelaunch_allowlist.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Set whether an account may send tokens before trading is enabled
+        ///
+        /// On success a `PrelaunchAllowlist` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0x5010C2EB")]
+        pub fn set_prelaunch_allowed(&mut self, account: AccountId, allowed: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_prelaunch_allowed", Error::PermissionDenied));
+            }
+
+            self.validate_account("set_prelaunch_allowed", account)?;
+
+            if allowed {
+                self.prelaunch_allowlist.insert(account, true);
+            } else {
+                self.prelaunch_allowlist.take(&account);
+            }
+
+            self.env().emit_event(PrelaunchAllowlist {
+                account,
+                allowed
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether `from` may send tokens, i.e. trading is enabled or `from` is the owner
+        /// or on the pre-launch allowlist.
+        fn is_trading_allowed(&self, from: AccountId) -> bool {
+            self.trading_enabled || from == self.owner || self.is_prelaunch_allowed(from)
+        }
+
+        /// Returns whether the contract is paused, a timed pause counts only until it lifts
+        #[ink(message, selector = "0xD8FEA916")]
+        pub fn paused(&self) -> bool {
+            self.paused && self.paused_until.map_or(true, |until| self.env().block_timestamp() < until)
+        }
+
+        /// Returns whether the contract is paused and the timestamp a timed pause lifts at,
+        /// `None` if the pause is indefinite
+        #[ink(message, selector = "0xA16611A5")]
+        pub fn pause_status(&self) -> (bool, Option<Timestamp>) {
+            if self.paused() {
+                (true, self.paused_until)
+            } else {
+                (false, None)
+            }
+        }
+
+        /// Pause the contract until the owner unpauses it
+        ///
+        /// On success a `Paused` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x81E0C604")]
+        pub fn pause(&mut self) -> Result<()> {
+            self.set_paused_until("pause", None)
+        }
+
+        /// Pause the contract for `duration_ms`, after which it is unpaused without a transaction.
+        /// Replaces any current pause, so calling it again extends or shortens the pause.
+        ///
+        /// On success a `Paused` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xD8F926C1")]
+        pub fn pause_for(&mut self, duration_ms: u64) -> Result<()> {
+            let until = self.env().block_timestamp().saturating_add(duration_ms);
+            self.set_paused_until("pause_for", Some(until))
+        }
+
+        /// Pauses the contract until `until`, or indefinitely if `None`, on behalf of the owner.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        fn set_paused_until(&mut self, message: &'static str, until: Option<Timestamp>) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail(message, Error::PermissionDenied));
+            }
+
+            self.paused = true;
+            self.paused_until = until;
+
+            self.env().emit_event(Paused {
+                by: caller,
+                until
+            });
+
+            Ok(())
+        }
+
+        /// Unpause the contract, e.g. after the circuit breaker tripped
+        ///
+        /// On success an `Unpaused` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x67616649")]
+        pub fn unpause(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("unpause", Error::PermissionDenied));
+            }
+
+            self.paused = false;
+            self.paused_until = None;
+
+            self.env().emit_event(Unpaused {
+                by: caller
+            });
+
+            Ok(())
+        }
+
+        /// Returns the circuit breaker threshold in basis points of total supply, `0` if disabled
+        #[ink(message, selector = "0xA4459400")]
+        pub fn circuit_breaker_bps(&self) -> Balance {
+            self.circuit_breaker_bps
+        }
+
+        /// Set the largest fraction of total supply, in basis points, a single transfer may move
+        ///
+        /// A transfer moving more rejects with `CircuitBreakerTripped` and pauses the contract until the
+        /// owner unpauses it. `0` disables the breaker.
+        ///
+        /// On success a `CircuitBreakerChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x6ED5C333")]
+        pub fn set_circuit_breaker(&mut self, bps: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_circuit_breaker", Error::PermissionDenied));
+            }
+
+            self.circuit_breaker_bps = bps;
+
+            self.env().emit_event(CircuitBreakerChanged {
+                bps
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether an account is a system account
+        #[ink(message, selector = "0x2661AEE1")]
+        pub fn is_system_account(&self, account: AccountId) -> bool {
+            self.system_accounts.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Designate an account as a system account, exempt from the circuit breaker, or remove it
+        ///
+        /// On success a `SystemAccount` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0x0A070A5B")]
+        pub fn set_system_account(&mut self, account: AccountId, system: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_system_account", Error::PermissionDenied));
+            }
+
+            self.validate_account("set_system_account", account)?;
+
+            if system {
+                self.system_accounts.insert(account, true);
+            } else {
+                self.system_accounts.take(&account);
+            }
+
+            self.env().emit_event(SystemAccount {
+                account,
+                system
+            });
+
+            Ok(())
+        }
+
+        /// Returns the contract trusted to call the `*_for` messages, `None` if there is none
+        #[ink(message, selector = "0x91C55059")]
+        pub fn trusted_forwarder(&self) -> Option<AccountId> {
+            self.trusted_forwarder
+        }
+
+        /// Set the contract trusted to call `transfer_for` and `approve_for` on behalf of senders it
+        /// authenticated itself, or remove it with `None`
+        ///
+        /// The forwarder can act for any account, so only a vetted contract should be trusted.
+        ///
+        /// On success a `TrustedForwarderChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `forwarder` is the zero account.
+        #[ink(message, selector = "0xD41D7E38")]
+        pub fn set_trusted_forwarder(&mut self, forwarder: Option<AccountId>) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_trusted_forwarder", Error::PermissionDenied));
+            }
+
+            if let Some(forwarder) = forwarder {
+                self.validate_account("set_trusted_forwarder", forwarder)?;
+            }
+
+            self.trusted_forwarder = forwarder;
+
+            self.env().emit_event(TrustedForwarderChanged {
+                forwarder
+            });
+
+            Ok(())
+        }
+
+        /// Returns `sender` as the account a `*_for` message acts for, if the caller is the trusted forwarder.
+        ///
+        /// # Errors
+        ///
+        /// Returns `UntrustedForwarder` error if the caller is not the trusted forwarder.
+        fn forwarded_sender(&mut self, message: &'static str, sender: AccountId) -> Result<AccountId> {
+            if self.trusted_forwarder != Some(self.env().caller()) {
+                return Err(self.fail(message, Error::UntrustedForwarder));
+            }
+            Ok(sender)
+        }
+
+        /// Returns whether an account is protected from compliance actions
+        ///
+        /// The owner's and the fee collector's accounts are always protected.
+        #[ink(message, selector = "0x1D464EDB")]
+        pub fn is_protected_account(&self, account: AccountId) -> bool {
+            account == self.owner || account == self.fee_collector || self.protected_accounts.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Protect an account from blacklisting, freezing, destroying its funds and rekeying
+        ///
+        /// On success an `AccountProtection` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        #[ink(message, selector = "0x5CD581E1")]
+        pub fn protect_account(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("protect_account", Error::PermissionDenied));
+            }
+
+            self.validate_account("protect_account", account)?;
+
+            self.protected_accounts.insert(account, true);
+
+            self.env().emit_event(AccountProtection {
+                account,
+                protected: true
+            });
+
+            Ok(())
+        }
+
+        /// Remove the protection of an account, making it subject to compliance actions again
+        ///
+        /// The owner's account stays protected for as long as it is the owner.
+        ///
+        /// On success an `AccountProtection` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x62FA70C6")]
+        pub fn unprotect_account(&mut self, account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("unprotect_account", Error::PermissionDenied));
+            }
+
+            self.protected_accounts.take(&account);
+
+            self.env().emit_event(AccountProtection {
+                account,
+                protected: false
+            });
+
+            Ok(())
+        }
+
+        /// Checks that `account` is not protected from compliance actions.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ProtectedAccount` error if `account` is protected.
+        fn ensure_unprotected(&mut self, message: &'static str, account: AccountId) -> Result<()> {
+            if self.is_protected_account(account) {
+                return Err(self.fail(message, Error::ProtectedAccount));
+            }
+            Ok(())
+        }
+
+        /// Returns the transfer-mining program configuration
+        #[ink(message, selector = "0x45328C7D")]
+        pub fn points_program(&self) -> PointsProgram {
+            self.points_program
+        }
+
+        /// Configure the transfer-mining program
+        ///
+        /// While `active`, each fee-paying transfer of `value` accrues `value / points_divisor` points to its
+        /// sender. Claimed points are paid by minting if `mint` is set, or else from the contract's own
+        /// balance, which the owner funds by transferring tokens to the contract's account. At most
+        /// `epoch_cap` points are converted per `epoch_ms` milliseconds.
+        ///
+        /// On success a `PointsProgramChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xC9E614EC")]
+        pub fn set_points_program(&mut self, active: bool, points_divisor: Balance, mint: bool, epoch_cap: Balance, epoch_ms: u64) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_points_program", Error::PermissionDenied));
+            }
+
+            self.points_program = PointsProgram {
+                active,
+                divisor: points_divisor,
+                mint,
+                epoch_cap,
+                epoch_ms
+            };
+
+            self.env().emit_event(PointsProgramChanged {
+                program: self.points_program
+            });
+
+            Ok(())
+        }
+
+        /// Returns the unclaimed reward points of an account
+        #[ink(message, selector = "0x070989A9")]
+        pub fn points_of(&self, account: AccountId) -> Balance {
+            self.reward_points.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Returns the number of tokens paid per claimed point, `0` if points can't be claimed
+        #[ink(message, selector = "0x899E7C1B")]
+        pub fn points_rate(&self) -> Balance {
+            self.points_rate
+        }
+
+        /// Open point claims at `rate` tokens per point, or close them with a `rate` of `0`
+        ///
+        /// On success a `PointsConversion` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x5A5B912D")]
+        pub fn convert_points(&mut self, rate: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("convert_points", Error::PermissionDenied));
+            }
+
+            self.points_rate = rate;
+
+            self.env().emit_event(PointsConversion {
+                rate
+            });
+
+            Ok(())
+        }
+
+        /// Claim the caller's reward points as tokens at the current rate
+        ///
+        /// If fewer points than the caller holds are left in the current epoch's cap, only those are
+        /// converted and the rest stay claimable.
+        ///
+        /// On success a `Transfer` event and a `PointsClaimed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `NothingToClaim` error if no conversion rate is set or the caller has no points.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's account is blacklisted.
+        ///
+        /// Returns `PointsCapExceeded` error if the current epoch's cap is exhausted.
+        ///
+        /// Returns `InsufficientBalance` error if claims are paid from the contract's balance and it
+        /// is not funded enough, not counting tokens escrowed for distributions.
+        ///
+        /// Returns `Overflow` error if the claimed value or the resulting supply would overflow.
+        ///
+        /// Returns `SupplyCapExceeded` error if points are minted and the total supply would exceed the max supply.
+        #[ink(message, selector = "0xBC596831")]
+        pub fn claim_points_as_tokens(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            let points = self.points_of(caller);
+            if self.points_rate == 0 || points == 0 {
+                return Err(self.fail("claim_points_as_tokens", Error::NothingToClaim));
+            }
+
+            self.ensure_compliant("claim_points_as_tokens", caller, Direction::Credit)?;
+
+            let now = self.env().block_timestamp();
+            let (mut epoch_start, mut converted) = self.points_epoch;
+            if now >= epoch_start.saturating_add(self.points_program.epoch_ms) {
+                epoch_start = now;
+                converted = 0;
+            }
+            let claimed = core::cmp::min(points, self.points_program.epoch_cap.saturating_sub(converted));
+            if claimed == 0 {
+                return Err(self.fail("claim_points_as_tokens", Error::PointsCapExceeded));
+            }
+
+            let value = match claimed.checked_mul(self.points_rate) {
+                Some(value) => value,
+                None => return Err(self.fail("claim_points_as_tokens", Error::Overflow))
+            };
+            let balance = self.balance(caller);
+            if balance.checked_add(value).is_none() {
+                return Err(self.fail("claim_points_as_tokens", Error::Overflow));
+            }
+            let from = if self.points_program.mint {
+                let new_supply = match self.total_supply().checked_add(value) {
+                    Some(new_supply) => new_supply,
+                    None => return Err(self.fail("claim_points_as_tokens", Error::Overflow))
+                };
+                self.ensure_within_supply_cap("claim_points_as_tokens", new_supply)?;
+                Lazy::<Balance>::set(&mut self.total_supply, new_supply);
+                None
+            } else {
+                let pool = self.env().account_id();
+                let pool_balance = self.balance(pool);
+                if pool_balance.saturating_sub(self.escrowed) < value {
+                    return Err(self.fail("claim_points_as_tokens", Error::InsufficientBalance));
+                }
+                self.set_balance(pool, pool_balance - value);
+                Some(pool)
+            };
+            self.set_balance(caller, balance + value);
+            self.record_volume(from, Some(caller), value, value);
+
+            self.reward_points.insert(caller, points - claimed);
+            self.points_epoch = (epoch_start, converted + claimed);
+
+            self.env().emit_event(Transfer {
+                from,
+                to: Some(caller),
+                value
+            });
+            self.env().emit_event(PointsClaimed {
+                account: caller,
+                points: claimed,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Accrues reward points to the sender of a fee-paying transfer of `value` while the program is active.
+        fn accrue_points(&mut self, from: AccountId, value: Balance) {
+            let program = self.points_program;
+            if !program.active || program.divisor == 0 {
+                return;
+            }
+            let points = self.points_of(from).saturating_add(value / program.divisor);
+            self.reward_points.insert(from, points);
+        }
+
+        /// Returns the share of the transfer fees an account pays that is rebated to it, in basis points of the fee
+        #[ink(message, selector = "0x91B1F38C")]
+        pub fn fee_rebate_of(&self, account: AccountId) -> Balance {
+            self.fee_rebates.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Returns the fee rebate an account accrued and has not claimed yet
+        #[ink(message, selector = "0x4B357740")]
+        pub fn accrued_rebate_of(&self, account: AccountId) -> Balance {
+            self.accrued_rebates.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Set the share of the transfer fees an account pays that is rebated to it, in basis points of
+        /// the fee, capped at 10000. `0` ends the rebate, keeping what was accrued claimable.
+        ///
+        /// On success a `FeeRebateChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x4EAB94D9")]
+        pub fn set_rebate(&mut self, account: AccountId, rebate_bps_of_fee: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_rebate", Error::PermissionDenied));
+            }
+
+            let rebate_bps = if rebate_bps_of_fee > 10000 { 10000 } else { rebate_bps_of_fee };
+            if rebate_bps == 0 {
+                self.fee_rebates.take(&account);
+            } else {
+                self.fee_rebates.insert(account, rebate_bps);
+            }
+
+            self.env().emit_event(FeeRebateChanged {
+                account,
+                rebate_bps
+            });
+
+            Ok(())
+        }
+
+        /// Claim the caller's accrued fee rebate, paid from the fee collector's balance
+        ///
+        /// On success a `Transfer` event and a `FeeRebateClaimed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `NothingToClaim` error if the caller has no accrued rebate.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's or the fee collector's account is blacklisted, or
+        /// `AccountFrozen` error if the fee collector's account is frozen.
+        ///
+        /// Returns `InsufficientBalance` error if the fee collector's balance doesn't cover the rebate,
+        /// which then stays claimable.
+        #[ink(message, selector = "0x1032C2A6")]
+        pub fn claim_fee_rebate(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("claim_fee_rebate")?;
+
+            let value = self.accrued_rebate_of(caller);
+            if value == 0 {
+                return Err(self.fail("claim_fee_rebate", Error::NothingToClaim));
+            }
+
+            self.ensure_compliant("claim_fee_rebate", caller, Direction::Credit)?;
+            self.ensure_compliant("claim_fee_rebate", self.fee_collector, Direction::Debit)?;
+
+            if self.spendable_balance_of(self.fee_collector) < value {
+                return Err(self.fail("claim_fee_rebate", Error::InsufficientBalance));
+            }
+
+            self.move_balance("claim_fee_rebate", self.fee_collector, caller, value)?;
+            self.accrued_rebates.take(&caller);
+
+            self.env().emit_event(FeeRebateClaimed {
+                account: caller,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Accrues the rebate of an account on a transfer `fee` it paid.
+        fn accrue_rebate(&mut self, from: AccountId, fee: Balance) {
+            let value = fee.saturating_mul(self.fee_rebate_of(from)) / 10000;
+            if value == 0 {
+                return;
+            }
+            let accrued = self.accrued_rebate_of(from).saturating_add(value);
+            self.accrued_rebates.insert(from, accrued);
+
+            self.env().emit_event(FeeRebateAccrued {
+                account: from,
+                value
+            });
+        }
+
+        /// Take a snapshot of all balances and the total supply, returning its id
+        ///
+        /// Balances are recorded lazily: an account's balance as of the snapshot is stored on its first
+        /// change after it, so taking a snapshot costs the same regardless of the number of holders.
+        ///
+        /// On success a `Snapshot` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x798ADA01")]
+        pub fn snapshot(&mut self) -> Result<u32> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("snapshot", Error::PermissionDenied));
+            }
+
+            Ok(self.take_snapshot())
+        }
+
+        /// Takes a snapshot, returning its id.
+        fn take_snapshot(&mut self) -> u32 {
+            let id = self.snapshot_id + 1;
+            self.snapshot_id = id;
+            self.supply_snapshots.insert(id, self.total_supply());
+
+            self.env().emit_event(Snapshot {
+                id
+            });
+
+            id
+        }
+
+        /// Returns the id of the last snapshot taken, `0` if there is none
+        #[ink(message, selector = "0x626A6BDD")]
+        pub fn snapshot_id(&self) -> u32 {
+            self.snapshot_id
+        }
+
+        /// Returns the balance of an account as of a snapshot, or `None` if the snapshot id is unknown
+        ///
+        /// The cost grows with the number of snapshots taken since `snapshot_id`.
+        #[ink(message, selector = "0x5A2F8344")]
+        pub fn balance_of_at(&self, account: AccountId, snapshot_id: u32) -> Option<Balance> {
+            if snapshot_id == 0 || snapshot_id > self.snapshot_id {
+                return None;
+            }
+            // The first checkpoint at or after the snapshot holds the balance as of it; without
+            // one the balance hasn't changed since.
+            let balance = (snapshot_id..=self.snapshot_id)
+                .find_map(|id| self.balance_snapshots.get(&(account, id)).copied())
+                .unwrap_or_else(|| self.balance(account));
+            Some(balance)
+        }
+
+        /// Returns the total supply as of a snapshot, or `None` if the snapshot id is unknown
+        #[ink(message, selector = "0x3727369D")]
+        pub fn total_supply_at(&self, snapshot_id: u32) -> Option<Balance> {
+            self.supply_snapshots.get(&snapshot_id).copied()
+        }
+
+        /// Sets the balance of `account`, recording its balance as of the last snapshot and keeping
+        /// the holder count, indexes and leaderboard. All balance updates go through this function.
+        ///
+        /// An account enters the balances and the holders index when it first receives tokens, at
+        /// which point the default privacy policy applies to it.
+        fn set_balance(&mut self, account: AccountId, balance: Balance) {
+            if !self.balances.contains_key(&account) {
+                if balance == 0 {
+                    return;
+                }
+                self.holders.push(account);
+                if self.default_private && account != self.owner && !self.privacy_sources.contains_key(&account) {
+                    self.update_flags(account, |flags| flags.private = true);
+                    self.privacy_sources.insert(account, PrivacySource::User);
+                    self.env().emit_event(Privacy {
+                        account,
+                        private: true
+                    });
+                }
+            }
+            self.checkpoint_balance(account);
+            let previous = self.balance(account);
+            if previous == 0 && balance > 0 {
+                self.stats.holder_count = self.stats.holder_count.saturating_add(1);
+                self.current_holders_index.insert(account, self.current_holders.len());
+                self.current_holders.push(account);
+                self.env().emit_event(NewHolder {
+                    account,
+                    block: self.env().block_number()
+                });
+            } else if previous > 0 && balance == 0 {
+                self.stats.holder_count = self.stats.holder_count.saturating_sub(1);
+                if let Some(index) = self.current_holders_index.take(&account) {
+                    self.current_holders.swap_remove_drop(index);
+                    if let Some(moved) = self.current_holders.get(index).copied() {
+                        self.current_holders_index.insert(moved, index);
+                    }
+                }
+            }
+            self.balances.insert(account, balance);
+            self.update_top_holders(account, previous, balance);
+            if balance != previous {
+                self.record_balance_history(account, balance);
+            }
+        }
+
+        /// Returns the average balance of an account between two timestamps, weighted by the time
+        /// each balance was held, computed exactly from the account's balance history.
+        ///
+        /// Before its first kept balance change an account is taken to hold nothing, so a window
+        /// starting earlier averages in zero for that span. Only the last `MAX_BALANCE_HISTORY`
+        /// changes are kept, so windows should not reach further back. An empty window, `to_timestamp` not after
+        /// `from_timestamp`, returns the balance as of `from_timestamp`.
+        ///
+        /// The cost grows with the number of balance changes inside the window.
+        #[ink(message, selector = "0x64C35619")]
+        #[allow(clippy::useless_conversion)] // `Balance` is `u128` unless `custom-environment` is selected
+        pub fn time_weighted_balance(&self, account: AccountId, from_timestamp: Timestamp, to_timestamp: Timestamp) -> Balance {
+            let history = match self.balance_history.get(&account) {
+                Some(history) => history,
+                None => return 0
+            };
+            // Index of the first change after `from_timestamp`, the one before it holds the opening balance
+            let start = match history.binary_search_by_key(&from_timestamp, |&(_, timestamp, _)| timestamp) {
+                Ok(index) => index + 1,
+                Err(index) => index
+            };
+            let mut balance = if start == 0 { 0 } else { history[start - 1].2 };
+            if to_timestamp <= from_timestamp {
+                return balance;
+            }
+
+            let mut weighted: u128 = 0;
+            let mut cursor = from_timestamp;
+            for &(_, timestamp, next) in history[start..].iter().take_while(|&&(_, timestamp, _)| timestamp < to_timestamp) {
+                weighted = weighted.saturating_add(Self::weigh(balance, timestamp - cursor));
+                cursor = timestamp;
+                balance = next;
+            }
+            weighted = weighted.saturating_add(Self::weigh(balance, to_timestamp - cursor));
+
+            Balance::try_from(weighted / u128::from(to_timestamp - from_timestamp)).unwrap_or(Balance::MAX)
+        }
+
+        /// Returns `balance` held for `duration` milliseconds, in 128 bits.
+        #[allow(clippy::useless_conversion)] // `Balance` is `u128` unless `custom-environment` is selected
+        fn weigh(balance: Balance, duration: Timestamp) -> u128 {
+            u128::from(balance).saturating_mul(u128::from(duration))
+        }
+
+        /// Returns the balance of an account at the end of block `block`, or `None` if its balance
+        /// was never recorded by then or `block` predates the kept history
+        ///
+        /// The last `MAX_BALANCE_HISTORY` balance changes of each account are kept, so unlike
+        /// `balance_of_at` this does not depend on snapshots being taken.
+        #[ink(message, selector = "0xD3595EE9")]
+        pub fn balance_at_block(&self, account: AccountId, block: BlockNumber) -> Option<Balance> {
+            let history = self.balance_history.get(&account)?;
+            // Index of the first change after `block`, the one before it holds the balance
+            let index = match history.binary_search_by_key(&block, |&(number, _, _)| number) {
+                Ok(index) => index + 1,
+                Err(index) => index
+            };
+            index.checked_sub(1).map(|index| history[index].2)
+        }
+
+        /// Appends the new balance of `account` to its balance history, replacing a change recorded
+        /// earlier in the same block and dropping the oldest change once the history is full.
+        fn record_balance_history(&mut self, account: AccountId, balance: Balance) {
+            let block = self.env().block_number();
+            let now = self.env().block_timestamp();
+            let history = self.balance_history.entry(account).or_insert_with(Vec::new);
+            match history.last_mut() {
+                Some(last) if last.0 == block => last.2 = balance,
+                _ => {
+                    if history.len() >= MAX_BALANCE_HISTORY {
+                        history.remove(0);
+                    }
+                    history.push((block, now, balance))
+                }
+            }
+        }
+
+        /// Returns the announced dust sweep, or `None` if there is none
+        #[ink(message, selector = "0xDEA3435F")]
+        pub fn dust_sweep(&self) -> Option<DustSweep> {
+            self.dust_sweep
+        }
+
+        /// Announce a sweep of balances below `threshold`, executable after `execute_after`, giving
+        /// holders notice to consolidate. Swept balances are burned if `burn` is set and moved to the
+        /// owner otherwise. Replaces any previous announcement.
+        ///
+        /// On success a `DustSweepAnnounced` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidDustSweep` error if `threshold` is zero or `execute_after` has already passed.
+        #[ink(message, selector = "0x355C2253")]
+        pub fn announce_dust_sweep(&mut self, threshold: Balance, execute_after: Timestamp, burn: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("announce_dust_sweep", Error::PermissionDenied));
+            }
+
+            let now = self.env().block_timestamp();
+            if threshold == 0 || execute_after <= now {
+                return Err(self.fail("announce_dust_sweep", Error::InvalidDustSweep));
+            }
+
+            self.dust_sweep = Some(DustSweep {
+                threshold,
+                announced_at: now,
+                execute_after,
+                burn
+            });
+
+            self.env().emit_event(DustSweepAnnounced {
+                threshold,
+                execute_after,
+                burn
+            });
+
+            Ok(())
+        }
+
+        /// Sweep the holders index from `start` for at most `limit` accounts, returning the index to
+        /// continue from. Balances strictly below the announced threshold are swept and the accounts'
+        /// balance entries removed, except for accounts whose balance changed since the announcement,
+        /// the owner, the contract's own account, protected, blacklisted or frozen accounts,
+        /// accounts with self-locks or holds, and accounts the owner's balance can't take in.
+        ///
+        /// A swept account is removed from the holders index by moving the last holder into its
+        /// place, which is examined next. The announcement is cleared once the index is exhausted.
+        ///
+        /// On success a `DustSwept` event is emitted for each swept account, preceded by a `Transfer`
+        /// event to the owner, or a `Redeem` event if swept balances are burned.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidDustSweep` error if no sweep was announced.
+        ///
+        /// Returns `DustSweepNotDue` error if the announced notice hasn't passed yet.
+        ///
+        /// Returns `BatchTooLarge` error if `limit` is more than `MAX_BATCH_SIZE`.
+        ///
+        /// Returns `InvalidCommitment` error if a balances root commitment is in progress, as it pages
+        /// through the same index.
+        ///
+        /// Returns `ReentrantCall` error while `transfer_and_call` notifies a contract recipient.
+        #[ink(message, selector = "0xC18F9D4C")]
+        pub fn execute_dust_sweep(&mut self, start: u32, limit: u32) -> Result<u32> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("execute_dust_sweep", Error::PermissionDenied));
+            }
+
+            if self.notifying_receiver {
+                return Err(self.fail("execute_dust_sweep", Error::ReentrantCall));
+            }
+
+            let sweep = match self.dust_sweep {
+                Some(sweep) => sweep,
+                None => return Err(self.fail("execute_dust_sweep", Error::InvalidDustSweep))
+            };
+
+            if self.env().block_timestamp() <= sweep.execute_after {
+                return Err(self.fail("execute_dust_sweep", Error::DustSweepNotDue));
+            }
+
+            if limit as usize > MAX_BATCH_SIZE {
+                return Err(self.fail("execute_dust_sweep", Error::BatchTooLarge));
+            }
+
+            if self.pending_commitment.is_some() {
+                return Err(self.fail("execute_dust_sweep", Error::InvalidCommitment));
+            }
+
+            let mut index = start;
+            for _ in 0..limit {
+                let account = match self.holders.get(index) {
+                    Some(account) => *account,
+                    None => break
+                };
+                let balance = self.balance(account);
+                let touched = self.balance_history.get(&account)
+                    .and_then(|history| history.last())
+                    .map_or(false, |&(_, timestamp, _)| timestamp >= sweep.announced_at);
+                if balance == 0
+                    || balance >= sweep.threshold
+                    || touched
+                    || account == self.owner
+                    || account == self.env().account_id()
+                    || self.is_protected_account(account)
+                    || self.is_account_blacklisted(account)
+                    || self.is_account_frozen(account)
+                    || self.locked_balance_of(account) > 0
+                    || self.on_hold_balance_of(account) > 0
+                    || (!sweep.burn && self.balance(self.owner).checked_add(balance).is_none())
+                {
+                    index += 1;
+                    continue;
+                }
+
+                if sweep.burn {
+                    self.burn_balance(account, balance, None);
+                } else {
+                    self.move_balance("execute_dust_sweep", account, self.owner, balance)?;
+                }
+                self.balances.take(&account);
+                self.holders.swap_remove_drop(index);
+
+                self.env().emit_event(DustSwept {
+                    account,
+                    value: balance,
+                    burned: sweep.burn
+                });
+            }
+
+            if index >= self.holders.len() {
+                self.dust_sweep = None;
+            }
+
+            Ok(index)
+        }
+
+        /// Returns the largest holders sorted by descending balance
+        ///
+        /// The leaderboard is maintained as balances change: a member whose balance falls keeps its
+        /// place at the new balance until a larger holder outside the leaderboard is credited or debited.
+        #[ink(message, selector = "0x2325BCF8")]
+        pub fn top_holders(&self) -> Vec<(AccountId, Balance)> {
+            self.top_holders.clone()
+        }
+
+        /// Returns the number of accounts on the top holders leaderboard
+        #[ink(message, selector = "0x7CB4089A")]
+        pub fn top_holders_limit(&self) -> u32 {
+            self.top_holders_limit
+        }
+
+        /// Set the number of accounts on the top holders leaderboard, capped at `MAX_TOP_HOLDERS`
+        ///
+        /// Shrinking drops the smallest members; growing lets accounts join as their balances change.
+        ///
+        /// On success a `TopHoldersLimitChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xDF925D06")]
+        pub fn set_top_holders_limit(&mut self, limit: u32) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_top_holders_limit", Error::PermissionDenied));
+            }
+
+            self.top_holders_limit = if limit > MAX_TOP_HOLDERS { MAX_TOP_HOLDERS } else { limit };
+            let top_holders = &mut *self.top_holders;
+            top_holders.truncate(self.top_holders_limit as usize);
+            self.top_holders_floor = Self::floor_of(top_holders, self.top_holders_limit);
+
+            self.env().emit_event(TopHoldersLimitChanged {
+                limit: self.top_holders_limit
+            });
+
+            Ok(())
+        }
+
+        /// Moves `account` to its place on the leaderboard after its balance changed from `previous`
+        /// to `balance`, in O(N). Balance changes below the floor of a full leaderboard return early.
+        fn update_top_holders(&mut self, account: AccountId, previous: Balance, balance: Balance) {
+            if previous < self.top_holders_floor && balance < self.top_holders_floor {
+                return;
+            }
+
+            let limit = self.top_holders_limit;
+            let top_holders = &mut *self.top_holders;
+            top_holders.retain(|(holder, _)| *holder != account);
+            if balance > 0 {
+                let index = top_holders.iter().position(|(_, other)| *other < balance).unwrap_or(top_holders.len());
+                top_holders.insert(index, (account, balance));
+                top_holders.truncate(limit as usize);
+            }
+            self.top_holders_floor = Self::floor_of(top_holders, limit);
+        }
+
+        /// Returns the smallest balance of a full leaderboard, `0` if it isn't full.
+        fn floor_of(top_holders: &[(AccountId, Balance)], limit: u32) -> Balance {
+            match top_holders.last() {
+                Some((_, balance)) if top_holders.len() >= limit as usize => *balance,
+                _ => 0
+            }
+        }
+
+        /// Records the balance of `account` as of the last snapshot, if it is about to change for the
+        /// first time since.
+        fn checkpoint_balance(&mut self, account: AccountId) {
+            if self.snapshot_id == 0 {
+                return;
+            }
+            let balance = self.balance(account);
+            self.balance_snapshots.entry((account, self.snapshot_id)).or_insert(balance);
+        }
+
+        /// Returns the number of accounts currently holding a nonzero balance
+        #[ink(message, selector = "0x2C8E7FDE")]
+        pub fn holders_count(&self) -> u32 {
+            self.current_holders.len()
+        }
+
+        /// Returns up to `limit` accounts holding a nonzero balance from position `offset` of the current holders list.
+        ///
+        /// At most `MAX_BATCH_SIZE` accounts are returned per call. An account whose balance drops to
+        /// zero is replaced by the last one, so the list should be paged through within a single block
+        /// for a consistent snapshot.
+        #[ink(message, selector = "0xD6F3E41E")]
+        pub fn holders(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            let limit = core::cmp::min(limit as usize, MAX_BATCH_SIZE) as u32;
+            let end = core::cmp::min(self.current_holders.len(), offset.saturating_add(limit));
+            (offset..end)
+                .filter_map(|index| self.current_holders.get(index).copied())
+                .collect()
+        }
+
+        /// Returns the number of accounts that ever held a balance and weren't swept as dust, the length of the holders index
+        #[ink(message, selector = "0x4E73CDA3")]
+        pub fn holders_len(&self) -> u32 {
+            self.holders.len()
+        }
+
+        /// Returns the latest finalized balances root, or `None` if none was committed yet
+        #[ink(message, selector = "0xFBF06931")]
+        pub fn latest_balances_root(&self) -> Option<BalancesRoot> {
+            self.balances_roots.get(&self.balances_root_count).copied()
+        }
+
+        /// Returns the finalized balances root with sequence number `id`, starting at `1`
+        #[ink(message, selector = "0xADECB8C9")]
+        pub fn balances_root(&self, id: u32) -> Option<BalancesRoot> {
+            self.balances_roots.get(&id).copied()
+        }
+
+        /// Returns the commitment in progress, if any
+        #[ink(message, selector = "0xC20AF471")]
+        pub fn pending_commitment(&self) -> Option<Commitment> {
+            self.pending_commitment.clone()
+        }
+
+        /// Start committing to a Merkle root of all non-zero balances as of now
+        ///
+        /// Takes a snapshot the leaves are read from, so balance changes while the commitment is built
+        /// don't affect it. Anyone can drive a commitment, through `commit_page` and `finalize_commitment`.
+        ///
+        /// On success a `Snapshot` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidCommitment` error if a commitment is already in progress.
+        #[ink(message, selector = "0x76AD7638")]
+        pub fn begin_commitment(&mut self) -> Result<()> {
+            if self.pending_commitment.is_some() {
+                return Err(self.fail("begin_commitment", Error::InvalidCommitment));
+            }
+
+            let snapshot_id = self.take_snapshot();
+            self.pending_commitment = Some(Commitment {
+                snapshot_id,
+                block: self.env().block_number(),
+                next: 0,
+                end: self.holders.len(),
+                frontier: Vec::new()
+            });
+
+            Ok(())
+        }
+
+        /// Add the holders at index `start` up to `start + limit` of the holders index to the
+        /// commitment in progress, skipping accounts without balance as of its snapshot.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidCommitment` error if no commitment is in progress or `start` is not where the
+        /// previous page ended.
+        ///
+        /// Returns `BatchTooLarge` error if `limit` exceeds `MAX_BATCH_SIZE`.
+        #[ink(message, selector = "0x20778D97")]
+        pub fn commit_page(&mut self, start: u32, limit: u32) -> Result<()> {
+            let mut commitment = match self.pending_commitment.clone() {
+                Some(commitment) if commitment.next == start => commitment,
+                _ => {
+                    return Err(self.fail("commit_page", Error::InvalidCommitment));
+                }
+            };
+
+            if limit as usize > MAX_BATCH_SIZE {
+                return Err(self.fail("commit_page", Error::BatchTooLarge));
+            }
+
+            let end = core::cmp::min(commitment.end, start.saturating_add(limit));
+            for index in start..end {
+                let account = match self.holders.get(index) {
+                    Some(account) => *account,
+                    None => break
+                };
+                let balance = self.balance_of_at(account, commitment.snapshot_id).unwrap_or(0);
+                if balance == 0 {
+                    continue;
+                }
+                // Merge the new leaf up through the completed subtrees, like carrying in a binary counter
+                let mut node = self.leaf_hash(account, balance);
+                let mut level = 0;
+                while let Some(Some(sibling)) = commitment.frontier.get(level).copied() {
+                    node = self.node_hash(sibling, node);
+                    commitment.frontier[level] = None;
+                    level += 1;
+                }
+                if level == commitment.frontier.len() {
+                    commitment.frontier.push(Some(node));
+                } else {
+                    commitment.frontier[level] = Some(node);
+                }
+            }
+            commitment.next = end;
+            self.pending_commitment = Some(commitment);
+
+            Ok(())
+        }
+
+        /// Finalize the commitment in progress once all its pages are committed, storing its root
+        ///
+        /// Finalized roots are never modified; each commitment stores a new one.
+        ///
+        /// On success a `BalancesRootCommitted` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidCommitment` error if no commitment is in progress or pages are left.
+        #[ink(message, selector = "0xE1AE88A3")]
+        pub fn finalize_commitment(&mut self) -> Result<()> {
+            let commitment = match self.pending_commitment.clone() {
+                Some(commitment) if commitment.next == commitment.end => commitment,
+                _ => {
+                    return Err(self.fail("finalize_commitment", Error::InvalidCommitment));
+                }
+            };
+
+            // Fold the pending subtrees from the smallest up, an empty tree has the zero root
+            let mut root: Option<[u8; 32]> = None;
+            for node in commitment.frontier.iter().flatten() {
+                root = Some(match root {
+                    Some(lower) => self.node_hash(*node, lower),
+                    None => *node
+                });
+            }
+            let root = BalancesRoot {
+                root: root.unwrap_or([0x0; 32]),
+                block: commitment.block,
+                snapshot_id: commitment.snapshot_id
+            };
+
+            let id = self.balances_root_count + 1;
+            self.balances_root_count = id;
+            self.balances_roots.insert(id, root);
+            self.pending_commitment = None;
+
+            self.env().emit_event(BalancesRootCommitted {
+                id,
+                root: root.root,
+                block: root.block
+            });
+
+            Ok(())
+        }
+
+        /// Commit a balances root in a single call, when the holders index fits in one page
+        ///
+        /// On success a `Snapshot` event and a `BalancesRootCommitted` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidCommitment` error if a commitment is already in progress.
+        ///
+        /// Returns `BatchTooLarge` error if the holders index holds more than `MAX_BATCH_SIZE`
+        /// accounts, which then need the paged `begin_commitment` flow.
+        #[ink(message, selector = "0xC097B6B6")]
+        pub fn commit_balances_root(&mut self) -> Result<()> {
+            if self.holders.len() as usize > MAX_BATCH_SIZE {
+                return Err(self.fail("commit_balances_root", Error::BatchTooLarge));
+            }
+
+            self.begin_commitment()?;
+            self.commit_page(0, MAX_BATCH_SIZE as u32)?;
+            self.finalize_commitment()
+        }
+
+        /// Returns whether `proof` shows `account` held `balance` in the latest balances root
+        ///
+        /// `proof` lists the sibling hashes from the leaf up; pairs are hashed in sorted order so no
+        /// positions are needed.
+        #[ink(message, selector = "0xC05EA62A")]
+        pub fn verify_balance_proof(&self, account: AccountId, balance: Balance, proof: Vec<[u8; 32]>) -> bool {
+            let root = match self.latest_balances_root() {
+                Some(root) => root.root,
+                None => return false
+            };
+            self.proof_root(self.leaf_hash(account, balance), &proof) == root
+        }
+
+        /// Returns the root `proof` leads to from `leaf`, hashing in one sibling per level.
+        fn proof_root(&self, leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+            proof.iter().fold(leaf, |node, sibling| self.node_hash(node, *sibling))
+        }
+
+        /// Returns the Merkle leaf of `account` holding `balance`.
+        fn leaf_hash(&self, account: AccountId, balance: Balance) -> [u8; 32] {
+            self.env().hash_encoded::<env::hash::Blake2x256, _>(&(account, balance))
+        }
+
+        /// Returns the Merkle node over two children, hashed in sorted order.
+        fn node_hash(&self, a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+            let pair = if a <= b { (a, b) } else { (b, a) };
+            self.env().hash_encoded::<env::hash::Blake2x256, _>(&pair)
+        }
+
+        /// Returns the time in milliseconds after funding during which a distribution can be claimed
+        #[ink(message, selector = "0x21E266DC")]
+        pub fn distribution_window(&self) -> u64 {
+            self.distribution_window_ms
+        }
+
+        /// Set the time in milliseconds after funding during which new distributions can be claimed
+        ///
+        /// On success a `DistributionWindowChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xBC6AA71E")]
+        pub fn set_distribution_window(&mut self, window_ms: u64) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_distribution_window", Error::PermissionDenied));
+            }
+
+            self.distribution_window_ms = window_ms;
+
+            self.env().emit_event(DistributionWindowChanged {
+                window_ms
+            });
+
+            Ok(())
+        }
+
+        /// Returns the distribution to the holders at a snapshot, or `None` if none was funded
+        #[ink(message, selector = "0xEDCD033D")]
+        pub fn distribution(&self, snapshot_id: u32) -> Option<Distribution> {
+            self.distributions.get(&snapshot_id).copied()
+        }
+
+        /// Returns whether an account claimed its share of a snapshot's distribution
+        #[ink(message, selector = "0x491C829D")]
+        pub fn has_claimed_distribution(&self, snapshot_id: u32, account: AccountId) -> bool {
+            self.distribution_claims.get(&(snapshot_id, account)).copied().unwrap_or(false)
+        }
+
+        /// Escrow `total` tokens from the owner's account for distribution to the holders at a snapshot
+        ///
+        /// Each holder can claim `total * balance_of_at / total_supply_at` tokens until the distribution
+        /// window has passed, after which the owner can sweep the unclaimed remainder.
+        ///
+        /// On success a `Transfer` event and a `DistributionFunded` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownSnapshot` error if the snapshot id is unknown.
+        ///
+        /// Returns `DistributionExists` error if a distribution was already funded for the snapshot.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the owner's account fails compliance screening.
+        ///
+        /// Returns `InsufficientBalance` error if owner's balance is insufficient.
+        #[ink(message, selector = "0x4F1A05ED")]
+        pub fn distribute(&mut self, snapshot_id: u32, total: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("distribute", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("distribute")?;
+
+            if self.total_supply_at(snapshot_id).is_none() {
+                return Err(self.fail("distribute", Error::UnknownSnapshot));
+            }
+
+            if self.distributions.contains_key(&snapshot_id) {
+                return Err(self.fail("distribute", Error::DistributionExists));
+            }
+
+            self.ensure_compliant("distribute", self.owner, Direction::Debit)?;
+
+            if self.spendable_balance_of(self.owner) < total {
+                return Err(self.fail("distribute", Error::InsufficientBalance));
+            }
+
+            let escrowed = self.or_overflow("distribute", self.escrowed.checked_add(total))?;
+            self.move_balance("distribute", self.owner, self.env().account_id(), total)?;
+            self.escrowed = escrowed;
+            let deadline = self.env().block_timestamp().saturating_add(self.distribution_window_ms);
+            self.distributions.insert(snapshot_id, Distribution {
+                total,
+                claimed: 0,
+                deadline,
+                swept: false
+            });
+
+            self.env().emit_event(DistributionFunded {
+                snapshot_id,
+                total,
+                deadline
+            });
+
+            Ok(())
+        }
+
+        /// Claim the caller's share of the distribution to the holders at a snapshot
+        ///
+        /// The share is `total * balance_of_at / total_supply_at`, rounded down so the claims never
+        /// exceed the escrowed tokens.
+        ///
+        /// On success a `Transfer` event and a `DistributionClaimed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownDistribution` error if no distribution was funded for the snapshot.
+        ///
+        /// Returns `ClaimWindowClosed` error if the distribution's deadline has passed.
+        ///
+        /// Returns `AlreadyClaimed` error if the caller already claimed its share.
+        ///
+        /// Returns `NothingToClaim` error if the caller's share is zero.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's account is blacklisted.
+        #[ink(message, selector = "0x04CCB210")]
+        pub fn claim_distribution(&mut self, snapshot_id: u32) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("claim_distribution")?;
+
+            let mut distribution = match self.distribution(snapshot_id) {
+                Some(distribution) => distribution,
+                None => {
+                    return Err(self.fail("claim_distribution", Error::UnknownDistribution));
+                }
+            };
+
+            if self.env().block_timestamp() > distribution.deadline {
+                return Err(self.fail("claim_distribution", Error::ClaimWindowClosed));
+            }
+
+            if self.has_claimed_distribution(snapshot_id, caller) {
+                return Err(self.fail("claim_distribution", Error::AlreadyClaimed));
+            }
+
+            let balance = self.balance_of_at(caller, snapshot_id).unwrap_or(0);
+            let supply = self.total_supply_at(snapshot_id).unwrap_or(0);
+            let value = if supply == 0 { 0 } else { mul_div(distribution.total, balance, supply) };
+            if value == 0 {
+                return Err(self.fail("claim_distribution", Error::NothingToClaim));
+            }
+
+            self.ensure_compliant("claim_distribution", caller, Direction::Credit)?;
+
+            distribution.claimed = self.or_overflow("claim_distribution", distribution.claimed.checked_add(value))?;
+            let escrowed = self.or_overflow("claim_distribution", self.escrowed.checked_sub(value))?;
+            self.move_balance("claim_distribution", self.env().account_id(), caller, value)?;
+            self.escrowed = escrowed;
+            self.distributions.insert(snapshot_id, distribution);
+            self.distribution_claims.insert((snapshot_id, caller), true);
+
+            self.env().emit_event(DistributionClaimed {
+                snapshot_id,
+                account: caller,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Return the unclaimed remainder of a snapshot's distribution to the owner after its deadline
+        ///
+        /// On success a `Transfer` event and a `DistributionSwept` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownDistribution` error if no distribution was funded for the snapshot.
+        ///
+        /// Returns `ClaimWindowOpen` error if the distribution's deadline has not passed yet.
+        ///
+        /// Returns `NothingToClaim` error if the distribution was already swept.
+        ///
+        /// Returns `AccountBlackListed` error if the owner's account is blacklisted.
+        #[ink(message, selector = "0xDFFF268A")]
+        pub fn sweep_distribution(&mut self, snapshot_id: u32) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("sweep_distribution", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("sweep_distribution")?;
+
+            let mut distribution = match self.distribution(snapshot_id) {
+                Some(distribution) => distribution,
+                None => {
+                    return Err(self.fail("sweep_distribution", Error::UnknownDistribution));
+                }
+            };
+
+            if self.env().block_timestamp() <= distribution.deadline {
+                return Err(self.fail("sweep_distribution", Error::ClaimWindowOpen));
+            }
+
+            if distribution.swept {
+                return Err(self.fail("sweep_distribution", Error::NothingToClaim));
+            }
+
+            self.ensure_compliant("sweep_distribution", self.owner, Direction::Credit)?;
+
+            let value = self.or_overflow("sweep_distribution", distribution.total.checked_sub(distribution.claimed))?;
+            let escrowed = self.or_overflow("sweep_distribution", self.escrowed.checked_sub(value))?;
+            self.move_balance("sweep_distribution", self.env().account_id(), self.owner, value)?;
+            self.escrowed = escrowed;
+            distribution.swept = true;
+            self.distributions.insert(snapshot_id, distribution);
+
+            self.env().emit_event(DistributionSwept {
+                snapshot_id,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Returns the airdrop created under an id, or `None` if there is none
+        #[ink(message, selector = "0x91B0A242")]
+        pub fn airdrop(&self, airdrop_id: u32) -> Option<Airdrop> {
+            self.airdrops.get(&airdrop_id).copied()
+        }
+
+        /// Returns whether an account claimed its amount of an airdrop
+        #[ink(message, selector = "0x1FD727F7")]
+        pub fn has_claimed_airdrop(&self, airdrop_id: u32, account: AccountId) -> bool {
+            self.airdrop_claims.get(&(airdrop_id, account)).copied().unwrap_or(false)
+        }
+
+        /// Escrow `total` tokens from the owner's account for the accounts listed in the Merkle tree
+        /// under `merkle_root`, claimable until `expiry`. Returns the id of the new airdrop.
+        ///
+        /// The tree is built off-chain like a balances root: leaves are the blake2 hashes of
+        /// `(account, amount)` and nodes hash their children in sorted order. After `expiry` the owner
+        /// can close the airdrop, sweeping the unclaimed remainder back.
+        ///
+        /// On success a `Transfer` event and an `AirdropCreated` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `InvalidAirdrop` error if `total` is zero or `expiry` has already passed.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the owner's account fails compliance screening.
+        ///
+        /// Returns `InsufficientBalance` error if owner's balance is insufficient.
+        #[ink(message, selector = "0xEEC72BDE")]
+        pub fn create_airdrop(&mut self, merkle_root: [u8; 32], total: Balance, expiry: Timestamp) -> Result<u32> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("create_airdrop", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("create_airdrop")?;
+
+            if total == 0 || expiry <= self.env().block_timestamp() {
+                return Err(self.fail("create_airdrop", Error::InvalidAirdrop));
+            }
+
+            self.ensure_compliant("create_airdrop", self.owner, Direction::Debit)?;
+
+            if self.spendable_balance_of(self.owner) < total {
+                return Err(self.fail("create_airdrop", Error::InsufficientBalance));
+            }
+
+            let escrowed = self.or_overflow("create_airdrop", self.escrowed.checked_add(total))?;
+            self.move_balance("create_airdrop", self.owner, self.env().account_id(), total)?;
+            self.escrowed = escrowed;
+            let id = self.airdrop_count + 1;
+            self.airdrop_count = id;
+            self.airdrops.insert(id, Airdrop {
+                root: merkle_root,
+                total,
+                claimed: 0,
+                expiry,
+                closed: false
+            });
+
+            self.env().emit_event(AirdropCreated {
+                id,
+                root: merkle_root,
+                total,
+                expiry
+            });
+
+            Ok(id)
+        }
+
+        /// Claim the caller's `amount` of an airdrop, proven by the sibling hashes of its leaf from
+        /// the bottom of the tree up. No transfer fee is charged.
+        ///
+        /// On success a `Transfer` event and an `AirdropClaimed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownAirdrop` error if no airdrop was created under `airdrop_id`.
+        ///
+        /// Returns `ClaimWindowClosed` error if the airdrop's expiry has passed.
+        ///
+        /// Returns `AlreadyClaimed` error if the caller already claimed its amount.
+        ///
+        /// Returns `NothingToClaim` error if `amount` is zero.
+        ///
+        /// Returns `InvalidProof` error if `proof` doesn't show the caller was allotted `amount`.
+        ///
+        /// Returns `InsufficientBalance` error if the airdrop's remaining tokens don't cover `amount`.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's account is blacklisted.
+        #[ink(message, selector = "0x0BA35542")]
+        pub fn claim_airdrop(&mut self, airdrop_id: u32, amount: Balance, proof: Vec<[u8; 32]>) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("claim_airdrop")?;
+
+            let mut airdrop = match self.airdrop(airdrop_id) {
+                Some(airdrop) => airdrop,
+                None => {
+                    return Err(self.fail("claim_airdrop", Error::UnknownAirdrop));
+                }
+            };
+
+            if self.env().block_timestamp() > airdrop.expiry {
+                return Err(self.fail("claim_airdrop", Error::ClaimWindowClosed));
+            }
+
+            if self.has_claimed_airdrop(airdrop_id, caller) {
+                return Err(self.fail("claim_airdrop", Error::AlreadyClaimed));
+            }
+
+            if amount == 0 {
+                return Err(self.fail("claim_airdrop", Error::NothingToClaim));
+            }
+
+            if self.proof_root(self.leaf_hash(caller, amount), &proof) != airdrop.root {
+                return Err(self.fail("claim_airdrop", Error::InvalidProof));
+            }
+
+            if airdrop.total - airdrop.claimed < amount {
+                return Err(self.fail("claim_airdrop", Error::InsufficientBalance));
+            }
+
+            self.ensure_compliant("claim_airdrop", caller, Direction::Credit)?;
+
+            airdrop.claimed = self.or_overflow("claim_airdrop", airdrop.claimed.checked_add(amount))?;
+            let escrowed = self.or_overflow("claim_airdrop", self.escrowed.checked_sub(amount))?;
+            self.move_balance("claim_airdrop", self.env().account_id(), caller, amount)?;
+            self.escrowed = escrowed;
+            self.airdrops.insert(airdrop_id, airdrop);
+            self.airdrop_claims.insert((airdrop_id, caller), true);
+
+            self.env().emit_event(AirdropClaimed {
+                id: airdrop_id,
+                account: caller,
+                value: amount
+            });
+
+            Ok(())
+        }
+
+        /// Return the unclaimed remainder of an airdrop to the owner after its expiry
+        ///
+        /// On success a `Transfer` event and an `AirdropClosed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `UnknownAirdrop` error if no airdrop was created under `airdrop_id`.
+        ///
+        /// Returns `ClaimWindowOpen` error if the airdrop's expiry has not passed yet.
+        ///
+        /// Returns `NothingToClaim` error if the airdrop was already closed.
+        ///
+        /// Returns `AccountBlackListed` error if the owner's account is blacklisted.
+        #[ink(message, selector = "0x00839954")]
+        pub fn close_airdrop(&mut self, airdrop_id: u32) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("close_airdrop", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("close_airdrop")?;
+
+            let mut airdrop = match self.airdrop(airdrop_id) {
+                Some(airdrop) => airdrop,
+                None => {
+                    return Err(self.fail("close_airdrop", Error::UnknownAirdrop));
+                }
+            };
+
+            if self.env().block_timestamp() <= airdrop.expiry {
+                return Err(self.fail("close_airdrop", Error::ClaimWindowOpen));
+            }
+
+            if airdrop.closed {
+                return Err(self.fail("close_airdrop", Error::NothingToClaim));
+            }
+
+            self.ensure_compliant("close_airdrop", self.owner, Direction::Credit)?;
+
+            let value = self.or_overflow("close_airdrop", airdrop.total.checked_sub(airdrop.claimed))?;
+            let escrowed = self.or_overflow("close_airdrop", self.escrowed.checked_sub(value))?;
+            self.move_balance("close_airdrop", self.env().account_id(), self.owner, value)?;
+            self.escrowed = escrowed;
+            airdrop.closed = true;
+            self.airdrops.insert(airdrop_id, airdrop);
+
+            self.env().emit_event(AirdropClosed {
+                id: airdrop_id,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Escrow `total` of the owner's tokens for `beneficiary`, unlocked linearly from `start`
+        /// over `duration_ms` with nothing unlocked before `cliff_ms` has passed.
+        ///
+        /// A beneficiary has at most one vesting schedule.
+        ///
+        /// On success a `Transfer` event and a `VestingCreated` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `InvalidAccount` error if `beneficiary` is the zero account.
+        ///
+        /// Returns `InvalidVesting` error if `total` or `duration_ms` is zero, `cliff_ms` exceeds
+        /// `duration_ms` or `beneficiary` already has a vesting schedule.
+        ///
+        /// Returns `AccountBlackListed` or `AccountFrozen` error if the owner's account fails compliance screening.
+        ///
+        /// Returns `InsufficientBalance` error if owner's balance is insufficient.
+        #[ink(message, selector = "0xB471202D")]
+        pub fn create_vesting(
+            &mut self,
+            beneficiary: AccountId,
+            total: Balance,
+            start: Timestamp,
+            duration_ms: u64,
+            cliff_ms: u64
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("create_vesting", Error::PermissionDenied));
+            }
+
+            self.ensure_not_paused("create_vesting")?;
+            self.validate_account("create_vesting", beneficiary)?;
+
+            if total == 0 || duration_ms == 0 || cliff_ms > duration_ms || self.vesting_schedules.contains_key(&beneficiary) {
+                return Err(self.fail("create_vesting", Error::InvalidVesting));
+            }
+
+            self.ensure_compliant("create_vesting", self.owner, Direction::Debit)?;
+
+            if self.spendable_balance_of(self.owner) < total {
+                return Err(self.fail("create_vesting", Error::InsufficientBalance));
+            }
+
+            let escrowed = self.or_overflow("create_vesting", self.escrowed.checked_add(total))?;
+            self.move_balance("create_vesting", self.owner, self.env().account_id(), total)?;
+            self.escrowed = escrowed;
+            self.vesting_schedules.insert(beneficiary, VestingSchedule {
+                total,
+                claimed: 0,
+                start,
+                duration_ms,
+                cliff_ms
+            });
+
+            self.env().emit_event(VestingCreated {
+                beneficiary,
+                total,
+                start,
+                duration_ms,
+                cliff_ms
+            });
+
+            Ok(())
+        }
+
+        /// Returns the vesting schedule of `beneficiary`, or `None` if there is none
+        #[ink(message, selector = "0x71F9A899")]
+        pub fn vesting_schedule(&self, beneficiary: AccountId) -> Option<VestingSchedule> {
+            self.vesting_schedules.get(&beneficiary).copied()
+        }
+
+        /// Returns the tokens of `beneficiary`'s vesting schedule unlocked so far, claimed or not,
+        /// `0` if there is none
+        #[ink(message, selector = "0x978A3F5A")]
+        pub fn vested_amount(&self, beneficiary: AccountId) -> Balance {
+            let schedule = match self.vesting_schedules.get(&beneficiary) {
+                Some(schedule) => schedule,
+                None => return 0
+            };
+            let elapsed = self.env().block_timestamp().saturating_sub(schedule.start);
+            if elapsed < schedule.cliff_ms {
+                0
+            } else if elapsed >= schedule.duration_ms {
+                schedule.total
+            } else {
+                mul_div(schedule.total, Balance::from(elapsed), Balance::from(schedule.duration_ms))
+            }
+        }
+
+        /// Claim the unlocked but unclaimed tokens of the caller's vesting schedule, without fees
+        ///
+        /// On success a `Transfer` event and a `VestingClaimed` event are emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `NothingToClaim` error if the caller has no vesting schedule or no unclaimed
+        /// tokens were unlocked.
+        ///
+        /// Returns `AccountBlackListed` error if the caller's account is blacklisted.
+        #[ink(message, selector = "0xB3E0FC7D")]
+        pub fn claim_vested(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused("claim_vested")?;
+
+            let mut schedule = match self.vesting_schedule(caller) {
+                Some(schedule) => schedule,
+                None => {
+                    return Err(self.fail("claim_vested", Error::NothingToClaim));
+                }
+            };
+
+            let value = self.vested_amount(caller) - schedule.claimed;
+            if value == 0 {
+                return Err(self.fail("claim_vested", Error::NothingToClaim));
+            }
+
+            self.ensure_compliant("claim_vested", caller, Direction::Credit)?;
+
+            schedule.claimed = self.or_overflow("claim_vested", schedule.claimed.checked_add(value))?;
+            let escrowed = self.or_overflow("claim_vested", self.escrowed.checked_sub(value))?;
+            self.move_balance("claim_vested", self.env().account_id(), caller, value)?;
+            self.escrowed = escrowed;
+            self.vesting_schedules.insert(caller, schedule);
+
+            self.env().emit_event(VestingClaimed {
+                beneficiary: caller,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Moves `value` tokens between accounts without fees or transfer checks, for tokens the
+        /// contract escrows or pays out, such as distributions, vesting schedules, airdrops, claimable
+        /// transfers, gifts and fee rebates.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if `from` holds less than `value`, or `Overflow` error
+        /// if crediting `to` would overflow, failing `message` before changing anything.
+        fn move_balance(&mut self, message: &'static str, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            let from_balance = match self.balance(from).checked_sub(value) {
+                Some(from_balance) => from_balance,
+                None => return Err(self.fail(message, Error::InsufficientBalance))
+            };
+            let to_balance = if to == from { from_balance } else { self.balance(to) };
+            let to_balance = self.or_overflow(message, to_balance.checked_add(value))?;
+            self.set_balance(from, from_balance);
+            self.set_balance(to, to_balance);
+            self.record_volume(Some(from), Some(to), value, value);
+
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: Some(to),
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Returns whether a transfer of `value` from `from` moves more than `circuit_breaker_bps` of
+        /// total supply, tripping the circuit breaker.
+        ///
+        /// Transfers from the owner or a system account are exempt.
+        fn trips_circuit_breaker(&self, from: AccountId, value: Balance) -> bool {
+            if self.circuit_breaker_bps == 0 || from == self.owner || self.is_system_account(from) {
+                return false;
+            }
+            value.saturating_mul(10000) > self.total_supply().saturating_mul(self.circuit_breaker_bps)
+        }
+
+        /// Returns the contract-wide throughput cap
+        #[ink(message, selector = "0xEA1E011A")]
+        pub fn throughput_cap(&self) -> ThroughputCap {
+            self.throughput_cap
+        }
+
+        /// Set the throughput cap: transfers may move at most `max_value_per_window` in total within
+        /// `window_ms` milliseconds, across all accounts. Setting either param to `0` disables the cap.
+        ///
+        /// Transfers from the owner or a system account are exempt and don't count toward the cap.
+        /// The current window restarts.
+        ///
+        /// On success a `ThroughputCapChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x94A7E7BD")]
+        pub fn set_throughput_cap(&mut self, max_value_per_window: Balance, window_ms: u64) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("set_throughput_cap", Error::PermissionDenied));
+            }
+
+            self.throughput_cap = ThroughputCap {
+                max_value_per_window,
+                window_ms
+            };
+            self.throughput_window = (0, 0);
+
+            self.env().emit_event(ThroughputCapChanged {
+                cap: self.throughput_cap
+            });
+
+            Ok(())
+        }
+
+        /// Returns the start of the current throughput window and the value moved within it, the window
+        /// being started by the first counted transfer after the previous window ended, or `None` if
+        /// transfers from `from` don't count toward the throughput cap.
+        fn throughput_window_for(&self, from: AccountId) -> Option<(Timestamp, Balance)> {
+            let cap = self.throughput_cap;
+            if cap.max_value_per_window == 0 || cap.window_ms == 0 || from == self.owner || self.is_system_account(from) {
+                return None;
+            }
+
+            let now = self.env().block_timestamp();
+            Some(match self.throughput_window {
+                (start, moved) if moved > 0 && now < start.saturating_add(cap.window_ms) => (start, moved),
+                _ => (now, 0)
+            })
+        }
+
+        /// Counts `value` sent by `from` toward the throughput cap.
+        fn record_throughput(&mut self, from: AccountId, value: Balance) {
+            if let Some((window_start, moved)) = self.throughput_window_for(from) {
+                self.throughput_window = (window_start, moved.saturating_add(value));
+            }
+        }
+
+        /// Screens an account whose balance a transfer, mint, burn or migration is about to change.
+        ///
+        /// Blacklisted accounts can neither send nor receive tokens, frozen accounts can only receive,
+        /// and credits to the zero account are rejected. `destroy_black_funds` is the only path
+        /// debiting a blacklisted account.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account and is credited.
+        ///
+        /// Returns `AccountBlackListed` error if `account` is blacklisted.
+        ///
+        /// Returns `AccountFrozen` error if `account` is frozen and is debited.
+        ///
+        /// Returns `ReentrantCall` error while `transfer_and_call` notifies a contract recipient.
+        fn ensure_compliant(&mut self, message: &'static str, account: AccountId, direction: Direction) -> Result<()> {
+            if self.notifying_receiver {
+                return Err(self.fail(message, Error::ReentrantCall));
+            }
+
+            if direction == Direction::Credit {
+                self.validate_account(message, account)?;
+            }
+
+            if self.is_account_blacklisted(account) {
+                return Err(self.fail(message, Error::AccountBlackListed));
+            }
+
+            if direction == Direction::Debit && self.is_account_frozen(account) {
+                return Err(self.fail(message, Error::AccountFrozen));
+            }
+            Ok(())
+        }
+
+        /// Returns `AccountFrozen` error if `account` is frozen, for paths letting others spend its tokens.
+        fn ensure_not_frozen(&mut self, message: &'static str, account: AccountId) -> Result<()> {
+            if self.is_account_frozen(account) {
+                return Err(self.fail(message, Error::AccountFrozen));
+            }
+            Ok(())
+        }
+
+        /// Returns whether contracts with a code hash are banned from calling the token
+        #[ink(message, selector = "0x5A9FA2A8")]
+        pub fn is_code_hash_banned(&self, code_hash: Hash) -> bool {
+            self.banned_code_hashes.get(&code_hash).copied().unwrap_or(false)
+        }
+
+        /// Ban contracts with a code hash from transferring and approving, wherever they are instantiated
+        ///
+        /// On success a `CodeHashBanned` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x9222832E")]
+        pub fn ban_code_hash(&mut self, code_hash: Hash) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("ban_code_hash", Error::PermissionDenied));
+            }
+
+            self.banned_code_hashes.insert(code_hash, true);
+
+            self.env().emit_event(CodeHashBanned {
+                code_hash
+            });
+
+            Ok(())
+        }
+
+        /// Lift the ban on contracts with a code hash
+        ///
+        /// On success a `CodeHashUnbanned` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0xDBB8AB20")]
+        pub fn unban_code_hash(&mut self, code_hash: Hash) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("unban_code_hash", Error::PermissionDenied));
+            }
+
+            self.banned_code_hashes.take(&code_hash);
+
+            self.env().emit_event(CodeHashUnbanned {
+                code_hash
+            });
+
+            Ok(())
+        }
+
+        /// Checks that the caller is not a contract whose code hash is banned. The code hash is only
+        /// looked up while some hash is banned.
+        ///
+        /// # Errors
+        ///
+        /// Returns `CallerCodeBanned` error if the caller's code hash is banned.
+        fn ensure_caller_allowed(&mut self, message: &'static str) -> Result<()> {
+            if self.banned_code_hashes.is_empty() {
+                return Ok(());
+            }
+            match code_hash_of(self.env().caller()) {
+                Some(code_hash) if self.is_code_hash_banned(code_hash) => Err(self.fail(message, Error::CallerCodeBanned)),
+                _ => Ok(())
+            }
+        }
+
+        /// Checks that token transfers are not halted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ContractPaused` error if the contract is paused.
+        ///
+        /// Returns `ReentrantCall` error while `transfer_and_call` notifies a contract recipient.
+        fn ensure_not_paused(&mut self, message: &'static str) -> Result<()> {
+            if self.notifying_receiver {
+                return Err(self.fail(message, Error::ReentrantCall));
+            }
+            if self.paused() {
+                return Err(self.fail(message, Error::ContractPaused));
+            }
+            Ok(())
+        }
+
+        /// Counts a failure with `error` in `message`, returning the error.
+        ///
+        /// `message` names the failing message. Shared checks such as `ensure_compliant` are passed
+        /// the name of the message they check for.
+        ///
+        /// A `TransactionFailed` event is only emitted if the contract was constructed with `verbose_errors`.
+        fn fail(&mut self, message: &'static str, error: Error) -> Error {
+            let code = error as usize;
+            let counts = &mut self.failure_counts;
+            if counts.len() <= code {
+                counts.resize(code + 1, 0);
+            }
+            counts[code] = counts[code].saturating_add(1);
+
+            if self.verbose_errors {
+                self.env().emit_event(TransactionFailed {
+                    caller: self.env().caller(),
+                    message: message.into(),
+                    error
+                });
+            }
+            error
+        }
+
+        /// Returns the result of a checked operation, failing `message` with `Overflow` error if
+        /// it overflowed.
+        fn or_overflow<T>(&mut self, message: &'static str, result: Option<T>) -> Result<T> {
+            match result {
+                Some(value) => Ok(value),
+                None => Err(self.fail(message, Error::Overflow))
+            }
+        }
+
+        /// Returns how many times each error was returned since the last reset, as pairs of error
+        /// code and count, leaving out errors that didn't occur
+        #[ink(message, selector = "0xF3E9748D")]
+        pub fn failure_stats(&self) -> Vec<(u32, u64)> {
+            self.failure_counts
+                .iter()
+                .enumerate()
+                .filter(|&(_, &count)| count > 0)
+                .map(|(code, &count)| (code as u32, count))
+                .collect()
+        }
+
+        /// Reset the failure counters
+        ///
+        /// On success a `FailureStatsReset` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message, selector = "0x087744ED")]
+        pub fn reset_failure_stats(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.fail("reset_failure_stats", Error::PermissionDenied));
+            }
+
+            Lazy::<Vec<u64>>::set(&mut self.failure_counts, Vec::new());
+
+            self.env().emit_event(FailureStatsReset {
+                by: caller
+            });
+
+            Ok(())
+        }
+
+        /// Returns the nonce the next signed admin operation must carry
+        #[ink(message, selector = "0xD2F5B369")]
+        pub fn admin_nonce(&self) -> u64 {
+            self.admin_nonce
+        }
+
+        /// Returns the hash the owner signs to authorize `op` with the given `nonce` and `deadline`
+        ///
+        /// The hash commits to this contract's account so a signature can't be replayed on another deployment.
+        #[ink(message, selector = "0x03101B90")]
+        pub fn admin_op_hash(&self, op: AdminOp, nonce: u64, deadline: Timestamp) -> [u8; 32] {
+            self.env().hash_encoded::<env::hash::Blake2x256, _>(&(self.env().account_id(), op, nonce, deadline))
+        }
+
+        /// Execute an admin operation signed off-chain by the owner, on behalf of any relayer
+        ///
+        /// `signature` is the owner's sr25519 signature of `admin_op_hash(op, nonce, deadline)`, verified
+        /// through the runtime's chain extension. On success the admin nonce is incremented, the events of
+        /// the executed operation are emitted and a `SignedAdminOpExecuted` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if the ownership was renounced.
+        ///
+        /// Returns `SignatureExpired` error if the block timestamp is past `deadline`.
+        ///
+        /// Returns `InvalidNonce` error if `nonce` is not the current admin nonce.
+        ///
+        /// Returns `InvalidSignature` error if `signature` is not the owner's signature of the operation.
+        ///
+        /// Returns any error the operation itself returns when called directly by the owner.
+        #[ink(message, selector = "0x571736CB")]
+        pub fn execute_signed_admin_op(&mut self, op: AdminOp, nonce: u64, deadline: Timestamp, signature: [u8; 64]) -> Result<()> {
+            // The zero account's key is degenerate, so no signature may stand for it
+            if self.owner == AccountId::default() {
+                return Err(self.fail("execute_signed_admin_op", Error::PermissionDenied));
+            }
+
+            if self.env().block_timestamp() > deadline {
+                return Err(self.fail("execute_signed_admin_op", Error::SignatureExpired));
+            }
+
+            if nonce != self.admin_nonce {
+                return Err(self.fail("execute_signed_admin_op", Error::InvalidNonce));
+            }
+
+            let hash = self.admin_op_hash(op.clone(), nonce, deadline);
+            if !sr25519_verify(self.owner, hash, signature) {
+                return Err(self.fail("execute_signed_admin_op", Error::InvalidSignature));
+            }
+
+            let next_nonce = self.or_overflow("execute_signed_admin_op", nonce.checked_add(1))?;
+
+            match op {
+                AdminOp::AddToBlackList(account, reason) => self.blacklist("execute_signed_admin_op", account, reason)?,
+                AdminOp::RemoveFromBlackList(account) => self.unblacklist(account),
+                AdminOp::FreezeUntil(account, until) => self.freeze("execute_signed_admin_op", account, until)?,
+                AdminOp::Unfreeze(account) => self.unfreeze(account),
+                AdminOp::SetPrivate(account, private) => self.set_private("execute_signed_admin_op", account, private)?,
+            }
+
+            self.admin_nonce = next_nonce;
+
+            self.env().emit_event(SignedAdminOpExecuted {
+                nonce,
+                relayer: self.env().caller()
+            });
+
+            Ok(())
+        }
+
+        /// Checks that `account` is a real account, i.e. not the zero account.
+        ///
+        /// Tokens are never burned by sending them to the zero account; removal messages such as
+        /// `remove_account_from_blacklist` and `unfreeze_account` skip this check so stale entries
+        /// can always be cleaned up.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account.
+        fn validate_account(&mut self, message: &'static str, account: AccountId) -> Result<()> {
+            if account == AccountId::default() {
+                return Err(self.fail(message, Error::InvalidAccount));
+            }
+            Ok(())
+        }
+
+        /// Checks that `account` is a real account distinct from `actor`, the account acting on it.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidAccount` error if `account` is the zero account or `actor`'s account.
+        fn validate_counterparty(&mut self, message: &'static str, actor: AccountId, account: AccountId) -> Result<()> {
+            self.validate_account(message, account)?;
+            if account == actor {
+                return Err(self.fail(message, Error::InvalidAccount));
+            }
+            Ok(())
+        }
+
+    }
+
+    // Trait messages get selectors composed from the trait name, e.g. `Erc20::transfer`.
+    impl Erc20 for Entropy {
+        /// Same as `balance_of`.
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            Entropy::balance_of(self, owner)
+        }
+
+        /// Same as `allowance`.
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            Entropy::allowance(self, owner, spender)
+        }
+
+        /// Same as `transfer`.
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, extra: Option<String>) -> Result<()> {
+            Entropy::transfer(self, to, value, extra)
+        }
+
+        /// Same as `transfer_from`.
+        #[ink(message)]
+        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            Entropy::transfer_from(self, from, to, value)
+        }
+
+        /// Same as `approve`.
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            Entropy::approve(self, spender, value)
+        }
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_env::{
+            hash::{
+                Blake2x256,
+                CryptoHash,
+                HashOutput,
+            },
+            Clear,
+        };
+
+        type Event = <Entropy as ::ink_lang::BaseEvent>::Type;
+
+        use ink_lang as ink;
+
+        fn encoded_into_hash<T>(entity: &T) -> Hash
+            where T: scale::Encode
+        {
+            let mut result = Hash::clear();
+            let len_result = result.as_ref().len();
+            let encoded = entity.encode();
+            let len_encoded = encoded.len();
+            if len_encoded <= len_result {
+                result.as_mut()[..len_encoded].copy_from_slice(&encoded);
+                return result
+            }
+            let mut hash_output =
+                <<Blake2x256 as HashOutput>::Type as Default>::default();
+            <Blake2x256 as CryptoHash>::hash(&encoded, &mut hash_output);
+            let copy_len = core::cmp::min(hash_output.len(), len_result);
+            result.as_mut()[0..copy_len].copy_from_slice(&hash_output[0..copy_len]);
+            result
+        }
+
+        fn assert_transfer_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_from: Option<AccountId>,
+            expected_to: Option<AccountId>,
+            expected_value: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Transfer(Transfer { from, to, value }) = decoded_event {
+                assert_eq!(from, expected_from, "encountered invalid Transfer.from");
+                assert_eq!(to, expected_to, "encountered invalid Transfer.to");
+                assert_eq!(value, expected_value, "encountered invalid Trasfer.value");
+            } else {
+                panic!("encountered unexpected event kind: expected a Transfer event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::Transfer",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::Transfer::from",
+                    value: &expected_from,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::Transfer::to",
+                    value: &expected_to,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::Transfer::value",
+                    value: &expected_value,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_transfer_with_memo_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_from: AccountId,
+            expected_to: AccountId,
+            expected_value: Balance,
+            expected_memo: &[u8]
+        ) {
+            let mut expected_memo_hash = <Blake2x256 as HashOutput>::Type::default();
+            <Blake2x256 as CryptoHash>::hash(expected_memo, &mut expected_memo_hash);
+            let expected_memo_hash = Hash::from(expected_memo_hash);
+
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TransferWithMemo(TransferWithMemo { from, to, value, memo_hash, memo }) = decoded_event {
+                assert_eq!(from, expected_from, "encountered invalid TransferWithMemo.from");
+                assert_eq!(to, expected_to, "encountered invalid TransferWithMemo.to");
+                assert_eq!(value, expected_value, "encountered invalid TransferWithMemo.value");
+                assert_eq!(memo_hash, expected_memo_hash, "encountered invalid TransferWithMemo.memo_hash");
+                assert_eq!(memo, expected_memo, "encountered invalid TransferWithMemo.memo");
+            } else {
+                panic!("encountered unexpected event kind: expected a TransferWithMemo event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::TransferWithMemo",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransferWithMemo::from",
+                    value: &expected_from,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransferWithMemo::to",
+                    value: &expected_to,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransferWithMemo::memo_hash",
+                    value: &expected_memo_hash,
+                })
+            ];
+            assert_eq!(event.topics.len(), expected_topics.len());
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_fee_charged_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_from: AccountId,
+            expected_collector: AccountId,
+            expected_fee: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::FeeCharged(FeeCharged { from, collector, fee }) = decoded_event {
+                assert_eq!(from, expected_from, "encountered invalid FeeCharged.from");
+                assert_eq!(collector, expected_collector, "encountered invalid FeeCharged.collector");
+                assert_eq!(fee, expected_fee, "encountered invalid FeeCharged.fee");
+            } else {
+                panic!("encountered unexpected event kind: expected a FeeCharged event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::FeeCharged",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::FeeCharged::from",
+                    value: &expected_from,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::FeeCharged::collector",
+                    value: &expected_collector,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_new_holder_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_account: AccountId,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::NewHolder(NewHolder { account, .. }) = decoded_event {
+                assert_eq!(account, expected_account, "encountered invalid NewHolder.account");
+            } else {
+                panic!("encountered unexpected event kind: expected a NewHolder event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::NewHolder",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::NewHolder::account",
+                    value: &expected_account,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_issue_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_value: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Issue(Issue { amount }) = decoded_event {
+                assert_eq!(amount, expected_value, "encountered invalid Issue.amount");
+            } else {
+                panic!("encountered unexpected event kind: expected an Issue event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::Issue",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::Issue::amount",
+                    value: &expected_value,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_redeem_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_value: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Redeem(Redeem { amount, .. }) = decoded_event {
+                assert_eq!(amount, expected_value, "encountered invalid Redeem.amount");
+            } else {
+                panic!("encountered unexpected event kind: expected a Redeem event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::Redeem",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::Redeem::amount",
+                    value: &expected_value,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_privacy_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_account: AccountId,
+            expected_private: bool,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Privacy(Privacy { account, private }) = decoded_event {
+                assert_eq!(account, expected_account, "encountered invalid Privacy.account");
+                assert_eq!(private, expected_private, "encountered invalid Privacy.private");
+            } else {
+                panic!("encountered unexpected event kind: expected a Privacy event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::Privacy",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::Privacy::account",
+                    value: &expected_account,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::Privacy::private",
+                    value: &expected_private,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_added_blacklist_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_account: AccountId,
+            expected_reason: &str
+        ) {
+            let mut expected_reason_hash = <Blake2x256 as HashOutput>::Type::default();
+            <Blake2x256 as CryptoHash>::hash(expected_reason.as_bytes(), &mut expected_reason_hash);
+            let expected_reason_hash = Hash::from(expected_reason_hash);
+
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::AddedBlackList(AddedBlackList { account, reason_hash, .. }) = decoded_event {
+                assert_eq!(account, expected_account, "encountered invalid AddedBlackList.account");
+                assert_eq!(reason_hash, expected_reason_hash, "encountered invalid AddedBlackList.reason_hash");
+            } else {
+                panic!("encountered unexpected event kind: expected a AddedBlackList event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::AddedBlackList",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::AddedBlackList::account",
+                    value: &expected_account,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::AddedBlackList::reason_hash",
+                    value: &expected_reason_hash,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_removed_blacklist_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_account: AccountId
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::RemovedBlackList(RemovedBlackList { account }) = decoded_event {
+                assert_eq!(account, expected_account, "encountered invalid RemovedBlackList.account");
+            } else {
+                panic!("encountered unexpected event kind: expected a RemovedBlackList event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::RemovedBlackList",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::RemovedBlackList::account",
+                    value: &expected_account,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_destroyed_black_funds_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_account: AccountId,
+            expected_funds: Balance
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::DestroyedBlackFunds(DestroyedBlackFunds { account, funds }) = decoded_event {
+                assert_eq!(account, expected_account, "encountered invalid DestroyedBlackFunds.account");
+                assert_eq!(funds, expected_funds, "encountered invalid DestroyedBlackFunds.funds");
+            } else {
+                panic!("encountered unexpected event kind: expected a DestroyedBlackFunds event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::DestroyedBlackFunds",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::DestroyedBlackFunds::account",
+                    value: &expected_account,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::DestroyedBlackFunds::funds",
+                    value: &expected_funds,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_transaction_failed_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_caller: AccountId,
+            expected_message: &str,
+            expected_error: Error
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TransactionFailed(TransactionFailed { caller, message, error }) = decoded_event {
+                assert_eq!(caller, expected_caller, "encountered invalid TransactionFailed.caller");
+                assert_eq!(message, expected_message, "encountered invalid TransactionFailed.message");
+                assert_eq!(error, expected_error, "encountered invalid TransactionFailed.error");
+            } else {
+                panic!("encountered unexpected event kind: expected a TransactionFailed event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::TransactionFailed",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransactionFailed::caller",
+                    value: &expected_caller,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransactionFailed::error",
+                    value: &expected_error,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn assert_auto_frozen_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_account: AccountId,
+            expected_rule: VelocityRule
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::AutoFrozen(AutoFrozen { account, rule }) = decoded_event {
+                assert_eq!(account, expected_account, "encountered invalid AutoFrozen.account");
+                assert_eq!(rule, expected_rule, "encountered invalid AutoFrozen.rule");
+            } else {
+                panic!("encountered unexpected event kind: expected an AutoFrozen event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::AutoFrozen",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::AutoFrozen::account",
                     value: &expected_account,
                 }),
                 encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::DestroyedBlackFunds::funds",
-                    value: &expected_funds,
+                    prefix: b"Entropy::AutoFrozen::rule",
+                    value: &expected_rule,
+                })
+            ];
+            for (n, (actual_topic, expected_topic)) in
+                event.topics.iter().zip(expected_topics).enumerate()
+            {
+                let topic = actual_topic
+                    .decode::<Hash>()
+                    .expect("encountered invalid topic encoding");
+                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
+            }
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<Environment>().unwrap_or_else(|_| [0x0; 32].into());
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<Environment>(caller, callee, 1000000, 1000000, data);
+        }
+
+        #[cfg(not(feature = "custom-environment"))]
+        fn set_caller_with_value(caller: AccountId, value: Balance) {
+            let callee = ink_env::account_id::<Environment>().unwrap_or_else(|_| [0x0; 32].into());
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<Environment>(caller, callee, 1000000, value, data);
+        }
+
+        thread_local! {
+            /// Code hashes of the accounts registered as contracts with `mock_contract`.
+            static CODE_HASHES: core::cell::RefCell<Vec<(AccountId, Hash)>> = core::cell::RefCell::new(Vec::new());
+        }
+
+        /// Registers `account` as a contract instantiated from `code_hash`.
+        fn mock_contract(account: AccountId, code_hash: Hash) {
+            CODE_HASHES.with(|hashes| hashes.borrow_mut().push((account, code_hash)));
+        }
+
+        /// Sender, value and data of a notification `mock_notify_received` got.
+        type Notification = (AccountId, Balance, Vec<u8>);
+
+        thread_local! {
+            /// Contracts registered with `mock_receiver`, whether they accept tokens and the notifications they got.
+            static RECEIVERS: core::cell::RefCell<Vec<(AccountId, bool, Vec<Notification>)>> = core::cell::RefCell::new(Vec::new());
+        }
+
+        /// Registers `account` as a contract implementing `on_entropy_received`, accepting tokens if `accept`.
+        fn mock_receiver(account: AccountId, accept: bool) {
+            mock_contract(account, Hash::from([0x9; 32]));
+            RECEIVERS.with(|receivers| receivers.borrow_mut().push((account, accept, Vec::new())));
+        }
+
+        /// Notifications `account` got through `mock_notify_received`.
+        fn mock_notifications(account: AccountId) -> Vec<Notification> {
+            RECEIVERS.with(|receivers| receivers.borrow().iter().find(|(receiver, _, _)| *receiver == account).map(|(_, _, notifications)| notifications.clone()).unwrap_or_default())
+        }
+
+        /// Answer of `notify_received` in tests. Contracts not registered with `mock_receiver` lack the
+        /// message, failing the call.
+        pub(super) fn mock_notify_received(account: AccountId, from: AccountId, value: Balance, data: Vec<u8>) -> bool {
+            RECEIVERS.with(|receivers| {
+                match receivers.borrow_mut().iter_mut().find(|(receiver, _, _)| *receiver == account) {
+                    Some((_, accept, notifications)) => {
+                        notifications.push((from, value, data));
+                        *accept
+                    }
+                    None => false
+                }
+            })
+        }
+
+        thread_local! {
+            /// Balances of the PSP22 tokens registered with `mock_token`, by token and holder.
+            static TOKENS: core::cell::RefCell<Vec<(AccountId, AccountId, Balance)>> = core::cell::RefCell::new(Vec::new());
+        }
+
+        /// Registers `token` as a PSP22 contract, crediting `holder` with `balance` of it.
+        fn mock_token(token: AccountId, holder: AccountId, balance: Balance) {
+            mock_contract(token, Hash::from([0x8; 32]));
+            TOKENS.with(|tokens| tokens.borrow_mut().push((token, holder, balance)));
+        }
+
+        /// Balance of `holder` in the PSP22 `token` registered with `mock_token`.
+        fn mock_token_balance(token: AccountId, holder: AccountId) -> Balance {
+            TOKENS.with(|tokens| tokens.borrow().iter().filter(|(t, h, _)| *t == token && *h == holder).map(|(_, _, balance)| *balance).sum())
+        }
+
+        /// Answer of `transfer_foreign_token` in tests. Contracts not registered with `mock_token` lack the
+        /// message, failing the call, and tokens refuse transfers exceeding the contract's balance.
+        pub(super) fn mock_token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            let registered = TOKENS.with(|tokens| tokens.borrow().iter().any(|(t, _, _)| *t == token));
+            let balance = mock_token_balance(token, contract);
+            if !registered || balance < value {
+                return false;
+            }
+            TOKENS.with(|tokens| {
+                let mut tokens = tokens.borrow_mut();
+                tokens.retain(|(t, h, _)| *t != token || *h != contract);
+                tokens.push((token, contract, balance - value));
+                tokens.push((token, to, value));
+            });
+            true
+        }
+
+        thread_local! {
+            /// Code hashes uploaded with `mock_upload`.
+            static UPLOADED_CODE: core::cell::RefCell<Vec<Hash>> = core::cell::RefCell::new(Vec::new());
+        }
+
+        /// Uploads code under `code_hash`, so the contract can be upgraded to it.
+        fn mock_upload(code_hash: Hash) {
+            UPLOADED_CODE.with(|uploaded| uploaded.borrow_mut().push(code_hash));
+        }
+
+        /// Answer of `set_code_hash` in tests, registering the contract under `code_hash` if it was uploaded.
+        pub(super) fn mock_set_code_hash(code_hash: Hash) -> bool {
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            if !UPLOADED_CODE.with(|uploaded| uploaded.borrow().contains(&code_hash)) {
+                return false;
+            }
+            CODE_HASHES.with(|hashes| hashes.borrow_mut().retain(|(account, _)| *account != contract));
+            mock_contract(contract, code_hash);
+            true
+        }
+
+        /// Code hash `code_hash_of` reports in tests, set with `mock_contract`.
+        pub(super) fn mock_code_hash(account: AccountId) -> Option<Hash> {
+            CODE_HASHES.with(|hashes| hashes.borrow().iter().find(|(contract, _)| *contract == account).map(|(_, code_hash)| *code_hash))
+        }
+
+        /// Answer of `sr25519_verify` in tests, accepting signatures made with `mock_sign`.
+        pub(super) fn mock_sr25519_verify(signer: AccountId, hash: [u8; 32], signature: [u8; 64]) -> bool {
+            signature == mock_sign(signer, hash)
+        }
+
+        /// Signature `sr25519_verify` accepts in tests for `hash` signed by `signer`.
+        fn mock_sign(signer: AccountId, hash: [u8; 32]) -> [u8; 64] {
+            let digest = encoded_into_hash(&(signer, hash));
+            let mut signature = [0x0; 64];
+            signature[..32].copy_from_slice(digest.as_ref());
+            signature[32..].copy_from_slice(&hash);
+            signature
+        }
+
+        /// Signature `ecdsa_recover_eth_address` recovers to `eth_address` for `hash`.
+        fn mock_ecdsa_sign(eth_address: [u8; 20], hash: [u8; 32]) -> EcdsaSignature {
+            let digest = encoded_into_hash(&(eth_address, hash));
+            let mut rs = [0x0; 64];
+            rs[..20].copy_from_slice(&eth_address);
+            rs[20..52].copy_from_slice(digest.as_ref());
+            EcdsaSignature { rs, v: 0 }
+        }
+
+        /// Ethereum address `ecdsa_recover_eth_address` recovers in tests from signatures made by `mock_ecdsa_sign`.
+        pub(super) fn mock_ecdsa_recover(signature: EcdsaSignature, hash: [u8; 32]) -> Option<[u8; 20]> {
+            let mut eth_address = [0x0; 20];
+            eth_address.copy_from_slice(&signature.rs[..20]);
+            if signature == mock_ecdsa_sign(eth_address, hash) {
+                Some(eth_address)
+            } else {
+                None
+            }
+        }
+
+        fn assert_transfer_reversed_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_tx_id: u64,
+            expected_from: AccountId,
+            expected_to: AccountId,
+            expected_value: Balance,
+            expected_reason: &str
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TransferReversed(TransferReversed { tx_id, from, to, value, reason }) = decoded_event {
+                assert_eq!(tx_id, expected_tx_id, "encountered invalid TransferReversed.tx_id");
+                assert_eq!(from, expected_from, "encountered invalid TransferReversed.from");
+                assert_eq!(to, expected_to, "encountered invalid TransferReversed.to");
+                assert_eq!(value, expected_value, "encountered invalid TransferReversed.value");
+                assert_eq!(reason, expected_reason, "encountered invalid TransferReversed.reason");
+            } else {
+                panic!("encountered unexpected event kind: expected a TransferReversed event")
+            }
+
+            let expected_topics = vec![
+                encoded_into_hash(&PrefixedValue {
+                    value: b"Entropy::TransferReversed",
+                    prefix: b"",
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransferReversed::tx_id",
+                    value: &expected_tx_id,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransferReversed::from",
+                    value: &expected_from,
+                }),
+                encoded_into_hash(&PrefixedValue {
+                    prefix: b"Entropy::TransferReversed::to",
+                    value: &expected_to,
                 })
             ];
             for (n, (actual_topic, expected_topic)) in
@@ -946,512 +9946,5248 @@ mod entropy {
             }
         }
 
-        fn assert_transaction_failed_event(
-            event: &ink_env::test::EmittedEvent,
-            expected_error: String
-        ) {
-            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+        /// The default constructor does its job.
+        #[ink::test]
+        fn new_works() {
+            // Constructor works.
+            let _entropy = Entropy::new(100);
+
+            // Transfer event triggered during initial construction.
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(2, emitted_events.len());
+
+            assert_transfer_event(
+                &emitted_events[0],
+                None,
+                Some(AccountId::from([0x01; 32])),
+                100,
+            );
+            assert_new_holder_event(&emitted_events[1], AccountId::from([0x01; 32]));
+        }
+
+        #[ink::test]
+        fn default_works() {
+            let entropy = Entropy::default();
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(2, emitted_events.len());
+
+            // default values
+            let default_decimals = 6;
+            let default_initial_supply: Balance = Balance::pow(10, default_decimals) * 1_000_000;
+            let default_name = "Entropy Coin";
+            let default_symbol = "ENT";
+
+            assert_transfer_event(
+                &emitted_events[0],
+                None,
+                Some(AccountId::from([0x01; 32])),
+                default_initial_supply,
+            );
+            assert_new_holder_event(&emitted_events[1], AccountId::from([0x01; 32]));
+            
+            assert_eq!(entropy.total_supply(), default_initial_supply);
+            assert_eq!(entropy.name(), default_name);
+            assert_eq!(entropy.symbol(), default_symbol);
+            assert_eq!(entropy.decimals(), default_decimals);
+        }
+
+        /// The total supply was applied.
+        #[ink::test]
+        fn total_supply_works() {
+            // Constructor works.
+            let entropy = Entropy::new(100);
+            // Transfer event triggered during initial construction.
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_transfer_event(
+                &emitted_events[0],
+                None,
+                Some(AccountId::from([0x01; 32])),
+                100,
+            );
+            // Get the token total supply.
+            assert_eq!(entropy.total_supply(), 100);
+        }
+
+        /// Get the actual balance of an account.
+        #[ink::test]
+        fn balance_of_works() {
+            // Constructor works
+            let entropy = Entropy::new(100);
+            // Transfer event triggered during initial construction
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_transfer_event(
+                &emitted_events[0],
+                None,
+                Some(AccountId::from([0x01; 32])),
+                100,
+            );
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+            // Alice owns all the tokens on deployment
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            // Bob does not owns tokens
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn batch_balance_queries_work() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 30, None), Ok(()));
+            assert_eq!(entropy.approve(accounts.charlie, 5), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.alice, 7), Ok(()));
+
+            let owners = vec![accounts.bob, accounts.django, accounts.alice, accounts.bob];
+            assert_eq!(entropy.balances_of(owners), Ok(vec![30, 0, 70, 30]));
+            let pairs = vec![
+                (accounts.alice, accounts.charlie),
+                (accounts.bob, accounts.alice),
+                (accounts.charlie, accounts.alice),
+                (accounts.alice, accounts.charlie)
+            ];
+            assert_eq!(entropy.allowances_of(pairs), Ok(vec![5, 7, 0, 5]));
+            assert_eq!(entropy.balances_of(Vec::new()), Ok(Vec::new()));
+            assert_eq!(entropy.allowances_of(Vec::new()), Ok(Vec::new()));
+
+            // Private accounts read as `0` to other callers, like the single lookups
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.balances_of(vec![accounts.bob, accounts.alice]), Ok(vec![0, 70]));
+            assert_eq!(entropy.allowances_of(vec![(accounts.bob, accounts.alice)]), Ok(vec![0]));
+
+            assert_eq!(entropy.balances_of(vec![accounts.bob; MAX_BATCH_SIZE + 1]), Err(Error::BatchTooLarge));
+            assert_eq!(entropy.allowances_of(vec![(accounts.bob, accounts.alice); MAX_BATCH_SIZE + 1]), Err(Error::BatchTooLarge));
+        }
+
+        /// Hands the ownership over from the current caller to `new_owner`, which is left as caller.
+        fn hand_over_ownership(entropy: &mut Entropy, new_owner: AccountId) {
+            assert_eq!(entropy.propose_owner(new_owner), Ok(()));
+            set_caller(new_owner);
+            assert_eq!(entropy.accept_ownership(), Ok(()));
+        }
+
+        #[ink::test]
+        fn transfer_ownership_works() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100);
+
+            // Transfer event triggered during initial construction.
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+
+            // Assert owner is alice
+            assert_eq!(entropy.owner(), accounts.alice);
+            assert_eq!(entropy.pending_owner(), None);
+
+            // Propose bob, then re-propose charlie, which overwrites the proposal
+            assert_eq!(entropy.propose_owner(accounts.bob), Ok(()));
+            assert_eq!(entropy.propose_owner(accounts.charlie), Ok(()));
+            assert_eq!(entropy.pending_owner(), Some(accounts.charlie));
+
+            // Only the pending owner may accept
+            set_caller(accounts.bob);
+            assert_eq!(entropy.accept_ownership(), Err(Error::PermissionDenied));
+            assert_eq!(entropy.propose_owner(accounts.bob), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.accept_ownership(), Err(Error::PermissionDenied));
+
+            // Alice keeps her privileges until the proposal is accepted
+            assert_eq!(entropy.owner(), accounts.alice);
+            assert_eq!(entropy.set_params(1, 10), Ok(()));
+
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.accept_ownership(), Ok(()));
+            assert_eq!(entropy.owner(), accounts.charlie);
+            assert_eq!(entropy.pending_owner(), None);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 1);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events_before].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::OwnershipTransferred(OwnershipTransferred { old, new }) = decoded_event {
+                assert_eq!((old, new), (accounts.alice, accounts.charlie));
+            } else {
+                panic!("encountered unexpected event kind: expected an OwnershipTransferred event")
+            }
+            assert_eq!(entropy.accept_ownership(), Err(Error::PermissionDenied));
+
+            // Alice lost her privileges
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_params(2, 10), Err(Error::PermissionDenied));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.set_params(2, 10), Ok(()));
+        }
+
+        #[ink::test]
+        fn renounce_ownership_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+            let zero = AccountId::from([0x0; 32]);
+            let hash = Hash::from([0x1; 32]);
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.renounce_ownership(true), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.renounce_ownership(false), Err(Error::RenounceNotConfirmed));
+            assert_eq!(entropy.owner(), accounts.alice);
+
+            // Renouncing drops a pending proposal
+            assert_eq!(entropy.propose_owner(accounts.charlie), Ok(()));
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.renounce_ownership(true), Ok(()));
+            assert_eq!(entropy.owner(), zero);
+            assert_eq!(entropy.pending_owner(), None);
+            assert_eq!(entropy.ownership_history().last().map(|record| record.owner), Some(zero));
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 1);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events_before].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::OwnershipRenounced(OwnershipRenounced { previous }) = decoded_event {
+                assert_eq!(previous, accounts.alice);
+            } else {
+                panic!("encountered unexpected event kind: expected an OwnershipRenounced event")
+            }
+
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.accept_ownership(), Err(Error::PermissionDenied));
+
+            // No signature stands for the zero account
+            let op = AdminOp::AddToBlackList(accounts.bob, "sanctioned".into());
+            let signature = mock_sign(zero, entropy.admin_op_hash(op.clone(), 0, 100));
+            assert_eq!(entropy.execute_signed_admin_op(op, 0, 100, signature), Err(Error::PermissionDenied));
+
+            // Every owner-only function is out of reach for good, the former owner's included
+            set_caller(accounts.alice);
+            let denied = Err(Error::PermissionDenied);
+            assert_eq!(entropy.renounce_ownership(true), denied);
+            assert_eq!(entropy.balance_of_unchecked(accounts.bob), None);
+            assert_eq!(entropy.set_fee_rounding(FeeRounding::Up), denied);
+            assert_eq!(entropy.set_params(1, 10), denied);
+            assert_eq!(entropy.set_fee_collector(accounts.bob), denied);
+            assert_eq!(entropy.propose_owner(accounts.bob), denied);
+            assert_eq!(entropy.upgrade(hash), denied);
+            assert_eq!(entropy.set_strict_approvals(true), denied);
+            assert_eq!(entropy.set_dispute_window(10), denied);
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), denied);
+            assert_eq!(entropy.issue(10), denied);
+            assert_eq!(entropy.issue_to(accounts.bob, 10), denied);
+            assert_eq!(entropy.redeem(10, hash), Err(Error::PermissionDenied));
+            assert_eq!(entropy.mint(accounts.bob, 10), Err(PSP22Error::Custom("PermissionDenied".into())));
+            assert_eq!(entropy.set_account_private(accounts.bob, true), denied);
+            assert_eq!(entropy.set_default_private_for_new_accounts(true), denied);
+            assert_eq!(entropy.add_auditor(accounts.bob), denied);
+            assert_eq!(entropy.remove_auditor(accounts.bob), denied);
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), denied);
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), denied);
+            assert_eq!(entropy.add_accounts_to_blacklist(vec![accounts.bob], "sanctioned".into()), denied);
+            assert_eq!(entropy.remove_accounts_from_blacklist(vec![accounts.bob]), denied);
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), denied);
+            assert_eq!(entropy.destroy_black_funds_partial(accounts.bob, 10), denied);
+            assert_eq!(entropy.seize_black_funds(accounts.bob, accounts.charlie), denied);
+            assert_eq!(entropy.seize_unclaimed_transfer(1, accounts.charlie), denied);
+            assert_eq!(entropy.seize_expired_gift(hash, accounts.charlie), denied);
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.charlie, 10), denied);
+            assert_eq!(entropy.destroy_black_funds_batch(vec![accounts.bob]), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_destruction_delay(10), denied);
+            assert_eq!(entropy.set_appeal_terms(10, 10), denied);
+            assert_eq!(entropy.resolve_appeal(accounts.bob, true), denied);
+            assert_eq!(entropy.rescue_native(accounts.bob, 10), denied);
+            assert_eq!(entropy.rescue_token(accounts.django, accounts.bob, 10), denied);
+            assert_eq!(entropy.set_min_holding_period(10), denied);
+            assert_eq!(entropy.set_default_transfer_limit(10), denied);
+            assert_eq!(entropy.set_transfer_limit(accounts.bob, Some(10)), denied);
+            assert_eq!(entropy.freeze_account(accounts.bob), denied);
+            assert_eq!(entropy.freeze_account_until(accounts.bob, 10), denied);
+            assert_eq!(entropy.unfreeze_account(accounts.bob), denied);
+            assert_eq!(entropy.set_velocity_rule(1, 10, 0), denied);
+            assert_eq!(entropy.set_velocity_exempt(accounts.bob, true), denied);
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, true), denied);
+            assert_eq!(entropy.rekey_account(accounts.bob, accounts.charlie), denied);
+            assert_eq!(entropy.enable_trading(), denied);
+            assert_eq!(entropy.set_prelaunch_allowed(accounts.bob, true), denied);
+            assert_eq!(entropy.pause(), denied);
+            assert_eq!(entropy.pause_for(10), denied);
+            assert_eq!(entropy.unpause(), denied);
+            assert_eq!(entropy.set_circuit_breaker(10), denied);
+            assert_eq!(entropy.set_system_account(accounts.bob, true), denied);
+            assert_eq!(entropy.set_trusted_forwarder(Some(accounts.bob)), denied);
+            assert_eq!(entropy.protect_account(accounts.bob), denied);
+            assert_eq!(entropy.unprotect_account(accounts.bob), denied);
+            assert_eq!(entropy.set_points_program(true, 10, false, 0, 0), denied);
+            assert_eq!(entropy.convert_points(10), denied);
+            assert_eq!(entropy.set_rebate(accounts.bob, 10), denied);
+            assert_eq!(entropy.snapshot(), Err(Error::PermissionDenied));
+            assert_eq!(entropy.announce_dust_sweep(10, 10, true), denied);
+            assert_eq!(entropy.execute_dust_sweep(0, 10), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_top_holders_limit(10), denied);
+            assert_eq!(entropy.set_distribution_window(10), denied);
+            assert_eq!(entropy.distribute(0, 10), denied);
+            assert_eq!(entropy.sweep_distribution(0), denied);
+            assert_eq!(entropy.create_vesting(accounts.bob, 10, 0, 10, 0), denied);
+            assert_eq!(entropy.create_airdrop([0x1; 32], 10, 10), Err(Error::PermissionDenied));
+            assert_eq!(entropy.close_airdrop(1), denied);
+            assert_eq!(entropy.set_throughput_cap(10, 10), denied);
+            assert_eq!(entropy.ban_code_hash(hash), denied);
+            assert_eq!(entropy.unban_code_hash(hash), denied);
+            assert_eq!(entropy.reset_failure_stats(), denied);
+        }
+
+        #[ink::test]
+        fn fee_rounding_works() {
+            let mut entropy = Entropy::new(1_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // 10 basis points: 4_999 -> 4.999, 5_000 -> 5, 5_001 -> 5.001, 5_499 -> 5.499, 5_500 -> 5.5, 1 -> 0.001
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.get_params(), FeeParams { basis_points_rate: 10, maximum_fee: 1_000, rounding: FeeRounding::Up });
+            let values = [4_999, 5_000, 5_001, 5_499, 5_500, 1];
+            let fees = |entropy: &Entropy| values.iter().map(|value| entropy.estimate_fee(*value)).collect::<Vec<_>>();
+            assert_eq!(fees(&entropy), vec![5, 5, 6, 6, 6, 1]);
+
+            assert_eq!(entropy.set_fee_rounding(FeeRounding::Down), Ok(()));
+            assert_eq!(entropy.get_params().rounding, FeeRounding::Down);
+            assert_eq!(fees(&entropy), vec![4, 5, 5, 5, 5, 0]);
+
+            assert_eq!(entropy.set_fee_rounding(FeeRounding::HalfUp), Ok(()));
+            assert_eq!(fees(&entropy), vec![5, 5, 5, 5, 6, 0]);
+
+            // Transfers charge the estimated fee, still capped at the maximum
+            assert_eq!(entropy.transfer(accounts.bob, 5_500, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 5_494);
+            assert_eq!(entropy.set_params(10, 3), Ok(()));
+            assert_eq!(entropy.estimate_fee(5_500), 3);
+            assert_eq!(entropy.set_params(0, 3), Ok(()));
+            assert_eq!(entropy.estimate_fee(5_500), 0);
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_fee_rounding(FeeRounding::Down), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn fee_overflow_boundary_works() {
+            let mut entropy = Entropy::new(100);
+
+            // value * 20 overflows from u128::MAX / 20 + 1 on, the fee is still the capped maximum
+            assert_eq!(entropy.set_params(20, 50_000_000), Ok(()));
+            let boundary = Balance::MAX / 20;
+            for rounding in [FeeRounding::Down, FeeRounding::Up, FeeRounding::HalfUp].iter() {
+                assert_eq!(entropy.set_fee_rounding(*rounding), Ok(()));
+                for value in [boundary - 1, boundary, boundary + 1, Balance::MAX].iter() {
+                    assert_eq!(entropy.estimate_fee(*value), 50_000_000);
+                }
+            }
+
+            // Below the cap the split computation is exact
+            assert_eq!(entropy.set_fee_rounding(FeeRounding::Down), Ok(()));
+            assert_eq!(entropy.estimate_fee(24_999_999_999), 49_999_999);
+            assert_eq!(entropy.set_fee_rounding(FeeRounding::Up), Ok(()));
+            assert_eq!(entropy.estimate_fee(24_999_999_999), 50_000_000);
+            assert_eq!(entropy.estimate_fee(24_999_995_000), 49_999_990);
+        }
+
+        #[ink::test]
+        fn split_transfers_pay_no_less_fee() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 50_000, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            set_caller(accounts.bob);
+
+            // One transfer of 9_990 pays 9.99 rounded up
+            let collected_before = entropy.balance_of(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.charlie, 9_990, None), Ok(()));
+            let single_fee = entropy.balance_of(accounts.alice) - collected_before;
+            assert_eq!(single_fee, 10);
+
+            // Ten transfers of 999 pay 0.999 rounded up each
+            let collected_before = entropy.balance_of(accounts.alice);
+            for _ in 0..10 {
+                assert_eq!(entropy.transfer(accounts.django, 999, None), Ok(()));
+            }
+            let split_fee = entropy.balance_of(accounts.alice) - collected_before;
+            assert_eq!(split_fee, 10);
+            assert!(split_fee >= single_fee);
+
+            // Rounding down lets the split evade the fee entirely
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_fee_rounding(FeeRounding::Down), Ok(()));
+            assert_eq!(entropy.estimate_fee(9_990), 9);
+            assert_eq!(entropy.estimate_fee(999), 0);
+        }
+
+        #[test]
+        fn chain_extension_func_ids_are_distinct() {
+            let func_ids = [SR25519_VERIFY_FUNC_ID, CODE_HASH_FUNC_ID, ECDSA_RECOVER_FUNC_ID, SET_CODE_HASH_FUNC_ID];
+            for (n, func_id) in func_ids.iter().enumerate() {
+                assert!(!func_ids[n + 1..].contains(func_id), "function id 0x{:04x} is used twice", func_id);
+            }
+        }
+
+        #[ink::test]
+        fn upgrade_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            assert_eq!(entropy.version(), CONTRACT_VERSION);
+
+            mock_contract(contract, Hash::from([0x1; 32]));
+            mock_upload(Hash::from([0x2; 32]));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.upgrade(Hash::from([0x2; 32])), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.upgrade(Hash::default()), Err(Error::InvalidCodeHash));
+            assert_eq!(entropy.upgrade(Hash::from([0x3; 32])), Err(Error::InvalidCodeHash));
+
+            // Upgrading keeps the storage and records both code hashes
+            assert_eq!(entropy.upgrade(Hash::from([0x2; 32])), Ok(()));
+            assert_eq!(mock_code_hash(contract), Some(Hash::from([0x2; 32])));
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::ContractUpgraded(ContractUpgraded { old_code_hash, new_code_hash }) = decoded_event {
+                assert_eq!(old_code_hash, Hash::from([0x1; 32]));
+                assert_eq!(new_code_hash, Hash::from([0x2; 32]));
+            } else {
+                panic!("encountered unexpected event kind: expected a ContractUpgraded event")
+            }
+        }
+
+        #[ink::test]
+        fn contract_info_works() {
+            let mut entropy = Entropy::construct(1_000, "Test Coin".into(), "TST".into(), 4);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.contract_info(), ContractInfo {
+                name: "Test Coin".into(),
+                symbol: "TST".into(),
+                decimals: 4,
+                total_supply: 1_000,
+                owner: accounts.alice,
+                basis_points_rate: 0,
+                maximum_fee: 0,
+                paused: false,
+                version: CONTRACT_VERSION
+            });
+
+            assert_eq!(entropy.set_params(10, 500), Ok(()));
+            assert_eq!(entropy.issue(200), Ok(()));
+            assert_eq!(entropy.pause(), Ok(()));
+            hand_over_ownership(&mut entropy, accounts.bob);
+
+            let info = entropy.contract_info();
+            assert_eq!(info.name, entropy.name());
+            assert_eq!(info.symbol, entropy.symbol());
+            assert_eq!(info.decimals, entropy.decimals());
+            assert_eq!(info.total_supply, entropy.total_supply());
+            assert_eq!(info.total_supply, 1_200);
+            assert_eq!(info.owner, entropy.owner());
+            assert_eq!(info.owner, accounts.bob);
+            assert_eq!((info.basis_points_rate, info.maximum_fee), (10, 500));
+            assert_eq!(info.basis_points_rate, entropy.basis_points_rate());
+            assert_eq!(info.maximum_fee, entropy.maximum_fee());
+            assert_eq!(info.paused, entropy.paused());
+            assert_eq!(info.paused, true);
+            assert_eq!(info.version, entropy.version());
+        }
+
+        #[ink::test]
+        fn ownership_history_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Ownership passes from Alice to Bob at block 2 and on to Charlie at block 4
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            hand_over_ownership(&mut entropy, accounts.bob);
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            hand_over_ownership(&mut entropy, accounts.charlie);
+
+            assert_eq!(entropy.ownership_history(), vec![
+                OwnerRecord { owner: accounts.alice, from_block: 0 },
+                OwnerRecord { owner: accounts.bob, from_block: 2 },
+                OwnerRecord { owner: accounts.charlie, from_block: 4 },
+            ]);
+            assert_eq!(entropy.owner_at_block(1), Some(accounts.alice));
+            assert_eq!(entropy.owner_at_block(2), Some(accounts.bob));
+            assert_eq!(entropy.owner_at_block(3), Some(accounts.bob));
+            assert_eq!(entropy.owner_at_block(100), Some(accounts.charlie));
+
+            // Pending and failed transfers aren't recorded
+            assert_eq!(entropy.propose_owner(accounts.django), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.accept_ownership(), Err(Error::PermissionDenied));
+            assert_eq!(entropy.ownership_history().len(), 3);
+
+            // The oldest records are dropped once the history is full
+            set_caller(accounts.charlie);
+            for i in 0..MAX_OWNERSHIP_HISTORY {
+                let next = if i % 2 == 0 { accounts.django } else { accounts.charlie };
+                hand_over_ownership(&mut entropy, next);
+            }
+            let history = entropy.ownership_history();
+            assert_eq!(history.len(), MAX_OWNERSHIP_HISTORY);
+            assert_eq!(history[0].owner, accounts.django);
+            assert_eq!(entropy.owner_at_block(1), None);
+            assert_eq!(entropy.owner_at_block(4), Some(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100_000_000);
+            // Transfer event triggered during initial construction.
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            // Alice transfers 20_000_000 tokens to Bob.
+            assert_eq!(entropy.transfer(accounts.bob, 20_000_000, None), Ok(()));
+            // Bob owns 20_000_000 tokens.
+            assert_eq!(entropy.balance_of(accounts.bob), 20_000_000);
+            // Alice remains 80_000_000 tokens.
+            assert_eq!(entropy.balance_of(accounts.alice), 80_000_000);
+
+            // Set transaction fee
+            assert_eq!(entropy.set_params(10, 50_000_000), Ok(()));
+            // Bob transfers 10_000_000 tokens to Charlie. Fee is 10_000_000 * 10 / 10000 = 10_000,
+            // so 9_990_000 tokens transferred to Charlie, 10_000 tokens transferred to Alice, who is the fee collector
+            assert_eq!(entropy.send("transfer", accounts.bob, accounts.charlie, 10_000_000), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 10_000_000);
+            assert_eq!(entropy.balance_of(accounts.charlie), 10_000_000 - 10_000);
+            assert_eq!(entropy.balance_of(accounts.alice), 80_000_000 + 10_000);
+            
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 8);
+            // Check first transfer event related to Entropy instantiation, followed by Alice becoming a holder.
+            assert_transfer_event(&emitted_events[0], None, Some(accounts.alice), 100_000_000);
+            assert_new_holder_event(&emitted_events[1], accounts.alice);
+            // Check the transfer event relating to the actual trasfer, after Bob becomes a holder.
+            assert_new_holder_event(&emitted_events[2], accounts.bob);
+            assert_transfer_event(&emitted_events[3], Some(accounts.alice), Some(accounts.bob), 20_000_000);
+            // Charlie becomes a holder, then check the fee event (5th event is the Params event)
+            assert_new_holder_event(&emitted_events[5], accounts.charlie);
+            assert_fee_charged_event(&emitted_events[6], accounts.bob, accounts.alice, 10_000);
+            // Check the transfer event to Charlie
+            assert_transfer_event(&emitted_events[7], Some(accounts.bob), Some(accounts.charlie), 10_000_000 - 10_000);
+        }
+
+        #[ink::test]
+        fn fee_transfer_events_work() {
+            let mut entropy = Entropy::construct_with_config(1_000_000, "Entropy Coin".into(), "ENT".into(), 6, ContractConfig { fee_transfer_events: true, ..Default::default() });
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            assert_eq!(entropy.fee_transfer_events(), true);
+            assert_eq!(Entropy::new(1).fee_transfer_events(), false);
+
+            assert_eq!(entropy.transfer(accounts.bob, 100_000, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+
+            // The fee leg emits a Transfer then a FeeCharged event, both before the transfer's own event
+            set_caller(accounts.bob);
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.transfer(accounts.alice, 10_000, None), Ok(()));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 3);
+            assert_transfer_event(&emitted_events[emitted_events_before], Some(accounts.bob), Some(accounts.alice), 10);
+            assert_fee_charged_event(&emitted_events[emitted_events_before + 1], accounts.bob, accounts.alice, 10);
+            assert_transfer_event(&emitted_events[emitted_events_before + 2], Some(accounts.bob), Some(accounts.alice), 9_990);
+
+            // Fee-free transfers emit neither
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, true), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, true), Ok(()));
+            set_caller(accounts.bob);
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.transfer(accounts.alice, 10_000, None), Ok(()));
+            assert_eq!(ink_env::test::recorded_events().count(), emitted_events_before + 1);
+        }
+
+        #[ink::test]
+        fn invalid_transfer_should_fail() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            // Get contract address.
+            let callee = ink_env::account_id::<Environment>()
+                .unwrap_or([0x0; 32].into());
+            // Create call
+            let mut data =
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
+            data.push_arg(&accounts.bob);
+            // Push the new execution context to set Bob as caller
+            ink_env::test::push_execution_context::<Environment>(
+                accounts.bob,
+                callee,
+                1000000,
+                1000000,
+                data,
+            );
+
+            // Bob fails to transfers 10 tokens to Eve.
+            assert_eq!(
+                entropy.transfer(accounts.eve, 10, None),
+                Err(Error::InsufficientBalance)
+            );
+            // Alice owns all the tokens.
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.balance_of(accounts.eve), 0);
+
+            // Only the events of the initial construction, the failure emits none.
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 2);
+            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
+        }
+
+        #[ink::test]
+        fn transfer_from_works() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100);
+            // Transfer event triggered during initial construction.
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            // Bob fails to transfer tokens owned by Alice.
+            assert_eq!(
+                entropy.transfer_from(accounts.alice, accounts.eve, 10),
+                Err(Error::InsufficientAllowance)
+            );
+            // Alice approves Bob for token transfers on her behalf.
+            assert_eq!(entropy.approve(accounts.bob, 10), Ok(()));
+
+            // The approve event takes place.
+            assert_eq!(ink_env::test::recorded_events().count(), 3);
+
+            // Get contract address.
+            let callee = ink_env::account_id::<Environment>()
+                .unwrap_or([0x0; 32].into());
+            // Create call.
+            let mut data =
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
+            data.push_arg(&accounts.bob);
+            // Push the new execution context to set Bob as caller.
+            ink_env::test::push_execution_context::<Environment>(
+                accounts.bob,
+                callee,
+                1000000,
+                1000000,
+                data,
+            );
+
+            // Bob transfers tokens from Alice to Eve.
+            assert_eq!(
+                entropy.transfer_from(accounts.alice, accounts.eve, 10),
+                Ok(())
+            );
+            // Eve owns tokens.
+            assert_eq!(entropy.balance_of(accounts.eve), 10);
+
+            // Check all transfer events that happened during the previous calls:
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 5);
+            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
+            // The event `emitted_events[2]` is an Approve event that we skip checking.
+            assert_new_holder_event(&emitted_events[3], AccountId::from([0x05; 32]));
+            assert_transfer_event(&emitted_events[4], Some(AccountId::from([0x01; 32])), Some(AccountId::from([0x05; 32])), 10);
+        }
+
+        #[ink::test]
+        fn allowance_must_not_change_on_failed_transfer() {
+            let mut entropy = Entropy::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            // Alice approves Bob for token transfers on her behalf.
+            let alice_balance = entropy.balance_of(accounts.alice);
+            let initial_allowance = alice_balance + 2;
+            assert_eq!(entropy.approve(accounts.bob, initial_allowance), Ok(()));
+
+            // Get contract address.
+            let callee = ink_env::account_id::<Environment>()
+                .unwrap_or([0x0; 32].into());
+            // Create call.
+            let mut data =
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
+            data.push_arg(&accounts.bob);
+            // Push the new execution context to set Bob as caller.
+            ink_env::test::push_execution_context::<Environment>(
+                accounts.bob,
+                callee,
+                1000000,
+                1000000,
+                data,
+            );
+
+            // Bob tries to transfer tokens from Alice to Eve.
+            let emitted_events_before =
+                ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(
+                entropy.transfer_from(accounts.alice, accounts.eve, alice_balance + 1),
+                Err(Error::InsufficientBalance)
+            );
+            // Allowance must have stayed the same
+            assert_eq!(
+                entropy.allowance(accounts.alice, accounts.bob),
+                initial_allowance
+            );
+            // No event has been emitted
+            let emitted_events_after =
+                ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events_before.len(), emitted_events_after.len());
+        }
+
+        #[ink::test]
+        fn infinite_allowance_is_not_decremented() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.approve(accounts.bob, Balance::MAX), Ok(()));
+            let emitted_events_before = ink_env::test::recorded_events().count();
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.eve, 10), Ok(()));
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.eve, 20), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), Balance::MAX);
+            assert_eq!(entropy.balance_of(accounts.eve), 30);
+
+            // Only the new holder and the two transfers were recorded, no approvals
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 3);
+            assert_new_holder_event(&emitted_events[emitted_events_before], accounts.eve);
+            assert_transfer_event(&emitted_events[emitted_events_before + 1], Some(accounts.alice), Some(accounts.eve), 10);
+            assert_transfer_event(&emitted_events[emitted_events_before + 2], Some(accounts.alice), Some(accounts.eve), 20);
+        }
+
+        #[ink::test]
+        fn allowance_expiry_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let now = ink_env::block_timestamp::<Environment>().expect("Cannot get block timestamp");
+
+            // Alice lets Bob spend 50 until the next block and Charlie 20 without expiry
+            assert_eq!(entropy.approve_with_expiry(accounts.bob, 50, now + 5), Ok(()));
+            assert_eq!(entropy.approve(accounts.charlie, 20), Ok(()));
+            assert_eq!(entropy.allowance_expiry(accounts.alice, accounts.bob), Some(now + 5));
+            assert_eq!(entropy.allowance_expiry(accounts.alice, accounts.charlie), None);
+
+            // Spending and raising the allowance keep the expiry
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.eve, 10), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.increase_allowance(accounts.bob, 5), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 45);
+            assert_eq!(entropy.allowance_expiry(accounts.alice, accounts.bob), Some(now + 5));
+
+            // The allowance is still live at its expiry timestamp
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 45);
+
+            // Past it, the allowance counts as zero and cannot be spent
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(entropy.allowance_expiry(accounts.alice, accounts.bob), None);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.eve, 1), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.balance_of(accounts.eve), 10);
+
+            // Approvals without expiry are unaffected
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.eve, 20), Ok(()));
+
+            // A plain approve replaces an expired allowance with one that never expires
+            set_caller(accounts.alice);
+            assert_eq!(entropy.approve(accounts.bob, 30), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 30);
+            assert_eq!(entropy.allowance_expiry(accounts.alice, accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn zero_entries_are_pruned() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            for _ in 0..3 {
+                // Allowances, including one spent down to zero
+                assert_eq!(entropy.approve(accounts.bob, 50), Ok(()));
+                assert_eq!(entropy.approve_for_recipient(accounts.bob, accounts.eve, 10), Ok(()));
+                assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 50);
+                assert_eq!(entropy.allowance_for_recipient(accounts.alice, accounts.bob, accounts.eve), 10);
+                set_caller(accounts.bob);
+                assert_eq!(entropy.transfer_from(accounts.alice, accounts.eve, 10), Ok(()));
+                set_caller(accounts.alice);
+                assert_eq!(entropy.approve(accounts.bob, 0), Ok(()));
+                assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 0);
+                assert_eq!(entropy.allowance_for_recipient(accounts.alice, accounts.bob, accounts.eve), 0);
+
+                // Boolean flags
+                assert_eq!(entropy.set_velocity_exempt(accounts.bob, true), Ok(()));
+                assert_eq!(entropy.set_prelaunch_allowed(accounts.bob, true), Ok(()));
+                assert_eq!(entropy.set_system_account(accounts.bob, true), Ok(()));
+                assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+                assert_eq!(entropy.set_velocity_exempt(accounts.bob, false), Ok(()));
+                assert_eq!(entropy.set_prelaunch_allowed(accounts.bob, false), Ok(()));
+                assert_eq!(entropy.set_system_account(accounts.bob, false), Ok(()));
+                assert_eq!(entropy.remove_account_from_blacklist(accounts.charlie), Ok(()));
+                assert_eq!(entropy.is_velocity_exempt(accounts.bob), false);
+                assert_eq!(entropy.is_prelaunch_allowed(accounts.bob), false);
+                assert_eq!(entropy.is_system_account(accounts.bob), false);
+                assert_eq!(entropy.is_account_blacklisted(accounts.charlie), false);
+
+                // No entry is left behind
+                assert_eq!(entropy.allowances.len(), 0);
+                assert_eq!(entropy.recipient_allowances.len(), 0);
+                assert_eq!(entropy.velocity_exempt.len(), 0);
+                assert_eq!(entropy.prelaunch_allowlist.len(), 0);
+                assert_eq!(entropy.system_accounts.len(), 0);
+                assert_eq!(entropy.accounts_blacklisted.len(), 0);
+                assert_eq!(entropy.account_flags.len(), 0);
+            }
+            assert_eq!(entropy.balance_of(accounts.eve), 30);
+        }
+
+        #[cfg(feature = "debug-logs")]
+        #[ink::test]
+        fn debug_logs_work() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert!(ink_env::test::recorded_debug_messages().any(|message| message.starts_with("Entropy: Transfer 0xa tokens")));
+        }
+
+        #[ink::test]
+        fn error_names_work() {
+            assert_eq!(Error::PermissionDenied.as_str(), "PermissionDenied");
+            assert_eq!(format!("{}", Error::InvalidCodeHash), "InvalidCodeHash");
+            assert_eq!(PSP22Error::from(Error::ZeroAmount), PSP22Error::Custom("ZeroAmount".into()));
+            assert_eq!(PSP22Error::from(Error::InsufficientBalance), PSP22Error::InsufficientBalance);
+        }
+
+        #[ink::test]
+        fn verbose_errors_work() {
+            let mut entropy = Entropy::construct_with_config(100, "Entropy Coin".into(), "ENT".into(), 6, ContractConfig { verbose_errors: true, ..Default::default() });
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.verbose_errors(), true);
+            assert_eq!(Entropy::new(100).verbose_errors(), false);
+
+            // Failures emit a TransactionFailed event
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.issue(0), Err(Error::ZeroAmount));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.eve, 10, None), Err(Error::InsufficientBalance));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 2);
+            assert_transaction_failed_event(&emitted_events[emitted_events_before], accounts.alice, "issue_to", Error::ZeroAmount);
+            assert_transaction_failed_event(&emitted_events[emitted_events_before + 1], accounts.bob, "transfer", Error::InsufficientBalance);
+
+            // Failures in shared checks name the message they were made for
+            set_caller(accounts.alice);
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.approve(AccountId::default(), 1), Err(Error::InvalidAccount));
+            assert_eq!(entropy.permit(accounts.bob, accounts.django, 1, Timestamp::MAX, 1, [0; 64]), Err(Error::InvalidNonce));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.create_hold(accounts.bob, 1, Timestamp::MAX), Err(Error::AccountBlackListed));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 3);
+            assert_transaction_failed_event(&emitted_events[emitted_events_before], accounts.alice, "approve", Error::InvalidAccount);
+            assert_transaction_failed_event(&emitted_events[emitted_events_before + 1], accounts.alice, "permit", Error::InvalidNonce);
+            assert_transaction_failed_event(&emitted_events[emitted_events_before + 2], accounts.charlie, "create_hold", Error::AccountBlackListed);
+
+            // They are counted either way
+            assert_eq!(entropy.failure_stats(), vec![
+                (Error::InsufficientBalance as u32, 1),
+                (Error::AccountBlackListed as u32, 1),
+                (Error::InvalidAccount as u32, 1),
+                (Error::InvalidNonce as u32, 1),
+                (Error::ZeroAmount as u32, 1)
+            ]);
+        }
+
+        #[ink::test]
+        fn transfer_and_call_works() {
+            let mut entropy = Entropy::new(1_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            mock_receiver(accounts.eve, true);
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 100_000, None), Ok(()));
+
+            // Accounts without code get the tokens without any notification
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_and_call(accounts.charlie, 10_000, vec![0x1]), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 9_990);
+
+            // Contracts are notified of the amount received net of the fee
+            assert_eq!(entropy.transfer_and_call(accounts.eve, 10_000, vec![0x2, 0x3]), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.eve), 9_990);
+            assert_eq!(mock_notifications(accounts.eve), vec![(accounts.bob, 9_990, vec![0x2, 0x3])]);
+
+            // Plain transfers never notify
+            assert_eq!(entropy.transfer(accounts.eve, 10_000, None), Ok(()));
+            assert_eq!(mock_notifications(accounts.eve).len(), 1);
+
+            // Failing checks return before any notification
+            assert_eq!(entropy.transfer_and_call(accounts.eve, 1_000_000, Vec::new()), Err(Error::InsufficientBalance));
+            assert_eq!(mock_notifications(accounts.eve).len(), 1);
+        }
+
+        #[ink::test]
+        fn transfer_with_memo_works() {
+            let mut entropy = Entropy::new(1_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+
+            // The memo follows the transfer's own event, carrying the full value
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.transfer_with_memo(accounts.bob, 10_000, b"customer-4711".to_vec()), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 9_990);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let count = emitted_events.len();
+            assert_eq!(count, emitted_events_before + 4);
+            assert_transfer_event(&emitted_events[count - 2], Some(accounts.alice), Some(accounts.bob), 9_990);
+            assert_transfer_with_memo_event(&emitted_events[count - 1], accounts.alice, accounts.bob, 10_000, b"customer-4711");
+
+            // Memos up to the cap are accepted, longer ones rejected before moving tokens
+            assert_eq!(entropy.transfer_with_memo(accounts.bob, 10_000, vec![0x7; MAX_MEMO_LEN]), Ok(()));
+            assert_eq!(entropy.transfer_with_memo(accounts.bob, 10_000, vec![0x7; MAX_MEMO_LEN + 1]), Err(Error::MemoTooLarge));
+            assert_eq!(entropy.balance_of(accounts.bob), 19_980);
+
+            // The checks of transfer still apply
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer_with_memo(accounts.bob, 10, Vec::new()), Err(Error::InsufficientBalance));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "reverted with ReceiverRejected")]
+        fn transfer_and_call_reverts_on_rejection() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            mock_receiver(accounts.eve, false);
+
+            let _ = entropy.transfer_and_call(accounts.eve, 100, Vec::new());
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "reverted with ReceiverRejected")]
+        fn transfer_and_call_reverts_without_hook() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            mock_contract(accounts.django, Hash::from([0x8; 32]));
+
+            let _ = entropy.transfer_and_call(accounts.django, 100, Vec::new());
+        }
+
+        #[ink::test]
+        fn transfer_and_call_blocks_reentrancy() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            assert_eq!(entropy.transfer(accounts.eve, 100, None), Ok(()));
+
+            // As seen by a recipient calling back while it is notified
+            entropy.notifying_receiver = true;
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Err(Error::ReentrantCall));
+            assert_eq!(entropy.transfer_and_call(accounts.bob, 10, Vec::new()), Err(Error::ReentrantCall));
+            assert_eq!(entropy.burn_my_tokens(10), Err(Error::ReentrantCall));
+            assert_eq!(entropy.create_hold(accounts.bob, 10, 100), Err(Error::ReentrantCall));
+            assert_eq!(entropy.can_transfer(accounts.eve, accounts.bob, 10), Err(Error::ReentrantCall));
+            assert_eq!(entropy.balance_of(accounts.eve), 100);
+
+            // The flag is cleared once the notification returns
+            entropy.notifying_receiver = false;
+            mock_receiver(accounts.django, true);
+            assert_eq!(entropy.transfer_and_call(accounts.django, 10, Vec::new()), Ok(()));
+            assert!(!entropy.notifying_receiver);
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+        }
+
+        #[ink::test]
+        fn banned_code_hash_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Two instances of the same aggregator code, at Eve's and Frank's addresses
+            let aggregator = Hash::from([0x7; 32]);
+            mock_contract(accounts.eve, aggregator);
+            mock_contract(accounts.frank, aggregator);
+            mock_contract(accounts.django, Hash::from([0x8; 32]));
+
+            assert_eq!(entropy.transfer(accounts.bob, 200, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.eve, 100), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.eve, 10), Ok(()));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.ban_code_hash(aggregator), Ok(()));
+            assert_eq!(entropy.is_code_hash_banned(aggregator), true);
+
+            // Neither instance can move or approve tokens any more
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.eve, 10), Err(Error::CallerCodeBanned));
+            assert_eq!(entropy.transfer(accounts.charlie, 5, None), Err(Error::CallerCodeBanned));
+            assert_eq!(entropy.approve(accounts.frank, 5), Err(Error::CallerCodeBanned));
+            set_caller(accounts.frank);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.frank, 10), Err(Error::CallerCodeBanned));
+
+            // Other contracts and plain accounts are unaffected
+            set_caller(accounts.django);
+            assert_eq!(entropy.approve(accounts.charlie, 5), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 5, None), Ok(()));
+            assert_eq!(entropy.ban_code_hash(aggregator), Err(Error::PermissionDenied));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.unban_code_hash(aggregator), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.eve, 10), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.eve), 20);
+        }
+
+        /// Selectors documented for PSP22 tooling, which the `selector` attributes must keep.
+        const PSP22_SELECTORS: [(&str, [u8; 4]); 13] = [
+            ("PSP22::total_supply", [0x16, 0x2D, 0xF8, 0xC2]),
+            ("PSP22::balance_of", [0x65, 0x68, 0x38, 0x2F]),
+            ("PSP22::allowance", [0x4D, 0x47, 0xD9, 0x21]),
+            ("PSP22::transfer", [0xDB, 0x20, 0xF9, 0xF5]),
+            ("PSP22::transfer_from", [0x54, 0xB3, 0xC7, 0x6E]),
+            ("PSP22::approve", [0xB2, 0x0F, 0x1B, 0xBD]),
+            ("PSP22::increase_allowance", [0x96, 0xD6, 0xB5, 0x7A]),
+            ("PSP22::decrease_allowance", [0xFE, 0xCB, 0x57, 0xD5]),
+            ("PSP22Metadata::token_name", [0x3D, 0x26, 0x1B, 0xD4]),
+            ("PSP22Metadata::token_symbol", [0x34, 0x20, 0x5B, 0xE5]),
+            ("PSP22Metadata::token_decimals", [0x72, 0x71, 0xB7, 0x82]),
+            ("PSP22Mintable::mint", [0xFC, 0x3C, 0x75, 0xD4]),
+            ("PSP22Burnable::burn", [0x7A, 0x9D, 0xA5, 0x10]),
+        ];
+
+        #[ink::test]
+        fn psp22_selectors_are_stable() {
+            // Each selector is the head of the Blake2x256 hash of its PSP22 name
+            for (name, selector) in PSP22_SELECTORS.iter() {
+                let mut output = <Blake2x256 as HashOutput>::Type::default();
+                <Blake2x256 as CryptoHash>::hash(name.as_bytes(), &mut output);
+                assert_eq!(&output[0..4], &selector[..], "encountered invalid selector of {}", name);
+            }
+
+            // A PSP22 transfer call as tooling encodes it
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let callee = ink_env::account_id::<Environment>().unwrap_or_else(|_| [0x0; 32].into());
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0xDB, 0x20, 0xF9, 0xF5]));
+            data.push_arg(&accounts.bob);
+            data.push_arg(&(10 as Balance));
+            data.push_arg(&Vec::<u8>::new());
+            ink_env::test::push_execution_context::<Environment>(accounts.alice, callee, 1000000, 1000000, data);
+            assert_eq!(entropy.psp22_transfer(accounts.bob, 10, Vec::new()), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn psp22_aliases_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let zero = AccountId::from([0x0; 32]);
+
+            assert_eq!(entropy.token_name(), Some(String::from("Entropy Coin")));
+            assert_eq!(entropy.token_symbol(), Some(String::from("ENT")));
+            assert_eq!(entropy.token_decimals(), 6);
+
+            // The trailing data is ignored
+            assert_eq!(entropy.psp22_transfer(accounts.bob, 100, vec![0x01, 0x02]), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+            assert_eq!(entropy.psp22_transfer(zero, 1, Vec::new()), Err(PSP22Error::ZeroRecipientAddress));
+            assert_eq!(entropy.psp22_transfer(accounts.bob, 1_000, Vec::new()), Err(PSP22Error::InsufficientBalance));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.psp22_approve(accounts.charlie, 30), Ok(()));
+            assert_eq!(entropy.psp22_increase_allowance(accounts.charlie, 20), Ok(()));
+            assert_eq!(entropy.psp22_decrease_allowance(accounts.charlie, 10), Ok(()));
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 40);
+            assert_eq!(entropy.psp22_decrease_allowance(accounts.charlie, 41), Err(PSP22Error::InsufficientAllowance));
+            assert_eq!(entropy.psp22_approve(zero, 1), Err(PSP22Error::ZeroRecipientAddress));
+            assert_eq!(entropy.psp22_increase_allowance(zero, 1), Err(PSP22Error::ZeroRecipientAddress));
+            assert_eq!(entropy.psp22_decrease_allowance(zero, 1), Err(PSP22Error::ZeroRecipientAddress));
+
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.psp22_transfer_from(accounts.bob, accounts.django, 40, Vec::new()), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.django), 40);
+            assert_eq!(entropy.psp22_transfer_from(accounts.bob, accounts.django, 1, Vec::new()), Err(PSP22Error::InsufficientAllowance));
+            assert_eq!(entropy.psp22_transfer_from(zero, accounts.django, 1, Vec::new()), Err(PSP22Error::ZeroSenderAddress));
+            assert_eq!(entropy.psp22_transfer_from(accounts.bob, zero, 1, Vec::new()), Err(PSP22Error::ZeroRecipientAddress));
+
+            // Other errors are passed by name
+            set_caller(accounts.alice);
+            assert_eq!(entropy.add_account_to_blacklist(accounts.django, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.psp22_transfer(accounts.django, 1, Vec::new()), Err(PSP22Error::Custom(String::from("AccountBlackListed"))));
+        }
+
+        /// Pulls `value` of `from`'s tokens to `to` through the `Erc20` trait, as a vault holding the token would.
+        fn pull_through_erc20(token: &mut Entropy, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            Erc20::transfer_from(token, from, to, value)
+        }
+
+        #[ink::test]
+        fn erc20_trait_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(Erc20::transfer(&mut entropy, accounts.bob, 100, None), Ok(()));
+            assert_eq!(Erc20::balance_of(&entropy, accounts.bob), 100);
+            set_caller(accounts.bob);
+            assert_eq!(Erc20::approve(&mut entropy, accounts.charlie, 60), Ok(()));
+            assert_eq!(Erc20::allowance(&entropy, accounts.bob, accounts.charlie), 60);
+
+            set_caller(accounts.charlie);
+            assert_eq!(pull_through_erc20(&mut entropy, accounts.bob, accounts.charlie, 60), Ok(()));
+            assert_eq!(pull_through_erc20(&mut entropy, accounts.bob, accounts.charlie, 1), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.balance_of(accounts.bob), 40);
+            assert_eq!(entropy.balance_of(accounts.charlie), 60);
+            assert_eq!(Erc20::transfer(&mut entropy, accounts.bob, 61, None), Err(Error::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn psp22_mintable_burnable_works() {
+            let mut entropy = Entropy::new(1_000);
+            let mut reference = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Minting and burning on the owner's account match issue and redeem
+            assert_eq!(entropy.mint(accounts.alice, 500), Ok(()));
+            assert_eq!(reference.issue(500), Ok(()));
+            assert_eq!(entropy.burn(accounts.alice, 200), Ok(()));
+            assert_eq!(reference.redeem(200, Hash::from([0x01; 32])), Ok(1));
+            assert_eq!(entropy.balance_of(accounts.alice), reference.balance_of(accounts.alice));
+            assert_eq!(entropy.total_supply(), reference.total_supply());
+            assert_eq!(entropy.global_stats(), reference.global_stats());
+            assert_eq!(entropy.burn(accounts.alice, 2_000), Err(PSP22Error::InsufficientBalance));
+
+            // Any recipient, the owner only
+            assert_eq!(entropy.mint(accounts.bob, 100), Ok(()));
+            assert_eq!(entropy.mint(AccountId::default(), 100), Err(PSP22Error::ZeroRecipientAddress));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.mint(accounts.bob, 100), Err(PSP22Error::Custom(String::from("PermissionDenied"))));
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+
+            // Burning another account's tokens spends its allowance
+            assert_eq!(entropy.approve(accounts.charlie, 30), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.burn(accounts.bob, 40), Err(PSP22Error::InsufficientAllowance));
+            assert_eq!(entropy.burn(accounts.bob, 30), Ok(()));
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 0);
+            assert_eq!(entropy.burn(AccountId::default(), 1), Err(PSP22Error::ZeroSenderAddress));
+
+            assert_eq!(entropy.balance_of(accounts.bob), 70);
+            assert_eq!(entropy.total_supply(), 1_370);
+        }
+
+        #[ink::test]
+        fn timed_pause_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.pause_for(10), Err(Error::PermissionDenied));
+            assert_eq!(entropy.pause(), Err(Error::PermissionDenied));
+
+            // A pause for two blocks, extended by another block while active
+            set_caller(accounts.alice);
+            assert_eq!(entropy.pause_for(10), Ok(()));
+            assert_eq!(entropy.pause_status(), (true, Some(10)));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Err(Error::ContractPaused));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.pause_for(10), Ok(()));
+            assert_eq!(entropy.pause_status(), (true, Some(15)));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Err(Error::ContractPaused));
+
+            // Transfers resume without an unpause once the timestamp passes
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.paused(), false);
+            assert_eq!(entropy.pause_status(), (false, None));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+
+            // An indefinite pause holds until unpaused
+            assert_eq!(entropy.pause(), Ok(()));
+            for _ in 0..10 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.pause_status(), (true, None));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Err(Error::ContractPaused));
+            assert_eq!(entropy.unpause(), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 20);
+        }
+
+        #[ink::test]
+        fn destruction_quarantine_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_destruction_delay(10), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_destruction_delay(10), Ok(()));
+            assert_eq!(entropy.destruction_delay(), 10);
+
+            // Funds can't be destroyed for two blocks after blacklisting
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::AddedBlackList(AddedBlackList { account, destroyable_at, .. }) = decoded_event {
+                assert_eq!(account, accounts.bob);
+                assert_eq!(destroyable_at, 10);
+            } else {
+                panic!("encountered unexpected event kind: expected a AddedBlackList event")
+            }
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::QuarantineActive));
+
+            // Blacklisting again keeps the clock, removing and re-adding resets it
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.destroyable_at(accounts.bob), 10);
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.destroyable_at(accounts.bob), 15);
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::QuarantineActive));
+            assert_eq!(entropy.destroy_black_funds_batch(vec![accounts.bob]), Ok(vec![accounts.bob]));
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+        }
+
+        // The off-chain environment keeps native balances as `DefaultEnvironment` balances
+        #[cfg(not(feature = "custom-environment"))]
+        #[ink::test]
+        fn appeals_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            let native_balance = |account| ink_env::test::get_account_balance::<Environment>(account).expect("Cannot get balance");
+            let statement = Hash::from([0x5; 32]);
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            assert_eq!(entropy.set_appeal_terms(50, 10), Ok(()));
+            assert_eq!(entropy.appeal_terms(), (50, 10));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+
+            set_caller_with_value(accounts.django, 50);
+            assert_eq!(entropy.lodge_appeal(statement), Err(Error::AccountNotBlackListed));
+            set_caller_with_value(accounts.bob, 49);
+            assert_eq!(entropy.lodge_appeal(statement), Err(Error::InsufficientBond));
+
+            // Both appeal, blocking destruction until resolved or timed out
+            set_caller_with_value(accounts.bob, 50);
+            assert_eq!(entropy.lodge_appeal(statement), Ok(()));
+            assert_eq!(entropy.lodge_appeal(statement), Err(Error::AppealExists));
+            set_caller_with_value(accounts.charlie, 50);
+            assert_eq!(entropy.lodge_appeal(statement), Ok(()));
+            ink_env::test::set_account_balance::<Environment>(contract, 100).expect("Cannot set balance");
+            let bob_native = native_balance(accounts.bob);
+            let alice_native = native_balance(accounts.alice);
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::AppealPending));
+            assert_eq!(entropy.destroy_black_funds_batch(vec![accounts.bob, accounts.charlie]), Ok(vec![accounts.bob, accounts.charlie]));
+
+            // Upholding clears the blacklist and refunds the bond
+            set_caller(accounts.bob);
+            assert_eq!(entropy.resolve_appeal(accounts.bob, true), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.resolve_appeal(accounts.bob, true), Ok(()));
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), false);
+            assert_eq!(entropy.appeal_of(accounts.bob).map(|appeal| appeal.status), Some(AppealStatus::Upheld));
+            assert_eq!(native_balance(accounts.bob), bob_native + 50);
+            assert_eq!(entropy.resolve_appeal(accounts.bob, false), Err(Error::NoPendingAppeal));
+
+            // Rejecting forfeits the bond to the owner and lets destruction proceed
+            assert_eq!(entropy.resolve_appeal(accounts.charlie, false), Ok(()));
+            assert_eq!(entropy.is_account_blacklisted(accounts.charlie), true);
+            assert_eq!(native_balance(accounts.alice), alice_native + 50);
+            assert_eq!(native_balance(contract), 0);
+            set_caller_with_value(accounts.charlie, 50);
+            assert_eq!(entropy.lodge_appeal(statement), Err(Error::AppealExists));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Ok(()));
+
+            // An unresolved appeal stops blocking destruction after the timeout
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            set_caller_with_value(accounts.bob, 50);
+            assert_eq!(entropy.lodge_appeal(statement), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::AppealPending));
+            for _ in 0..2 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
+        }
+
+        #[cfg(not(feature = "custom-environment"))]
+        #[ink::test]
+        fn rescue_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            let native_balance = |account| ink_env::test::get_account_balance::<Environment>(account).expect("Cannot get balance");
+            let token = AccountId::from([0x9; 32]);
+
+            // A pending appeal bond stays out of reach
+            assert_eq!(entropy.set_appeal_terms(50, 10), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            set_caller_with_value(accounts.bob, 50);
+            assert_eq!(entropy.lodge_appeal(Hash::from([0x5; 32])), Ok(()));
+            ink_env::test::set_account_balance::<Environment>(contract, 300).expect("Cannot set balance");
+            let django_native = native_balance(accounts.django);
+
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.rescue_native(accounts.charlie, 10), Err(Error::PermissionDenied));
+            assert_eq!(entropy.rescue_token(token, accounts.charlie, 10), Err(Error::PermissionDenied));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.rescue_native(AccountId::from([0x0; 32]), 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.rescue_native(accounts.django, 251), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.rescue_native(accounts.django, 250), Ok(()));
+            assert_eq!(native_balance(accounts.django), django_native + 250);
+            assert_eq!(native_balance(contract), 50);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Rescued(Rescued { token, to, amount }) = decoded_event {
+                assert_eq!((token, to, amount), (None, accounts.django, 250));
+            } else {
+                panic!("encountered unexpected event kind: expected a Rescued event")
+            }
+
+            // Foreign tokens go through the token's PSP22 transfer
+            mock_token(token, contract, 500);
+            assert_eq!(entropy.rescue_token(token, AccountId::from([0x0; 32]), 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.rescue_token(contract, accounts.django, 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.rescue_token(AccountId::from([0x6; 32]), accounts.django, 10), Err(Error::RescueFailed));
+            assert_eq!(entropy.rescue_token(token, accounts.django, 501), Err(Error::RescueFailed));
+            assert_eq!(entropy.rescue_token(token, accounts.django, 200), Ok(()));
+            assert_eq!(mock_token_balance(token, accounts.django), 200);
+            assert_eq!(mock_token_balance(token, contract), 300);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Rescued(Rescued { token: rescued, to, amount }) = decoded_event {
+                assert_eq!((rescued, to, amount), (Some(token), accounts.django, 200));
+            } else {
+                panic!("encountered unexpected event kind: expected a Rescued event")
+            }
+        }
+
+        #[ink::test]
+        fn time_weighted_balance_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let advance_blocks = |blocks| {
+                for _ in 0..blocks {
+                    ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+                }
+            };
+
+            // Bob holds nothing until 5, 100 until 15, 400 until 20 and 200 after
+            advance_blocks(1);
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            advance_blocks(2);
+            assert_eq!(entropy.transfer(accounts.bob, 150, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 150, None), Ok(()));
+            advance_blocks(1);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
+            advance_blocks(2);
+
+            assert_eq!(entropy.time_weighted_balance(accounts.bob, 5, 15), 100);
+            assert_eq!(entropy.time_weighted_balance(accounts.bob, 10, 20), 250);
+            assert_eq!(entropy.time_weighted_balance(accounts.bob, 0, 20), 150);
+            assert_eq!(entropy.time_weighted_balance(accounts.bob, 0, 30), 166);
+            assert_eq!(entropy.time_weighted_balance(accounts.bob, 17, 17), 400);
+            assert_eq!(entropy.time_weighted_balance(accounts.bob, 20, 30), 200);
+
+            // The owner held the initial supply from deployment
+            assert_eq!(entropy.time_weighted_balance(accounts.alice, 0, 10), 950);
+            assert_eq!(entropy.time_weighted_balance(accounts.django, 0, 30), 0);
+        }
+
+        #[ink::test]
+        fn failure_stats_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.failure_stats(), Vec::new());
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_params(1, 1), Err(Error::PermissionDenied));
+            assert_eq!(entropy.reset_failure_stats(), Err(Error::PermissionDenied));
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.bob, 1), Err(Error::InsufficientAllowance));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::AccountNotBlackListed));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 1, None), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.add_account_to_blacklist(AccountId::default(), "sanctioned".into()), Err(Error::InvalidAccount));
+            assert_eq!(entropy.pause(), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Err(Error::ContractPaused));
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Err(Error::ContractPaused));
+
+            assert_eq!(entropy.failure_stats(), vec![
+                (Error::PermissionDenied as u32, 2),
+                (Error::InsufficientBalance as u32, 1),
+                (Error::InsufficientAllowance as u32, 1),
+                (Error::AccountBlackListed as u32, 1),
+                (Error::AccountNotBlackListed as u32, 1),
+                (Error::InvalidAccount as u32, 1),
+                (Error::ContractPaused as u32, 2)
+            ]);
+
+            assert_eq!(entropy.reset_failure_stats(), Ok(()));
+            assert_eq!(entropy.failure_stats(), Vec::new());
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Err(Error::ContractPaused));
+            assert_eq!(entropy.failure_stats(), vec![(Error::ContractPaused as u32, 1)]);
+        }
+
+        #[ink::test]
+        fn new_holder_event_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let new_holders = || {
+                ink_env::test::recorded_events()
+                    .filter_map(|event| match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                        Ok(Event::NewHolder(NewHolder { account, .. })) => Some(account),
+                        _ => None
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            // Receiving again while holding emits nothing
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(new_holders(), vec![accounts.alice, accounts.bob]);
+
+            // An emptied account counts as a new holder when refunded
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.issue(10), Ok(()));
+            assert_eq!(new_holders(), vec![accounts.alice, accounts.bob, accounts.charlie, accounts.bob]);
+
+            // Each event matches an increment of the holder count
+            assert_eq!(entropy.global_stats().holder_count, 3);
+        }
+
+        #[ink::test]
+        fn strict_approvals_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_strict_approvals(true), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_strict_approvals(true), Ok(()));
+            assert_eq!(entropy.strict_approvals(), true);
+
+            // Changing a nonzero allowance requires zeroing it first
+            assert_eq!(entropy.approve(accounts.bob, 100), Ok(()));
+            assert_eq!(entropy.approve(accounts.bob, 50), Err(Error::NonZeroAllowanceExists));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 100);
+            assert_eq!(entropy.approve(accounts.bob, 0), Ok(()));
+            assert_eq!(entropy.approve(accounts.bob, 50), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 50);
+
+            // Relative adjustments are unaffected
+            assert_eq!(entropy.increase_allowance(accounts.bob, 30), Ok(()));
+            assert_eq!(entropy.decrease_allowance(accounts.bob, 100), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.decrease_allowance(accounts.bob, 20), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 60);
+
+            // Turning the flag off allows overwriting again
+            assert_eq!(entropy.approve(accounts.bob, 10), Err(Error::NonZeroAllowanceExists));
+            assert_eq!(entropy.set_strict_approvals(false), Ok(()));
+            assert_eq!(entropy.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn strict_approvals_constructor_flag_works() {
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Approvals stay permissive by default
+            let mut entropy = Entropy::new(1_000);
+            assert_eq!(entropy.strict_approvals(), false);
+            assert_eq!(entropy.approve(accounts.bob, 100), Ok(()));
+            assert_eq!(entropy.approve(accounts.bob, 50), Ok(()));
+
+            let mut entropy = Entropy::construct_with_config(1_000, "Entropy Coin".into(), "ENT".into(), 6, ContractConfig { strict_approvals: true, ..Default::default() });
+            assert_eq!(entropy.strict_approvals(), true);
+            assert_eq!(entropy.approve(accounts.bob, 100), Ok(()));
+            assert_eq!(entropy.approve(accounts.bob, 50), Err(Error::NonZeroAllowanceExists));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 100);
+
+            // Resetting to zero first goes through
+            assert_eq!(entropy.approve(accounts.bob, 0), Ok(()));
+            assert_eq!(entropy.approve(accounts.bob, 50), Ok(()));
+
+            // Relative adjustments are unaffected
+            assert_eq!(entropy.increase_allowance(accounts.bob, 25), Ok(()));
+            assert_eq!(entropy.decrease_allowance(accounts.bob, 5), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 70);
+        }
+
+        #[ink::test]
+        fn ecdsa_permit_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let eth_address = [0x42; 20];
+
+            // Permits from unlinked accounts are rejected
+            let hash = entropy.permit_hash(accounts.bob, accounts.charlie, 100, 100, 0);
+            assert_eq!(entropy.permit_ecdsa(accounts.bob, accounts.charlie, 100, 100, 0, mock_ecdsa_sign(eth_address, hash)), Err(Error::EthAddressNotLinked));
+
+            // Linking requires a proof signed by the address's key
+            set_caller(accounts.bob);
+            let proof = mock_ecdsa_sign([0x43; 20], entropy.link_eth_hash(accounts.bob, eth_address, 0));
+            assert_eq!(entropy.link_eth_address(eth_address, proof), Err(Error::InvalidSignature));
+            let proof = mock_ecdsa_sign(eth_address, entropy.link_eth_hash(accounts.bob, eth_address, 0));
+            assert_eq!(entropy.link_eth_address(eth_address, proof), Ok(()));
+            assert_eq!(entropy.eth_address_of(accounts.bob), Some(eth_address));
+            assert_eq!(entropy.account_of_eth_address(eth_address), Some(accounts.bob));
+            assert_eq!(entropy.nonce_of(accounts.bob), 1);
+            set_caller(accounts.django);
+            let proof = mock_ecdsa_sign(eth_address, entropy.link_eth_hash(accounts.django, eth_address, 0));
+            assert_eq!(entropy.link_eth_address(eth_address, proof), Err(Error::EthAddressLinked));
+
+            // A relayer submits the permit signed by the linked key, sharing the signed transfers' nonce
+            set_caller(accounts.eve);
+            let hash = entropy.permit_hash(accounts.bob, accounts.charlie, 100, 100, 0);
+            assert_eq!(entropy.permit_ecdsa(accounts.bob, accounts.charlie, 100, 100, 0, mock_ecdsa_sign(eth_address, hash)), Err(Error::InvalidNonce));
+            let hash = entropy.permit_hash(accounts.bob, accounts.charlie, 100, 100, 1);
+            assert_eq!(entropy.permit_ecdsa(accounts.bob, accounts.charlie, 100, 100, 1, mock_ecdsa_sign([0x43; 20], hash)), Err(Error::InvalidSignature));
+            assert_eq!(entropy.permit_ecdsa(accounts.bob, accounts.charlie, 100, 100, 1, mock_ecdsa_sign(eth_address, hash)), Ok(()));
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 100);
+            assert_eq!(entropy.nonce_of(accounts.bob), 2);
+            assert_eq!(entropy.permit_ecdsa(accounts.bob, accounts.charlie, 100, 100, 1, mock_ecdsa_sign(eth_address, hash)), Err(Error::InvalidNonce));
+
+            // A transfer signature can't be replayed as a permit
+            let transfer_hash = entropy.transfer_hash(accounts.bob, accounts.charlie, 100, 100, 2);
+            assert_eq!(entropy.permit(accounts.bob, accounts.charlie, 100, 100, 2, mock_sign(accounts.bob, transfer_hash)), Err(Error::InvalidSignature));
+            let hash = entropy.permit_hash(accounts.bob, accounts.charlie, 0, 100, 2);
+            assert_eq!(entropy.permit(accounts.bob, accounts.charlie, 0, 100, 2, mock_sign(accounts.bob, hash)), Ok(()));
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 0);
+
+            // Once unlinked the key no longer signs for the account
+            set_caller(accounts.bob);
+            assert_eq!(entropy.unlink_eth_address(), Ok(()));
+            assert_eq!(entropy.unlink_eth_address(), Err(Error::EthAddressNotLinked));
+            assert_eq!(entropy.account_of_eth_address(eth_address), None);
+            let hash = entropy.permit_hash(accounts.bob, accounts.charlie, 100, 100, 3);
+            assert_eq!(entropy.permit_ecdsa(accounts.bob, accounts.charlie, 100, 100, 3, mock_ecdsa_sign(eth_address, hash)), Err(Error::EthAddressNotLinked));
+        }
+
+        #[ink::test]
+        fn dust_sweep_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            for (account, value) in [(accounts.bob, 5), (accounts.charlie, 50), (accounts.django, 3), (accounts.eve, 2)].iter() {
+                assert_eq!(entropy.transfer(*account, *value, None), Ok(()));
+            }
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+
+            // Balances below 10 are swept after two blocks of notice
+            set_caller(accounts.bob);
+            assert_eq!(entropy.announce_dust_sweep(10, 15, false), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.announce_dust_sweep(10, 5, false), Err(Error::InvalidDustSweep));
+            assert_eq!(entropy.execute_dust_sweep(0, 10), Err(Error::InvalidDustSweep));
+            assert_eq!(entropy.announce_dust_sweep(10, 15, false), Ok(()));
+            assert_eq!(entropy.execute_dust_sweep(0, 10), Err(Error::DustSweepNotDue));
+
+            // Eve consolidates during the notice, so she is skipped
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.transfer(accounts.eve, 1, None), Ok(()));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.execute_dust_sweep(0, 10), Err(Error::DustSweepNotDue));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+
+            // The index is alice, bob, charlie, django, eve: the first page sweeps bob, whose place
+            // eve takes, and the last page sweeps django
+            assert_eq!(entropy.execute_dust_sweep(0, 2), Ok(1));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.execute_dust_sweep(1, 2), Ok(3));
+            assert_eq!(entropy.balance_of(accounts.django), 3);
+            assert_eq!(entropy.dust_sweep().is_some(), true);
+            assert_eq!(entropy.execute_dust_sweep(3, 2), Ok(3));
+            assert_eq!(entropy.balance_of(accounts.django), 0);
+            assert_eq!(entropy.dust_sweep(), None);
+
+            assert_eq!(entropy.holders_len(), 3);
+            assert_eq!(entropy.balance_of(accounts.charlie), 50);
+            assert_eq!(entropy.balance_of(accounts.eve), 3);
+            assert_eq!(entropy.balance_of(accounts.alice), 1_000 - 50 - 3);
+            assert_eq!(entropy.global_stats().holder_count, 3);
+            assert_eq!(entropy.total_supply(), 1_000);
+
+            // A burning sweep destroys the dust instead
+            assert_eq!(entropy.announce_dust_sweep(10, 30, true), Ok(()));
+            for _ in 0..4 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.execute_dust_sweep(0, 10), Ok(2));
+            assert_eq!(entropy.balance_of(accounts.eve), 0);
+            assert_eq!(entropy.total_supply(), 997);
+        }
+
+        #[ink::test]
+        fn redemption_receipts_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            // Receipts are numbered sequentially from 1
+            assert_eq!(entropy.redeem(100, Hash::from([0x01; 32])), Ok(1));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.redeem(200, Hash::from([0x02; 32])), Ok(2));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.redeem(300, Hash::from([0x03; 32])), Ok(3));
+            assert_eq!(entropy.total_supply(), 400);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events.last().unwrap().data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Redeem(Redeem { amount, receipt_id }) = decoded_event {
+                assert_eq!(amount, 300);
+                assert_eq!(receipt_id, Some(3));
+            } else {
+                panic!("encountered unexpected event kind: expected a Redeem event")
+            }
+
+            let second = RedemptionReceipt {
+                amount: 200,
+                account: accounts.alice,
+                block: 1,
+                payout_reference_hash: Hash::from([0x02; 32])
+            };
+            assert_eq!(entropy.redemption_receipt(2), Some(second));
+            assert_eq!(entropy.redemption_receipt(0), None);
+            assert_eq!(entropy.redemption_receipt(4), None);
+
+            // Failed redemptions don't consume an id
+            assert_eq!(entropy.redeem(1_000, Hash::from([0x04; 32])), Err(Error::InsufficientBalance));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.redeem(1, Hash::from([0x04; 32])), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.redeem(50, Hash::from([0x04; 32])), Ok(4));
+
+            // Earlier receipts are left untouched by later redemptions
+            assert_eq!(entropy.redemption_receipt(2), Some(second));
+
+            // Holder burns don't issue receipts
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.burn(accounts.bob, 10), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.redemption_receipt(5), None);
+
+            // Pagination
+            let ids = |receipts: Vec<(u64, RedemptionReceipt)>| receipts.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+            assert_eq!(ids(entropy.redemption_receipts(1, 2)), vec![1, 2]);
+            assert_eq!(ids(entropy.redemption_receipts(3, 2)), vec![3, 4]);
+            assert_eq!(ids(entropy.redemption_receipts(5, 2)), Vec::<u64>::new());
+            assert_eq!(ids(entropy.redemption_receipts(0, 10)), vec![1, 2, 3, 4]);
+            assert_eq!(entropy.redemption_receipts(2, 1), vec![(2, second)]);
+        }
+
+        #[ink::test]
+        fn min_holding_period_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_min_holding_period(10), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+
+            // Receipts before the period is on aren't tracked
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.set_min_holding_period(10), Ok(()));
+            assert_eq!(entropy.min_holding_period(), 10);
+            assert_eq!(entropy.held_balance_of(accounts.bob), 0);
+
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.held_balance_of(accounts.bob), 50);
+
+            // Bob can't forward what he just received, only his older balance
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 101, None), Err(Error::HoldingPeriodActive));
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Err(Error::HoldingPeriodActive));
+
+            // The owner is exempt
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.alice, 10, None), Err(Error::HoldingPeriodActive));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Ok(()));
+            assert_eq!(entropy.held_balance_of(accounts.alice), 0);
+
+            // Once the period has passed, the receipt can be spent
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Err(Error::HoldingPeriodActive));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.held_balance_of(accounts.bob), 0);
+            assert_eq!(entropy.transfer(accounts.charlie, 50, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+
+            // System accounts are exempt
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_system_account(accounts.django, true), Ok(()));
+            assert_eq!(entropy.transfer(accounts.django, 20, None), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer(accounts.eve, 20, None), Ok(()));
+
+            // Turning the period off releases everything
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_min_holding_period(0), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer(accounts.frank, 20, None), Ok(()));
+        }
+
+        #[ink::test]
+        fn self_spending_limit_works() {
+            let mut entropy = Entropy::new(10_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 5_000, None), Ok(()));
+            set_caller(accounts.bob);
+
+            // Setting a first limit takes effect at once
+            assert_eq!(entropy.set_my_spending_limit(100), Ok(()));
+            assert_eq!(entropy.spending_limit_of(accounts.bob), SpendingLimit { amount_per_day: 100, pending: None });
+            assert_eq!(entropy.transfer(accounts.charlie, 60, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 50, None), Err(Error::SpendingLimitExceeded));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::SpendingLimitExceeded(SpendingLimitExceeded { account, value, remaining }) = decoded_event {
+                assert_eq!((account, value, remaining), (accounts.bob, 50, 40));
+            } else {
+                panic!("encountered unexpected event kind: expected a SpendingLimitExceeded event")
+            }
+            assert_eq!(entropy.transfer(accounts.charlie, 40, None), Ok(()));
+
+            // Raising the limit only takes effect after the delay
+            assert_eq!(entropy.set_my_spending_limit(300), Ok(()));
+            assert_eq!(entropy.spending_limit_of(accounts.bob), SpendingLimit {
+                amount_per_day: 100,
+                pending: Some((300, SPENDING_LIMIT_DELAY_MS))
+            });
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            // The window has ended, but the old limit still applies
+            assert_eq!(entropy.transfer(accounts.charlie, 150, None), Err(Error::SpendingLimitExceeded));
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+
+            // Lowering the limit takes effect at once and cancels the pending raise
+            assert_eq!(entropy.set_my_spending_limit(300), Ok(()));
+            assert_eq!(entropy.set_my_spending_limit(50), Ok(()));
+            assert_eq!(entropy.spending_limit_of(accounts.bob), SpendingLimit { amount_per_day: 50, pending: None });
+
+            // A raise that has waited out its delay applies
+            assert_eq!(entropy.set_my_spending_limit(300), Ok(()));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.spending_limit_of(accounts.bob).amount_per_day, 50);
+            for _ in 0..4 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.spending_limit_of(accounts.bob), SpendingLimit { amount_per_day: 300, pending: None });
+            assert_eq!(entropy.transfer(accounts.charlie, 150, None), Ok(()));
+
+            // So does a removal
+            assert_eq!(entropy.set_my_spending_limit(0), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 151, None), Err(Error::SpendingLimitExceeded));
+            for _ in 0..4 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.spending_limit_of(accounts.bob), SpendingLimit::default());
+            assert_eq!(entropy.transfer(accounts.charlie, 150, None), Ok(()));
+
+            // Other accounts are unaffected
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.django, 400, None), Ok(()));
+        }
+
+        #[ink::test]
+        fn transfer_limits_work() {
+            let mut entropy = Entropy::new(10_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 5_000, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 5_000 - 1_000, None), Ok(()));
+            assert_eq!(entropy.transfer_limit_of(accounts.bob), 0);
+            assert_eq!(entropy.remaining_limit_of(accounts.bob), None);
+
+            // Only the owner can set limits
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_default_transfer_limit(100), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_transfer_limit(accounts.bob, Some(0)), Err(Error::PermissionDenied));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_default_transfer_limit(100), Ok(()));
+            assert_eq!(entropy.set_transfer_limit(accounts.charlie, Some(300)), Ok(()));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TransferLimitChanged(TransferLimitChanged { account, limit }) = decoded_event {
+                assert_eq!((account, limit), (accounts.charlie, Some(300)));
+            } else {
+                panic!("encountered unexpected event kind: expected a TransferLimitChanged event")
+            }
+            assert_eq!(entropy.default_transfer_limit(), 100);
+            assert_eq!(entropy.transfer_limit_of(accounts.bob), 100);
+            assert_eq!(entropy.transfer_limit_of(accounts.charlie), 300);
+            // The owner is exempt
+            assert_eq!(entropy.transfer_limit_of(accounts.alice), 0);
+            assert_eq!(entropy.transfer(accounts.django, 500, None), Ok(()));
+
+            // The default limit applies to accounts without their own
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.eve, 60, None), Ok(()));
+            assert_eq!(entropy.remaining_limit_of(accounts.bob), Some(40));
+            assert_eq!(entropy.transfer(accounts.eve, 50, None), Err(Error::TransferLimitExceeded));
+            assert_eq!(entropy.balance_of(accounts.bob), 5_000 - 60);
+            assert_eq!(entropy.transfer(accounts.eve, 40, None), Ok(()));
+
+            // An account's own limit overrides the default
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.eve, 250, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.eve, 51, None), Err(Error::TransferLimitExceeded));
+
+            // The window still holds exactly TRANSFER_LIMIT_WINDOW_MS after it began
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            set_caller(accounts.bob);
+            assert_eq!(entropy.remaining_limit_of(accounts.bob), Some(0));
+            assert_eq!(entropy.transfer(accounts.eve, 1, None), Err(Error::TransferLimitExceeded));
+
+            // and resets once more time has passed
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.remaining_limit_of(accounts.bob), Some(100));
+            assert_eq!(entropy.transfer(accounts.eve, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.eve, 1, None), Err(Error::TransferLimitExceeded));
+
+            // An account's own zero limit leaves it unlimited, and clearing it restores the default
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_transfer_limit(accounts.charlie, Some(0)), Ok(()));
+            assert_eq!(entropy.remaining_limit_of(accounts.charlie), None);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.eve, 1_000, None), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_transfer_limit(accounts.charlie, None), Ok(()));
+            assert_eq!(entropy.transfer_limit_of(accounts.charlie), 100);
+
+            // A zero default removes the limit
+            assert_eq!(entropy.set_default_transfer_limit(0), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.eve, 1_000, None), Ok(()));
+        }
+
+        /// Asserts that `transfer` from `from` fails or succeeds as `can_transfer` predicted, returning the outcome.
+        fn assert_transfer_verdict(entropy: &mut Entropy, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            let verdict = entropy.can_transfer(from, to, value);
+            set_caller(from);
+            assert_eq!(entropy.transfer(to, value, None), verdict);
+            verdict
+        }
+
+        /// Asserts that `transfer_from` by `spender` fails or succeeds as `can_transfer_from` predicted, returning the outcome.
+        fn assert_transfer_from_verdict(entropy: &mut Entropy, spender: AccountId, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            let verdict = entropy.can_transfer_from(spender, from, to, value);
+            set_caller(spender);
+            assert_eq!(entropy.transfer_from(from, to, value), verdict);
+            verdict
+        }
+
+        #[ink::test]
+        fn can_transfer_works() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            for account in [accounts.bob, accounts.charlie, accounts.django, accounts.eve].iter() {
+                assert_eq!(entropy.transfer(*account, 10_000, None), Ok(()));
+            }
+            assert_eq!(entropy.add_account_to_blacklist(accounts.frank, "sanctioned".into()), Ok(()));
+
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.bob, accounts.charlie, 100), Ok(()));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.bob, accounts.charlie, 1_000_000), Err(Error::InsufficientBalance));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.bob, AccountId::from([0x0; 32]), 100), Err(Error::InvalidAccount));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.bob, accounts.frank, 100), Err(Error::AccountBlackListed));
+
+            // Limits
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_my_spending_limit(500), Ok(()));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.bob, accounts.charlie, 600), Err(Error::SpendingLimitExceeded));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_transfer_limit(accounts.charlie, Some(300)), Ok(()));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.charlie, accounts.django, 301), Err(Error::TransferLimitExceeded));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.charlie, accounts.django, 300), Ok(()));
+
+            // The dry run doesn't trip the velocity rule, the transfer does
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_velocity_rule(1, 1_000, 0), Ok(()));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.django, accounts.eve, 10), Ok(()));
+            assert_eq!(entropy.can_transfer(accounts.django, accounts.eve, 10), Err(Error::AccountFrozen));
+            assert_eq!(entropy.is_account_frozen(accounts.django), false);
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.django, accounts.eve, 10), Err(Error::AccountFrozen));
+            assert_eq!(entropy.is_account_frozen(accounts.django), true);
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.django, accounts.eve, 10), Err(Error::AccountFrozen));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_velocity_rule(0, 0, 0), Ok(()));
+
+            // Nor the circuit breaker
+            assert_eq!(entropy.set_circuit_breaker(100), Ok(()));
+            assert_eq!(entropy.can_transfer(accounts.eve, accounts.charlie, 1_001), Err(Error::CircuitBreakerTripped));
+            assert_eq!(entropy.paused(), false);
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.eve, accounts.charlie, 1_001), Err(Error::CircuitBreakerTripped));
+            assert_eq!(entropy.paused(), true);
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.eve, accounts.charlie, 10), Err(Error::ContractPaused));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.unpause(), Ok(()));
+            assert_eq!(entropy.set_circuit_breaker(0), Ok(()));
+
+            assert_eq!(entropy.set_throughput_cap(500, 1_000), Ok(()));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.eve, accounts.charlie, 400), Ok(()));
+            assert_eq!(assert_transfer_verdict(&mut entropy, accounts.eve, accounts.charlie, 200), Err(Error::ThroughputExceeded));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_throughput_cap(0, 0), Ok(()));
+
+            // Allowances
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.django, 50), Ok(()));
+            assert_eq!(entropy.approve_for_recipient(accounts.eve, accounts.charlie, 80), Ok(()));
+            assert_eq!(assert_transfer_from_verdict(&mut entropy, accounts.django, accounts.bob, accounts.eve, 60), Err(Error::InsufficientAllowance));
+            assert_eq!(assert_transfer_from_verdict(&mut entropy, accounts.django, accounts.bob, accounts.eve, 50), Ok(()));
+            assert_eq!(assert_transfer_from_verdict(&mut entropy, accounts.eve, accounts.bob, accounts.django, 80), Err(Error::InsufficientAllowance));
+            assert_eq!(assert_transfer_from_verdict(&mut entropy, accounts.eve, accounts.bob, accounts.charlie, 80), Ok(()));
+            assert_eq!(assert_transfer_from_verdict(&mut entropy, accounts.django, accounts.bob, accounts.frank, 0), Err(Error::AccountBlackListed));
+        }
+
+        #[ink::test]
+        fn throughput_cap_works() {
+            let mut entropy = Entropy::new(10_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 1_000, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 1_000, None), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_throughput_cap(500, 10), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_throughput_cap(500, 10), Ok(()));
+            assert_eq!(entropy.throughput_cap(), ThroughputCap { max_value_per_window: 500, window_ms: 10 });
+
+            // Transfers from several accounts fill the same window
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.django, 200, None), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 200, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.django, 101, None), Err(Error::ThroughputExceeded));
+            assert_eq!(entropy.transfer(accounts.django, 100, None), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 1, None), Err(Error::ThroughputExceeded));
+
+            // The owner and system accounts bypass the cap
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.eve, 1_000, None), Ok(()));
+            assert_eq!(entropy.set_system_account(accounts.eve, true), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer(accounts.django, 1_000, None), Ok(()));
+
+            // The window resets once it has passed
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 1, None), Err(Error::ThroughputExceeded));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.transfer(accounts.django, 500, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.django, 1, None), Err(Error::ThroughputExceeded));
+
+            // Disabling the cap lifts it
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_throughput_cap(0, 10), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 300, None), Ok(()));
+        }
+
+        #[ink::test]
+        fn balance_at_block_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            // Block 0: bob receives 100, then 50 more, only the end of the block is kept
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            // Blocks 1 and 2: nothing happens
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            // Block 3: bob sends 30
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 30, None), Ok(()));
+            // Block 5: bob burns his balance
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.burn(accounts.bob, 120), Ok(()));
+
+            assert_eq!(entropy.balance_at_block(accounts.bob, 0), Some(150));
+            assert_eq!(entropy.balance_at_block(accounts.bob, 2), Some(150));
+            assert_eq!(entropy.balance_at_block(accounts.bob, 3), Some(120));
+            assert_eq!(entropy.balance_at_block(accounts.bob, 4), Some(120));
+            assert_eq!(entropy.balance_at_block(accounts.bob, 5), Some(0));
+            assert_eq!(entropy.balance_at_block(accounts.bob, 100), Some(0));
+
+            // Charlie's history starts at block 3
+            assert_eq!(entropy.balance_at_block(accounts.charlie, 2), None);
+            assert_eq!(entropy.balance_at_block(accounts.charlie, 3), Some(30));
+
+            assert_eq!(entropy.balance_at_block(accounts.alice, 0), Some(850));
+            assert_eq!(entropy.balance_at_block(accounts.django, 5), None);
+        }
+
+        #[ink::test]
+        fn balance_history_is_capped() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            // Bob receives 1 in each of blocks 0 to MAX_BALANCE_HISTORY
+            for _ in 0..=MAX_BALANCE_HISTORY {
+                assert_eq!(entropy.transfer(accounts.bob, 1, None), Ok(()));
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            let last = MAX_BALANCE_HISTORY as BlockNumber;
+
+            // Only the last MAX_BALANCE_HISTORY changes are kept, block 0 was dropped
+            assert_eq!(entropy.balance_history.get(&accounts.bob).map(Vec::len), Some(MAX_BALANCE_HISTORY));
+            assert_eq!(entropy.balance_at_block(accounts.bob, 0), None);
+            assert_eq!(entropy.balance_at_block(accounts.bob, 1), Some(2));
+            assert_eq!(entropy.balance_at_block(accounts.bob, last), Some(last as Balance + 1));
+
+            // Changes within one block still take a single entry
+            assert_eq!(entropy.transfer(accounts.bob, 1, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 1, None), Ok(()));
+            assert_eq!(entropy.balance_history.get(&accounts.bob).map(Vec::len), Some(MAX_BALANCE_HISTORY));
+            assert_eq!(entropy.balance_at_block(accounts.bob, 1), None);
+            assert_eq!(entropy.balance_at_block(accounts.bob, last + 1), Some(last as Balance + 3));
+        }
+
+        #[ink::test]
+        fn trusted_forwarder_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+            let forwarder = accounts.eve;
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+
+            // Without a trusted forwarder nobody can act for another account
+            set_caller(forwarder);
+            assert_eq!(entropy.transfer_for(accounts.bob, forwarder, 10, None), Err(Error::UntrustedForwarder));
+            assert_eq!(entropy.set_trusted_forwarder(Some(forwarder)), Err(Error::PermissionDenied));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_trusted_forwarder(Some(AccountId::from([0x0; 32]))), Err(Error::InvalidAccount));
+            assert_eq!(entropy.set_trusted_forwarder(Some(forwarder)), Ok(()));
+            assert_eq!(entropy.trusted_forwarder(), Some(forwarder));
+
+            // The forwarder acts on behalf of the sender it names
+            set_caller(forwarder);
+            assert_eq!(entropy.transfer_for(accounts.bob, accounts.charlie, 30, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 70);
+            assert_eq!(entropy.balance_of(accounts.charlie), 30);
+            assert_eq!(entropy.balance_of(forwarder), 0);
+            assert_eq!(entropy.approve_for(accounts.bob, accounts.django, 20), Ok(()));
+            assert_eq!(entropy.allowance(accounts.bob, accounts.django), 20);
+            assert_eq!(entropy.allowance(forwarder, accounts.django), 0);
+
+            // The sender's own checks still apply
+            assert_eq!(entropy.transfer_for(accounts.bob, accounts.charlie, 71, None), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.approve_for(accounts.bob, accounts.bob, 1), Err(Error::InvalidAccount));
+
+            // Untrusted callers can't spoof a sender
+            for spoofer in [accounts.charlie, accounts.django, accounts.alice].iter() {
+                set_caller(*spoofer);
+                assert_eq!(entropy.transfer_for(accounts.bob, *spoofer, 10, None), Err(Error::UntrustedForwarder));
+                assert_eq!(entropy.approve_for(accounts.bob, *spoofer, 10), Err(Error::UntrustedForwarder));
+            }
+            assert_eq!(entropy.balance_of(accounts.bob), 70);
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 0);
+
+            // Removing the forwarder revokes its trust
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_trusted_forwarder(None), Ok(()));
+            set_caller(forwarder);
+            assert_eq!(entropy.transfer_for(accounts.bob, accounts.charlie, 10, None), Err(Error::UntrustedForwarder));
+        }
+
+        #[ink::test]
+        fn transfer_keep_alive_works() {
+            let mut entropy = Entropy::new(10_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 1_000, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_keep_alive_minimum(300), Ok(()));
+            assert_eq!(entropy.keep_alive_minimum_of(accounts.bob), 300);
+
+            // Transfers can go down to the minimum exactly
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 701), Err(Error::WouldDropBelowMinimum));
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 1_001), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 200), Ok(()));
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 500), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 300);
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 1), Err(Error::WouldDropBelowMinimum));
+
+            // Plain transfers ignore the minimum
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 200);
+
+            // The fee is part of the value, so it doesn't eat into the minimum
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 1_101, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.balance_of(accounts.bob), 1_299);
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 1_000), Err(Error::WouldDropBelowMinimum));
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 999), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 300);
+
+            // transfer_all leaves exactly the minimum
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.bob, 501, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_all(accounts.charlie), Ok(500));
+            assert_eq!(entropy.balance_of(accounts.bob), 300);
+
+            // Clearing the minimum lets keep-alive transfers and transfer_all empty the account
+            assert_eq!(entropy.set_keep_alive_minimum(0), Ok(()));
+            assert_eq!(entropy.keep_alive_minimum_of(accounts.bob), 0);
+            assert_eq!(entropy.transfer_keep_alive(accounts.charlie, 100), Ok(()));
+            assert_eq!(entropy.transfer_all(accounts.charlie), Ok(200));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn allowance_adjustments_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            let assert_last_approval = |expected_value: Balance| {
+                let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+                let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events.last().unwrap().data[..])
+                    .expect("encountered invalid contract event data buffer");
+                if let Event::Approval(Approval { owner, spender, value }) = decoded_event {
+                    assert_eq!((owner, spender, value), (accounts.alice, accounts.bob, expected_value));
+                } else {
+                    panic!("encountered unexpected event kind: expected an Approval event")
+                }
+            };
+
+            assert_eq!(entropy.increase_allowance(accounts.bob, 100), Ok(()));
+            assert_last_approval(100);
+            assert_eq!(entropy.increase_allowance(accounts.bob, 50), Ok(()));
+            assert_last_approval(150);
+
+            // Decreasing below the current allowance is refused
+            assert_eq!(entropy.decrease_allowance(accounts.bob, 151), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 150);
+
+            // The spender spends part of it before the decrease
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.charlie, 120), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.decrease_allowance(accounts.bob, 50), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.decrease_allowance(accounts.bob, 30), Ok(()));
+            assert_last_approval(0);
+
+            // Increasing saturates instead of overflowing
+            assert_eq!(entropy.increase_allowance(accounts.bob, Balance::MAX - 1), Ok(()));
+            assert_eq!(entropy.increase_allowance(accounts.bob, 2), Ok(()));
+            assert_last_approval(Balance::MAX);
+            assert_eq!(entropy.increase_allowance(accounts.bob, Balance::MAX), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), Balance::MAX);
+            assert_eq!(entropy.decrease_allowance(accounts.bob, Balance::MAX), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 0);
+
+            assert_eq!(entropy.increase_allowance(accounts.alice, 1), Err(Error::InvalidAccount));
+        }
+
+        #[ink::test]
+        fn burn_my_tokens_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+            let holders = [accounts.alice, accounts.bob, accounts.charlie];
+            let assert_supply_invariant = |entropy: &Entropy| {
+                let sum = holders.iter().fold(0, |sum, holder| sum + entropy.balance_of(*holder));
+                assert_eq!(entropy.total_supply(), sum);
+            };
+
+            assert_eq!(entropy.transfer(accounts.bob, 300, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.burn_my_tokens(301), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.burn_my_tokens(100), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 200);
+            assert_eq!(entropy.total_supply(), 900);
+            assert_supply_invariant(&entropy);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let count = emitted_events.len();
+            assert_transfer_event(&emitted_events[count - 2], Some(accounts.bob), None, 100);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[count - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Burn(Burn { account, value }) = decoded_event {
+                assert_eq!((account, value), (accounts.bob, 100));
+            } else {
+                panic!("encountered unexpected event kind: expected a Burn event")
+            }
+
+            // A mix of issues and burns keeps the supply equal to the sum of balances
+            set_caller(accounts.alice);
+            assert_eq!(entropy.issue(500), Ok(()));
+            assert_eq!(entropy.burn_my_tokens(250), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.burn_my_tokens(200), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+            set_caller(accounts.alice);
+            assert_eq!(entropy.issue(50), Ok(()));
+            assert_eq!(entropy.total_supply(), 1_000);
+            assert_supply_invariant(&entropy);
+
+            // Blacklisted holders can't burn
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.burn_my_tokens(10), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.burn(accounts.bob, 10), Err(PSP22Error::Custom(String::from("AccountBlackListed"))));
+            assert_eq!(entropy.balance_of(accounts.bob), 200);
+            assert_supply_invariant(&entropy);
+
+            // PSP22 `burn` on the caller's own account is the same holder burn
+            set_caller(accounts.alice);
+            assert_eq!(entropy.burn(accounts.alice, 2_000), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(entropy.burn(accounts.alice, 50), Ok(()));
+            assert_eq!(entropy.total_supply(), 950);
+            assert_supply_invariant(&entropy);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let count = emitted_events.len();
+            assert_transfer_event(&emitted_events[count - 2], Some(accounts.alice), None, 50);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[count - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Burn(Burn { account, value }) = decoded_event {
+                assert_eq!((account, value), (accounts.alice, 50));
+            } else {
+                panic!("encountered unexpected event kind: expected a Burn event")
+            }
+        }
+
+        #[ink::test]
+        fn burn_from_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            // Bob fails to burn tokens owned by Alice.
+            assert_eq!(entropy.burn_from(accounts.alice, 10), Err(Error::InsufficientAllowance));
+            // Alice approves Bob for more than she owns.
+            assert_eq!(entropy.approve(accounts.bob, 150), Ok(()));
+
+            // Get contract address.
+            let callee = ink_env::account_id::<Environment>()
+                .unwrap_or_else(|_| [0x0; 32].into());
+            // Create call.
+            let mut data =
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
+            data.push_arg(&accounts.bob);
+            // Push the new execution context to set Bob as caller.
+            ink_env::test::push_execution_context::<Environment>(
+                accounts.bob,
+                callee,
+                1000000,
+                1000000,
+                data,
+            );
+
+            // Bob burns tokens of Alice.
+            assert_eq!(entropy.burn_from(accounts.alice, 30), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.alice), 70);
+            assert_eq!(entropy.total_supply(), 70);
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 120);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let count = emitted_events.len();
+            assert_transfer_event(&emitted_events[count - 3], Some(accounts.alice), None, 30);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[count - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Approval(Approval { owner, spender, value }) = decoded_event {
+                assert_eq!((owner, spender, value), (accounts.alice, accounts.bob, 120));
+            } else {
+                panic!("encountered unexpected event kind: expected an Approval event")
+            }
+
+            // The allowance is checked before the balance, and is left untouched on failure
+            assert_eq!(entropy.burn_from(accounts.alice, 121), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.burn_from(accounts.alice, 71), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 120);
+            assert_eq!(entropy.balance_of(accounts.alice), 70);
+            assert_eq!(entropy.total_supply(), 70);
+
+            // Burning needs an allowance even on the caller's own account
+            assert_eq!(entropy.burn_from(accounts.bob, 0), Ok(()));
+            assert_eq!(entropy.burn_from(accounts.bob, 1), Err(Error::InsufficientAllowance));
+        }
+
+        #[ink::test]
+        fn overflow_is_reported() {
+            let mut entropy = Entropy::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.issue_to(accounts.bob, Balance::MAX - 100), Ok(()));
+            assert_eq!(entropy.total_supply(), Balance::MAX);
+
+            // Issuing more is refused without changing any state
+            assert_eq!(entropy.issue(1), Err(Error::Overflow));
+            assert_eq!(entropy.issue_to(accounts.bob, 1), Err(Error::Overflow));
+            assert_eq!(entropy.issue_to(accounts.charlie, Balance::MAX), Err(Error::Overflow));
+            assert_eq!(entropy.mint(accounts.charlie, 1), Err(PSP22Error::Custom(String::from("Overflow"))));
+            assert_eq!(entropy.total_supply(), Balance::MAX);
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            assert_eq!(entropy.balance_of(accounts.bob), Balance::MAX - 100);
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+
+            // Transfers of huge amounts compute their fee without overflowing
+            assert_eq!(entropy.set_params(20, 50_000_000), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, Balance::MAX - 100, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), Balance::MAX - 100 - 50_000_000);
+            assert_eq!(entropy.balance_of(accounts.alice), 100 + 50_000_000);
+            assert_eq!(entropy.total_supply(), Balance::MAX);
+
+            // After burning, issuing fits again
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.burn_my_tokens(10), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.issue(11), Err(Error::Overflow));
+            assert_eq!(entropy.issue(10), Ok(()));
+            assert_eq!(entropy.total_supply(), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn internal_overflow_is_reported() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+            let start = ink_env::block_timestamp::<Environment>().expect("Cannot get block timestamp");
+
+            // Balances above the total supply can't be reached through messages, so the state is forced
+            assert_eq!(entropy.set_fee_collector(accounts.bob), Ok(()));
+            assert_eq!(entropy.set_params(20, 50_000_000), Ok(()));
+            entropy.set_balance(accounts.bob, Balance::MAX - 9_990);
+
+            // The fee collector receiving a transfer is credited the net amount and the fee together
+            assert_eq!(entropy.transfer(accounts.bob, 10_000, None), Err(Error::Overflow));
+            assert_eq!(entropy.transfer(accounts.bob, 9_990, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), Balance::MAX);
+            assert_eq!(entropy.balance_of(accounts.alice), 100_000 - 9_990);
+
+            // Escrow accounting that would underflow leaves the claim unmade
+            assert_eq!(entropy.create_vesting(accounts.charlie, 1_000, start, 10, 0), Ok(()));
+            entropy.escrowed = 0;
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.claim_vested(), Err(Error::Overflow));
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+            assert_eq!(entropy.vesting_schedule(accounts.charlie).map(|schedule| schedule.claimed), Some(0));
+
+            // Exhausted counters and nonces are refused
+            entropy.hold_count = u64::MAX;
+            set_caller(accounts.alice);
+            assert_eq!(entropy.create_hold(accounts.charlie, 10, Timestamp::MAX), Err(Error::Overflow));
+            assert_eq!(entropy.on_hold_balance_of(accounts.alice), 0);
+            entropy.nonces.insert(accounts.alice, u64::MAX);
+            assert_eq!(
+                entropy.transfer_with_signature(accounts.alice, accounts.charlie, 1, Timestamp::MAX, u64::MAX, [0; 64]),
+                Err(Error::Overflow)
+            );
+            assert_eq!(entropy.nonce_of(accounts.alice), u64::MAX);
+        }
+
+        #[ink::test]
+        fn pause_halts_token_movement() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.approve(accounts.charlie, 50), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.pause(), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.pause(), Ok(()));
+
+            assert_eq!(entropy.transfer(accounts.django, 1, None), Err(Error::ContractPaused));
+            assert_eq!(entropy.issue(1), Err(Error::ContractPaused));
+            assert_eq!(entropy.issue_to(accounts.django, 1), Err(Error::ContractPaused));
+            assert_eq!(entropy.redeem(1, Hash::from([0x01; 32])), Err(Error::ContractPaused));
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::ContractPaused));
+            assert_eq!(entropy.destroy_black_funds_batch(vec![accounts.bob]), Err(Error::ContractPaused));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.charlie, 10), Err(Error::ContractPaused));
+
+            // Queries keep working and the pending allowance is untouched
+            assert_eq!(entropy.balance_of(accounts.alice), 900);
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+            assert_eq!(entropy.total_supply(), 1_000);
+            assert_eq!(entropy.allowance(accounts.alice, accounts.charlie), 50);
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.unpause(), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.charlie), 50);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.charlie, 50), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.charlie), 0);
+            set_caller(accounts.alice);
+            assert_eq!(entropy.issue(1), Ok(()));
+            assert_eq!(entropy.redeem(1, Hash::from([0x01; 32])), Ok(1));
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
+        }
+
+        #[ink::test]
+        fn calculate_fee_matches_transfers() {
+            let mut entropy = Entropy::new(100_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 50_000_000, None), Ok(()));
+            assert_eq!(entropy.calculate_fee(1_000), (0, 1_000));
+
+            // 10 basis points capped at 1_000, which clamps from 1_000_000 on
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            let values = [1, 999, 1_000, 1_001, 999_999, 1_000_000, 1_000_001, 2_500_000];
+            for rounding in [FeeRounding::Down, FeeRounding::Up].iter() {
+                set_caller(accounts.alice);
+                assert_eq!(entropy.set_fee_rounding(*rounding), Ok(()));
+                set_caller(accounts.bob);
+                for value in values.iter() {
+                    let (fee, received) = entropy.calculate_fee(*value);
+                    assert_eq!(fee + received, *value);
+                    let before = (
+                        entropy.balance_of(accounts.bob),
+                        entropy.balance_of(accounts.charlie),
+                        entropy.balance_of(accounts.alice)
+                    );
+                    assert_eq!(entropy.transfer(accounts.charlie, *value, None), Ok(()));
+                    assert_eq!(entropy.balance_of(accounts.bob), before.0 - value);
+                    assert_eq!(entropy.balance_of(accounts.charlie), before.1 + received);
+                    assert_eq!(entropy.balance_of(accounts.alice), before.2 + fee);
+                }
+            }
+            assert_eq!(entropy.calculate_fee(999_999), (1_000, 998_999));
+            assert_eq!(entropy.calculate_fee(1_000_000), (1_000, 999_000));
+            assert_eq!(entropy.calculate_fee(2_500_000), (1_000, 2_499_000));
+
+            // Fee exempt senders are charged nothing
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, true), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.calculate_fee(1_000_000), (0, 1_000_000));
+        }
+
+        #[ink::test]
+        fn fee_exemption_works() {
+            let mut entropy = Entropy::new(1_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100_000, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 100_000, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+
+            assert_eq!(entropy.is_fee_exempt(accounts.bob), false);
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, true), Ok(()));
+            assert_eq!(entropy.is_fee_exempt(accounts.bob), true);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::FeeExemption(FeeExemption { account, exempt }) = decoded_event {
+                assert_eq!((account, exempt), (accounts.bob, true));
+            } else {
+                panic!("encountered unexpected event kind: expected a FeeExemption event")
+            }
+
+            // The exempt sender pays no fee, a normal sender still does
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.django, 10_000, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.django), 10_000);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 10_000, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.django), 19_990);
+
+            // Transfers to an exempt account are spared too
+            assert_eq!(entropy.transfer(accounts.bob, 10_000, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 100_000);
+
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, false), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, false), Ok(()));
+            assert_eq!(entropy.is_fee_exempt(accounts.bob), false);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.django, 10_000, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.django), 29_980);
+        }
+
+        #[ink::test]
+        fn fee_collector_works() {
+            let mut entropy = Entropy::new(1_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.fee_collector(), accounts.alice);
+            assert_eq!(entropy.transfer(accounts.bob, 100_000, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.alice), 900_010);
+
+            assert_eq!(entropy.set_fee_collector(accounts.bob), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_fee_collector(AccountId::from([0x0; 32])), Err(Error::InvalidAccount));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.eve, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.set_fee_collector(accounts.eve), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.set_fee_collector(accounts.django), Ok(()));
+            assert_eq!(entropy.fee_collector(), accounts.django);
+            assert_eq!(entropy.is_protected_account(accounts.django), true);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::FeeCollectorChanged(FeeCollectorChanged { collector }) = decoded_event {
+                assert_eq!(collector, accounts.django);
+            } else {
+                panic!("encountered unexpected event kind: expected a FeeCollectorChanged event")
+            }
+
+            // Subsequent fees land in the new collector's account, the owner's is left alone
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.django), 10);
+            assert_eq!(entropy.balance_of(accounts.alice), 900_010);
+            assert_eq!(entropy.balance_of(accounts.charlie), 19_980);
+
+            // The collector survives ownership changes
+            set_caller(accounts.alice);
+            hand_over_ownership(&mut entropy, accounts.charlie);
+            assert_eq!(entropy.fee_collector(), accounts.django);
+        }
+
+        #[ink::test]
+        fn gift_codes_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            let hash_bytes = |code: &[u8]| {
+                let mut output = <Blake2x256 as HashOutput>::Type::default();
+                <Blake2x256 as CryptoHash>::hash(code, &mut output);
+                Hash::from(output)
+            };
+
+            // Two gifts valid for two blocks
+            assert_eq!(entropy.create_gift(hash_bytes(b"spring"), 100, 10), Ok(()));
+            assert_eq!(entropy.create_gift(hash_bytes(b"autumn"), 50, 10), Ok(()));
+            assert_eq!(entropy.create_gift(hash_bytes(b"spring"), 100, 10), Err(Error::InvalidGift));
+            assert_eq!(entropy.create_gift(hash_bytes(b"winter"), 100, 0), Err(Error::InvalidGift));
+            assert_eq!(entropy.balance_of(contract), 150);
+
+            // Wrong codes don't match, the right one pays once
+            set_caller(accounts.bob);
+            assert_eq!(entropy.redeem_gift(b"sprung".to_vec()), Err(Error::UnknownGift));
+            assert_eq!(entropy.redeem_gift(b"spring".to_vec()), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.redeem_gift(b"spring".to_vec()), Err(Error::GiftSpent));
+
+            // Only the creator reclaims, and only after expiry
+            assert_eq!(entropy.reclaim_expired_gift(hash_bytes(b"autumn")), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.reclaim_expired_gift(hash_bytes(b"autumn")), Err(Error::ClaimWindowOpen));
+            for _ in 0..3 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.redeem_gift(b"autumn".to_vec()), Err(Error::ClaimWindowClosed));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.reclaim_expired_gift(hash_bytes(b"spring")), Err(Error::GiftSpent));
+            assert_eq!(entropy.reclaim_expired_gift(hash_bytes(b"autumn")), Ok(()));
+            assert_eq!(entropy.reclaim_expired_gift(hash_bytes(b"autumn")), Err(Error::GiftSpent));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.redeem_gift(b"autumn".to_vec()), Err(Error::GiftSpent));
+
+            assert_eq!(entropy.balance_of(accounts.alice), 900);
+            assert_eq!(entropy.balance_of(contract), 0);
+        }
+
+        #[ink::test]
+        fn expired_gifts_of_blacklisted_creators_are_seizable() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            let treasury = accounts.frank;
+            let hash_bytes = |code: &[u8]| {
+                let mut output = <Blake2x256 as HashOutput>::Type::default();
+                <Blake2x256 as CryptoHash>::hash(code, &mut output);
+                Hash::from(output)
+            };
+
+            // Bob creates a gift valid for two blocks
+            assert_eq!(entropy.transfer(accounts.bob, 300, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.create_gift(hash_bytes(b"spring"), 50, 10), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.seize_expired_gift(hash_bytes(b"autumn"), treasury), Err(Error::UnknownGift));
+            assert_eq!(entropy.seize_expired_gift(hash_bytes(b"spring"), treasury), Err(Error::ClaimWindowOpen));
+            for _ in 0..3 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+
+            // Gifts of creators in good standing go back to them only
+            assert_eq!(entropy.seize_expired_gift(hash_bytes(b"spring"), treasury), Err(Error::AccountNotBlackListed));
+
+            // Once Bob is blacklisted the gift can no longer be reclaimed, but can be seized
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.reclaim_expired_gift(hash_bytes(b"spring")), Err(Error::AccountBlackListed));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.seize_expired_gift(hash_bytes(b"spring"), accounts.bob), Err(Error::InvalidAccount));
+            assert_eq!(entropy.seize_expired_gift(hash_bytes(b"spring"), treasury), Ok(()));
+            assert_eq!(entropy.seize_expired_gift(hash_bytes(b"spring"), treasury), Err(Error::GiftSpent));
+            assert_eq!(entropy.gift(hash_bytes(b"spring")).map(|gift| gift.spent), Some(true));
+
+            assert_eq!(entropy.balance_of(treasury), 50);
+            assert_eq!(entropy.balance_of(contract), 0);
+            assert_eq!(entropy.balance_of(accounts.bob), 250);
+            assert_eq!(entropy.total_supply(), 1_000);
+        }
+
+        #[ink::test]
+        fn holds_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.create_hold(accounts.bob, 0, 10), Err(Error::InvalidHold));
+            assert_eq!(entropy.create_hold(accounts.bob, 300, 0), Err(Error::InvalidHold));
+            assert_eq!(entropy.create_hold(accounts.alice, 300, 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.create_hold(accounts.bob, 1_001, 10), Err(Error::InsufficientBalance));
+
+            // Two holds valid for two blocks, counted in the balance but not spendable
+            assert_eq!(entropy.create_hold(accounts.bob, 300, 10), Ok(1));
+            assert_eq!(entropy.create_hold(accounts.charlie, 100, 10), Ok(2));
+            assert_eq!(entropy.hold(1), Some(Hold { payer: accounts.alice, to: accounts.bob, value: 300, expires_at: 10 }));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::HoldCreated(HoldCreated { id, payer, to, value, expires_at }) = decoded_event {
+                assert_eq!((id, payer, to, value, expires_at), (2, accounts.alice, accounts.charlie, 100, 10));
+            } else {
+                panic!("encountered unexpected event kind: expected a HoldCreated event")
+            }
+            assert_eq!(entropy.balance_of(accounts.alice), 1_000);
+            assert_eq!(entropy.on_hold_balance_of(accounts.alice), 400);
+            assert_eq!(entropy.spendable_balance_of(accounts.alice), 600);
+            assert_eq!(entropy.create_hold(accounts.bob, 601, 10), Err(Error::InsufficientBalance));
+
+            // Transfers can't dip into held tokens
+            assert_eq!(entropy.transfer(accounts.django, 601, None), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.transfer(accounts.django, 200, None), Ok(()));
+            assert_eq!(entropy.spendable_balance_of(accounts.alice), 400);
+
+            // Only the recipient captures, once
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.capture_hold(1), Err(Error::PermissionDenied));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.capture_hold(3), Err(Error::UnknownHold));
+            assert_eq!(entropy.capture_hold(1), Ok(()));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::HoldCaptured(HoldCaptured { id, payer, to, value }) = decoded_event {
+                assert_eq!((id, payer, to, value), (1, accounts.alice, accounts.bob, 300));
+            } else {
+                panic!("encountered unexpected event kind: expected a HoldCaptured event")
+            }
+            assert_eq!(entropy.capture_hold(1), Err(Error::UnknownHold));
+            assert_eq!(entropy.release_hold(1), Err(Error::UnknownHold));
+            assert_eq!(entropy.hold(1), None);
+            assert_eq!(entropy.balance_of(accounts.bob), 300);
+            assert_eq!(entropy.balance_of(accounts.alice), 500);
+            assert_eq!(entropy.on_hold_balance_of(accounts.alice), 100);
+
+            // The payer releases only after expiry, and an expired hold can't be captured
+            assert_eq!(entropy.release_hold(2), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.release_hold(2), Err(Error::ClaimWindowOpen));
+            for _ in 0..3 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.capture_hold(2), Err(Error::ClaimWindowClosed));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.release_hold(2), Ok(()));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::HoldReleased(HoldReleased { id, payer, value }) = decoded_event {
+                assert_eq!((id, payer, value), (2, accounts.alice, 100));
+            } else {
+                panic!("encountered unexpected event kind: expected a HoldReleased event")
+            }
+            assert_eq!(entropy.on_hold_balance_of(accounts.alice), 0);
+            assert_eq!(entropy.spendable_balance_of(accounts.alice), 500);
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+
+            // The recipient may release at any time
+            assert_eq!(entropy.create_hold(accounts.bob, 50, 1_000), Ok(3));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.release_hold(3), Ok(()));
+            assert_eq!(entropy.spendable_balance_of(accounts.alice), 500);
+        }
+
+        #[ink::test]
+        fn recipient_allowance_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Bob may pull 100 tokens to Charlie and 20 tokens anywhere
+            assert_eq!(entropy.approve_for_recipient(accounts.bob, accounts.charlie, 100), Ok(()));
+            assert_eq!(entropy.approve(accounts.bob, 20), Ok(()));
+            assert_eq!(entropy.allowance_for_recipient(accounts.alice, accounts.bob, accounts.charlie), 100);
+            assert_eq!(entropy.allowance_for_recipient(accounts.alice, accounts.bob, accounts.django), 0);
+
+            // The scoped allowance is consumed first
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.charlie, 60), Ok(()));
+            assert_eq!(entropy.allowance_for_recipient(accounts.alice, accounts.bob, accounts.charlie), 40);
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 20);
+
+            // It can't fund transfers to another destination
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.django, 40), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.balance_of(accounts.django), 0);
+            assert_eq!(entropy.allowance_for_recipient(accounts.alice, accounts.bob, accounts.charlie), 40);
+
+            // Transfers the scoped allowance doesn't cover fall back to the general one
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.django, 20), Ok(()));
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.charlie, 50), Err(Error::InsufficientAllowance));
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.charlie, 40), Ok(()));
+            assert_eq!(entropy.allowance_for_recipient(accounts.alice, accounts.bob, accounts.charlie), 0);
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(entropy.balance_of(accounts.charlie), 100);
+            assert_eq!(entropy.balance_of(accounts.django), 20);
+        }
+
+        #[ink::test]
+        fn claimable_transfer_works() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+
+            assert_eq!(entropy.transfer(accounts.bob, 50_000, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+
+            // Bob escrows two transfers claimable for two blocks, without paying a fee yet
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_claimable(accounts.charlie, 10_000, 10), Ok(1));
+            assert_eq!(entropy.transfer_claimable(accounts.django, 5_000, 10), Ok(2));
+            assert_eq!(entropy.transfer_claimable(accounts.bob, 5_000, 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.balance_of(accounts.bob), 35_000);
+            assert_eq!(entropy.balance_of(contract), 15_000);
+
+            // Only the recipient can claim, paying the fee
+            assert_eq!(entropy.claim_incoming(1), Err(Error::PermissionDenied));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.claim_incoming(1), Ok(()));
+            assert_eq!(entropy.claim_incoming(1), Err(Error::UnknownTransfer));
+            assert_eq!(entropy.balance_of(accounts.charlie), 9_990);
+            assert_eq!(entropy.transfer_record(entropy.last_tx_id()).map(|record| (record.from, record.net, record.fee)), Some((accounts.bob, 9_990, 10)));
+
+            // The second transfer can't be refunded before its timeout, nor claimed after it
+            assert_eq!(entropy.refund_unclaimed(2), Err(Error::ClaimWindowOpen));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            set_caller(accounts.django);
+            assert_eq!(entropy.claim_incoming(2), Err(Error::ClaimWindowClosed));
+
+            // Anyone can refund it to Bob
+            set_caller(accounts.eve);
+            assert_eq!(entropy.refund_unclaimed(2), Ok(()));
+            assert_eq!(entropy.refund_unclaimed(2), Err(Error::UnknownTransfer));
+            assert_eq!(entropy.pending_transfer(2), None);
+            assert_eq!(entropy.balance_of(accounts.bob), 40_000);
+            assert_eq!(entropy.balance_of(accounts.django), 0);
+            assert_eq!(entropy.balance_of(contract), 0);
+        }
+
+        #[ink::test]
+        fn unclaimed_transfers_of_blacklisted_senders_are_seizable() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            let treasury = accounts.frank;
+
+            // Bob escrows a claimable transfer expiring after two blocks
+            assert_eq!(entropy.transfer(accounts.bob, 300, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_claimable(accounts.charlie, 100, 10), Ok(1));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.seize_unclaimed_transfer(1, treasury), Err(Error::ClaimWindowOpen));
+            for _ in 0..3 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+
+            // Escrow of senders in good standing goes back to them only
+            assert_eq!(entropy.seize_unclaimed_transfer(1, treasury), Err(Error::AccountNotBlackListed));
+
+            // Once Bob is blacklisted it can no longer be refunded, but can be seized
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.refund_unclaimed(1), Err(Error::AccountBlackListed));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.seize_unclaimed_transfer(1, treasury), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.seize_unclaimed_transfer(2, treasury), Err(Error::UnknownTransfer));
+            assert_eq!(entropy.seize_unclaimed_transfer(1, accounts.bob), Err(Error::InvalidAccount));
+
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.seize_unclaimed_transfer(1, treasury), Ok(()));
+            assert_eq!(entropy.pending_transfer(1), None);
+            assert_eq!(entropy.refund_unclaimed(1), Err(Error::UnknownTransfer));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_new_holder_event(&emitted_events[emitted_events_before], treasury);
+            assert_transfer_event(&emitted_events[emitted_events_before + 1], Some(contract), Some(treasury), 100);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events_before + 2].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::SeizedBlackFunds(SeizedBlackFunds { account, treasury: to, funds }) = decoded_event {
+                assert_eq!((account, to, funds), (accounts.bob, treasury, 100));
+            } else {
+                panic!("encountered unexpected event kind: expected a SeizedBlackFunds event")
+            }
+            assert_eq!(entropy.balance_of(treasury), 100);
+            assert_eq!(entropy.balance_of(contract), 0);
+            assert_eq!(entropy.balance_of(accounts.bob), 200);
+            assert_eq!(entropy.total_supply(), 1_000);
+        }
+
+        #[ink::test]
+        fn issue_works() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100);
+
+            // Transfer event triggered during initial construction.
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+
+            // Issue 100 more tokens
+            assert_eq!(entropy.issue(100), Ok(()));
+
+            // Check total supply
+            assert_eq!(entropy.total_supply(), 200);
+
+            // Check Alice's new balance
+            assert_eq!(entropy.balance_of(accounts.alice), 200);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 4);
+
+            // Check first transfer event related to Entropy instantiation.
+            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
+            // Check second Issue event
+            assert_issue_event(&emitted_events[2], 100);
+            // Check the transfer event of the issued tokens
+            assert_transfer_event(&emitted_events[3], None, Some(AccountId::from([0x01; 32])), 100);
+        }
+
+        #[ink::test]
+        fn issue_to_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            // Minting to Bob charges no transfer fee
+            assert_eq!(entropy.set_params(20, 1_000), Ok(()));
+            assert_eq!(entropy.issue_to(accounts.bob, 1_000), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 1_000);
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            assert_eq!(entropy.total_supply(), 1_100);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let count = emitted_events.len();
+            assert_issue_event(&emitted_events[count - 2], 1_000);
+            assert_transfer_event(&emitted_events[count - 1], None, Some(accounts.bob), 1_000);
+
+            assert_eq!(entropy.issue_to(AccountId::from([0x0; 32]), 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.issue_to(accounts.charlie, 10), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.issue_to(accounts.bob, 0), Err(Error::ZeroAmount));
+            assert_eq!(entropy.issue(0), Err(Error::ZeroAmount));
+            assert_eq!(entropy.total_supply(), 1_100);
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.issue_to(accounts.bob, 10), Err(Error::PermissionDenied));
+            assert_eq!(entropy.issue(10), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn supply_cap_works() {
+            let mut entropy = Entropy::construct_with_config(100, "Entropy Coin".into(), "ENT".into(), 6, ContractConfig { max_supply: Some(1_000), ..Default::default() });
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.max_supply(), Some(1_000));
+            assert_eq!(Entropy::new(100).max_supply(), None);
+
+            // Minting up to exactly the cap succeeds, one unit over fails without changing any state
+            assert_eq!(entropy.issue_to(accounts.bob, 400), Ok(()));
+            assert_eq!(entropy.issue(500), Ok(()));
+            assert_eq!(entropy.total_supply(), 1_000);
+            assert_eq!(entropy.issue(1), Err(Error::SupplyCapExceeded));
+            assert_eq!(entropy.issue_to(accounts.bob, 1), Err(Error::SupplyCapExceeded));
+            assert_eq!(entropy.mint(accounts.bob, 1), Err(PSP22Error::Custom(String::from("SupplyCapExceeded"))));
+            assert_eq!(entropy.total_supply(), 1_000);
+            assert_eq!(entropy.balance_of(accounts.alice), 600);
+            assert_eq!(entropy.balance_of(accounts.bob), 400);
+
+            // Redeemed tokens can be issued again, up to the cap only
+            assert_eq!(entropy.redeem(300, Hash::from([0x01; 32])), Ok(1));
+            assert_eq!(entropy.total_supply(), 700);
+            assert_eq!(entropy.issue(301), Err(Error::SupplyCapExceeded));
+            assert_eq!(entropy.issue_to(accounts.bob, 300), Ok(()));
+            assert_eq!(entropy.total_supply(), 1_000);
+            assert_eq!(entropy.issue(1), Err(Error::SupplyCapExceeded));
+            assert_eq!(entropy.max_supply(), Some(1_000));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "SupplyCapExceeded")]
+        fn initial_supply_over_cap_reverts_deployment() {
+            Entropy::construct_with_config(1_001, "Entropy Coin".into(), "ENT".into(), 6, ContractConfig { max_supply: Some(1_000), ..Default::default() });
+        }
+
+        #[ink::test]
+        fn redeem_works() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100);
+
+            // Transfer event triggered during initial construction.
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.balance_of(accounts.alice), 100);
+
+            // Redeem 50 tokens
+            assert_eq!(entropy.redeem(50, Hash::from([0x01; 32])), Ok(1));
+
+            // Check total supply
+            assert_eq!(entropy.total_supply(), 50);
+
+            // Check Alice's new balance
+            assert_eq!(entropy.balance_of(accounts.alice), 50);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 3);
+
+            // Check first transfer event related to Entropy instantiation.
+            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
+            // Check second Redeem event
+            assert_redeem_event(&emitted_events[2], 50);
+
+            // Redeeming nothing fails without issuing a receipt
+            assert_eq!(entropy.redeem(0, Hash::from([0x02; 32])), Err(Error::ZeroAmount));
+            assert_eq!(entropy.redemption_receipt(2), None);
+        }
+
+        #[ink::test]
+        fn account_private_works() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100);
+
+            // Transfer event triggered during initial construction.
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.is_account_private(accounts.bob), false);
+
+            // Set Bob as private
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+
+            // Check Bob's privateness
+            assert_eq!(entropy.is_account_private(accounts.bob), true);
+
+            // Set Bob's privateness back
+            assert_eq!(entropy.set_account_private(accounts.bob, false), Ok(()));
+
+            // Check Bob's privateness again
+            assert_eq!(entropy.is_account_private(accounts.bob), false);
+
+            // Check events
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 4);
+
+            // Check first transfer event related to Entropy instantiation.
+            assert_transfer_event(&emitted_events[0], None, Some(accounts.alice), 100);
+            // Check 2nd and 3rd Privacy event
+            assert_privacy_event(&emitted_events[2], accounts.bob, true);
+            assert_privacy_event(&emitted_events[3], accounts.bob, false);
+        }
+
+        #[ink::test]
+        fn private_balances_are_hidden() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.charlie, 40), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+
+            // Other accounts read zero
+            set_caller(accounts.django);
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 0);
+            assert_eq!(entropy.balance_of(accounts.alice), 900);
+
+            // The account itself, the spender of the allowance and the owner see the real values
+            set_caller(accounts.bob);
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 40);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 40);
+            set_caller(accounts.alice);
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 40);
+
+            // Only the owner can read balances unchecked
+            assert_eq!(entropy.balance_of_unchecked(accounts.bob), Some(100));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.balance_of_unchecked(accounts.bob), None);
+
+            // Hiding the balance doesn't affect transfers
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.charlie, 40), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 40);
+            set_caller(accounts.alice);
+            assert_eq!(entropy.balance_of(accounts.bob), 60);
+
+            // Making the account public again reveals it
+            assert_eq!(entropy.set_account_private(accounts.bob, false), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.balance_of(accounts.bob), 60);
+        }
+
+        #[ink::test]
+        fn self_privacy_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.privacy_source_of(accounts.bob), None);
+
+            // Accounts toggle their own privacy
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_my_privacy(true), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.bob), true);
+            assert_eq!(entropy.privacy_source_of(accounts.bob), Some(PrivacySource::User));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_my_privacy(false), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.bob), false);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_privacy_event(&emitted_events[emitted_events.len() - 2], accounts.bob, true);
+            assert_privacy_event(&emitted_events[emitted_events.len() - 1], accounts.bob, false);
+
+            // The owner's choice overrides the account's
+            assert_eq!(entropy.set_my_privacy(true), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_account_private(accounts.bob, false), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.bob), false);
+            assert_eq!(entropy.privacy_source_of(accounts.bob), Some(PrivacySource::Owner));
+
+            // An account made public by the owner may still hide itself
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_my_privacy(true), Ok(()));
+            assert_eq!(entropy.privacy_source_of(accounts.bob), Some(PrivacySource::User));
+
+            // But can't touch its privacy once the owner forced it private
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_my_privacy(false), Err(Error::PrivacyForced));
+            assert_eq!(entropy.set_my_privacy(true), Err(Error::PrivacyForced));
+            assert_eq!(entropy.is_account_private(accounts.bob), true);
+            assert_eq!(entropy.privacy_source_of(accounts.bob), Some(PrivacySource::Owner));
+
+            // Until the owner lifts it
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_account_private(accounts.bob, false), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_my_privacy(true), Ok(()));
+            assert_eq!(entropy.set_my_privacy(false), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.bob), false);
+
+            // The owner's account can't be private
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_my_privacy(true), Err(Error::InvalidAccount));
+        }
+
+        #[ink::test]
+        fn default_privacy_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.set_default_private_for_new_accounts(true), Ok(()));
+            assert_eq!(entropy.default_private_for_new_accounts(), true);
+            assert_eq!(entropy.set_account_private(accounts.eve, false), Ok(()));
+
+            // First-time recipients are made private, existing holders and explicit choices are kept
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.eve, 100, None), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.bob), false);
+            assert_eq!(entropy.is_account_private(accounts.charlie), true);
+            assert_eq!(entropy.is_account_private(accounts.eve), false);
+
+            // Zero-value transfers don't count as receiving tokens
+            assert_eq!(entropy.transfer(accounts.django, 0, None), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.django), false);
+
+            // Repeat recipients aren't re-flagged
+            assert_eq!(entropy.set_account_private(accounts.charlie, false), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.charlie), false);
+
+            // Nor are first-time recipients once the policy is off
+            assert_eq!(entropy.set_default_private_for_new_accounts(false), Ok(()));
+            assert_eq!(entropy.transfer(accounts.django, 100, None), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.django), false);
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_default_private_for_new_accounts(true), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn auditor_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.charlie, 40), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+            assert_eq!(entropy.add_auditor(accounts.eve), Ok(()));
+            assert_eq!(entropy.is_auditor(accounts.eve), true);
+
+            // Other accounts only see redacted data, except the spender its own allowance
+            set_caller(accounts.django);
+            assert_eq!(entropy.private_balance_of(accounts.bob), None);
+            assert_eq!(entropy.private_allowance(accounts.bob, accounts.charlie), None);
+            assert_eq!(entropy.account_info(accounts.bob), None);
+            assert_eq!(entropy.private_balance_of(accounts.alice), Some(900));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.private_allowance(accounts.bob, accounts.charlie), Some(40));
+
+            // The auditor sees real data
+            set_caller(accounts.eve);
+            assert_eq!(entropy.private_balance_of(accounts.bob), Some(100));
+            assert_eq!(entropy.private_allowance(accounts.bob, accounts.charlie), Some(40));
+            assert_eq!(entropy.account_info(accounts.bob), Some(AccountInfo {
+                balance: 100,
+                sent: 0,
+                received: 100,
+                private: true,
+                blacklisted: false,
+                frozen_until: None
+            }));
+
+            // But can't mutate anything
+            assert_eq!(entropy.set_account_private(accounts.bob, false), Err(Error::PermissionDenied));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Err(Error::PermissionDenied));
+            assert_eq!(entropy.add_auditor(accounts.django), Err(Error::PermissionDenied));
+            assert_eq!(entropy.remove_auditor(accounts.eve), Err(Error::PermissionDenied));
+
+            // And loses access once removed
+            set_caller(accounts.alice);
+            assert_eq!(entropy.remove_auditor(accounts.eve), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.private_balance_of(accounts.bob), None);
+            assert_eq!(entropy.account_info(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn account_flags_are_independent() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let flags = |entropy: &Entropy, account: AccountId| {
+                let status = entropy.account_status(account);
+                (status.private, status.blacklisted, status.frozen, status.fee_exempt)
+            };
+
+            // A never-touched account has all flags off
+            assert_eq!(flags(&entropy, accounts.django), (false, false, false, false));
+            assert_eq!(entropy.flags(accounts.django), AccountFlags::default());
+
+            // Setting each flag in turn keeps the ones set before
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (true, false, false, false));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (true, true, false, false));
+            assert_eq!(entropy.freeze_account(accounts.bob), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (true, true, true, false));
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, true), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (true, true, true, true));
+
+            // Clearing each flag in turn keeps the others
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (true, false, true, true));
+            assert_eq!(entropy.set_account_private(accounts.bob, false), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (false, false, true, true));
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, false), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (false, false, true, false));
+            assert_eq!(entropy.unfreeze_account(accounts.bob), Ok(()));
+            assert_eq!(flags(&entropy, accounts.bob), (false, false, false, false));
+
+            // Once all flags are off the entry is dropped
+            assert_eq!(entropy.account_flags.get(&accounts.bob), None);
+            assert_eq!(flags(&entropy, accounts.django), (false, false, false, false));
+        }
+
+        #[ink::test]
+        fn account_status_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+            assert_eq!(entropy.set_fee_exempt(accounts.bob, true), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.freeze_account_until(accounts.charlie, 100), Ok(()));
+
+            let bob = AccountStatus {
+                balance: 100,
+                private: true,
+                blacklisted: false,
+                fee_exempt: true,
+                frozen: false
+            };
+            let charlie = AccountStatus {
+                balance: 200,
+                private: false,
+                blacklisted: true,
+                fee_exempt: false,
+                frozen: true
+            };
+            assert_eq!(entropy.account_status(accounts.bob), bob);
+            assert_eq!(entropy.account_status(accounts.charlie), charlie);
+            assert_eq!(entropy.account_status(accounts.django), AccountStatus {
+                balance: 0,
+                private: false,
+                blacklisted: false,
+                fee_exempt: false,
+                frozen: false
+            });
+
+            // Other callers see the flags of a private account but not its balance
+            set_caller(accounts.django);
+            let hidden_bob = AccountStatus { balance: 0, ..bob };
+            assert_eq!(entropy.account_status(accounts.bob), hidden_bob);
+            assert_eq!(
+                entropy.accounts_status(vec![accounts.charlie, accounts.bob, accounts.charlie]),
+                Ok(vec![charlie, hidden_bob, charlie])
+            );
+            assert_eq!(entropy.accounts_status(Vec::new()), Ok(Vec::new()));
+            assert_eq!(entropy.accounts_status(vec![accounts.bob; MAX_BATCH_SIZE + 1]), Err(Error::BatchTooLarge));
+        }
+
+        #[ink::test]
+        fn blacklist_works() {
+            // Constructor works.
+            let mut entropy = Entropy::new(100);
+
+            // Transfer event triggered during initial construction.
+            let accounts =
+                ink_env::test::default_accounts::<Environment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(entropy.is_account_blacklisted(accounts.alice), false);
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), false);
+
+            // Alice transfers 10 tokens to bob
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+
+            // Destroying bob's funds should fail
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::AccountNotBlackListed));
+
+            // Add bob to blacklist
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+
+            // Assert bob is on blacklist
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), true);
+            assert_eq!(entropy.blacklist_entry(accounts.bob), Some(BlacklistEntry {
+                since: 0,
+                reason: "sanctioned".into()
+            }));
+
+            // Bob should be forbidden to transfer tokens
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.charlie, 10), Err(Error::AccountBlackListed));
+
+            // Destroying bob's funds should now succeed
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
+
+            // Assert totol supply
+            assert_eq!(entropy.total_supply(), 90);
+
+            // Remove bob from blacklist
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), Ok(()));
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), false);
+            assert_eq!(entropy.blacklist_entry(accounts.bob), None);
+
+            // Check events
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 8);
+            assert_transfer_event(&emitted_events[0], None, Some(accounts.alice), 100);
+            assert_new_holder_event(&emitted_events[2], accounts.bob);
+            assert_transfer_event(&emitted_events[3], Some(accounts.alice), Some(accounts.bob), 10);
+            assert_added_blacklist_event(&emitted_events[4], accounts.bob, "sanctioned");
+            assert_transfer_event(&emitted_events[5], Some(accounts.bob), None, 10);
+            assert_destroyed_black_funds_event(&emitted_events[6], accounts.bob, 10);
+            assert_removed_blacklist_event(&emitted_events[7], accounts.bob);
+        }
+
+        #[ink::test]
+        fn blacklist_entry_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.blacklist_entry(accounts.bob), None);
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            let since = ink_env::block_timestamp::<Environment>().expect("Cannot get block timestamp");
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "OFAC SDN list".into()), Ok(()));
+            assert_eq!(entropy.blacklist_entry(accounts.bob), Some(BlacklistEntry {
+                since,
+                reason: "OFAC SDN list".into()
+            }));
+
+            // Blacklisting again replaces the reason only
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "court order".into()), Ok(()));
+            assert_eq!(entropy.blacklist_entry(accounts.bob), Some(BlacklistEntry {
+                since,
+                reason: "court order".into()
+            }));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_added_blacklist_event(&emitted_events[emitted_events.len() - 1], accounts.bob, "court order");
+
+            // Removal forgets the entry, so blacklisting again starts over
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), Ok(()));
+            assert_eq!(entropy.blacklist_entry(accounts.bob), None);
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "".into()), Ok(()));
+            assert!(entropy.blacklist_entry(accounts.bob).unwrap().since > since);
+        }
+
+        #[ink::test]
+        fn blacklisted_accounts_pagination_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            assert_eq!(entropy.blacklisted_count(), 0);
+            assert_eq!(entropy.blacklisted_accounts(0, 10), Vec::new());
+
+            let blacklist = vec![accounts.bob, accounts.charlie, accounts.django, accounts.eve, accounts.frank];
+            for account in blacklist.iter() {
+                assert_eq!(entropy.add_account_to_blacklist(*account, "sanctioned".into()), Ok(()));
+            }
+            // Blacklisting again keeps a single position
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.blacklisted_count(), 5);
+            assert_eq!(entropy.blacklisted_accounts(0, 10), blacklist);
+
+            // Frank moves into Django's position
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.django), Ok(()));
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.django), Ok(()));
+            assert_eq!(entropy.blacklisted_count(), 4);
+            let mut pages = entropy.blacklisted_accounts(0, 3);
+            assert_eq!(pages.len(), 3);
+            pages.extend(entropy.blacklisted_accounts(3, 3));
+            assert_eq!(pages, vec![accounts.bob, accounts.charlie, accounts.frank, accounts.eve]);
+            assert_eq!(entropy.blacklisted_accounts(4, 3), Vec::new());
+            assert_eq!(entropy.blacklisted_accounts(u32::MAX, u32::MAX), Vec::new());
+
+            // Removing the last account and moving the new last one stay consistent
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.eve), Ok(()));
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), Ok(()));
+            assert_eq!(entropy.blacklisted_accounts(0, 10), vec![accounts.frank, accounts.charlie]);
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.frank), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.django, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.blacklisted_accounts(0, 10), vec![accounts.charlie, accounts.django]);
+        }
+
+        #[ink::test]
+        fn holders_pagination_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            assert_eq!(entropy.holders_count(), 1);
+            assert_eq!(entropy.holders(0, 10), vec![accounts.alice]);
+
+            for account in [accounts.bob, accounts.charlie, accounts.django].iter() {
+                assert_eq!(entropy.transfer(*account, 100, None), Ok(()));
+            }
+            assert_eq!(entropy.holders(0, 10), vec![accounts.alice, accounts.bob, accounts.charlie, accounts.django]);
+
+            // Bob sends everything to Eve: Django moves into Bob's position and Eve is appended
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.eve, 100, None), Ok(()));
+            assert_eq!(entropy.holders_count(), 4);
+            assert_eq!(entropy.holders(0, 10), vec![accounts.alice, accounts.django, accounts.charlie, accounts.eve]);
+
+            // Charlie drops out between two pages, Eve takes his position
+            let mut pages = entropy.holders(0, 2);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 100, None), Ok(()));
+            pages.extend(entropy.holders(2, 2));
+            assert_eq!(pages, vec![accounts.alice, accounts.django, accounts.eve]);
+            assert_eq!(entropy.holders(3, 2), Vec::new());
+            assert_eq!(entropy.holders(u32::MAX, u32::MAX), Vec::new());
+
+            // Refunding Bob adds him back once
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.holders(0, 10), vec![accounts.alice, accounts.django, accounts.eve, accounts.bob]);
+
+            // Alice empties her account, then the fee leg of Eve's transfer makes her a holder again
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.frank, 700, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.holders(0, 10), vec![accounts.bob, accounts.django, accounts.eve, accounts.frank]);
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.alice), 1);
+            assert_eq!(entropy.holders(0, 10), vec![accounts.bob, accounts.django, accounts.frank, accounts.alice]);
+            assert_eq!(entropy.holders_count() as u64, entropy.global_stats().holder_count);
+        }
+
+        #[ink::test]
+        fn batch_blacklist_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            let blacklisted_at = entropy.destroyable_at(accounts.bob);
+
+            // Bob is already blacklisted, Charlie is listed twice
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            let batch = vec![accounts.bob, accounts.charlie, accounts.django, accounts.charlie];
+            assert_eq!(entropy.add_accounts_to_blacklist(batch, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), true);
+            assert_eq!(entropy.is_account_blacklisted(accounts.charlie), true);
+            assert_eq!(entropy.is_account_blacklisted(accounts.django), true);
+            assert_eq!(entropy.destroyable_at(accounts.bob), blacklisted_at);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 3);
+            assert_added_blacklist_event(&emitted_events[emitted_events_before], accounts.bob, "sanctioned");
+            assert_added_blacklist_event(&emitted_events[emitted_events_before + 1], accounts.charlie, "sanctioned");
+            assert_added_blacklist_event(&emitted_events[emitted_events_before + 2], accounts.django, "sanctioned");
+
+            // A batch with a protected or zero account blacklists none
+            assert_eq!(entropy.add_accounts_to_blacklist(vec![accounts.eve, accounts.alice], "sanctioned".into()), Err(Error::ProtectedAccount));
+            assert_eq!(entropy.add_accounts_to_blacklist(vec![accounts.eve, AccountId::from([0x0; 32])], "sanctioned".into()), Err(Error::InvalidAccount));
+            assert_eq!(entropy.is_account_blacklisted(accounts.eve), false);
+
+            // Eve isn't blacklisted, Bob is listed twice
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            let batch = vec![accounts.bob, accounts.eve, accounts.charlie, accounts.bob];
+            assert_eq!(entropy.remove_accounts_from_blacklist(batch), Ok(()));
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), false);
+            assert_eq!(entropy.is_account_blacklisted(accounts.charlie), false);
+            assert_eq!(entropy.is_account_blacklisted(accounts.django), true);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 3);
+            assert_removed_blacklist_event(&emitted_events[emitted_events_before], accounts.bob);
+            assert_removed_blacklist_event(&emitted_events[emitted_events_before + 1], accounts.eve);
+            assert_removed_blacklist_event(&emitted_events[emitted_events_before + 2], accounts.charlie);
+
+            // Empty batches are no-ops
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.add_accounts_to_blacklist(Vec::new(), "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.remove_accounts_from_blacklist(Vec::new()), Ok(()));
+            assert_eq!(ink_env::test::recorded_events().count(), emitted_events_before);
+
+            let oversized = vec![accounts.bob; MAX_BATCH_SIZE + 1];
+            assert_eq!(entropy.add_accounts_to_blacklist(oversized.clone(), "sanctioned".into()), Err(Error::BatchTooLarge));
+            assert_eq!(entropy.remove_accounts_from_blacklist(oversized), Err(Error::BatchTooLarge));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.add_accounts_to_blacklist(vec![accounts.eve], "sanctioned".into()), Err(Error::PermissionDenied));
+            assert_eq!(entropy.remove_accounts_from_blacklist(vec![accounts.django]), Err(Error::PermissionDenied));
+        }
+
+        #[ink::test]
+        fn destroy_black_funds_partial_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.destroy_black_funds_partial(accounts.bob, 30), Err(Error::AccountNotBlackListed));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.destroy_black_funds_partial(accounts.bob, 30), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.destroy_black_funds_partial(accounts.bob, 101), Err(Error::InsufficientBalance));
+
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.destroy_black_funds_partial(accounts.bob, 30), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 70);
+            assert_eq!(entropy.total_supply(), 970);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 2);
+            assert_transfer_event(&emitted_events[emitted_events_before], Some(accounts.bob), None, 30);
+            assert_destroyed_black_funds_event(&emitted_events[emitted_events_before + 1], accounts.bob, 30);
+
+            // The rest of the balance can be seized later
+            assert_eq!(entropy.destroy_black_funds_partial(accounts.bob, 70), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.total_supply(), 900);
+        }
+
+        #[ink::test]
+        fn seize_black_funds_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let treasury = accounts.frank;
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            assert_eq!(entropy.seize_black_funds(accounts.bob, treasury), Err(Error::AccountNotBlackListed));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.eve, "sanctioned".into()), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.seize_black_funds(accounts.bob, treasury), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.seize_black_funds(accounts.bob, AccountId::from([0x0; 32])), Err(Error::InvalidAccount));
+            assert_eq!(entropy.seize_black_funds(accounts.bob, accounts.bob), Err(Error::InvalidAccount));
+            assert_eq!(entropy.seize_black_funds(accounts.bob, accounts.eve), Err(Error::AccountBlackListed));
+
+            // Seizing moves the funds into the treasury and keeps the supply
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.seize_black_funds(accounts.bob, treasury), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.balance_of(treasury), 100);
+            assert_eq!(entropy.total_supply(), 1_000);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_new_holder_event(&emitted_events[emitted_events_before], treasury);
+            assert_transfer_event(&emitted_events[emitted_events_before + 1], Some(accounts.bob), Some(treasury), 100);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events_before + 2].data[..])
                 .expect("encountered invalid contract event data buffer");
-            if let Event::TransactionFailed(TransactionFailed { error }) = decoded_event {
-                assert_eq!(error, error, "encountered invalid TransactionFailed.error");
+            if let Event::SeizedBlackFunds(SeizedBlackFunds { account, treasury: to, funds }) = decoded_event {
+                assert_eq!((account, to, funds), (accounts.bob, treasury, 100));
             } else {
-                panic!("encountered unexpected event kind: expected a TransactionFailed event")
+                panic!("encountered unexpected event kind: expected a SeizedBlackFunds event")
             }
 
-            let expected_topics = vec![
-                encoded_into_hash(&PrefixedValue {
-                    value: b"Entropy::TransactionFailed",
-                    prefix: b"",
-                }),
-                encoded_into_hash(&PrefixedValue {
-                    prefix: b"Entropy::TransactionFailed::error",
-                    value: &expected_error,
-                })
-            ];
-            for (n, (actual_topic, expected_topic)) in
-                event.topics.iter().zip(expected_topics).enumerate()
-            {
-                let topic = actual_topic
-                    .decode::<Hash>()
-                    .expect("encountered invalid topic encoding");
-                assert_eq!(topic, expected_topic, "encountered invalid topic at {}", n);
-            }
+            // Destroying burns the funds and reduces the supply
+            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+            assert_eq!(entropy.balance_of(treasury), 100);
+            assert_eq!(entropy.total_supply(), 900);
         }
 
-        /// The default constructor does its job.
         #[ink::test]
-        fn new_works() {
-            // Constructor works.
-            let _entropy = Entropy::new(100);
+        fn force_transfer_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.set_params(20, 1_000), Ok(()));
+            assert_eq!(entropy.freeze_account(accounts.bob), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.charlie, 10), Err(Error::PermissionDenied));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.charlie, 10), Err(Error::PermissionDenied));
+
+            // Blacklisted destinations and protected sources are refused
+            set_caller(accounts.alice);
+            assert_eq!(entropy.add_account_to_blacklist(accounts.eve, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.eve, 10), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.force_transfer(accounts.alice, accounts.charlie, 10), Err(Error::ProtectedAccount));
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.bob, 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.charlie, 101), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+
+            // The frozen balance moves without a fee
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.charlie, 60), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 40);
+            assert_eq!(entropy.balance_of(accounts.charlie), 60);
+            assert_eq!(entropy.balance_of(accounts.alice), 900);
 
-            // Transfer event triggered during initial construction.
             let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(1, emitted_events.len());
+            assert_eq!(emitted_events.len(), emitted_events_before + 3);
+            assert_new_holder_event(&emitted_events[emitted_events_before], accounts.charlie);
+            assert_transfer_event(&emitted_events[emitted_events_before + 1], Some(accounts.bob), Some(accounts.charlie), 60);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events_before + 2].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::ForcedTransfer(ForcedTransfer { operator, from, to, value }) = decoded_event {
+                assert_eq!((operator, from, to, value), (accounts.alice, accounts.bob, accounts.charlie, 60));
+            } else {
+                panic!("encountered unexpected event kind: expected a ForcedTransfer event")
+            }
 
-            assert_transfer_event(
-                &emitted_events[0],
-                None,
-                Some(AccountId::from([0x01; 32])),
-                100,
-            );
+            // Blacklisted sources go through seizure instead
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.force_transfer(accounts.bob, accounts.charlie, 40), Err(Error::AccountBlackListed));
         }
 
         #[ink::test]
-        fn default_works() {
-            let entropy = Entropy::default();
+        fn destroy_black_funds_batch_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.django, 300, None), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.eve, "sanctioned".into()), Ok(()));
+
+            // Django isn't blacklisted and Eve holds no funds
+            let batch = vec![accounts.bob, accounts.django, accounts.charlie, accounts.eve];
+            assert_eq!(entropy.destroy_black_funds_batch(batch), Ok(vec![accounts.django, accounts.eve]));
+            assert_eq!(entropy.total_supply(), 700);
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+            assert_eq!(entropy.balance_of(accounts.django), 300);
 
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(1, emitted_events.len());
+            let oversized = vec![accounts.bob; MAX_BATCH_SIZE + 1];
+            assert_eq!(entropy.destroy_black_funds_batch(oversized), Err(Error::BatchTooLarge));
 
-            // default values
-            let default_decimals = 6;
-            let default_initial_supply :u128 = u128::pow(10, default_decimals) * 1_000_000;
-            let default_name = "Entropy Coin";
-            let default_symbol = "ENT";
+            set_caller(accounts.bob);
+            assert_eq!(entropy.destroy_black_funds_batch(vec![accounts.charlie]), Err(Error::PermissionDenied));
+        }
 
-            assert_transfer_event(
-                &emitted_events[0],
-                None,
-                Some(AccountId::from([0x01; 32])),
-                default_initial_supply,
-            );
-            
-            assert_eq!(entropy.total_supply(), default_initial_supply);
-            assert_eq!(entropy.name(), default_name);
-            assert_eq!(entropy.symbol(), default_symbol);
-            assert_eq!(entropy.decimals(), default_decimals);
+        #[ink::test]
+        fn permission_check_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Assert owner is alice
+            assert_eq!(entropy.owner(), accounts.alice);
+
+            // Get contract address.
+            let callee = ink_env::account_id::<Environment>().unwrap_or([0x0; 32].into());
+
+            // Create call.
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&accounts.bob);
+
+            // Push the new execution context to set Bob as caller.
+            ink_env::test::push_execution_context::<Environment>(accounts.bob, callee, 1000000, 1000000, data);
+
+            // Bob should not have the permission to call privileged apis
+            assert_eq!(entropy.propose_owner(accounts.charlie), Err(Error::PermissionDenied));
+            assert_eq!(entropy.accept_ownership(), Err(Error::PermissionDenied));
+            assert_eq!(entropy.issue(100), Err(Error::PermissionDenied));
+            assert_eq!(entropy.redeem(100, Hash::from([0x01; 32])), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_params(10, 50), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_account_private(accounts.charlie, true), Err(Error::PermissionDenied));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Err(Error::PermissionDenied));
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.charlie), Err(Error::PermissionDenied));
+            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Err(Error::PermissionDenied));
+            assert_eq!(entropy.freeze_account_until(accounts.charlie, 100), Err(Error::PermissionDenied));
+            assert_eq!(entropy.unfreeze_account(accounts.charlie), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_velocity_rule(2, 20, 0), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_velocity_exempt(accounts.charlie, true), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_dispute_window(10), Err(Error::PermissionDenied));
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), Err(Error::PermissionDenied));
+            assert_eq!(entropy.rekey_account(accounts.charlie, accounts.django), Err(Error::PermissionDenied));
+            assert_eq!(entropy.enable_trading(), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_prelaunch_allowed(accounts.charlie, true), Err(Error::PermissionDenied));
+            assert_eq!(entropy.protect_account(accounts.charlie), Err(Error::PermissionDenied));
+            assert_eq!(entropy.unprotect_account(accounts.charlie), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_points_program(true, 100, true, 10, 100), Err(Error::PermissionDenied));
+            assert_eq!(entropy.convert_points(2), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_circuit_breaker(1000), Err(Error::PermissionDenied));
+            assert_eq!(entropy.set_system_account(accounts.charlie, true), Err(Error::PermissionDenied));
+            assert_eq!(entropy.unpause(), Err(Error::PermissionDenied));
+
+            // Transfer ownership to bob
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&accounts.bob);
+            ink_env::test::push_execution_context::<Environment>(accounts.alice, callee, 1000000, 1000000, data);
+            assert_eq!(entropy.propose_owner(accounts.bob), Ok(()));
+
+            // Now bob accepts and is new owner, should have permission to call privileged apis
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&accounts.bob);
+            ink_env::test::push_execution_context::<Environment>(accounts.bob, callee, 1000000, 1000000, data);
+            assert_eq!(entropy.accept_ownership(), Ok(()));
+            assert_eq!(entropy.owner(), accounts.bob);
+            assert_eq!(entropy.issue(100), Ok(()));
+            assert_eq!(entropy.redeem(100, Hash::from([0x01; 32])), Ok(1));
+            assert_eq!(entropy.set_params(10, 50), Ok(()));
+            assert_eq!(entropy.set_account_private(accounts.charlie, true), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Ok(()));
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.charlie), Ok(()));
+            assert_eq!(entropy.freeze_account_until(accounts.charlie, 100), Ok(()));
+            assert_eq!(entropy.unfreeze_account(accounts.charlie), Ok(()));
+            assert_eq!(entropy.set_velocity_rule(2, 20, 0), Ok(()));
+            assert_eq!(entropy.set_velocity_exempt(accounts.charlie, true), Ok(()));
+            assert_eq!(entropy.set_dispute_window(10), Ok(()));
+            assert_eq!(entropy.rekey_account(accounts.charlie, accounts.django), Ok(()));
+            assert_eq!(entropy.enable_trading(), Ok(()));
+            assert_eq!(entropy.set_prelaunch_allowed(accounts.charlie, true), Ok(()));
+            assert_eq!(entropy.set_circuit_breaker(1000), Ok(()));
+            assert_eq!(entropy.set_system_account(accounts.charlie, true), Ok(()));
+            assert_eq!(entropy.unpause(), Ok(()));
+            assert_eq!(entropy.protect_account(accounts.charlie), Ok(()));
+            assert_eq!(entropy.unprotect_account(accounts.charlie), Ok(()));
+            assert_eq!(entropy.set_points_program(true, 100, true, 10, 100), Ok(()));
+            assert_eq!(entropy.convert_points(2), Ok(()));
         }
 
-        /// The total supply was applied.
         #[ink::test]
-        fn total_supply_works() {
-            // Constructor works.
-            let entropy = Entropy::new(100);
-            // Transfer event triggered during initial construction.
+        fn launch_guard_works() {
+            let mut entropy = Entropy::construct_with_config(100, "Entropy Coin".into(), "ENT".into(), 6, ContractConfig { trading_enabled: false, ..Default::default() });
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.trading_enabled(), false);
+
+            // The owner can fund accounts before launch
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 20, None), Ok(()));
+
+            // Ordinary accounts cannot send yet, neither directly nor through an allowance
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.eve, 10, None), Err(Error::TradingNotEnabled));
+            assert_eq!(entropy.approve(accounts.django, 10), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer_from(accounts.charlie, accounts.eve, 10), Err(Error::TradingNotEnabled));
+            assert_eq!(entropy.allowance(accounts.charlie, accounts.django), 10);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.eve, 10, None), Err(Error::TradingNotEnabled));
+            assert_eq!(entropy.balance_of(accounts.eve), 0);
+
+            // Allowlisted accounts, e.g. a vesting contract, can send before launch
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_prelaunch_allowed(accounts.bob, true), Ok(()));
+            assert_eq!(entropy.is_prelaunch_allowed(accounts.bob), true);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.eve, 10, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.eve), 10);
+
+            // Enabling trading opens transfers to everyone
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            set_caller(accounts.alice);
+            assert_eq!(entropy.enable_trading(), Ok(()));
+            assert_eq!(entropy.trading_enabled(), true);
             let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_transfer_event(
-                &emitted_events[0],
-                None,
-                Some(AccountId::from([0x01; 32])),
-                100,
-            );
-            // Get the token total supply.
-            assert_eq!(entropy.total_supply(), 100);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TradingEnabled(TradingEnabled { block }) = decoded_event {
+                assert_eq!(block, 1, "encountered invalid TradingEnabled.block");
+            } else {
+                panic!("encountered unexpected event kind: expected a TradingEnabled event")
+            }
+
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer_from(accounts.charlie, accounts.eve, 10), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.eve), 20);
+
+            // Enabling again is a no-op, trading stays enabled
+            set_caller(accounts.alice);
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.enable_trading(), Ok(()));
+            assert_eq!(entropy.trading_enabled(), true);
+            assert_eq!(ink_env::test::recorded_events().count(), emitted_events_before);
         }
 
-        /// Get the actual balance of an account.
         #[ink::test]
-        fn balance_of_works() {
-            // Constructor works
-            let entropy = Entropy::new(100);
-            // Transfer event triggered during initial construction
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_transfer_event(
-                &emitted_events[0],
-                None,
-                Some(AccountId::from([0x01; 32])),
-                100,
-            );
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
-            // Alice owns all the tokens on deployment
+        fn invalid_accounts_are_rejected() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let zero = AccountId::from([0x0; 32]);
+
+            // Zero account arguments
+            assert_eq!(entropy.transfer(zero, 10, None), Err(Error::InvalidAccount));
+            assert_eq!(entropy.approve(zero, 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.transfer_from(accounts.alice, zero, 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.propose_owner(zero), Err(Error::InvalidAccount));
+            assert_eq!(entropy.set_account_private(zero, true), Err(Error::InvalidAccount));
+            assert_eq!(entropy.add_account_to_blacklist(zero, "sanctioned".into()), Err(Error::InvalidAccount));
+            assert_eq!(entropy.freeze_account_until(zero, 100), Err(Error::InvalidAccount));
+            assert_eq!(entropy.set_velocity_exempt(zero, true), Err(Error::InvalidAccount));
+            assert_eq!(entropy.set_prelaunch_allowed(zero, true), Err(Error::InvalidAccount));
+            assert_eq!(entropy.rekey_to(zero), Err(Error::InvalidAccount));
+            assert_eq!(entropy.rekey_account(accounts.bob, zero), Err(Error::InvalidAccount));
+
+            // Self-referential arguments
+            assert_eq!(entropy.approve(accounts.alice, 10), Err(Error::InvalidAccount));
+            assert_eq!(entropy.propose_owner(accounts.alice), Err(Error::InvalidAccount));
+            assert_eq!(entropy.set_account_private(accounts.alice, true), Err(Error::InvalidAccount));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.alice, "sanctioned".into()), Err(Error::ProtectedAccount));
+            assert_eq!(entropy.freeze_account_until(accounts.alice, 100), Err(Error::ProtectedAccount));
+            assert_eq!(entropy.rekey_to(accounts.alice), Err(Error::InvalidAccount));
+            assert_eq!(entropy.rekey_account(accounts.bob, accounts.bob), Err(Error::InvalidAccount));
+
+            // Nothing changed
+            assert_eq!(entropy.owner(), accounts.alice);
             assert_eq!(entropy.balance_of(accounts.alice), 100);
-            // Bob does not owns tokens
-            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.balance_of(zero), 0);
+            assert_eq!(entropy.allowance(accounts.alice, zero), 0);
+            assert_eq!(entropy.allowance(accounts.alice, accounts.alice), 0);
+            assert_eq!(entropy.is_account_private(accounts.alice), false);
+            assert_eq!(entropy.is_account_blacklisted(accounts.alice), false);
+            assert_eq!(entropy.is_account_frozen(accounts.alice), false);
+
+            // Explicit exceptions: self-transfers, spenders pulling to themselves and cleanup messages
+            assert_eq!(entropy.transfer(accounts.alice, 10, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.alice, 10), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.approve(accounts.bob, 10), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer_from(accounts.alice, accounts.bob, 10), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 10);
+            set_caller(accounts.alice);
+            assert_eq!(entropy.remove_account_from_blacklist(zero), Ok(()));
+            assert_eq!(entropy.unfreeze_account(zero), Ok(()));
         }
 
         #[ink::test]
-        fn transfer_ownership_works() {
-            // Constructor works.
+        fn rekey_works() {
             let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            // Transfer event triggered during initial construction.
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+            // Decorate Bob's account with a balance, flags and allowances
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.set_account_private(accounts.bob, true), Ok(()));
+            assert_eq!(entropy.set_velocity_exempt(accounts.bob, true), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.charlie, 10), Ok(()));
+            assert_eq!(entropy.approve(accounts.django, 20), Ok(()));
+            let emitted_events_before = ink_env::test::recorded_events().count();
 
-            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            // Rekeying to the same account fails
+            assert_eq!(entropy.rekey_to(accounts.bob), Err(Error::InvalidAccount));
 
-            // Assert owner is alice
-            assert_eq!(entropy.owner(), accounts.alice);
+            assert_eq!(entropy.rekey_to(accounts.eve), Ok(()));
 
-            // Transfer ownership to bob
-            assert_eq!(entropy.transfer_ownership(accounts.bob), Ok(()));
+            // The new account holds everything, which being private only it can see
+            assert_eq!(entropy.balance_of(accounts.eve), 0);
+            set_caller(accounts.eve);
+            assert_eq!(entropy.balance_of(accounts.eve), 50);
+            assert_eq!(entropy.is_account_private(accounts.eve), true);
+            assert_eq!(entropy.is_velocity_exempt(accounts.eve), true);
+            assert_eq!(entropy.allowance(accounts.eve, accounts.charlie), 0);
 
-            // Assert new owner is bob
-            assert_eq!(entropy.owner(), accounts.bob);
+            // The old account is empty
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.is_account_private(accounts.bob), false);
+            assert_eq!(entropy.is_velocity_exempt(accounts.bob), false);
+            assert_eq!(entropy.allowance(accounts.bob, accounts.charlie), 0);
+            assert_eq!(entropy.allowance(accounts.bob, accounts.django), 0);
+            assert_eq!(entropy.total_supply(), 100);
+
+            // Two zeroed approvals, the new holder, the balance transfer and the rekey itself
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 5);
+            assert_new_holder_event(&emitted_events[emitted_events_before + 2], accounts.eve);
+            assert_transfer_event(&emitted_events[emitted_events_before + 3], Some(accounts.bob), Some(accounts.eve), 50);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events_before + 4].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::AccountRekeyed(AccountRekeyed { old, new }) = decoded_event {
+                assert_eq!(old, accounts.bob, "encountered invalid AccountRekeyed.old");
+                assert_eq!(new, accounts.eve, "encountered invalid AccountRekeyed.new");
+            } else {
+                panic!("encountered unexpected event kind: expected an AccountRekeyed event")
+            }
         }
 
         #[ink::test]
-        fn transfer_works() {
-            // Constructor works.
+        fn rekey_refuses_blacklisted_accounts() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.django, "sanctioned".into()), Ok(()));
+
+            // Neither the account itself nor the owner can move a blacklisted balance
+            assert_eq!(entropy.rekey_account(accounts.bob, accounts.eve), Err(Error::AccountBlackListed));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.rekey_to(accounts.eve), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.balance_of(accounts.bob), 50);
+
+            // Nor can a balance be moved onto a blacklisted account
+            set_caller(accounts.alice);
+            assert_eq!(entropy.rekey_account(accounts.charlie, accounts.django), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.rekey_to(accounts.django), Err(Error::AccountBlackListed));
+
+            // Both variants work for ordinary accounts
+            assert_eq!(entropy.rekey_account(accounts.charlie, accounts.eve), Ok(()));
+            assert_eq!(entropy.rekey_to(accounts.eve), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.eve), 50);
+        }
+
+        #[ink::test]
+        fn compliance_screens_credited_accounts() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 50_000, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.set_points_program(true, 1_000, true, 15, 100), Ok(()));
+            assert_eq!(entropy.convert_points(3), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            let tx_id = entropy.last_tx_id();
+            assert_eq!(entropy.approve(accounts.charlie, 1_000), Ok(()));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.add_account_to_blacklist(accounts.django, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.freeze_account_until(accounts.eve, 1_000_000), Ok(()));
+
+            // Transfers can't credit blacklisted accounts, frozen accounts still receive
+            assert_eq!(entropy.transfer(accounts.django, 1, None), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.transfer(accounts.eve, 1_000, None), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.django, 1), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.eve, 999), Ok(()));
+
+            // Nor can privileged paths
+            set_caller(accounts.alice);
+            assert_eq!(entropy.rekey_account(accounts.charlie, accounts.django), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.reverse_transfer(tx_id, "disputed".into()), Err(Error::AccountBlackListed));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.claim_points_as_tokens(), Err(Error::AccountBlackListed));
+            assert_eq!(entropy.points_of(accounts.bob), 10);
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), Ok(()));
+            assert_eq!(entropy.reverse_transfer(tx_id, "disputed".into()), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.claim_points_as_tokens(), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.django), 0);
+            assert_eq!(entropy.balance_of(accounts.eve), 999 + 998);
+        }
+
+        #[ink::test]
+        fn reverse_transfer_works() {
             let mut entropy = Entropy::new(100_000_000);
-            // Transfer event triggered during initial construction.
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            assert_eq!(entropy.balance_of(accounts.bob), 0);
-            // Alice transfers 20_000_000 tokens to Bob.
-            assert_eq!(entropy.transfer(accounts.bob, 20_000_000), Ok(()));
-            // Bob owns 20_000_000 tokens.
-            assert_eq!(entropy.balance_of(accounts.bob), 20_000_000);
-            // Alice remains 80_000_000 tokens.
-            assert_eq!(entropy.balance_of(accounts.alice), 80_000_000);
+            assert_eq!(entropy.last_tx_id(), 0);
+            assert_eq!(entropy.dispute_window(), 24 * 60 * 60 * 1000);
 
-            // Set transaction fee
+            // Alice transfers 10_000_000 tokens to Bob with a 10_000 fee
             assert_eq!(entropy.set_params(10, 50_000_000), Ok(()));
-            // Bob transfers 10_000_000 tokens to Charlie. Fee is 10_000_000 * 10 / 10000 = 10_000,
-            // so 9_990_000 tokens transferred to Charlie, 10_000 tokens transferred to Alice, who is the contract owner
-            assert_eq!(entropy.transfer_from_to(accounts.bob, accounts.charlie, 10_000_000), Ok(()));
-            assert_eq!(entropy.balance_of(accounts.bob), 10_000_000);
-            assert_eq!(entropy.balance_of(accounts.charlie), 10_000_000 - 10_000);
-            assert_eq!(entropy.balance_of(accounts.alice), 80_000_000 + 10_000);
-            
+            assert_eq!(entropy.transfer(accounts.bob, 10_000_000, None), Ok(()));
+            // Failed transfers are not assigned an id
+            assert_eq!(entropy.send("transfer", accounts.charlie, accounts.bob, 1), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.last_tx_id(), 1);
+            assert_eq!(entropy.transfer_record(1), Some(TransferRecord {
+                from: accounts.alice,
+                to: accounts.bob,
+                net: 10_000_000 - 10_000,
+                fee: 10_000,
+                block: 0,
+                timestamp: 0,
+                reversed: false
+            }));
+
+            // Unknown ids cannot be reversed
+            assert_eq!(entropy.reverse_transfer(2, "mistake".into()), Err(Error::UnknownTransfer));
+
+            // The net amount moves back, the fee stays with the fee collector
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.balance_of(accounts.alice), 100_000_000);
+            assert_eq!(entropy.transfer_record(1).map(|record| record.reversed), Some(true));
 
             let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 5);
-            // Check first transfer event related to Entropy instantiation.
-            assert_transfer_event(&emitted_events[0], None, Some(accounts.alice), 100_000_000);
-            // Check the second transfer event relating to the actual trasfer.
-            assert_transfer_event(&emitted_events[1], Some(accounts.alice), Some(accounts.bob), 20_000_000);
-            // Check the 4th fee transfer event (3rd event is the Params event)
-            assert_transfer_event(&emitted_events[3], Some(accounts.bob), Some(accounts.alice), 10_000);
-            // Check the 5th transfer event to Charlie
-            assert_transfer_event(&emitted_events[4], Some(accounts.bob), Some(accounts.charlie), 10_000_000 - 10_000);
+            let len = emitted_events.len();
+            assert_transfer_event(&emitted_events[len - 2], Some(accounts.bob), Some(accounts.alice), 10_000_000 - 10_000);
+            assert_transfer_reversed_event(&emitted_events[len - 1], 1, accounts.alice, accounts.bob, 10_000_000 - 10_000, "mistake");
+
+            // Double reversal fails
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), Err(Error::TransferAlreadyReversed));
+            assert_eq!(entropy.balance_of(accounts.alice), 100_000_000);
         }
 
         #[ink::test]
-        fn invalid_transfer_should_fail() {
-            // Constructor works.
+        fn reverse_transfer_window_works() {
             let mut entropy = Entropy::new(100);
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Transfers can be reversed for 10ms, i.e. two blocks
+            assert_eq!(entropy.set_dispute_window(10), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+
+            // Exactly at the end of the window
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), Ok(()));
+
+            // One block past the end of the window
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.reverse_transfer(2, "mistake".into()), Err(Error::DisputeWindowClosed));
+            assert_eq!(entropy.balance_of(accounts.bob), 10);
+        }
 
-            assert_eq!(entropy.balance_of(accounts.bob), 0);
-            // Get contract address.
-            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
-                .unwrap_or([0x0; 32].into());
-            // Create call
-            let mut data =
-                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
-            data.push_arg(&accounts.bob);
-            // Push the new execution context to set Bob as caller
-            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
-                accounts.bob,
-                callee,
-                1000000,
-                1000000,
-                data,
-            );
+        #[ink::test]
+        fn reverse_transfer_requires_recipient_balance() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            // Bob fails to transfers 10 tokens to Eve.
-            assert_eq!(
-                entropy.transfer(accounts.eve, 10),
-                Err(Error::InsufficientBalance)
-            );
-            // Alice owns all the tokens.
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+
+            // Bob forwards part of the funds before the reversal
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 5, None), Ok(()));
+            set_caller(accounts.alice);
+
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.transfer_record(1).map(|record| record.reversed), Some(false));
+            assert_eq!(entropy.balance_of(accounts.bob), 5);
+            assert_eq!(entropy.balance_of(accounts.alice), 90);
+
+            // The forwarded transfer can still be reversed on its own
+            assert_eq!(entropy.reverse_transfer(2, "mistake".into()), Ok(()));
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), Ok(()));
             assert_eq!(entropy.balance_of(accounts.alice), 100);
-            assert_eq!(entropy.balance_of(accounts.bob), 0);
+
+            // Tokens the recipient locked or put on hold can't be clawed back
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.lock_my_tokens(4, 1_000), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.reverse_transfer(3, "mistake".into()), Err(Error::InsufficientBalance));
+
+            assert_eq!(entropy.transfer(accounts.django, 10, None), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.create_hold(accounts.charlie, 4, 1_000), Ok(1));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.reverse_transfer(4, "mistake".into()), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.balance_of(accounts.bob), 10);
+            assert_eq!(entropy.balance_of(accounts.django), 10);
+            assert_eq!(entropy.hold(1).map(|hold| hold.value), Some(4));
+        }
+
+        #[ink::test]
+        fn recent_transfers_are_bounded() {
+            let mut entropy = Entropy::new(10_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            for _ in 0..MAX_RECENT_TRANSFERS + 1 {
+                assert_eq!(entropy.transfer(accounts.bob, 1, None), Ok(()));
+            }
+            assert_eq!(entropy.last_tx_id(), MAX_RECENT_TRANSFERS + 1);
+            assert_eq!(entropy.transfer_record(1), None);
+            assert_eq!(entropy.transfer_record(2).map(|record| record.net), Some(1));
+            assert_eq!(entropy.reverse_transfer(1, "mistake".into()), Err(Error::UnknownTransfer));
+        }
+
+        #[ink::test]
+        fn transfer_with_signature_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.nonce_of(accounts.alice), 0);
+
+            // Alice signs off-chain, eve relays
+            let signature = mock_sign(accounts.alice, entropy.transfer_hash(accounts.alice, accounts.bob, 10, 100, 0));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.bob, 10, 100, 0, signature), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 10);
+            assert_eq!(entropy.nonce_of(accounts.alice), 1);
+
+            // Replays, forgeries and altered payloads are rejected
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.bob, 10, 100, 0, signature), Err(Error::InvalidNonce));
+            let signature = mock_sign(accounts.eve, entropy.transfer_hash(accounts.alice, accounts.eve, 10, 100, 1));
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.eve, 10, 100, 1, signature), Err(Error::InvalidSignature));
+            let signature = mock_sign(accounts.alice, entropy.transfer_hash(accounts.alice, accounts.bob, 10, 100, 1));
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.eve, 10, 100, 1, signature), Err(Error::InvalidSignature));
+
+            // A signed transfer that fails its transfer checks leaves the nonce unused
+            let signature = mock_sign(accounts.alice, entropy.transfer_hash(accounts.alice, accounts.bob, 1_000, 100, 1));
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.bob, 1_000, 100, 1, signature), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.nonce_of(accounts.alice), 1);
+
+            // Past its deadline, timestamp 10 after two blocks
+            let signature = mock_sign(accounts.alice, entropy.transfer_hash(accounts.alice, accounts.bob, 10, 5, 1));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.bob, 10, 5, 1, signature), Err(Error::SignatureExpired));
+
+            assert_eq!(entropy.balance_of(accounts.bob), 10);
             assert_eq!(entropy.balance_of(accounts.eve), 0);
+            assert_eq!(entropy.nonce_of(accounts.alice), 1);
+        }
 
-            // Transfer event triggered during initial construction.
+        #[ink::test]
+        fn permit_shares_nonces_with_signed_transfers() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            set_caller(accounts.eve);
+
+            // Alice signs a permit and a transfer, both with nonce 0
+            let permit_signature = mock_sign(accounts.alice, entropy.permit_hash(accounts.alice, accounts.bob, 50, 100, 0));
+            let transfer_signature = mock_sign(accounts.alice, entropy.transfer_hash(accounts.alice, accounts.charlie, 10, 100, 0));
+
+            // Relaying the permit voids the transfer signed with the same nonce
+            assert_eq!(entropy.permit(accounts.alice, accounts.bob, 50, 100, 0, permit_signature), Ok(()));
+            assert_eq!(entropy.allowance(accounts.alice, accounts.bob), 50);
             let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 2);
-            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Approval(Approval { owner, spender, value }) = decoded_event {
+                assert_eq!((owner, spender, value), (accounts.alice, accounts.bob, 50));
+            } else {
+                panic!("encountered unexpected event kind: expected an Approval event")
+            }
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.charlie, 10, 100, 0, transfer_signature), Err(Error::InvalidNonce));
+
+            // The next transfer takes nonce 1, after which the permit can't be replayed with it
+            let transfer_signature = mock_sign(accounts.alice, entropy.transfer_hash(accounts.alice, accounts.charlie, 10, 100, 1));
+            assert_eq!(entropy.transfer_with_signature(accounts.alice, accounts.charlie, 10, 100, 1, transfer_signature), Ok(()));
+            let permit_signature = mock_sign(accounts.alice, entropy.permit_hash(accounts.alice, accounts.bob, 50, 100, 1));
+            assert_eq!(entropy.permit(accounts.alice, accounts.bob, 50, 100, 1, permit_signature), Err(Error::InvalidNonce));
+            assert_eq!(entropy.permit(accounts.alice, accounts.bob, 50, 100, 0, permit_signature), Err(Error::InvalidNonce));
+            assert_eq!(entropy.nonce_of(accounts.alice), 2);
+            assert_eq!(entropy.balance_of(accounts.charlie), 10);
+        }
+
+        #[ink::test]
+        fn transfer_mining_works() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 50_000, None), Ok(()));
+
+            // Nothing accrues without fees or while the program is inactive
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            assert_eq!(entropy.points_of(accounts.bob), 0);
+
+            // 0.1% fee, 1 point per 1_000 tokens sent, at most 15 points converted per 100ms
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.set_points_program(true, 1_000, true, 15, 100), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 2_500, None), Ok(()));
+            assert_eq!(entropy.points_of(accounts.bob), 12);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 10_000, None), Ok(()));
+            assert_eq!(entropy.points_of(accounts.charlie), 10);
+
+            // Points can't be claimed before the owner sets a rate
+            assert_eq!(entropy.claim_points_as_tokens(), Err(Error::NothingToClaim));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.convert_points(3), Ok(()));
+
+            // Minting at 3 tokens per point
+            set_caller(accounts.bob);
+            let bob_balance = entropy.balance_of(accounts.bob);
+            assert_eq!(entropy.claim_points_as_tokens(), Ok(()));
+            assert_eq!(entropy.points_of(accounts.bob), 0);
+            assert_eq!(entropy.balance_of(accounts.bob), bob_balance + 36);
+            assert_eq!(entropy.total_supply(), 100_036);
+            assert_eq!(entropy.claim_points_as_tokens(), Err(Error::NothingToClaim));
+
+            // Only 3 points are left in this epoch's cap
+            set_caller(accounts.charlie);
+            let charlie_balance = entropy.balance_of(accounts.charlie);
+            assert_eq!(entropy.claim_points_as_tokens(), Ok(()));
+            assert_eq!(entropy.points_of(accounts.charlie), 7);
+            assert_eq!(entropy.balance_of(accounts.charlie), charlie_balance + 9);
+            assert_eq!(entropy.claim_points_as_tokens(), Err(Error::PointsCapExceeded));
+
+            // The cap resets with the next epoch, now paid from a funded pool
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_points_program(true, 1_000, false, 15, 100), Ok(()));
+            let pool = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            assert_eq!(entropy.set_fee_exempt(pool, true), Ok(()));
+            assert_eq!(entropy.transfer(pool, 20, None), Ok(()));
+            for _ in 0..20 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.claim_points_as_tokens(), Err(Error::InsufficientBalance));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(pool, 10, None), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.claim_points_as_tokens(), Ok(()));
+            assert_eq!(entropy.points_of(accounts.charlie), 0);
+            assert_eq!(entropy.balance_of(accounts.charlie), charlie_balance + 30);
+            assert_eq!(entropy.balance_of(pool), 9);
+            assert_eq!(entropy.total_supply(), 100_036 + 9);
         }
 
-        #[ink::test]
-        fn transfer_from_works() {
-            // Constructor works.
-            let mut entropy = Entropy::new(100);
-            // Transfer event triggered during initial construction.
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+        #[ink::test]
+        fn protected_accounts_work() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Ok(()));
+            assert_eq!(entropy.is_protected_account(accounts.alice), true);
+            assert_eq!(entropy.is_protected_account(accounts.bob), false);
+
+            // Bob is blacklisted before becoming a protected treasury account
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.protect_account(accounts.bob), Ok(()));
+            assert_eq!(entropy.is_protected_account(accounts.bob), true);
+
+            for account in &[accounts.alice, accounts.bob] {
+                assert_eq!(entropy.add_account_to_blacklist(*account, "sanctioned".into()), Err(Error::ProtectedAccount));
+                assert_eq!(entropy.freeze_account_until(*account, 100), Err(Error::ProtectedAccount));
+                assert_eq!(entropy.destroy_black_funds(*account), Err(Error::ProtectedAccount));
+                assert_eq!(entropy.rekey_account(*account, accounts.django), Err(Error::ProtectedAccount));
+            }
+            assert_eq!(entropy.balance_of(accounts.alice), 80);
+            assert_eq!(entropy.balance_of(accounts.bob), 10);
+            assert_eq!(entropy.is_account_frozen(accounts.bob), false);
+
+            // The same goes for admin operations signed off-chain
+            let op = AdminOp::FreezeUntil(accounts.bob, 100);
+            let signature = mock_sign(accounts.alice, entropy.admin_op_hash(op.clone(), 0, 100));
+            assert_eq!(entropy.execute_signed_admin_op(op, 0, 100, signature), Err(Error::ProtectedAccount));
+
+            // Ordinary accounts are unaffected
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Ok(()));
+            assert_eq!(entropy.freeze_account_until(accounts.django, 100), Ok(()));
+
+            // Protection is lifted explicitly
+            assert_eq!(entropy.unprotect_account(accounts.bob), Ok(()));
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
 
-            // Bob fails to transfer tokens owned by Alice.
-            assert_eq!(
-                entropy.transfer_from(accounts.alice, accounts.eve, 10),
-                Err(Error::InsufficientAllowance)
-            );
-            // Alice approves Bob for token transfers on her behalf.
-            assert_eq!(entropy.approve(accounts.bob, 10), Ok(()));
+            // The owner can't be unprotected
+            assert_eq!(entropy.unprotect_account(accounts.alice), Ok(()));
+            assert_eq!(entropy.is_protected_account(accounts.alice), true);
+        }
 
-            // The approve event takes place.
-            assert_eq!(ink_env::test::recorded_events().count(), 3);
+        #[ink::test]
+        fn circuit_breaker_works() {
+            let mut entropy = Entropy::new(1000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            // Get contract address.
-            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
-                .unwrap_or([0x0; 32].into());
-            // Create call.
-            let mut data =
-                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
-            data.push_arg(&accounts.bob);
-            // Push the new execution context to set Bob as caller.
-            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
-                accounts.bob,
-                callee,
-                1000000,
-                1000000,
-                data,
-            );
+            assert_eq!(entropy.transfer(accounts.bob, 500, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
 
-            // Bob transfers tokens from Alice to Eve.
-            assert_eq!(
-                entropy.transfer_from(accounts.alice, accounts.eve, 10),
-                Ok(())
-            );
-            // Eve owns tokens.
-            assert_eq!(entropy.balance_of(accounts.eve), 10);
+            // At most 10% of total supply per transfer
+            assert_eq!(entropy.set_circuit_breaker(1000), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 101, None), Err(Error::CircuitBreakerTripped));
+            assert_eq!(entropy.paused(), true);
+            assert_eq!(entropy.balance_of(accounts.bob), 400);
 
-            // Check all transfer events that happened during the previous calls:
             let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 4);
-            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
-            // The last event `emitted_events[3]` is an Approve event that we skip checking.
-            assert_transfer_event(&emitted_events[3], Some(AccountId::from([0x01; 32])), Some(AccountId::from([0x05; 32])), 10);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::CircuitBreakerTripped(CircuitBreakerTripped { from, to, value }) = decoded_event {
+                assert_eq!(from, accounts.bob);
+                assert_eq!(to, accounts.charlie);
+                assert_eq!(value, 101);
+            } else {
+                panic!("encountered unexpected event kind: expected a CircuitBreakerTripped event")
+            }
+
+            // Ordinary transfers fail until the owner unpauses
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Err(Error::ContractPaused));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.approve(accounts.django, 10), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer_from(accounts.charlie, accounts.django, 10), Err(Error::ContractPaused));
+            assert_eq!(entropy.unpause(), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.unpause(), Ok(()));
+            assert_eq!(entropy.paused(), false);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 1, None), Ok(()));
+
+            // The owner and system accounts are exempt
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.django, 300, None), Ok(()));
+            assert_eq!(entropy.set_system_account(accounts.charlie, true), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.eve, 250, None), Ok(()));
+            assert_eq!(entropy.paused(), false);
         }
 
         #[ink::test]
-        fn allowance_must_not_change_on_failed_transfer() {
-            let mut entropy = Entropy::new(100);
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+        fn global_stats_works() {
+            let mut entropy = Entropy::new(1_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.global_stats(), GlobalStats {
+                holder_count: 1,
+                current_supply: 1_000_000,
+                ..GlobalStats::default()
+            });
 
-            // Alice approves Bob for token transfers on her behalf.
-            let alice_balance = entropy.balance_of(accounts.alice);
-            let initial_allowance = alice_balance + 2;
-            assert_eq!(entropy.approve(accounts.bob, initial_allowance), Ok(()));
+            // Two fee-paying transfers and one moving Charlie's whole balance on
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 100_000, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 20_000, None), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 19_980, None), Ok(()));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.issue(5_000), Ok(()));
+            assert_eq!(entropy.redeem(3_000, Hash::from([0x01; 32])), Ok(1));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
 
-            // Get contract address.
-            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
-                .unwrap_or([0x0; 32].into());
-            // Create call.
-            let mut data =
-                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
-            data.push_arg(&accounts.bob);
-            // Push the new execution context to set Bob as caller.
-            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
-                accounts.bob,
-                callee,
-                1000000,
-                1000000,
-                data,
-            );
+            assert_eq!(entropy.global_stats(), GlobalStats {
+                total_transfers: 3,
+                total_volume: 139_980,
+                total_fees_collected: 140,
+                total_burned: 3_000,
+                total_destroyed_black_funds: 99_900,
+                holder_count: 2,
+                current_supply: 902_100
+            });
+        }
 
-            // Bob tries to transfer tokens from Alice to Eve.
-            let emitted_events_before =
-                ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(
-                entropy.transfer_from(accounts.alice, accounts.eve, alice_balance + 1),
-                Err(Error::InsufficientBalance)
-            );
-            // Allowance must have stayed the same
-            assert_eq!(
-                entropy.allowance(accounts.alice, accounts.bob),
-                initial_allowance
-            );
-            // One more failed event has been emitted
-            let emitted_events_after =
-                ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events_before.len() + 1, emitted_events_after.len());
+        #[ink::test]
+        fn top_holders_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.top_holders_limit(), 20);
+            assert_eq!(entropy.top_holders(), vec![(accounts.alice, 1_000)]);
+            assert_eq!(entropy.set_top_holders_limit(3), Ok(()));
+
+            assert_eq!(entropy.transfer(accounts.bob, 300, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.django, 100, None), Ok(()));
+            assert_eq!(entropy.top_holders(), vec![(accounts.alice, 400), (accounts.bob, 300), (accounts.charlie, 200)]);
+
+            // Transfers below the floor leave the leaderboard alone
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer(accounts.eve, 50, None), Ok(()));
+            assert_eq!(entropy.top_holders(), vec![(accounts.alice, 400), (accounts.bob, 300), (accounts.charlie, 200)]);
+
+            // Members move within the leaderboard
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 250, None), Ok(()));
+            assert_eq!(entropy.top_holders(), vec![(accounts.charlie, 450), (accounts.alice, 400), (accounts.bob, 50)]);
+
+            // Larger outsiders enter and push the smallest member out
+            set_caller(accounts.alice);
+            assert_eq!(entropy.transfer(accounts.django, 100, None), Ok(()));
+            assert_eq!(entropy.top_holders(), vec![(accounts.charlie, 450), (accounts.alice, 300), (accounts.django, 150)]);
+
+            // Emptied accounts drop out, burns and mints count too
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.frank, 450, None), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.redeem(250, Hash::from([0x01; 32])), Ok(1));
+            assert_eq!(entropy.top_holders(), vec![(accounts.frank, 450), (accounts.django, 150), (accounts.alice, 50)]);
+            assert_eq!(entropy.issue(1_000), Ok(()));
+            assert_eq!(entropy.top_holders(), vec![(accounts.alice, 1_050), (accounts.frank, 450), (accounts.django, 150)]);
+
+            // Shrinking drops the smallest members
+            assert_eq!(entropy.set_top_holders_limit(2), Ok(()));
+            assert_eq!(entropy.top_holders(), vec![(accounts.alice, 1_050), (accounts.frank, 450)]);
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_top_holders_limit(20), Err(Error::PermissionDenied));
         }
 
         #[ink::test]
-        fn issue_works() {
-            // Constructor works.
-            let mut entropy = Entropy::new(100);
+        fn volume_works() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.volume_of(accounts.alice), (0, 100_000));
+            assert_eq!(entropy.volume_of(accounts.bob), (0, 0));
+
+            // 0.2% fee, collected by alice as the fee collector
+            assert_eq!(entropy.set_params(20, 50), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 20_000, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.issue(500), Ok(()));
+            assert_eq!(entropy.redeem(300, Hash::from([0x01; 32])), Ok(1));
+
+            // Fees count toward the sender's sent volume only
+            assert_eq!(entropy.volume_of(accounts.bob), (10_000, 19_960));
+            assert_eq!(entropy.volume_of(accounts.charlie), (0, 9_980));
+            assert_eq!(entropy.volume_of(accounts.alice), (20_300, 100_500));
+
+            for account in &[accounts.bob, accounts.charlie] {
+                let (sent, received) = entropy.volume_of(*account);
+                assert_eq!(entropy.balance_of(*account), received - sent);
+            }
+            let fees = 40 + 20;
+            let (sent, received) = entropy.volume_of(accounts.alice);
+            assert_eq!(entropy.balance_of(accounts.alice), received - sent + fees);
+            assert_eq!(entropy.total_supply(), 100_000 + 500 - 300);
+        }
 
-            // Transfer event triggered during initial construction.
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+        #[ink::test]
+        fn signed_admin_op_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            assert_eq!(entropy.admin_nonce(), 0);
 
-            // Issue 100 more tokens
-            assert_eq!(entropy.issue(100), Ok(()));
+            // The owner signs off-chain, eve relays
+            let op = AdminOp::AddToBlackList(accounts.bob, "sanctioned".into());
+            let signature = mock_sign(accounts.alice, entropy.admin_op_hash(op.clone(), 0, 100));
+            set_caller(accounts.eve);
+            assert_eq!(entropy.execute_signed_admin_op(op.clone(), 0, 100, signature), Ok(()));
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), true);
+            assert_eq!(entropy.admin_nonce(), 1);
 
-            // Check total supply
-            assert_eq!(entropy.total_supply(), 200);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_added_blacklist_event(&emitted_events[emitted_events.len() - 2], accounts.bob, "sanctioned");
 
-            // Check Alice's new balance
-            assert_eq!(entropy.balance_of(accounts.alice), 200);
+            // The same signature can't be replayed
+            assert_eq!(entropy.execute_signed_admin_op(op, 0, 100, signature), Err(Error::InvalidNonce));
 
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 2);
+            let op = AdminOp::FreezeUntil(accounts.charlie, 50);
+            let signature = mock_sign(accounts.alice, entropy.admin_op_hash(op.clone(), 1, 100));
+            assert_eq!(entropy.execute_signed_admin_op(op, 1, 100, signature), Ok(()));
+            assert_eq!(entropy.frozen_until(accounts.charlie), Some(50));
 
-            // Check first transfer event related to Entropy instantiation.
-            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
-            // Check second Issue event
-            assert_issue_event(&emitted_events[1], 100);
+            let op = AdminOp::SetPrivate(accounts.charlie, true);
+            let signature = mock_sign(accounts.alice, entropy.admin_op_hash(op.clone(), 2, 100));
+            assert_eq!(entropy.execute_signed_admin_op(op, 2, 100, signature), Ok(()));
+            assert_eq!(entropy.is_account_private(accounts.charlie), true);
+            assert_eq!(entropy.admin_nonce(), 3);
         }
 
         #[ink::test]
-        fn redeem_works() {
-            // Constructor works.
+        fn signed_admin_op_rejects_invalid_submissions() {
             let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            // Transfer event triggered during initial construction.
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+            let op = AdminOp::AddToBlackList(accounts.bob, "sanctioned".into());
+            set_caller(accounts.eve);
 
-            assert_eq!(entropy.balance_of(accounts.alice), 100);
+            // Signed by someone other than the owner
+            let signature = mock_sign(accounts.eve, entropy.admin_op_hash(op.clone(), 0, 100));
+            assert_eq!(entropy.execute_signed_admin_op(op.clone(), 0, 100, signature), Err(Error::InvalidSignature));
 
-            // Redeem 50 tokens
-            assert_eq!(entropy.redeem(50), Ok(()));
+            // Signed by the owner, but for another operation
+            let signature = mock_sign(accounts.alice, entropy.admin_op_hash(AdminOp::Unfreeze(accounts.bob), 0, 100));
+            assert_eq!(entropy.execute_signed_admin_op(op.clone(), 0, 100, signature), Err(Error::InvalidSignature));
 
-            // Check total supply
-            assert_eq!(entropy.total_supply(), 50);
+            // Nonce from the future
+            let signature = mock_sign(accounts.alice, entropy.admin_op_hash(op.clone(), 1, 100));
+            assert_eq!(entropy.execute_signed_admin_op(op.clone(), 1, 100, signature), Err(Error::InvalidNonce));
 
-            // Check Alice's new balance
-            assert_eq!(entropy.balance_of(accounts.alice), 50);
+            // Past its deadline, timestamp 10 after two blocks
+            let signature = mock_sign(accounts.alice, entropy.admin_op_hash(op.clone(), 0, 5));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.execute_signed_admin_op(op, 0, 5, signature), Err(Error::SignatureExpired));
 
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 2);
+            assert_eq!(entropy.is_account_blacklisted(accounts.bob), false);
+            assert_eq!(entropy.admin_nonce(), 0);
+        }
 
-            // Check first transfer event related to Entropy instantiation.
-            assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
-            // Check second Redeem event
-            assert_redeem_event(&emitted_events[1], 50);
+        #[ink::test]
+        fn freeze_with_expiry_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.is_account_frozen(accounts.bob), false);
+            assert_eq!(entropy.frozen_until(accounts.bob), None);
+
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+
+            // Freeze bob until timestamp 10, i.e. two blocks from now
+            assert_eq!(entropy.freeze_account_until(accounts.bob, 10), Ok(()));
+            assert_eq!(entropy.frozen_until(accounts.bob), Some(10));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Err(Error::AccountFrozen));
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+
+            // The freeze expires on its own
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.is_account_frozen(accounts.bob), true);
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.is_account_frozen(accounts.bob), false);
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 10);
         }
 
         #[ink::test]
-        fn account_private_works() {
-            // Constructor works.
+        fn frozen_accounts_only_receive() {
             let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.approve(accounts.charlie, 20), Ok(()));
+            assert_eq!(entropy.freeze_account(accounts.bob), Err(Error::PermissionDenied));
+
+            // Bob is frozen until unfrozen
+            set_caller(accounts.alice);
+            assert_eq!(entropy.freeze_account(accounts.bob), Ok(()));
+            assert_eq!(entropy.freeze_account(accounts.alice), Err(Error::ProtectedAccount));
+            assert_eq!(entropy.is_account_frozen(accounts.bob), true);
+            assert_eq!(entropy.frozen_until(accounts.bob), Some(Timestamp::MAX));
+
+            // Deposits still land
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.issue_to(accounts.bob, 5), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 65);
+
+            // But bob can neither send nor let others spend for him
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Err(Error::AccountFrozen));
+            assert_eq!(entropy.approve(accounts.django, 10), Err(Error::AccountFrozen));
+            assert_eq!(entropy.increase_allowance(accounts.charlie, 10), Err(Error::AccountFrozen));
+            assert_eq!(entropy.decrease_allowance(accounts.charlie, 5), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.charlie, 10), Err(Error::AccountFrozen));
+            assert_eq!(entropy.balance_of(accounts.bob), 65);
+
+            // Once unfrozen, bob sends again
+            set_caller(accounts.alice);
+            assert_eq!(entropy.unfreeze_account(accounts.bob), Ok(()));
+            assert_eq!(entropy.is_account_frozen(accounts.bob), false);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Ok(()));
+            assert_eq!(entropy.approve(accounts.django, 10), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 55);
+            assert_eq!(entropy.balance_of(accounts.charlie), 10);
+        }
 
-            // Transfer event triggered during initial construction.
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+        #[ink::test]
+        fn self_locks_work() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+
+            // Bob locks 30 tokens for two blocks and 20 more for four
+            set_caller(accounts.bob);
+            assert_eq!(entropy.lock_my_tokens(0, 10), Err(Error::InvalidLock));
+            assert_eq!(entropy.lock_my_tokens(30, 0), Err(Error::InvalidLock));
+            assert_eq!(entropy.lock_my_tokens(30, 10), Ok(()));
+            assert_eq!(entropy.lock_my_tokens(20, 20), Ok(()));
+            assert_eq!(entropy.lock_my_tokens(60, 20), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.my_locks(), vec![SelfLock { amount: 30, until: 10 }, SelfLock { amount: 20, until: 20 }]);
+            assert_eq!(entropy.spendable_balance_of(accounts.bob), 50);
+
+            // Spends dipping into locked funds fail, whoever initiates them
+            assert_eq!(entropy.transfer(accounts.charlie, 51, None), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.approve(accounts.django, 100), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.django, 51), Err(Error::InsufficientBalance));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 40, None), Ok(()));
+
+            // Locks expire on their own
+            for _ in 0..3 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.my_locks(), vec![SelfLock { amount: 20, until: 20 }]);
+            assert_eq!(entropy.transfer(accounts.charlie, 41, None), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.transfer(accounts.charlie, 40, None), Ok(()));
+            for _ in 0..2 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.locked_balance_of(accounts.bob), 0);
+            assert_eq!(entropy.transfer(accounts.charlie, 20, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+        }
 
-            assert_eq!(entropy.is_account_private(accounts.alice), false);
+        #[ink::test]
+        fn transfer_locked_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            // Set Alice as private
-            assert_eq!(entropy.set_account_private(accounts.alice, true), Ok(()));
+            assert_eq!(entropy.transfer_locked(accounts.bob, 0, 10), Err(Error::InvalidLock));
+            assert_eq!(entropy.transfer_locked(accounts.bob, 100, 0), Err(Error::InvalidLock));
+            assert_eq!(entropy.transfer_locked(accounts.bob, 1_001, 10), Err(Error::InsufficientBalance));
 
-            // Check Alice's privateness
-            assert_eq!(entropy.is_account_private(accounts.alice), true);
+            // Bob receives 100 locked for two blocks and 50 he can spend right away
+            assert_eq!(entropy.transfer_locked(accounts.bob, 100, 10), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 150);
+            assert_eq!(entropy.locked_balance_of(accounts.bob), 100);
+            assert_eq!(entropy.spendable_balance_of(accounts.bob), 50);
 
-            // Set Alice's privateness back
-            assert_eq!(entropy.set_account_private(accounts.alice, false), Ok(()));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 2].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TokensLocked(TokensLocked { account, amount, until }) = decoded_event {
+                assert_eq!(account, accounts.bob);
+                assert_eq!(amount, 100);
+                assert_eq!(until, 10);
+            } else {
+                panic!("encountered unexpected event kind: expected a TokensLocked event")
+            }
 
-            // Check Alice's privateness again
-            assert_eq!(entropy.is_account_private(accounts.alice), false);
+            // Neither Bob nor a spender can move the locked tokens before maturity
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 51, None), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.approve(accounts.django, 150), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.django, 51), Err(Error::InsufficientBalance));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.django, 51), Err(Error::InsufficientBalance));
+
+            // Past maturity the tokens are spendable, and Bob can sweep the expired lock
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.locked_balance_of(accounts.bob), 0);
+            assert_eq!(entropy.transfer_from(accounts.bob, accounts.django, 150), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.claim_unlocked(), Ok(100));
 
-            // Check events
             let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 3);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TokensUnlocked(TokensUnlocked { account, amount }) = decoded_event {
+                assert_eq!(account, accounts.bob);
+                assert_eq!(amount, 100);
+            } else {
+                panic!("encountered unexpected event kind: expected a TokensUnlocked event")
+            }
+            assert_eq!(entropy.claim_unlocked(), Err(Error::NothingToClaim));
+        }
 
-            // Check first transfer event related to Entropy instantiation.
-            assert_transfer_event(&emitted_events[0], None, Some(accounts.alice), 100);
-            // Check 2nd and 3rd Privacy event
-            assert_privacy_event(&emitted_events[1], accounts.alice, true);
-            assert_privacy_event(&emitted_events[2], accounts.alice, false);
+        #[ink::test]
+        fn self_locks_yield_to_seizure_only() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.lock_my_tokens(100, 1_000), Ok(()));
+
+            // Rekeying carries the locks over
+            set_caller(accounts.alice);
+            assert_eq!(entropy.rekey_account(accounts.bob, accounts.charlie), Ok(()));
+            assert_eq!(entropy.locked_balance_of(accounts.charlie), 100);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 1, None), Err(Error::InsufficientBalance));
+
+            // Destroying a blacklisted account's funds overrides them
+            set_caller(accounts.alice);
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 0);
+            assert_eq!(entropy.locked_balance_of(accounts.charlie), 0);
         }
 
         #[ink::test]
-        fn blacklist_works() {
-            // Constructor works.
-            let mut entropy = Entropy::new(100);
+        fn seizure_releases_holds_and_held_receipts() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let treasury = accounts.frank;
+
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 100, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.create_hold(accounts.django, 40, 1_000), Ok(1));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.create_hold(accounts.django, 30, 1_000), Ok(2));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_min_holding_period(1_000), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 50, None), Ok(()));
+            assert_eq!(entropy.held_balance_of(accounts.charlie), 50);
+
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie, "sanctioned".into()), Ok(()));
+
+            // Seizing releases the holds the account pays
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.seize_black_funds(accounts.bob, treasury), Ok(()));
+            assert_eq!(entropy.balance_of(treasury), 100);
+            assert_eq!(entropy.hold(1), None);
+            assert_eq!(entropy.on_hold_balance_of(accounts.bob), 0);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events_before + 2].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::HoldReleased(HoldReleased { id, payer, value }) = decoded_event {
+                assert_eq!((id, payer, value), (1, accounts.bob, 40));
+            } else {
+                panic!("encountered unexpected event kind: expected a HoldReleased event")
+            }
 
-            // Transfer event triggered during initial construction.
-            let accounts =
-                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-                    .expect("Cannot get accounts");
+            // Destroying the whole balance releases holds and held receipts
+            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Ok(()));
+            assert_eq!(entropy.hold(2), None);
+            assert_eq!(entropy.on_hold_balance_of(accounts.charlie), 0);
+            assert_eq!(entropy.held_balance_of(accounts.charlie), 0);
 
-            assert_eq!(entropy.is_account_blacklisted(accounts.alice), false);
-            assert_eq!(entropy.is_account_blacklisted(accounts.bob), false);
+            // Once cleared, the accounts start afresh
+            assert_eq!(entropy.remove_account_from_blacklist(accounts.charlie), Ok(()));
+            assert_eq!(entropy.set_min_holding_period(0), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Ok(()));
+            assert_eq!(entropy.spendable_balance_of(accounts.charlie), 10);
+            set_caller(accounts.django);
+            assert_eq!(entropy.capture_hold(2), Err(Error::UnknownHold));
+        }
 
-            // Alice transfers 10 tokens to bob
-            assert_eq!(entropy.transfer(accounts.bob, 10), Ok(()));
+        #[ink::test]
+        fn velocity_rule_works() {
+            let mut entropy = Entropy::new(100);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // At most 2 outgoing transfers per 20ms, i.e. per 4 blocks
+            assert_eq!(entropy.set_velocity_rule(2, 20, 0), Ok(()));
+            let rule = VelocityRule { max_outflows_per_window: 2, window_ms: 20, max_share_bps: 0 };
+            assert_eq!(entropy.velocity_rule(), rule);
+
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+
+            // The 3rd transfer within the window trips the rule and is rejected
+            let emitted_events_before = ink_env::test::recorded_events().count();
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Err(Error::AccountFrozen));
+            assert_eq!(entropy.is_account_frozen(accounts.alice), true);
+            assert_eq!(entropy.frozen_until(accounts.alice), Some(20));
+            assert_eq!(entropy.balance_of(accounts.bob), 20);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), emitted_events_before + 1);
+            assert_auto_frozen_event(&emitted_events[emitted_events_before], accounts.alice, rule);
 
-            // Destroying bob's funds should fail
-            assert_eq!(entropy.destroy_black_funds(accounts.bob), Err(Error::AccountNotBlackListed));
+            // Subsequent transfers keep failing until the freeze expires
+            for _ in 0..3 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+                assert_eq!(entropy.transfer(accounts.bob, 10, None), Err(Error::AccountFrozen));
+            }
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 30);
+
+            // Trip the rule again, then have the owner clear the freeze early
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Err(Error::AccountFrozen));
+            assert_eq!(entropy.unfreeze_account(accounts.alice), Ok(()));
+            assert_eq!(entropy.is_account_frozen(accounts.alice), false);
+            assert_eq!(entropy.transfer(accounts.bob, 10, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 50);
+
+            // Exempt accounts are never frozen by the rule
+            assert_eq!(entropy.set_velocity_exempt(accounts.alice, true), Ok(()));
+            assert_eq!(entropy.is_velocity_exempt(accounts.alice), true);
+            for _ in 0..5 {
+                assert_eq!(entropy.transfer(accounts.bob, 1, None), Ok(()));
+            }
+            assert_eq!(entropy.is_account_frozen(accounts.alice), false);
+            assert_eq!(entropy.balance_of(accounts.bob), 55);
+        }
 
-            // Add bob to blacklist
-            assert_eq!(entropy.add_account_to_blacklist(accounts.bob), Ok(()));
+        #[ink::test]
+        fn velocity_rule_share_threshold_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // More than 2 outgoing transfers per 20ms moving more than half of the opening balance
+            assert_eq!(entropy.set_velocity_rule(2, 20, 5_000), Ok(()));
+            let rule = VelocityRule { max_outflows_per_window: 2, window_ms: 20, max_share_bps: 5_000 };
+            assert_eq!(entropy.velocity_rule(), rule);
+
+            // Many small transfers stay within the share
+            for _ in 0..4 {
+                assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            }
+            assert_eq!(entropy.is_account_frozen(accounts.alice), false);
 
-            // Assert bob is on blacklist
-            assert_eq!(entropy.is_account_blacklisted(accounts.bob), true);
+            // Moving 600 of the 1000 held when the window opened trips the rule
+            assert_eq!(entropy.transfer(accounts.bob, 200, None), Err(Error::AccountFrozen));
+            assert_eq!(entropy.is_account_frozen(accounts.alice), true);
+            assert_eq!(entropy.balance_of(accounts.bob), 400);
 
-            // Bob should be forbidden to transfer tokens
-            assert_eq!(entropy.transfer_from(accounts.bob, accounts.charlie, 10), Err(Error::AccountBlackListed));
+            // A single large transfer in a fresh window is within the count
+            assert_eq!(entropy.unfreeze_account(accounts.alice), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 500, None), Ok(()));
+            assert_eq!(entropy.is_account_frozen(accounts.alice), false);
 
-            // Destroying bob's funds should now succeed
-            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
+            // The share is capped at the whole balance
+            assert_eq!(entropy.set_velocity_rule(2, 20, 20_000), Ok(()));
+            assert_eq!(entropy.velocity_rule().max_share_bps, 10000);
+        }
 
-            // Assert totol supply
-            assert_eq!(entropy.total_supply(), 90);
+        #[ink::test]
+        fn snapshot_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.snapshot_id(), 0);
+            assert_eq!(entropy.balance_of_at(accounts.alice, 1), None);
+
+            assert_eq!(entropy.snapshot(), Ok(1));
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.snapshot(), Ok(2));
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.issue(500), Ok(()));
+
+            // Later balance changes don't affect earlier snapshots
+            assert_eq!(entropy.balance_of_at(accounts.alice, 1), Some(1_000));
+            assert_eq!(entropy.balance_of_at(accounts.bob, 1), Some(0));
+            assert_eq!(entropy.balance_of_at(accounts.alice, 2), Some(900));
+            assert_eq!(entropy.balance_of_at(accounts.bob, 2), Some(100));
+            assert_eq!(entropy.balance_of_at(accounts.charlie, 2), Some(0));
+            assert_eq!(entropy.total_supply_at(1), Some(1_000));
+            assert_eq!(entropy.total_supply_at(2), Some(1_000));
+            assert_eq!(entropy.total_supply(), 1_500);
+            assert_eq!(entropy.balance_of_at(accounts.bob, 3), None);
+            assert_eq!(entropy.total_supply_at(3), None);
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.snapshot(), Err(Error::PermissionDenied));
+        }
 
-            // Remove bob from blacklist
-            assert_eq!(entropy.remove_account_from_blacklist(accounts.bob), Ok(()));
-            assert_eq!(entropy.is_account_blacklisted(accounts.bob), false);
+        #[ink::test]
+        fn snapshot_checkpoints_are_keyed_per_snapshot() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Bob changes balance after snapshots 1, 3 and 4, but not after 2
+            assert_eq!(entropy.snapshot(), Ok(1));
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.snapshot(), Ok(2));
+            assert_eq!(entropy.snapshot(), Ok(3));
+            assert_eq!(entropy.transfer(accounts.bob, 50, None), Ok(()));
+            assert_eq!(entropy.snapshot(), Ok(4));
+            assert_eq!(entropy.transfer(accounts.bob, 25, None), Ok(()));
+            assert_eq!(entropy.snapshot(), Ok(5));
+
+            // Each change records one entry of its own, only the first after a snapshot
+            assert_eq!(entropy.balance_snapshots.get(&(accounts.bob, 1)), Some(&0));
+            assert_eq!(entropy.balance_snapshots.get(&(accounts.bob, 2)), None);
+            assert_eq!(entropy.balance_snapshots.get(&(accounts.bob, 3)), Some(&200));
+            assert_eq!(entropy.balance_snapshots.get(&(accounts.bob, 4)), Some(&250));
+            assert_eq!(entropy.balance_snapshots.get(&(accounts.bob, 5)), None);
+
+            assert_eq!(entropy.balance_of_at(accounts.bob, 1), Some(0));
+            assert_eq!(entropy.balance_of_at(accounts.bob, 2), Some(200));
+            assert_eq!(entropy.balance_of_at(accounts.bob, 3), Some(200));
+            assert_eq!(entropy.balance_of_at(accounts.bob, 4), Some(250));
+            assert_eq!(entropy.balance_of_at(accounts.bob, 5), Some(275));
+            assert_eq!(entropy.balance_of_at(accounts.alice, 2), Some(800));
+            assert_eq!(entropy.balance_of_at(accounts.alice, 5), Some(725));
+        }
 
-            // Check events
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 7);
-            assert_transfer_event(&emitted_events[0], None, Some(accounts.alice), 100);
-            assert_transfer_event(&emitted_events[1], Some(accounts.alice), Some(accounts.bob), 10);
-            assert_transaction_failed_event(&emitted_events[2], format!("{:?}", Error::AccountNotBlackListed));
-            assert_added_blacklist_event(&emitted_events[3], accounts.bob);
-            assert_transaction_failed_event(&emitted_events[4], format!("{:?}", Error::AccountBlackListed));
-            assert_destroyed_black_funds_event(&emitted_events[5], accounts.bob, 10);
-            assert_removed_blacklist_event(&emitted_events[6], accounts.bob);
+        #[ink::test]
+        fn vesting_works() {
+            let mut entropy = Entropy::new(10_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+            let start = ink_env::block_timestamp::<Environment>().expect("Cannot get block timestamp");
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.create_vesting(accounts.bob, 1_000, start, 40, 10), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.create_vesting(accounts.bob, 0, start, 40, 10), Err(Error::InvalidVesting));
+            assert_eq!(entropy.create_vesting(accounts.bob, 1_000, start, 0, 0), Err(Error::InvalidVesting));
+            assert_eq!(entropy.create_vesting(accounts.bob, 1_000, start, 40, 41), Err(Error::InvalidVesting));
+            assert_eq!(entropy.create_vesting(accounts.bob, 10_001, start, 40, 10), Err(Error::InsufficientBalance));
+
+            // Bob vests 1_000 over eight blocks with a two block cliff
+            assert_eq!(entropy.create_vesting(accounts.bob, 1_000, start, 40, 10), Ok(()));
+            assert_eq!(entropy.create_vesting(accounts.bob, 1_000, start, 40, 10), Err(Error::InvalidVesting));
+            assert_eq!(entropy.balance_of(accounts.alice), 9_000);
+            assert_eq!(entropy.balance_of(contract), 1_000);
+            assert_eq!(entropy.vesting_schedule(accounts.bob), Some(VestingSchedule {
+                total: 1_000,
+                claimed: 0,
+                start,
+                duration_ms: 40,
+                cliff_ms: 10
+            }));
+
+            // Nothing unlocks before the cliff
+            set_caller(accounts.bob);
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.vested_amount(accounts.bob), 0);
+            assert_eq!(entropy.claim_vested(), Err(Error::NothingToClaim));
+
+            // At the cliff a quarter has unlocked, halfway half
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.vested_amount(accounts.bob), 250);
+            assert_eq!(entropy.claim_vested(), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 250);
+            assert_eq!(entropy.claim_vested(), Err(Error::NothingToClaim));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.vested_amount(accounts.bob), 500);
+            assert_eq!(entropy.claim_vested(), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 500);
+
+            // Everything has unlocked past the duration
+            for _ in 0..5 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            assert_eq!(entropy.vested_amount(accounts.bob), 1_000);
+            assert_eq!(entropy.claim_vested(), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 1_000);
+            assert_eq!(entropy.balance_of(contract), 0);
+            assert_eq!(entropy.vesting_schedule(accounts.bob).map(|schedule| schedule.claimed), Some(1_000));
+            assert_eq!(entropy.claim_vested(), Err(Error::NothingToClaim));
+
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.vested_amount(accounts.charlie), 0);
+            assert_eq!(entropy.claim_vested(), Err(Error::NothingToClaim));
         }
 
         #[ink::test]
-        fn permission_check_works() {
-            let mut entropy = Entropy::new(100);
-            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+        fn snapshot_survives_burns() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
 
-            // Assert owner is alice
-            assert_eq!(entropy.owner(), accounts.alice);
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.snapshot(), Ok(1));
 
-            // Get contract address.
-            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap_or([0x0; 32].into());
+            // Redemptions and destroyed blacklisted funds leave the snapshot untouched
+            assert_eq!(entropy.redeem(200, Hash::from([0x01; 32])), Ok(1));
+            assert_eq!(entropy.add_account_to_blacklist(accounts.bob, "sanctioned".into()), Ok(()));
+            assert_eq!(entropy.destroy_black_funds(accounts.bob), Ok(()));
 
-            // Create call.
-            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
-            data.push_arg(&accounts.bob);
+            assert_eq!(entropy.balance_of(accounts.alice), 700);
+            assert_eq!(entropy.balance_of(accounts.bob), 0);
+            assert_eq!(entropy.total_supply(), 700);
+            assert_eq!(entropy.balance_of_at(accounts.alice, 1), Some(900));
+            assert_eq!(entropy.balance_of_at(accounts.bob, 1), Some(100));
+            assert_eq!(entropy.total_supply_at(1), Some(1_000));
+        }
 
-            // Push the new execution context to set Bob as caller.
-            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(accounts.bob, callee, 1000000, 1000000, data);
+        #[ink::test]
+        fn airdrop_works() {
+            let mut entropy = Entropy::new(10_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+
+            // Four leaves, paired bottom-up: ((bob, charlie), (django, eve))
+            let leaves = [
+                entropy.leaf_hash(accounts.bob, 100),
+                entropy.leaf_hash(accounts.charlie, 200),
+                entropy.leaf_hash(accounts.django, 300),
+                entropy.leaf_hash(accounts.eve, 400)
+            ];
+            let left = entropy.node_hash(leaves[0], leaves[1]);
+            let right = entropy.node_hash(leaves[2], leaves[3]);
+            let root = entropy.node_hash(left, right);
+
+            set_caller(accounts.bob);
+            assert_eq!(entropy.create_airdrop(root, 1_000, 20), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.create_airdrop(root, 0, 20), Err(Error::InvalidAirdrop));
+            assert_eq!(entropy.create_airdrop(root, 1_000, 0), Err(Error::InvalidAirdrop));
+            assert_eq!(entropy.create_airdrop(root, 10_001, 20), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.create_airdrop(root, 1_000, 20), Ok(1));
+            assert_eq!(entropy.airdrop(1), Some(Airdrop { root, total: 1_000, claimed: 0, expiry: 20, closed: false }));
+            assert_eq!(entropy.balance_of(accounts.alice), 9_000);
+            assert_eq!(entropy.balance_of(contract), 1_000);
+
+            // Claims pay the proven amount without a fee
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.claim_airdrop(2, 100, vec![leaves[1], right]), Err(Error::UnknownAirdrop));
+            assert_eq!(entropy.claim_airdrop(1, 100, vec![leaves[1], right]), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 100);
+            assert_eq!(entropy.has_claimed_airdrop(1, accounts.bob), true);
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::AirdropClaimed(AirdropClaimed { id, account, value }) = decoded_event {
+                assert_eq!((id, account, value), (1, accounts.bob, 100));
+            } else {
+                panic!("encountered unexpected event kind: expected an AirdropClaimed event")
+            }
+            assert_eq!(entropy.claim_airdrop(1, 100, vec![leaves[1], right]), Err(Error::AlreadyClaimed));
+
+            // Proofs must match the caller, the amount and the tree
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.claim_airdrop(1, 300, vec![leaves[0], right]), Err(Error::InvalidProof));
+            assert_eq!(entropy.claim_airdrop(1, 200, vec![leaves[2], right]), Err(Error::InvalidProof));
+            assert_eq!(entropy.claim_airdrop(1, 200, vec![leaves[0]]), Err(Error::InvalidProof));
+            set_caller(accounts.frank);
+            assert_eq!(entropy.claim_airdrop(1, 200, vec![leaves[0], right]), Err(Error::InvalidProof));
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.has_claimed_airdrop(1, accounts.charlie), false);
+            assert_eq!(entropy.claim_airdrop(1, 200, vec![leaves[0], right]), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 200);
+
+            // The remainder can only be swept once the airdrop expired
+            set_caller(accounts.alice);
+            assert_eq!(entropy.close_airdrop(1), Err(Error::ClaimWindowOpen));
+            for _ in 0..5 {
+                ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            }
+            set_caller(accounts.django);
+            assert_eq!(entropy.claim_airdrop(1, 300, vec![leaves[3], left]), Err(Error::ClaimWindowClosed));
+            assert_eq!(entropy.close_airdrop(1), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(entropy.close_airdrop(1), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.alice), 9_700);
+            assert_eq!(entropy.balance_of(contract), 0);
+            assert_eq!(entropy.airdrop(1).map(|airdrop| (airdrop.claimed, airdrop.closed)), Some((300, true)));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[emitted_events.len() - 1].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::AirdropClosed(AirdropClosed { id, value }) = decoded_event {
+                assert_eq!((id, value), (1, 700));
+            } else {
+                panic!("encountered unexpected event kind: expected an AirdropClosed event")
+            }
+            assert_eq!(entropy.close_airdrop(1), Err(Error::NothingToClaim));
+        }
 
-            // Bob should not have the permission to call privileged apis
-            assert_eq!(entropy.transfer_ownership(accounts.charlie), Err(Error::PermissionDenied));
-            assert_eq!(entropy.issue(100), Err(Error::PermissionDenied));
-            assert_eq!(entropy.redeem(100), Err(Error::PermissionDenied));
-            assert_eq!(entropy.set_params(10, 50), Err(Error::PermissionDenied));
-            assert_eq!(entropy.set_account_private(accounts.charlie, true), Err(Error::PermissionDenied));
-            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie), Err(Error::PermissionDenied));
-            assert_eq!(entropy.remove_account_from_blacklist(accounts.charlie), Err(Error::PermissionDenied));
-            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Err(Error::PermissionDenied));
+        #[ink::test]
+        fn distribution_works() {
+            let mut entropy = Entropy::new(100_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+            let contract = ink_env::account_id::<Environment>().expect("Cannot get contract account");
+
+            assert_eq!(entropy.transfer(accounts.bob, 30_000, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            assert_eq!(entropy.distribute(1, 1_001), Err(Error::UnknownSnapshot));
+            assert_eq!(entropy.snapshot(), Ok(1));
+
+            // Django only receives tokens after the snapshot
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.django, 10_000, None), Ok(()));
+            assert_eq!(entropy.distribute(1, 1_001), Err(Error::PermissionDenied));
+
+            // Claims close two blocks after funding
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_distribution_window(10), Ok(()));
+            assert_eq!(entropy.distribute(1, 1_001), Ok(()));
+            assert_eq!(entropy.distribute(1, 1_001), Err(Error::DistributionExists));
+            assert_eq!(entropy.balance_of(accounts.alice), 58_999);
+            assert_eq!(entropy.balance_of(contract), 1_001);
+
+            // Shares are rounded down
+            set_caller(accounts.bob);
+            assert_eq!(entropy.claim_distribution(1), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 20_300);
+            assert_eq!(entropy.claim_distribution(1), Err(Error::AlreadyClaimed));
+            assert_eq!(entropy.has_claimed_distribution(1, accounts.bob), true);
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.claim_distribution(1), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.charlie), 10_100);
+            set_caller(accounts.django);
+            assert_eq!(entropy.claim_distribution(1), Err(Error::NothingToClaim));
+            assert_eq!(entropy.claim_distribution(2), Err(Error::UnknownDistribution));
+
+            set_caller(accounts.alice);
+            assert_eq!(entropy.sweep_distribution(1), Err(Error::ClaimWindowOpen));
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            ink_env::test::advance_block::<Environment>().expect("Cannot advance block");
+            assert_eq!(entropy.claim_distribution(1), Err(Error::ClaimWindowClosed));
+
+            // The owner's unclaimed share and the rounding remainder are swept back
+            assert_eq!(entropy.sweep_distribution(1), Ok(()));
+            assert_eq!(entropy.sweep_distribution(1), Err(Error::NothingToClaim));
+            assert_eq!(entropy.balance_of(accounts.alice), 59_600);
+            assert_eq!(entropy.balance_of(contract), 0);
+            let distribution = entropy.distribution(1).expect("Distribution is kept");
+            assert_eq!((distribution.total, distribution.claimed, distribution.swept), (1_001, 400, true));
+        }
 
-            // Transfer ownership to bob
-            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
-            data.push_arg(&accounts.bob);
-            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(accounts.alice, callee, 1000000, 1000000, data);
-            assert_eq!(entropy.transfer_ownership(accounts.bob), Ok(()));
-            assert_eq!(entropy.owner(), accounts.bob);
+        #[ink::test]
+        fn balances_root_commitment_works() {
+            let mut entropy = Entropy::new(1_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            // Django is indexed as a holder but has no balance left
+            assert_eq!(entropy.transfer(accounts.bob, 100, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.charlie, 200, None), Ok(()));
+            assert_eq!(entropy.transfer(accounts.django, 50, None), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(entropy.transfer(accounts.alice, 50, None), Ok(()));
+            assert_eq!(entropy.holders_len(), 4);
+            assert_eq!(entropy.latest_balances_root(), None);
+
+            // Anyone commits page by page; transfers in between don't affect the commitment
+            set_caller(accounts.eve);
+            assert_eq!(entropy.commit_page(0, 2), Err(Error::InvalidCommitment));
+            assert_eq!(entropy.begin_commitment(), Ok(()));
+            assert_eq!(entropy.begin_commitment(), Err(Error::InvalidCommitment));
+            assert_eq!(entropy.commit_page(1, 2), Err(Error::InvalidCommitment));
+            assert_eq!(entropy.commit_page(0, 2), Ok(()));
+            assert_eq!(entropy.finalize_commitment(), Err(Error::InvalidCommitment));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.transfer(accounts.charlie, 10, None), Ok(()));
+            assert_eq!(entropy.commit_page(2, 10), Ok(()));
+            assert_eq!(entropy.finalize_commitment(), Ok(()));
+            assert_eq!(entropy.pending_commitment(), None);
+
+            let leaf_alice = entropy.leaf_hash(accounts.alice, 700);
+            let leaf_bob = entropy.leaf_hash(accounts.bob, 100);
+            let leaf_charlie = entropy.leaf_hash(accounts.charlie, 200);
+            let node_alice_bob = entropy.node_hash(leaf_alice, leaf_bob);
+            let root = entropy.latest_balances_root().expect("Root is committed");
+            assert_eq!(root.root, entropy.node_hash(node_alice_bob, leaf_charlie));
+            assert_eq!(root.snapshot_id, 1);
+
+            // Valid proofs verify, tampered ones don't
+            assert!(entropy.verify_balance_proof(accounts.bob, 100, vec![leaf_alice, leaf_charlie]));
+            assert!(entropy.verify_balance_proof(accounts.charlie, 200, vec![node_alice_bob]));
+            assert!(!entropy.verify_balance_proof(accounts.bob, 90, vec![leaf_alice, leaf_charlie]));
+            assert!(!entropy.verify_balance_proof(accounts.bob, 100, vec![leaf_charlie, leaf_charlie]));
+            assert!(!entropy.verify_balance_proof(accounts.django, 0, vec![node_alice_bob]));
+
+            // A new commitment stores a new root, leaving the finalized one untouched
+            assert_eq!(entropy.commit_balances_root(), Ok(()));
+            assert_eq!(entropy.balances_root(1), Some(root));
+            assert_eq!(entropy.latest_balances_root().map(|root| root.snapshot_id), Some(2));
+            assert!(!entropy.verify_balance_proof(accounts.bob, 100, vec![leaf_alice, leaf_charlie]));
+        }
 
-            // Now bob is new owner, should have permission to call privileged apis
-            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
-            data.push_arg(&accounts.bob);
-            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(accounts.bob, callee, 1000000, 1000000, data);
-            assert_eq!(entropy.issue(100), Ok(()));
-            assert_eq!(entropy.redeem(100), Ok(()));
-            assert_eq!(entropy.set_params(10, 50), Ok(()));
-            assert_eq!(entropy.set_account_private(accounts.charlie, true), Ok(()));
-            assert_eq!(entropy.add_account_to_blacklist(accounts.charlie), Ok(()));
-            assert_eq!(entropy.destroy_black_funds(accounts.charlie), Ok(()));
-            assert_eq!(entropy.remove_account_from_blacklist(accounts.charlie), Ok(()));
+        #[ink::test]
+        fn fee_rebate_works() {
+            let mut entropy = Entropy::new(1_000_000);
+            let accounts = ink_env::test::default_accounts::<Environment>().expect("Cannot get accounts");
+
+            assert_eq!(entropy.transfer(accounts.bob, 100_000, None), Ok(()));
+            assert_eq!(entropy.set_params(10, 1_000), Ok(()));
+            assert_eq!(entropy.set_rebate(accounts.bob, 5_000), Ok(()));
+            assert_eq!(entropy.fee_rebate_of(accounts.bob), 5_000);
+
+            // Bob pays a 10 token fee on each transfer, half of which accrues to him
+            set_caller(accounts.bob);
+            assert_eq!(entropy.set_rebate(accounts.bob, 10_000), Err(Error::PermissionDenied));
+            for _ in 0..3 {
+                assert_eq!(entropy.transfer(accounts.charlie, 10_000, None), Ok(()));
+            }
+            assert_eq!(entropy.accrued_rebate_of(accounts.bob), 15);
+            assert_eq!(entropy.balance_of(accounts.bob), 70_000);
+
+            // Charlie has no rebate
+            set_caller(accounts.charlie);
+            assert_eq!(entropy.transfer(accounts.django, 10_000, None), Ok(()));
+            assert_eq!(entropy.accrued_rebate_of(accounts.charlie), 0);
+            assert_eq!(entropy.claim_fee_rebate(), Err(Error::NothingToClaim));
+
+            // The rebate stays claimable while the fee collector can't pay it
+            set_caller(accounts.alice);
+            assert_eq!(entropy.set_params(0, 0), Ok(()));
+            assert_eq!(entropy.transfer(accounts.eve, 900_040, None), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.alice), 0);
+            set_caller(accounts.bob);
+            assert_eq!(entropy.claim_fee_rebate(), Err(Error::InsufficientBalance));
+            assert_eq!(entropy.accrued_rebate_of(accounts.bob), 15);
+
+            set_caller(accounts.eve);
+            assert_eq!(entropy.transfer(accounts.alice, 10_000, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(entropy.claim_fee_rebate(), Ok(()));
+            assert_eq!(entropy.balance_of(accounts.bob), 70_015);
+            assert_eq!(entropy.accrued_rebate_of(accounts.bob), 0);
+            assert_eq!(entropy.claim_fee_rebate(), Err(Error::NothingToClaim));
         }
 
     }
@@ -0,0 +1,123 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod receiver {
+    use ink_prelude::vec::Vec;
+
+    use ink_storage::collections::HashMap as StorageHashMap;
+
+    /// Example recipient of Entropy's `transfer_and_call`.
+    ///
+    /// Credits the tokens it is notified of to their sender, as a vault would, and rejects them while
+    /// the owner turned accepting off.
+    #[ink(storage)]
+    pub struct Receiver {
+        owner: AccountId,
+
+        /// Entropy contract whose notifications are trusted
+        token: AccountId,
+
+        /// Whether incoming tokens are accepted
+        accepting: bool,
+
+        /// Mapping of a sender to the tokens it sent through `transfer_and_call`
+        credits: StorageHashMap<AccountId, Balance>
+    }
+
+    /// Event emitted when tokens are credited to their sender.
+    #[ink(event)]
+    pub struct Credited {
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance,
+        data: Vec<u8>
+    }
+
+    impl Receiver {
+
+        /// Creates a new receiver accepting the tokens of the Entropy contract at `token`.
+        #[ink(constructor)]
+        pub fn new(token: AccountId) -> Self {
+            Self {
+                owner: Self::env().caller(),
+                token,
+                accepting: true,
+                credits: StorageHashMap::new()
+            }
+        }
+
+        /// Returns the tokens `account` sent through `transfer_and_call`.
+        #[ink(message)]
+        pub fn credit_of(&self, account: AccountId) -> Balance {
+            self.credits.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Set whether incoming tokens are accepted. Only the owner is allowed to call this function,
+        /// others are ignored.
+        #[ink(message)]
+        pub fn set_accepting(&mut self, accepting: bool) {
+            if self.env().caller() == self.owner {
+                self.accepting = accepting;
+            }
+        }
+
+        /// Notification of `value` tokens received from `from`, called by Entropy's `transfer_and_call`
+        /// under the selector of `ON_ENTROPY_RECEIVED_SELECTOR`.
+        ///
+        /// Returns whether the tokens are accepted: only notifications from the Entropy contract are,
+        /// while accepting is on.
+        #[ink(message, selector = "0x0100F98C")]
+        pub fn on_entropy_received(&mut self, from: AccountId, value: Balance, data: Vec<u8>) -> bool {
+            if self.env().caller() != self.token || !self.accepting {
+                return false;
+            }
+
+            let credit = self.credit_of(from);
+            self.credits.insert(from, credit.saturating_add(value));
+
+            self.env().emit_event(Credited {
+                from,
+                value,
+                data
+            });
+
+            true
+        }
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap_or_else(|_| [0x0; 32].into());
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(caller, callee, 1000000, 1000000, data);
+        }
+
+        #[ink::test]
+        fn on_entropy_received_works() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            let mut receiver = Receiver::new(accounts.django);
+
+            // Only the token's notifications count
+            assert_eq!(receiver.on_entropy_received(accounts.bob, 100, Vec::new()), false);
+            set_caller(accounts.django);
+            assert_eq!(receiver.on_entropy_received(accounts.bob, 100, Vec::new()), true);
+            assert_eq!(receiver.on_entropy_received(accounts.bob, 50, Vec::new()), true);
+            assert_eq!(receiver.credit_of(accounts.bob), 150);
+
+            set_caller(accounts.alice);
+            receiver.set_accepting(false);
+            set_caller(accounts.django);
+            assert_eq!(receiver.on_entropy_received(accounts.bob, 100, Vec::new()), false);
+            assert_eq!(receiver.credit_of(accounts.bob), 150);
+        }
+    }
+}
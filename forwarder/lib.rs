@@ -0,0 +1,309 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod forwarder {
+    use core::fmt;
+
+    use ink_prelude::{
+        format,
+        string::String,
+    };
+
+    /// Mock trusted forwarder for Entropy's `transfer_for` and `approve_for`.
+    ///
+    /// Users are authenticated simply as the forwarder's direct callers, whose transfers and approvals
+    /// are forwarded to Entropy naming them as sender. Entropy must trust the forwarder with
+    /// `set_trusted_forwarder`. A production forwarder would verify signed requests instead.
+    #[ink(storage)]
+    pub struct Forwarder {
+        /// Entropy contract calls are forwarded to
+        token: AccountId
+    }
+
+    /// Event emitted when a call is forwarded.
+    #[ink(event)]
+    pub struct Forwarded {
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        counterparty: AccountId,
+        value: Balance
+    }
+
+    /// Event emitted when error occurs
+    #[ink(event)]
+    pub struct TransactionFailed {
+        #[ink(topic)]
+        error: String
+    }
+
+    /// Forwarder error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the Entropy contract rejects a forwarded call
+        TokenCallFailed
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Self::TokenCallFailed => write!(f, "TokenCallFailed")
+            }
+        }
+    }
+
+    /// Forwarder result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl Forwarder {
+
+        /// Creates a new forwarder for the Entropy contract at `token`.
+        #[ink(constructor)]
+        pub fn new(token: AccountId) -> Self {
+            Self {
+                token
+            }
+        }
+
+        /// Returns the Entropy contract calls are forwarded to.
+        #[ink(message)]
+        pub fn token(&self) -> AccountId {
+            self.token
+        }
+
+        /// Transfers `value` of the caller's tokens to `to` through Entropy's `transfer_for`
+        ///
+        /// On success a `Forwarded` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `TokenCallFailed` error if Entropy rejects the transfer, in particular if it doesn't
+        /// trust this forwarder.
+        #[ink(message)]
+        pub fn forward_transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            let sender = self.env().caller();
+            if !token_transfer_for(self.token, sender, to, value) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenCallFailed)
+                });
+                return Err(Error::TokenCallFailed);
+            }
+
+            self.env().emit_event(Forwarded {
+                sender,
+                counterparty: to,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Allows `spender` to withdraw up to `value` of the caller's tokens through Entropy's `approve_for`
+        ///
+        /// On success a `Forwarded` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `TokenCallFailed` error if Entropy rejects the approval, in particular if it doesn't
+        /// trust this forwarder.
+        #[ink(message)]
+        pub fn forward_approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            let sender = self.env().caller();
+            if !token_approve_for(self.token, sender, spender, value) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenCallFailed)
+                });
+                return Err(Error::TokenCallFailed);
+            }
+
+            self.env().emit_event(Forwarded {
+                sender,
+                counterparty: spender,
+                value
+            });
+
+            Ok(())
+        }
+    }
+
+    /// Transfers `sender`'s tokens through Entropy's `transfer_for`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer_for(token: AccountId, sender: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer_for(sender, to, value, None).is_ok()
+    }
+
+    /// Sets an allowance on `sender`'s account through Entropy's `approve_for`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_approve_for(token: AccountId, sender: AccountId, spender: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.approve_for(sender, spender, value).is_ok()
+    }
+
+    // Off-chain stand-ins for the cross-contract calls, as ink's test environment can't execute other
+    // contracts. They operate on the ledger of `tests::MockToken`.
+
+    #[cfg(test)]
+    fn token_transfer_for(token: AccountId, sender: AccountId, to: AccountId, value: Balance) -> bool {
+        tests::MockToken::transfer_for(token, sender, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_approve_for(token: AccountId, sender: AccountId, spender: AccountId, value: Balance) -> bool {
+        tests::MockToken::approve_for(token, sender, spender, value)
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+        use std::{
+            cell::RefCell,
+            collections::HashMap,
+        };
+
+        type Event = <Forwarder as ::ink_lang::BaseEvent>::Type;
+
+        /// Ledger of `MockToken`
+        #[derive(Default)]
+        struct Ledger {
+            balances: HashMap<AccountId, Balance>,
+            allowances: HashMap<(AccountId, AccountId), Balance>,
+            trusted_forwarder: Option<AccountId>,
+        }
+
+        thread_local! {
+            static LEDGER: RefCell<Ledger> = RefCell::new(Default::default());
+        }
+
+        /// Stand-in for the Entropy contract, only accepting `*_for` calls from its trusted forwarder.
+        pub(super) struct MockToken;
+
+        impl MockToken {
+            fn fund(account: AccountId, balance: Balance) {
+                LEDGER.with(|ledger| ledger.borrow_mut().balances.insert(account, balance));
+            }
+
+            fn set_trusted_forwarder(forwarder: Option<AccountId>) {
+                LEDGER.with(|ledger| ledger.borrow_mut().trusted_forwarder = forwarder);
+            }
+
+            fn balance_of(account: AccountId) -> Balance {
+                LEDGER.with(|ledger| ledger.borrow().balances.get(&account).copied().unwrap_or(0))
+            }
+
+            fn allowance(owner: AccountId, spender: AccountId) -> Balance {
+                LEDGER.with(|ledger| ledger.borrow().allowances.get(&(owner, spender)).copied().unwrap_or(0))
+            }
+
+            /// Returns whether the forwarder calling the token is trusted.
+            fn is_trusted(token: AccountId) -> bool {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| ledger.borrow().trusted_forwarder == Some(forwarder_account()))
+            }
+
+            pub(super) fn transfer_for(token: AccountId, sender: AccountId, to: AccountId, value: Balance) -> bool {
+                if !Self::is_trusted(token) {
+                    return false;
+                }
+                LEDGER.with(|ledger| {
+                    let mut ledger = ledger.borrow_mut();
+                    let balance = ledger.balances.get(&sender).copied().unwrap_or(0);
+                    if balance < value {
+                        return false;
+                    }
+                    ledger.balances.insert(sender, balance - value);
+                    *ledger.balances.entry(to).or_insert(0) += value;
+                    true
+                })
+            }
+
+            pub(super) fn approve_for(token: AccountId, sender: AccountId, spender: AccountId, value: Balance) -> bool {
+                if !Self::is_trusted(token) {
+                    return false;
+                }
+                LEDGER.with(|ledger| ledger.borrow_mut().allowances.insert((sender, spender), value));
+                true
+            }
+        }
+
+        fn token_account() -> AccountId {
+            AccountId::from([0x10; 32])
+        }
+
+        fn forwarder_account() -> AccountId {
+            ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account")
+        }
+
+        /// Creates a forwarder trusted by the token and clears the ledger left over by previous tests.
+        fn new_forwarder() -> Forwarder {
+            LEDGER.with(|ledger| *ledger.borrow_mut() = Default::default());
+            MockToken::set_trusted_forwarder(Some(forwarder_account()));
+            Forwarder::new(token_account())
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = forwarder_account();
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(caller, callee, 1000000, 1000000, data);
+        }
+
+        #[ink::test]
+        fn forwards_callers_transfers_and_approvals() {
+            let mut forwarder = new_forwarder();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 100);
+
+            set_caller(accounts.bob);
+            assert_eq!(forwarder.forward_transfer(accounts.charlie, 30), Ok(()));
+            assert_eq!(forwarder.forward_approve(accounts.django, 20), Ok(()));
+            assert_eq!(MockToken::balance_of(accounts.bob), 70);
+            assert_eq!(MockToken::balance_of(accounts.charlie), 30);
+            assert_eq!(MockToken::allowance(accounts.bob, accounts.django), 20);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[0].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Forwarded(Forwarded { sender, counterparty, value }) = decoded_event {
+                assert_eq!((sender, counterparty, value), (accounts.bob, accounts.charlie, 30));
+            } else {
+                panic!("encountered unexpected event kind: expected a Forwarded event")
+            }
+
+            // Callers can only move their own tokens
+            set_caller(accounts.charlie);
+            assert_eq!(forwarder.forward_transfer(accounts.charlie, 31), Err(Error::TokenCallFailed));
+            assert_eq!(forwarder.forward_transfer(accounts.eve, 30), Ok(()));
+            assert_eq!(MockToken::balance_of(accounts.bob), 70);
+            assert_eq!(MockToken::balance_of(accounts.eve), 30);
+        }
+
+        #[ink::test]
+        fn untrusted_forwarder_is_rejected() {
+            let mut forwarder = new_forwarder();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 100);
+
+            // The token trusts another forwarder
+            MockToken::set_trusted_forwarder(Some(accounts.eve));
+            set_caller(accounts.bob);
+            assert_eq!(forwarder.forward_transfer(accounts.charlie, 30), Err(Error::TokenCallFailed));
+            assert_eq!(forwarder.forward_approve(accounts.django, 20), Err(Error::TokenCallFailed));
+            assert_eq!(MockToken::balance_of(accounts.bob), 100);
+            assert_eq!(MockToken::allowance(accounts.bob, accounts.django), 0);
+
+            MockToken::set_trusted_forwarder(None);
+            assert_eq!(forwarder.forward_transfer(accounts.charlie, 30), Err(Error::TokenCallFailed));
+        }
+    }
+}
@@ -0,0 +1,642 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod gas_station {
+    use core::fmt;
+
+    use ink_prelude::{
+        format,
+        string::String,
+        vec::Vec,
+    };
+
+    use ink_storage::collections::HashMap as StorageHashMap;
+
+    /// Transfer signed off-chain by `from` in the format of Entropy's `transfer_with_signature`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct SignedTransfer {
+        pub from: AccountId,
+        pub to: AccountId,
+        pub value: Balance,
+        pub deadline: Timestamp,
+        pub nonce: u64,
+        pub signature: [u8; 64],
+    }
+
+    /// User operation submitted through the gas station: a transfer plus the transfer paying its fee.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct RelayOp {
+        pub transfer: SignedTransfer,
+        /// Transfer of at least `fee_per_op()` tokens from the same user to the gas station
+        pub fee: SignedTransfer,
+    }
+
+    /// Gas station relaying Entropy transfers for users without native tokens.
+    ///
+    /// Relayers pay the native gas of submitting users' signed transfers and earn the fee in ENT each
+    /// user signs to the gas station alongside their transfer.
+    #[ink(storage)]
+    pub struct GasStation {
+        owner: AccountId,
+
+        /// Entropy contract whose transfers are relayed
+        token: AccountId,
+
+        /// Native token cost of relaying a single operation
+        native_fee_per_op: Balance,
+
+        /// ENT charged per native token
+        exchange_rate: Balance,
+
+        /// Maximum number of operations relayed per user within a window, `0` disables the limit
+        max_ops_per_window: u32,
+
+        /// Rate limiting window length in milliseconds
+        window_ms: u64,
+
+        /// Mapping of a user's current window start and number of operations relayed within it
+        user_ops: StorageHashMap<AccountId, (Timestamp, u32)>,
+
+        /// Mapping of a relayer's ENT earnings not withdrawn yet
+        earnings: StorageHashMap<AccountId, Balance>
+    }
+
+    /// Event emitted when a user operation is relayed.
+    #[ink(event)]
+    pub struct Relayed {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        relayer: AccountId,
+        to: AccountId,
+        value: Balance,
+        /// ENT received by the gas station for the operation
+        fee: Balance
+    }
+
+    /// Event emitted when a relayer withdraws earnings.
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        relayer: AccountId,
+        value: Balance
+    }
+
+    /// Event emitted when pricing is set.
+    #[ink(event)]
+    pub struct PricingChanged {
+        native_fee_per_op: Balance,
+        exchange_rate: Balance
+    }
+
+    /// Event emitted when the per-user rate limit is set.
+    #[ink(event)]
+    pub struct RateLimitChanged {
+        max_ops_per_window: u32,
+        window_ms: u64
+    }
+
+    /// Event emitted when error occurs
+    #[ink(event)]
+    pub struct TransactionFailed {
+        #[ink(topic)]
+        error: String
+    }
+
+    /// GasStation error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if not privileged.
+        PermissionDenied,
+        /// Returned if a fee transfer is not from the operation's user to the gas station
+        InvalidFee,
+        /// Returned if a fee transfer is below the fee per operation
+        InsufficientFee,
+        /// Returned if a user exceeds the number of operations relayed per window
+        RateLimited,
+        /// Returned if the Entropy contract rejects a transfer
+        TokenTransferFailed,
+        /// Returned if a relayer withdraws more than it earned
+        InsufficientEarnings
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Self::PermissionDenied => write!(f, "PermissionDenied"),
+                Self::InvalidFee => write!(f, "InvalidFee"),
+                Self::InsufficientFee => write!(f, "InsufficientFee"),
+                Self::RateLimited => write!(f, "RateLimited"),
+                Self::TokenTransferFailed => write!(f, "TokenTransferFailed"),
+                Self::InsufficientEarnings => write!(f, "InsufficientEarnings")
+            }
+        }
+    }
+
+    /// GasStation result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl GasStation {
+
+        /// Creates a new gas station for the Entropy contract at `token`.
+        #[ink(constructor)]
+        pub fn new(token: AccountId, native_fee_per_op: Balance, exchange_rate: Balance, max_ops_per_window: u32, window_ms: u64) -> Self {
+            Self {
+                owner: Self::env().caller(),
+                token,
+                native_fee_per_op,
+                exchange_rate,
+                max_ops_per_window,
+                window_ms,
+                user_ops: StorageHashMap::new(),
+                earnings: StorageHashMap::new()
+            }
+        }
+
+        /// Returns the contract owner.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns the Entropy contract whose transfers are relayed.
+        #[ink(message)]
+        pub fn token(&self) -> AccountId {
+            self.token
+        }
+
+        /// Returns the ENT fee charged per relayed operation.
+        #[ink(message)]
+        pub fn fee_per_op(&self) -> Balance {
+            self.native_fee_per_op.saturating_mul(self.exchange_rate)
+        }
+
+        /// Set the native cost of relaying an operation and the ENT charged per native token
+        ///
+        /// On success a `PricingChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message)]
+        pub fn set_pricing(&mut self, native_fee_per_op: Balance, exchange_rate: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::PermissionDenied)
+                });
+                return Err(Error::PermissionDenied);
+            }
+
+            self.native_fee_per_op = native_fee_per_op;
+            self.exchange_rate = exchange_rate;
+
+            self.env().emit_event(PricingChanged {
+                native_fee_per_op,
+                exchange_rate
+            });
+
+            Ok(())
+        }
+
+        /// Set how many operations of a single user are relayed per `window_ms` milliseconds
+        ///
+        /// On success a `RateLimitChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        #[ink(message)]
+        pub fn set_rate_limit(&mut self, max_ops_per_window: u32, window_ms: u64) -> Result<()> {
+            if self.env().caller() != self.owner {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::PermissionDenied)
+                });
+                return Err(Error::PermissionDenied);
+            }
+
+            self.max_ops_per_window = max_ops_per_window;
+            self.window_ms = window_ms;
+
+            self.env().emit_event(RateLimitChanged {
+                max_ops_per_window,
+                window_ms
+            });
+
+            Ok(())
+        }
+
+        /// Returns the ENT earnings of a relayer not withdrawn yet.
+        #[ink(message)]
+        pub fn earnings_of(&self, relayer: AccountId) -> Balance {
+            self.earnings.get(&relayer).copied().unwrap_or(0)
+        }
+
+        /// Submits users' signed operations in one call, crediting their fees to the caller's earnings
+        ///
+        /// Entropy has no multicall, so operations are submitted one after another. Each operation's fee
+        /// transfer is submitted before its transfer, so a relayer is paid even if the transfer itself
+        /// fails, and operations are relayed up to the first failing one. A `Relayed` event is emitted
+        /// per relayed operation.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidFee` error if a fee transfer is not from the operation's user to the gas station.
+        ///
+        /// Returns `InsufficientFee` error if a fee transfer is below `fee_per_op()`.
+        ///
+        /// Returns `RateLimited` error if a user exceeds the number of operations relayed per window.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects a fee transfer or a transfer.
+        #[ink(message)]
+        pub fn relay(&mut self, ops: Vec<RelayOp>) -> Result<()> {
+            let relayer = self.env().caller();
+            for op in ops {
+                self.relay_op(relayer, op)?;
+            }
+            Ok(())
+        }
+
+        /// Transfers `value` of the caller's earnings to the caller
+        ///
+        /// On success a `Withdrawn` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientEarnings` error if the caller earned less than `value`.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the transfer.
+        #[ink(message)]
+        pub fn withdraw(&mut self, value: Balance) -> Result<()> {
+            let relayer = self.env().caller();
+            let earnings = self.earnings_of(relayer);
+            if earnings < value {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InsufficientEarnings)
+                });
+                return Err(Error::InsufficientEarnings);
+            }
+
+            if !token_transfer(self.token, relayer, value) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenTransferFailed)
+                });
+                return Err(Error::TokenTransferFailed);
+            }
+            self.earnings.insert(relayer, earnings - value);
+
+            self.env().emit_event(Withdrawn {
+                relayer,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Checks and submits a single operation, crediting the ENT the gas station received to `relayer`.
+        fn relay_op(&mut self, relayer: AccountId, op: RelayOp) -> Result<()> {
+            let user = op.transfer.from;
+            let station = self.env().account_id();
+            if op.fee.from != user || op.fee.to != station {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InvalidFee)
+                });
+                return Err(Error::InvalidFee);
+            }
+            if op.fee.value < self.fee_per_op() {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InsufficientFee)
+                });
+                return Err(Error::InsufficientFee);
+            }
+
+            self.record_op(user)?;
+
+            // Entropy may charge its own transfer fee, so credit what actually arrived
+            let station_balance = token_balance_of(self.token, station);
+            if !token_transfer_with_signature(self.token, op.fee) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenTransferFailed)
+                });
+                return Err(Error::TokenTransferFailed);
+            }
+            let fee = token_balance_of(self.token, station).saturating_sub(station_balance);
+            let earnings = self.earnings_of(relayer);
+            self.earnings.insert(relayer, earnings.saturating_add(fee));
+
+            if !token_transfer_with_signature(self.token, op.transfer) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenTransferFailed)
+                });
+                return Err(Error::TokenTransferFailed);
+            }
+
+            self.env().emit_event(Relayed {
+                user,
+                relayer,
+                to: op.transfer.to,
+                value: op.transfer.value,
+                fee
+            });
+
+            Ok(())
+        }
+
+        /// Counts an operation of `user` against the rate limit, starting a new window if the current one elapsed.
+        ///
+        /// # Errors
+        ///
+        /// Returns `RateLimited` error if `user` already reached the limit within the current window.
+        fn record_op(&mut self, user: AccountId) -> Result<()> {
+            if self.max_ops_per_window == 0 {
+                return Ok(());
+            }
+
+            let now = self.env().block_timestamp();
+            let (mut start, mut count) = self.user_ops.get(&user).copied().unwrap_or((now, 0));
+            if now >= start.saturating_add(self.window_ms) {
+                start = now;
+                count = 0;
+            }
+            if count >= self.max_ops_per_window {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::RateLimited)
+                });
+                return Err(Error::RateLimited);
+            }
+            self.user_ops.insert(user, (start, count + 1));
+            Ok(())
+        }
+    }
+
+    /// Submits a signed transfer through Entropy's `transfer_with_signature`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer_with_signature(token: AccountId, transfer: SignedTransfer) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer_with_signature(transfer.from, transfer.to, transfer.value, transfer.deadline, transfer.nonce, transfer.signature).is_ok()
+    }
+
+    /// Transfers the gas station's own tokens through Entropy's `transfer`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer(to, value, None).is_ok()
+    }
+
+    /// Returns the Entropy balance of `account`.
+    #[cfg(not(test))]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        use ink_env::call::FromAccountId;
+
+        let token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.balance_of(account)
+    }
+
+    // Off-chain stand-ins for the cross-contract calls, as ink's test environment can't execute other
+    // contracts. They operate on the ledger of `tests::MockToken`.
+
+    #[cfg(test)]
+    fn token_transfer_with_signature(token: AccountId, transfer: SignedTransfer) -> bool {
+        tests::MockToken::transfer_with_signature(token, transfer)
+    }
+
+    #[cfg(test)]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        let from = ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account");
+        tests::MockToken::transfer(token, from, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        tests::MockToken::balance_of(token, account)
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+        use std::{
+            cell::RefCell,
+            collections::HashMap,
+        };
+
+        type Event = <GasStation as ::ink_lang::BaseEvent>::Type;
+
+        thread_local! {
+            /// Balances and signed transfer nonces of `MockToken`
+            static LEDGER: RefCell<(HashMap<AccountId, Balance>, HashMap<AccountId, u64>)> = RefCell::new(Default::default());
+        }
+
+        /// Stand-in for the Entropy contract, charging no transfer fee.
+        pub(super) struct MockToken;
+
+        impl MockToken {
+            fn fund(account: AccountId, balance: Balance) {
+                LEDGER.with(|ledger| ledger.borrow_mut().0.insert(account, balance));
+            }
+
+            pub(super) fn balance_of(token: AccountId, account: AccountId) -> Balance {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| ledger.borrow().0.get(&account).copied().unwrap_or(0))
+            }
+
+            pub(super) fn transfer(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| {
+                    let mut ledger = ledger.borrow_mut();
+                    let balance = ledger.0.get(&from).copied().unwrap_or(0);
+                    if balance < value {
+                        return false;
+                    }
+                    ledger.0.insert(from, balance - value);
+                    *ledger.0.entry(to).or_insert(0) += value;
+                    true
+                })
+            }
+
+            pub(super) fn transfer_with_signature(token: AccountId, transfer: SignedTransfer) -> bool {
+                let nonce = LEDGER.with(|ledger| ledger.borrow().1.get(&transfer.from).copied().unwrap_or(0));
+                let now = ink_env::block_timestamp::<ink_env::DefaultEnvironment>().expect("Cannot get block timestamp");
+                if transfer.nonce != nonce || now > transfer.deadline || transfer.signature != mock_sign(transfer.from, &transfer) {
+                    return false;
+                }
+                if !Self::transfer(token, transfer.from, transfer.to, transfer.value) {
+                    return false;
+                }
+                LEDGER.with(|ledger| ledger.borrow_mut().1.insert(transfer.from, nonce + 1));
+                true
+            }
+        }
+
+        fn token_account() -> AccountId {
+            AccountId::from([0x10; 32])
+        }
+
+        fn station_account() -> AccountId {
+            ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account")
+        }
+
+        /// Creates a gas station charging 2 native tokens at 5 ENT each per operation, for at most 2
+        /// operations per user every 100ms, and clears the ledger left over by previous tests.
+        fn new_station() -> GasStation {
+            LEDGER.with(|ledger| *ledger.borrow_mut() = Default::default());
+            GasStation::new(token_account(), 2, 5, 2, 100)
+        }
+
+        fn mock_sign(signer: AccountId, transfer: &SignedTransfer) -> [u8; 64] {
+            let payload = (signer, transfer.to, transfer.value, transfer.deadline, transfer.nonce);
+            let mut digest = [0x0; 32];
+            ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(&payload, &mut digest);
+            let mut signature = [0x0; 64];
+            signature[..32].copy_from_slice(&digest);
+            signature
+        }
+
+        /// Signs a transfer of `value` from `signer` to `to` with the given nonce.
+        fn signed(signer: AccountId, to: AccountId, value: Balance, nonce: u64) -> SignedTransfer {
+            let mut transfer = SignedTransfer { from: signer, to, value, deadline: 1_000, nonce, signature: [0x0; 64] };
+            transfer.signature = mock_sign(signer, &transfer);
+            transfer
+        }
+
+        /// Signs an operation of `user` paying the standard fee, using nonces `nonce` and `nonce + 1`.
+        fn op(user: AccountId, to: AccountId, value: Balance, nonce: u64) -> RelayOp {
+            RelayOp {
+                fee: signed(user, station_account(), 10, nonce),
+                transfer: signed(user, to, value, nonce + 1),
+            }
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = station_account();
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(caller, callee, 1000000, 1000000, data);
+        }
+
+        fn assert_relayed_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_user: AccountId,
+            expected_relayer: AccountId,
+            expected_value: Balance,
+            expected_fee: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Relayed(Relayed { user, relayer, value, fee, .. }) = decoded_event {
+                assert_eq!(user, expected_user, "encountered invalid Relayed.user");
+                assert_eq!(relayer, expected_relayer, "encountered invalid Relayed.relayer");
+                assert_eq!(value, expected_value, "encountered invalid Relayed.value");
+                assert_eq!(fee, expected_fee, "encountered invalid Relayed.fee");
+            } else {
+                panic!("encountered unexpected event kind: expected a Relayed event")
+            }
+        }
+
+        #[ink::test]
+        fn user_without_native_balance_transfers() {
+            let mut station = new_station();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            let (user, relayer) = (accounts.bob, accounts.eve);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(user, 0).expect("Cannot set balance");
+            MockToken::fund(user, 100);
+
+            assert_eq!(station.fee_per_op(), 10);
+
+            // The user only signs, the relayer submits and pays the native gas
+            set_caller(relayer);
+            assert_eq!(station.relay(vec![op(user, accounts.charlie, 50, 0)]), Ok(()));
+            assert_eq!(MockToken::balance_of(token_account(), accounts.charlie), 50);
+            assert_eq!(MockToken::balance_of(token_account(), user), 40);
+            assert_eq!(MockToken::balance_of(token_account(), station_account()), 10);
+            assert_eq!(station.earnings_of(relayer), 10);
+            assert_eq!(ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(user), Ok(0));
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_relayed_event(&emitted_events[emitted_events.len() - 1], user, relayer, 50, 10);
+
+            // The relayer withdraws its earnings in ENT
+            assert_eq!(station.withdraw(11), Err(Error::InsufficientEarnings));
+            assert_eq!(station.withdraw(10), Ok(()));
+            assert_eq!(station.earnings_of(relayer), 0);
+            assert_eq!(MockToken::balance_of(token_account(), relayer), 10);
+        }
+
+        #[ink::test]
+        fn relay_batches_operations() {
+            let mut station = new_station();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 100);
+            MockToken::fund(accounts.charlie, 100);
+
+            set_caller(accounts.eve);
+            assert_eq!(station.relay(vec![
+                op(accounts.bob, accounts.django, 10, 0),
+                op(accounts.charlie, accounts.django, 20, 0),
+                op(accounts.bob, accounts.django, 30, 2),
+            ]), Ok(()));
+            assert_eq!(MockToken::balance_of(token_account(), accounts.django), 60);
+            assert_eq!(station.earnings_of(accounts.eve), 30);
+        }
+
+        #[ink::test]
+        fn invalid_operations_are_rejected() {
+            let mut station = new_station();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 100);
+            set_caller(accounts.eve);
+
+            // Fee paid by someone else, to someone else, or too low
+            let mut invalid = op(accounts.bob, accounts.django, 10, 0);
+            invalid.fee = signed(accounts.charlie, station_account(), 10, 0);
+            assert_eq!(station.relay(vec![invalid]), Err(Error::InvalidFee));
+            invalid.fee = signed(accounts.bob, accounts.eve, 10, 0);
+            assert_eq!(station.relay(vec![invalid]), Err(Error::InvalidFee));
+            invalid.fee = signed(accounts.bob, station_account(), 9, 0);
+            assert_eq!(station.relay(vec![invalid]), Err(Error::InsufficientFee));
+
+            // Forged fee signature
+            invalid.fee = signed(accounts.bob, station_account(), 10, 0);
+            invalid.fee.signature = [0x1; 64];
+            assert_eq!(station.relay(vec![invalid]), Err(Error::TokenTransferFailed));
+            assert_eq!(station.earnings_of(accounts.eve), 0);
+            assert_eq!(MockToken::balance_of(token_account(), accounts.bob), 100);
+
+            // Only the owner sets pricing and rate limits
+            assert_eq!(station.set_pricing(1, 1), Err(Error::PermissionDenied));
+            assert_eq!(station.set_rate_limit(0, 0), Err(Error::PermissionDenied));
+            set_caller(accounts.alice);
+            assert_eq!(station.set_pricing(1, 20), Ok(()));
+            assert_eq!(station.fee_per_op(), 20);
+        }
+
+        #[ink::test]
+        fn rate_limit_works() {
+            let mut station = new_station();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 1_000);
+            set_caller(accounts.eve);
+
+            assert_eq!(station.relay(vec![op(accounts.bob, accounts.django, 1, 0)]), Ok(()));
+            assert_eq!(station.relay(vec![op(accounts.bob, accounts.django, 1, 2)]), Ok(()));
+            assert_eq!(station.relay(vec![op(accounts.bob, accounts.django, 1, 4)]), Err(Error::RateLimited));
+
+            // The window rolls after 100ms
+            for _ in 0..20 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>().expect("Cannot advance block");
+            }
+            assert_eq!(station.relay(vec![op(accounts.bob, accounts.django, 1, 4)]), Ok(()));
+            assert_eq!(MockToken::balance_of(token_account(), accounts.django), 3);
+        }
+    }
+}
@@ -0,0 +1,1000 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod lending_vault {
+    use core::fmt;
+
+    #[cfg(not(test))]
+    use entropy::Erc20;
+
+    use ink_prelude::{
+        format,
+        string::String,
+    };
+
+    use ink_storage::{
+        collections::HashMap as StorageHashMap,
+        traits::{
+            PackedLayout,
+            SpreadLayout,
+        },
+    };
+
+    /// Entropy units the oracle price is quoted for, i.e. one ENT at 6 decimals.
+    pub const PRICE_UNIT: Balance = 1_000_000;
+
+    /// Scale of the borrow index and the per-block interest rate.
+    pub const INDEX_SCALE: Balance = 1_000_000_000_000;
+
+    /// Scale of `health_factor`, which is 1 at `HEALTH_SCALE`.
+    pub const HEALTH_SCALE: Balance = 10_000;
+
+    /// Native currency borrowed by an account.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Loan {
+        /// Debt at the time of the loan's last update
+        pub principal: Balance,
+        /// Borrow index at the time of the loan's last update
+        pub index: Balance,
+    }
+
+    /// Risk parameters of the vault, all in basis points.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct RiskParams {
+        /// Share of the collateral value that can be borrowed, below which debt is liquidatable
+        pub collateral_factor: Balance,
+        /// Collateral value a liquidator seizes on top of the debt repaid
+        pub liquidation_bonus: Balance,
+        /// Share of a loan's debt a single liquidation may repay
+        pub close_factor: Balance,
+    }
+
+    /// Lending vault for Entropy.
+    ///
+    /// Users deposit ENT as collateral and borrow the vault's native currency against it. Borrows accrue
+    /// interest every block through a global borrow index, and loans whose debt exceeds the borrowing
+    /// limit at the owner-set price can be liquidated by anyone repaying part of the debt for discounted
+    /// collateral.
+    #[ink(storage)]
+    pub struct LendingVault {
+        owner: AccountId,
+
+        /// Entropy contract accepted as collateral
+        token: AccountId,
+
+        /// Native currency value of `PRICE_UNIT` ENT, set by the owner as the oracle
+        price: Balance,
+
+        risk_params: RiskParams,
+
+        /// Interest per block, scaled by `INDEX_SCALE`
+        interest_rate: Balance,
+
+        /// Accumulated interest factor since deployment, scaled by `INDEX_SCALE`
+        borrow_index: Balance,
+
+        /// Block the borrow index was last accrued at
+        accrued_block: BlockNumber,
+
+        /// Native currency available to borrow
+        cash: Balance,
+
+        /// Debt of all loans as of the last accrual
+        total_borrows: Balance,
+
+        /// Mapping of ENT deposited as collateral by each account
+        collateral: StorageHashMap<AccountId, Balance>,
+
+        /// Mapping of each account's loan
+        loans: StorageHashMap<AccountId, Loan>
+    }
+
+    /// Event emitted when collateral is deposited.
+    #[ink(event)]
+    pub struct Deposited {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance
+    }
+
+    /// Event emitted when collateral is withdrawn.
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance
+    }
+
+    /// Event emitted when native currency is borrowed.
+    #[ink(event)]
+    pub struct Borrowed {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        debt: Balance
+    }
+
+    /// Event emitted when debt is repaid.
+    #[ink(event)]
+    pub struct Repaid {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        debt: Balance
+    }
+
+    /// Event emitted when a loan is liquidated.
+    #[ink(event)]
+    pub struct Liquidated {
+        #[ink(topic)]
+        liquidator: AccountId,
+        #[ink(topic)]
+        borrower: AccountId,
+        repaid: Balance,
+        seized: Balance
+    }
+
+    /// Event emitted when native currency is added to or removed from the vault's cash.
+    #[ink(event)]
+    pub struct CashChanged {
+        #[ink(topic)]
+        by: AccountId,
+        cash: Balance
+    }
+
+    /// Event emitted when the oracle price is set.
+    #[ink(event)]
+    pub struct PriceChanged {
+        price: Balance
+    }
+
+    /// Event emitted when the risk parameters or interest rate are set.
+    #[ink(event)]
+    pub struct ParamsChanged {
+        risk_params: RiskParams,
+        interest_rate: Balance
+    }
+
+    /// Event emitted when error occurs
+    #[ink(event)]
+    pub struct TransactionFailed {
+        #[ink(topic)]
+        error: String
+    }
+
+    /// LendingVault error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if not privileged.
+        PermissionDenied,
+        /// Returned if a price of zero or a risk parameter above 100% is set
+        InvalidParams,
+        /// Returned if an amount is zero
+        ZeroAmount,
+        /// Returned if debt would exceed the borrowing limit of the collateral
+        InsufficientCollateral,
+        /// Returned if the vault lacks the native currency to lend or withdraw
+        InsufficientCash,
+        /// Returned if repaying an account without debt
+        NoDebt,
+        /// Returned if liquidating a loan within its borrowing limit
+        NotLiquidatable,
+        /// Returned if the Entropy contract rejects a transfer
+        TokenTransferFailed,
+        /// Returned if amounts are too large to compute with
+        Overflow
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Self::PermissionDenied => write!(f, "PermissionDenied"),
+                Self::InvalidParams => write!(f, "InvalidParams"),
+                Self::ZeroAmount => write!(f, "ZeroAmount"),
+                Self::InsufficientCollateral => write!(f, "InsufficientCollateral"),
+                Self::InsufficientCash => write!(f, "InsufficientCash"),
+                Self::NoDebt => write!(f, "NoDebt"),
+                Self::NotLiquidatable => write!(f, "NotLiquidatable"),
+                Self::TokenTransferFailed => write!(f, "TokenTransferFailed"),
+                Self::Overflow => write!(f, "Overflow")
+            }
+        }
+    }
+
+    /// LendingVault result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl LendingVault {
+
+        /// Creates an empty vault for the Entropy contract at `token`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `price` is zero or a risk parameter is invalid, see `set_params`.
+        #[ink(constructor)]
+        pub fn new(token: AccountId, price: Balance, risk_params: RiskParams, interest_rate: Balance) -> Self {
+            assert!(price > 0 && Self::valid_risk_params(&risk_params), "invalid vault parameters");
+            Self {
+                owner: Self::env().caller(),
+                token,
+                price,
+                risk_params,
+                interest_rate,
+                borrow_index: INDEX_SCALE,
+                accrued_block: Self::env().block_number(),
+                cash: 0,
+                total_borrows: 0,
+                collateral: StorageHashMap::new(),
+                loans: StorageHashMap::new()
+            }
+        }
+
+        /// Returns the contract owner.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns the Entropy contract accepted as collateral.
+        #[ink(message)]
+        pub fn token(&self) -> AccountId {
+            self.token
+        }
+
+        /// Returns the native currency value of `PRICE_UNIT` ENT.
+        #[ink(message)]
+        pub fn price(&self) -> Balance {
+            self.price
+        }
+
+        /// Returns the risk parameters.
+        #[ink(message)]
+        pub fn risk_params(&self) -> RiskParams {
+            self.risk_params
+        }
+
+        /// Returns the interest per block, scaled by `INDEX_SCALE`.
+        #[ink(message)]
+        pub fn interest_rate(&self) -> Balance {
+            self.interest_rate
+        }
+
+        /// Returns the native currency available to borrow.
+        #[ink(message)]
+        pub fn cash(&self) -> Balance {
+            self.cash
+        }
+
+        /// Returns the debt of all loans including interest up to the current block.
+        #[ink(message)]
+        pub fn total_borrows(&self) -> Balance {
+            mul_div(self.total_borrows, self.current_index(), self.borrow_index).unwrap_or(Balance::MAX)
+        }
+
+        /// Returns the ENT deposited as collateral by `account`.
+        #[ink(message)]
+        pub fn collateral_of(&self, account: AccountId) -> Balance {
+            self.collateral.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Returns the debt of `account` including interest up to the current block.
+        #[ink(message)]
+        pub fn debt_of(&self, account: AccountId) -> Balance {
+            self.debt_at(account, self.current_index()).unwrap_or(Balance::MAX)
+        }
+
+        /// Returns the native currency `account` can owe against its collateral at the current price.
+        #[ink(message)]
+        pub fn borrow_limit(&self, account: AccountId) -> Balance {
+            self.limit_for(self.collateral_of(account)).unwrap_or(Balance::MAX)
+        }
+
+        /// Returns the borrowing limit of `account` over its debt, scaled by `HEALTH_SCALE`.
+        ///
+        /// The loan is liquidatable below `HEALTH_SCALE`. Accounts without debt return `Balance::MAX`.
+        #[ink(message)]
+        pub fn health_factor(&self, account: AccountId) -> Balance {
+            match self.debt_of(account) {
+                0 => Balance::MAX,
+                debt => mul_div(self.borrow_limit(account), HEALTH_SCALE, debt).unwrap_or(Balance::MAX)
+            }
+        }
+
+        /// Set the native currency value of `PRICE_UNIT` ENT
+        ///
+        /// On success a `PriceChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidParams` error if `price` is zero.
+        #[ink(message)]
+        pub fn set_price(&mut self, price: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(self.reject(Error::PermissionDenied, 0));
+            }
+            if price == 0 {
+                return Err(self.reject(Error::InvalidParams, 0));
+            }
+
+            self.price = price;
+            self.env().emit_event(PriceChanged {
+                price
+            });
+
+            Ok(())
+        }
+
+        /// Set the risk parameters and the interest per block
+        ///
+        /// Interest up to the current block accrues at the previous rate.
+        ///
+        /// On success a `ParamsChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InvalidParams` error if the collateral or close factor is zero or above 100%, or the
+        /// liquidation bonus is above 100%.
+        #[ink(message)]
+        pub fn set_params(&mut self, risk_params: RiskParams, interest_rate: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(self.reject(Error::PermissionDenied, 0));
+            }
+            if !Self::valid_risk_params(&risk_params) {
+                return Err(self.reject(Error::InvalidParams, 0));
+            }
+
+            self.accrue_interest();
+            self.risk_params = risk_params;
+            self.interest_rate = interest_rate;
+            self.env().emit_event(ParamsChanged {
+                risk_params,
+                interest_rate
+            });
+
+            Ok(())
+        }
+
+        /// Adds the transferred native currency to the cash available to borrow
+        ///
+        /// On success a `CashChanged` event is emitted.
+        #[ink(message, payable)]
+        pub fn fund(&mut self) {
+            self.cash += self.env().transferred_balance();
+            self.env().emit_event(CashChanged {
+                by: self.env().caller(),
+                cash: self.cash
+            });
+        }
+
+        /// Withdraws `value` of the cash not lent out to the owner
+        ///
+        /// On success a `CashChanged` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `PermissionDenied` error if caller is not the owner.
+        ///
+        /// Returns `InsufficientCash` error if the cash is below `value`.
+        #[ink(message)]
+        pub fn withdraw_cash(&mut self, value: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(self.reject(Error::PermissionDenied, 0));
+            }
+            if self.cash < value {
+                return Err(self.reject(Error::InsufficientCash, 0));
+            }
+
+            self.cash -= value;
+            self.pay_native(caller, value);
+            self.env().emit_event(CashChanged {
+                by: caller,
+                cash: self.cash
+            });
+
+            Ok(())
+        }
+
+        /// Deposits `value` of the caller's ENT as collateral
+        ///
+        /// ENT is pulled with `transfer_from`, so the caller must have approved the vault. If Entropy
+        /// charges a transfer fee, what the vault actually received is credited.
+        ///
+        /// On success a `Deposited` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if `value` is zero.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the pull.
+        #[ink(message)]
+        pub fn deposit(&mut self, value: Balance) -> Result<()> {
+            let account = self.env().caller();
+            if value == 0 {
+                return Err(self.reject(Error::ZeroAmount, 0));
+            }
+
+            let vault = self.env().account_id();
+            let balance = token_balance_of(self.token, vault);
+            if !token_transfer_from(self.token, account, vault, value) {
+                return Err(self.reject(Error::TokenTransferFailed, 0));
+            }
+            let received = token_balance_of(self.token, vault).saturating_sub(balance);
+
+            let collateral = self.collateral_of(account);
+            self.collateral.insert(account, collateral + received);
+            self.env().emit_event(Deposited {
+                account,
+                value: received
+            });
+
+            Ok(())
+        }
+
+        /// Withdraws `value` of the caller's collateral
+        ///
+        /// On success a `Withdrawn` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if `value` is zero.
+        ///
+        /// Returns `InsufficientCollateral` error if the caller deposited less than `value`, or the
+        /// remaining collateral wouldn't cover the caller's debt.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the transfer.
+        #[ink(message)]
+        pub fn withdraw(&mut self, value: Balance) -> Result<()> {
+            let account = self.env().caller();
+            if value == 0 {
+                return Err(self.reject(Error::ZeroAmount, 0));
+            }
+
+            self.accrue_interest();
+            let collateral = self.collateral_of(account);
+            if collateral < value {
+                return Err(self.reject(Error::InsufficientCollateral, 0));
+            }
+            match (self.limit_for(collateral - value), self.debt_at(account, self.borrow_index)) {
+                (Some(limit), Some(debt)) if limit >= debt => (),
+                (Some(_), Some(_)) => return Err(self.reject(Error::InsufficientCollateral, 0)),
+                _ => return Err(self.reject(Error::Overflow, 0))
+            }
+
+            self.collateral.insert(account, collateral - value);
+            if !token_transfer(self.token, account, value) {
+                self.collateral.insert(account, collateral);
+                return Err(self.reject(Error::TokenTransferFailed, 0));
+            }
+            self.env().emit_event(Withdrawn {
+                account,
+                value
+            });
+
+            Ok(())
+        }
+
+        /// Borrows `value` native currency against the caller's collateral
+        ///
+        /// On success a `Borrowed` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if `value` is zero.
+        ///
+        /// Returns `InsufficientCollateral` error if the caller's debt would exceed its borrowing limit.
+        ///
+        /// Returns `InsufficientCash` error if the vault lacks `value` to lend.
+        #[ink(message)]
+        pub fn borrow(&mut self, value: Balance) -> Result<()> {
+            let account = self.env().caller();
+            if value == 0 {
+                return Err(self.reject(Error::ZeroAmount, 0));
+            }
+
+            self.accrue_interest();
+            let debt = match (self.limit_for(self.collateral_of(account)), self.debt_at(account, self.borrow_index)) {
+                (Some(limit), Some(debt)) if debt.checked_add(value).map_or(false, |debt| debt <= limit) => debt + value,
+                (Some(_), Some(_)) => return Err(self.reject(Error::InsufficientCollateral, 0)),
+                _ => return Err(self.reject(Error::Overflow, 0))
+            };
+            if self.cash < value {
+                return Err(self.reject(Error::InsufficientCash, 0));
+            }
+
+            self.set_debt(account, debt);
+            self.total_borrows = self.total_borrows.saturating_add(value);
+            self.cash -= value;
+            self.pay_native(account, value);
+            self.env().emit_event(Borrowed {
+                account,
+                value,
+                debt
+            });
+
+            Ok(())
+        }
+
+        /// Repays the caller's debt with the transferred native currency, refunding what exceeds the debt
+        ///
+        /// On success a `Repaid` event is emitted and the remaining debt is returned.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if no native currency is transferred.
+        ///
+        /// Returns `NoDebt` error if the caller owes nothing.
+        #[ink(message, payable)]
+        pub fn repay(&mut self) -> Result<Balance> {
+            let account = self.env().caller();
+            let value = self.env().transferred_balance();
+            if value == 0 {
+                return Err(self.reject(Error::ZeroAmount, 0));
+            }
+
+            self.accrue_interest();
+            let debt = match self.debt_at(account, self.borrow_index) {
+                Some(0) => return Err(self.reject(Error::NoDebt, value)),
+                Some(debt) => debt,
+                None => return Err(self.reject(Error::Overflow, value))
+            };
+
+            let repaid = value.min(debt);
+            self.settle(account, debt, repaid);
+            self.pay_native(account, value - repaid);
+            self.env().emit_event(Repaid {
+                account,
+                value: repaid,
+                debt: debt - repaid
+            });
+
+            Ok(debt - repaid)
+        }
+
+        /// Repays up to the close factor of `borrower`'s debt with the transferred native currency, seizing
+        /// collateral worth the repaid amount plus the liquidation bonus
+        ///
+        /// What exceeds the close factor is refunded. If the collateral is worth less than the repaid
+        /// amount plus the bonus, all of it is seized.
+        ///
+        /// On success a `Liquidated` event is emitted and the seized ENT is returned.
+        ///
+        /// # Errors
+        ///
+        /// Returns `ZeroAmount` error if no native currency is transferred.
+        ///
+        /// Returns `NotLiquidatable` error if `borrower`'s debt is within its borrowing limit.
+        ///
+        /// Returns `TokenTransferFailed` error if Entropy rejects the transfer of the seized collateral.
+        #[ink(message, payable)]
+        pub fn liquidate(&mut self, borrower: AccountId) -> Result<Balance> {
+            let liquidator = self.env().caller();
+            let value = self.env().transferred_balance();
+            if value == 0 {
+                return Err(self.reject(Error::ZeroAmount, 0));
+            }
+
+            self.accrue_interest();
+            let collateral = self.collateral_of(borrower);
+            let debt = match (self.limit_for(collateral), self.debt_at(borrower, self.borrow_index)) {
+                (Some(limit), Some(debt)) if debt > limit => debt,
+                (Some(_), Some(_)) => return Err(self.reject(Error::NotLiquidatable, value)),
+                _ => return Err(self.reject(Error::Overflow, value))
+            };
+
+            let max_repaid = mul_div(debt, self.risk_params.close_factor, 10_000).unwrap_or(debt).max(1);
+            let repaid = value.min(max_repaid);
+            let seized = match mul_div(repaid, 10_000 + self.risk_params.liquidation_bonus, 10_000)
+                .and_then(|seized_value| mul_div(seized_value, PRICE_UNIT, self.price)) {
+                Some(seized) => seized.min(collateral),
+                None => return Err(self.reject(Error::Overflow, value))
+            };
+
+            self.collateral.insert(borrower, collateral - seized);
+            if seized > 0 && !token_transfer(self.token, liquidator, seized) {
+                self.collateral.insert(borrower, collateral);
+                return Err(self.reject(Error::TokenTransferFailed, value));
+            }
+            self.settle(borrower, debt, repaid);
+            self.pay_native(liquidator, value - repaid);
+            self.env().emit_event(Liquidated {
+                liquidator,
+                borrower,
+                repaid,
+                seized
+            });
+
+            Ok(seized)
+        }
+
+        fn valid_risk_params(risk_params: &RiskParams) -> bool {
+            risk_params.collateral_factor > 0 && risk_params.collateral_factor <= 10_000
+                && risk_params.close_factor > 0 && risk_params.close_factor <= 10_000
+                && risk_params.liquidation_bonus <= 10_000
+        }
+
+        /// Returns the borrow index including interest up to the current block.
+        fn current_index(&self) -> Balance {
+            let blocks = Balance::from(self.env().block_number().saturating_sub(self.accrued_block));
+            let interest = mul_div(self.borrow_index, self.interest_rate.saturating_mul(blocks), INDEX_SCALE).unwrap_or(Balance::MAX);
+            self.borrow_index.saturating_add(interest)
+        }
+
+        /// Accrues interest up to the current block into the borrow index and total borrows.
+        fn accrue_interest(&mut self) {
+            let index = self.current_index();
+            self.total_borrows = mul_div(self.total_borrows, index, self.borrow_index).unwrap_or(Balance::MAX);
+            self.borrow_index = index;
+            self.accrued_block = self.env().block_number();
+        }
+
+        /// Returns the debt of `account` at borrow index `index`, rounded up in favour of the vault.
+        fn debt_at(&self, account: AccountId, index: Balance) -> Option<Balance> {
+            match self.loans.get(&account) {
+                Some(loan) if loan.principal > 0 => mul_div_up(loan.principal, index, loan.index),
+                _ => Some(0)
+            }
+        }
+
+        /// Returns the borrowing limit of `collateral` ENT at the current price.
+        fn limit_for(&self, collateral: Balance) -> Option<Balance> {
+            let value = mul_div(collateral, self.price, PRICE_UNIT)?;
+            mul_div(value, self.risk_params.collateral_factor, 10_000)
+        }
+
+        fn set_debt(&mut self, account: AccountId, debt: Balance) {
+            self.loans.insert(account, Loan {
+                principal: debt,
+                index: self.borrow_index
+            });
+        }
+
+        /// Reduces `account`'s `debt` by `repaid`, returning the native currency to the cash.
+        fn settle(&mut self, account: AccountId, debt: Balance, repaid: Balance) {
+            self.set_debt(account, debt - repaid);
+            self.total_borrows = self.total_borrows.saturating_sub(repaid);
+            self.cash += repaid;
+        }
+
+        /// Sends native currency from the vault.
+        ///
+        /// Payouts never exceed what the vault tracks as received, so a failure means its balance is
+        /// broken and the call is reverted by panicking.
+        fn pay_native(&self, to: AccountId, value: Balance) {
+            if value > 0 && self.env().transfer(to, value).is_err() {
+                panic!("native transfer failed");
+            }
+        }
+
+        /// Emits a `TransactionFailed` event for `error` and refunds the `native` currency transferred
+        /// with the failed call.
+        fn reject(&self, error: Error, native: Balance) -> Error {
+            self.env().emit_event(TransactionFailed {
+                error: format!("{:?}", error)
+            });
+            self.pay_native(self.env().caller(), native);
+            error
+        }
+    }
+
+    /// Returns `a * b / c` rounded down, or `None` on overflow.
+    fn mul_div(a: Balance, b: Balance, c: Balance) -> Option<Balance> {
+        a.checked_mul(b)?.checked_div(c)
+    }
+
+    /// Returns `a * b / c` rounded up, or `None` on overflow.
+    fn mul_div_up(a: Balance, b: Balance, c: Balance) -> Option<Balance> {
+        let product = a.checked_mul(b)?;
+        let quotient = product.checked_div(c)?;
+        Some(if product % c > 0 { quotient + 1 } else { quotient })
+    }
+
+    /// Pulls `value` of `from`'s tokens to `to` through `Erc20::transfer_from`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        Erc20::transfer_from(&mut token, from, to, value).is_ok()
+    }
+
+    /// Transfers the vault's own tokens through `Erc20::transfer`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        Erc20::transfer(&mut token, to, value, None).is_ok()
+    }
+
+    /// Returns the Entropy balance of `account` through `Erc20::balance_of`.
+    #[cfg(not(test))]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        use ink_env::call::FromAccountId;
+
+        let token: entropy::Entropy = FromAccountId::from_account_id(token);
+        Erc20::balance_of(&token, account)
+    }
+
+    // Off-chain stand-ins for the cross-contract calls, as ink's test environment can't execute other
+    // contracts. They operate on the ledger of `tests::MockToken`.
+
+    #[cfg(test)]
+    fn token_transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+        tests::MockToken::transfer(token, from, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        let from = ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account");
+        tests::MockToken::transfer(token, from, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        tests::MockToken::balance_of(token, account)
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+        use std::{
+            cell::RefCell,
+            collections::HashMap,
+        };
+
+        type Event = <LendingVault as ::ink_lang::BaseEvent>::Type;
+
+        thread_local! {
+            /// Balances of `MockToken`
+            static LEDGER: RefCell<HashMap<AccountId, Balance>> = RefCell::new(HashMap::new());
+        }
+
+        /// Stand-in for the Entropy contract, charging no transfer fee.
+        pub(super) struct MockToken;
+
+        impl MockToken {
+            fn fund(account: AccountId, balance: Balance) {
+                LEDGER.with(|ledger| ledger.borrow_mut().insert(account, balance));
+            }
+
+            pub(super) fn balance_of(token: AccountId, account: AccountId) -> Balance {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| ledger.borrow().get(&account).copied().unwrap_or(0))
+            }
+
+            pub(super) fn transfer(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+                assert_eq!(token, token_account());
+                LEDGER.with(|ledger| {
+                    let mut ledger = ledger.borrow_mut();
+                    let balance = ledger.get(&from).copied().unwrap_or(0);
+                    if balance < value {
+                        return false;
+                    }
+                    ledger.insert(from, balance - value);
+                    *ledger.entry(to).or_insert(0) += value;
+                    true
+                })
+            }
+        }
+
+        fn token_account() -> AccountId {
+            AccountId::from([0x10; 32])
+        }
+
+        fn vault_account() -> AccountId {
+            ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account")
+        }
+
+        /// Creates a vault pricing ENT 1:1 with the native currency, lending 75% of the collateral value,
+        /// paying a 5% liquidation bonus on up to 50% of a debt, at 0.1% interest per block. The owner
+        /// funds it with 1_000_000 cash, and the ledger and native balances left over by previous tests
+        /// are cleared.
+        fn new_vault() -> LendingVault {
+            LEDGER.with(|ledger| ledger.borrow_mut().clear());
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            for account in &[vault_account(), accounts.alice, accounts.bob, accounts.charlie] {
+                let balance = if *account == vault_account() { 0 } else { 10_000_000 };
+                ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(*account, balance).expect("Cannot set balance");
+            }
+            let risk_params = RiskParams { collateral_factor: 7_500, liquidation_bonus: 500, close_factor: 5_000 };
+            let mut vault = LendingVault::new(token_account(), PRICE_UNIT, risk_params, INDEX_SCALE / 1_000);
+            set_caller(accounts.alice, 1_000_000);
+            vault.fund();
+            vault
+        }
+
+        fn native_balance(account: AccountId) -> Balance {
+            ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(account).unwrap_or(0)
+        }
+
+        /// Calls the vault as `caller` transferring `value` native currency, which the off-chain
+        /// environment doesn't move by itself.
+        fn set_caller(caller: AccountId, value: Balance) {
+            let callee = vault_account();
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(caller, native_balance(caller) - value).expect("Cannot set balance");
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(callee, native_balance(callee) + value).expect("Cannot set balance");
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(caller, callee, 1000000, value, data);
+        }
+
+        fn advance_blocks(blocks: u32) {
+            for _ in 0..blocks {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>().expect("Cannot advance block");
+            }
+        }
+
+        fn assert_liquidated_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_liquidator: AccountId,
+            expected_borrower: AccountId,
+            expected_repaid: Balance,
+            expected_seized: Balance,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Liquidated(Liquidated { liquidator, borrower, repaid, seized }) = decoded_event {
+                assert_eq!(liquidator, expected_liquidator, "encountered invalid Liquidated.liquidator");
+                assert_eq!(borrower, expected_borrower, "encountered invalid Liquidated.borrower");
+                assert_eq!(repaid, expected_repaid, "encountered invalid Liquidated.repaid");
+                assert_eq!(seized, expected_seized, "encountered invalid Liquidated.seized");
+            } else {
+                panic!("encountered unexpected event kind: expected a Liquidated event")
+            }
+        }
+
+        #[ink::test]
+        fn deposit_and_borrow_work() {
+            let mut vault = new_vault();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 2_000_000);
+
+            set_caller(accounts.bob, 0);
+            assert_eq!(vault.borrow(1), Err(Error::InsufficientCollateral));
+            assert_eq!(vault.deposit(3_000_000), Err(Error::TokenTransferFailed));
+            assert_eq!(vault.deposit(2_000_000), Ok(()));
+            assert_eq!(vault.collateral_of(accounts.bob), 2_000_000);
+            assert_eq!(vault.borrow_limit(accounts.bob), 1_500_000);
+
+            // Borrowing is limited by the collateral and the cash
+            assert_eq!(vault.borrow(1_000_001), Err(Error::InsufficientCash));
+            assert_eq!(vault.borrow(600_000), Ok(()));
+            assert_eq!(native_balance(accounts.bob), 10_600_000);
+            assert_eq!(vault.cash(), 400_000);
+            assert_eq!(vault.health_factor(accounts.bob), 25_000);
+
+            set_caller(accounts.alice, 2_000_000);
+            vault.fund();
+            set_caller(accounts.bob, 0);
+            assert_eq!(vault.borrow(900_001), Err(Error::InsufficientCollateral));
+            assert_eq!(vault.borrow(900_000), Ok(()));
+            assert_eq!(vault.health_factor(accounts.bob), HEALTH_SCALE);
+
+            // Collateral backing debt can't be withdrawn
+            assert_eq!(vault.withdraw(1), Err(Error::InsufficientCollateral));
+            set_caller(accounts.bob, 500_000);
+            assert_eq!(vault.repay(), Ok(1_000_000));
+            assert_eq!(vault.withdraw(666_667), Err(Error::InsufficientCollateral));
+            assert_eq!(vault.withdraw(666_666), Ok(()));
+            assert_eq!(MockToken::balance_of(token_account(), accounts.bob), 666_666);
+
+            // Only the owner withdraws cash
+            assert_eq!(vault.withdraw_cash(1), Err(Error::PermissionDenied));
+            set_caller(accounts.alice, 0);
+            assert_eq!(vault.withdraw_cash(2_000_001), Err(Error::InsufficientCash));
+            assert_eq!(vault.withdraw_cash(2_000_000), Ok(()));
+            assert_eq!(native_balance(vault_account()), 0);
+        }
+
+        #[ink::test]
+        fn interest_accrues_per_block() {
+            let mut vault = new_vault();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 2_000_000);
+            set_caller(accounts.bob, 0);
+            assert_eq!(vault.deposit(2_000_000), Ok(()));
+            assert_eq!(vault.borrow(600_000), Ok(()));
+
+            // 0.1% per block, compounding whenever the vault is used
+            advance_blocks(10);
+            assert_eq!(vault.debt_of(accounts.bob), 606_000);
+            assert_eq!(vault.total_borrows(), 606_000);
+            assert_eq!(vault.borrow(1), Ok(()));
+            advance_blocks(10);
+            assert_eq!(vault.debt_of(accounts.bob), 612_062);
+
+            // Overpaying refunds the rest, and debt rounds up in favour of the vault
+            set_caller(accounts.bob, 700_000);
+            assert_eq!(vault.repay(), Ok(0));
+            assert_eq!(native_balance(accounts.bob), 10_600_001 - 612_062);
+            assert_eq!(vault.cash(), 1_012_061);
+            assert_eq!(vault.debt_of(accounts.bob), 0);
+            set_caller(accounts.bob, 1);
+            assert_eq!(vault.repay(), Err(Error::NoDebt));
+            assert_eq!(native_balance(accounts.bob), 10_600_001 - 612_062);
+
+            // Changing the rate only affects future blocks
+            set_caller(accounts.bob, 0);
+            assert_eq!(vault.borrow(100_000), Ok(()));
+            advance_blocks(10);
+            set_caller(accounts.alice, 0);
+            assert_eq!(vault.set_params(vault.risk_params(), 0), Ok(()));
+            advance_blocks(10);
+            assert_eq!(vault.debt_of(accounts.bob), 101_000);
+        }
+
+        #[ink::test]
+        fn liquidation_works() {
+            let mut vault = new_vault();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(accounts.bob, 1_000_000);
+            set_caller(accounts.bob, 0);
+            assert_eq!(vault.deposit(1_000_000), Ok(()));
+            assert_eq!(vault.borrow(600_000), Ok(()));
+
+            set_caller(accounts.charlie, 100_000);
+            assert_eq!(vault.liquidate(accounts.bob), Err(Error::NotLiquidatable));
+            assert_eq!(native_balance(accounts.charlie), 10_000_000);
+
+            // At 0.8 the 600_000 limit falls below the debt grown to 606_000
+            set_caller(accounts.alice, 0);
+            assert_eq!(vault.set_price(800_000), Ok(()));
+            assert_eq!(vault.health_factor(accounts.bob), 10_000);
+            advance_blocks(10);
+            assert_eq!(vault.health_factor(accounts.bob), 9_900);
+
+            // The close factor caps the repayment at half the debt, the rest is refunded, and collateral
+            // worth the repayment plus 5% is seized
+            set_caller(accounts.charlie, 400_000);
+            assert_eq!(vault.liquidate(accounts.bob), Ok(397_687));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_liquidated_event(&emitted_events[emitted_events.len() - 1], accounts.charlie, accounts.bob, 303_000, 397_687);
+            assert_eq!(native_balance(accounts.charlie), 10_000_000 - 303_000);
+            assert_eq!(MockToken::balance_of(token_account(), accounts.charlie), 397_687);
+            assert_eq!(vault.collateral_of(accounts.bob), 602_313);
+            assert_eq!(vault.debt_of(accounts.bob), 303_000);
+            assert!(vault.health_factor(accounts.bob) > HEALTH_SCALE);
+            assert_eq!(vault.liquidate(accounts.bob), Err(Error::NotLiquidatable));
+
+            // A crashed price seizes at most all the collateral
+            set_caller(accounts.alice, 0);
+            assert_eq!(vault.set_price(100_000), Ok(()));
+            set_caller(accounts.charlie, 151_500);
+            assert_eq!(vault.liquidate(accounts.bob), Ok(602_313));
+            assert_eq!(vault.collateral_of(accounts.bob), 0);
+            assert_eq!(vault.debt_of(accounts.bob), 151_500);
+        }
+
+        #[ink::test]
+        fn params_are_owner_only() {
+            let mut vault = new_vault();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            let risk_params = vault.risk_params();
+
+            set_caller(accounts.bob, 0);
+            assert_eq!(vault.set_price(1), Err(Error::PermissionDenied));
+            assert_eq!(vault.set_params(risk_params, 0), Err(Error::PermissionDenied));
+
+            set_caller(accounts.alice, 0);
+            assert_eq!(vault.set_price(0), Err(Error::InvalidParams));
+            assert_eq!(vault.set_params(RiskParams { collateral_factor: 10_001, ..risk_params }, 0), Err(Error::InvalidParams));
+            assert_eq!(vault.set_params(RiskParams { close_factor: 0, ..risk_params }, 0), Err(Error::InvalidParams));
+            assert_eq!(vault.set_price(2 * PRICE_UNIT), Ok(()));
+            assert_eq!(vault.borrow_limit(accounts.bob), 0);
+            assert_eq!(vault.price(), 2 * PRICE_UNIT);
+        }
+    }
+}
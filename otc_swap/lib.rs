@@ -0,0 +1,576 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod otc_swap {
+    use core::fmt;
+
+    use ink_prelude::{
+        format,
+        string::String,
+    };
+
+    use ink_storage::{
+        collections::HashMap as StorageHashMap,
+        traits::{
+            PackedLayout,
+            SpreadLayout,
+        },
+    };
+
+    /// Offer to swap `maker_amount` of `maker_token` escrowed by `maker` for `taker_amount` of `taker_token`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Offer {
+        pub maker: AccountId,
+        pub maker_token: AccountId,
+        /// Amount escrowed, which is what the contract received from the maker
+        pub maker_amount: Balance,
+        pub taker_token: AccountId,
+        pub taker_amount: Balance,
+        /// Only account allowed to fill the offer, anyone if `None`
+        pub taker: Option<AccountId>,
+        /// Timestamp after which the offer can no longer be filled
+        pub expiry: Timestamp,
+    }
+
+    /// OTC swap desk for Entropy-compatible tokens.
+    ///
+    /// Makers escrow their side of a swap in an offer, which a taker fills whole by paying the maker's
+    /// asking amount. Unfilled offers are refunded on cancellation, by the maker at any time or by
+    /// anyone once expired.
+    #[ink(storage)]
+    pub struct OtcSwap {
+        /// Identifier of the next offer created
+        next_offer_id: u64,
+
+        /// Mapping of offer identifiers to open offers
+        offers: StorageHashMap<u64, Offer>
+    }
+
+    /// Event emitted when an offer is created.
+    #[ink(event)]
+    pub struct OfferCreated {
+        #[ink(topic)]
+        offer_id: u64,
+        #[ink(topic)]
+        maker: AccountId,
+        taker: Option<AccountId>
+    }
+
+    /// Event emitted when an offer is filled.
+    #[ink(event)]
+    pub struct OfferFilled {
+        #[ink(topic)]
+        offer_id: u64,
+        #[ink(topic)]
+        maker: AccountId,
+        #[ink(topic)]
+        taker: AccountId
+    }
+
+    /// Event emitted when an offer is cancelled and refunded.
+    #[ink(event)]
+    pub struct OfferCancelled {
+        #[ink(topic)]
+        offer_id: u64,
+        #[ink(topic)]
+        by: AccountId
+    }
+
+    /// Event emitted when error occurs
+    #[ink(event)]
+    pub struct TransactionFailed {
+        #[ink(topic)]
+        error: String
+    }
+
+    /// OtcSwap error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if not privileged.
+        PermissionDenied,
+        /// Returned if an offer has a zero amount or an expiry in the past
+        InvalidOffer,
+        /// Returned if an offer doesn't exist or was already filled or cancelled
+        OfferNotFound,
+        /// Returned if filling an offer after its expiry
+        OfferExpired,
+        /// Returned if filling an offer designated to another taker
+        WrongTaker,
+        /// Returned if a token contract rejects a transfer
+        TokenTransferFailed
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Self::PermissionDenied => write!(f, "PermissionDenied"),
+                Self::InvalidOffer => write!(f, "InvalidOffer"),
+                Self::OfferNotFound => write!(f, "OfferNotFound"),
+                Self::OfferExpired => write!(f, "OfferExpired"),
+                Self::WrongTaker => write!(f, "WrongTaker"),
+                Self::TokenTransferFailed => write!(f, "TokenTransferFailed")
+            }
+        }
+    }
+
+    /// OtcSwap result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl Default for OtcSwap {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl OtcSwap {
+
+        /// Creates a new OTC swap desk without offers.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                next_offer_id: 0,
+                offers: StorageHashMap::new()
+            }
+        }
+
+        /// Returns the open offer `offer_id`.
+        #[ink(message)]
+        pub fn offer(&self, offer_id: u64) -> Option<Offer> {
+            self.offers.get(&offer_id).copied()
+        }
+
+        /// Escrows `maker_amount` of the caller's `maker_token` in an offer for `taker_amount` of `taker_token`
+        ///
+        /// Tokens are pulled with `transfer_from`, so the caller must have approved the contract. If the
+        /// token charges a transfer fee, the offer escrows what the contract actually received.
+        ///
+        /// On success an `OfferCreated` event is emitted and the offer identifier is returned.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidOffer` error if an amount is zero or `expiry` has passed.
+        ///
+        /// Returns `TokenTransferFailed` error if `maker_token` rejects the pull.
+        #[ink(message)]
+        pub fn create_offer(
+            &mut self,
+            maker_token: AccountId,
+            maker_amount: Balance,
+            taker_token: AccountId,
+            taker_amount: Balance,
+            taker: Option<AccountId>,
+            expiry: Timestamp
+        ) -> Result<u64> {
+            let maker = self.env().caller();
+            if maker_amount == 0 || taker_amount == 0 || expiry <= self.env().block_timestamp() {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::InvalidOffer)
+                });
+                return Err(Error::InvalidOffer);
+            }
+
+            let escrow = self.env().account_id();
+            let balance = token_balance_of(maker_token, escrow);
+            if !token_transfer_from(maker_token, maker, escrow, maker_amount) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenTransferFailed)
+                });
+                return Err(Error::TokenTransferFailed);
+            }
+            let received = token_balance_of(maker_token, escrow).saturating_sub(balance);
+
+            let offer_id = self.next_offer_id;
+            self.next_offer_id += 1;
+            self.offers.insert(offer_id, Offer {
+                maker,
+                maker_token,
+                maker_amount: received,
+                taker_token,
+                taker_amount,
+                taker,
+                expiry
+            });
+            self.env().emit_event(OfferCreated {
+                offer_id,
+                maker,
+                taker
+            });
+
+            Ok(offer_id)
+        }
+
+        /// Fills offer `offer_id`, paying the maker's asking amount and receiving the escrowed tokens
+        ///
+        /// The asking amount is pulled from the caller straight to the maker with `transfer_from`, so the
+        /// caller must have approved the contract on `taker_token`. Both legs settle or neither does: a
+        /// failed pull returns an error and leaves the offer open, and the escrow release panics if it
+        /// fails, reverting the whole call including the pull.
+        ///
+        /// On success an `OfferFilled` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `OfferNotFound` error if the offer doesn't exist.
+        ///
+        /// Returns `OfferExpired` error if the offer expired.
+        ///
+        /// Returns `WrongTaker` error if the offer is designated to another taker.
+        ///
+        /// Returns `TokenTransferFailed` error if `taker_token` rejects the pull.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `maker_token` rejects the release of the escrow to the caller.
+        #[ink(message)]
+        pub fn fill(&mut self, offer_id: u64) -> Result<()> {
+            let taker = self.env().caller();
+            let offer = match self.offer(offer_id) {
+                Some(offer) => offer,
+                None => {
+                    self.env().emit_event(TransactionFailed {
+                        error: format!("{:?}", Error::OfferNotFound)
+                    });
+                    return Err(Error::OfferNotFound);
+                }
+            };
+            if self.env().block_timestamp() > offer.expiry {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::OfferExpired)
+                });
+                return Err(Error::OfferExpired);
+            }
+            if offer.taker.map_or(false, |designated| designated != taker) {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::WrongTaker)
+                });
+                return Err(Error::WrongTaker);
+            }
+
+            // Close the offer before any token call, so it can't be filled or cancelled twice
+            self.offers.take(&offer_id);
+            if !token_transfer_from(offer.taker_token, taker, offer.maker, offer.taker_amount) {
+                self.offers.insert(offer_id, offer);
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenTransferFailed)
+                });
+                return Err(Error::TokenTransferFailed);
+            }
+            // The maker is paid, so only reverting the call can undo the settlement from here
+            if !token_transfer(offer.maker_token, taker, offer.maker_amount) {
+                panic!("settlement failed: escrow release rejected after the maker was paid");
+            }
+
+            self.env().emit_event(OfferFilled {
+                offer_id,
+                maker: offer.maker,
+                taker
+            });
+
+            Ok(())
+        }
+
+        /// Cancels offer `offer_id`, refunding the escrow to its maker
+        ///
+        /// The maker can cancel at any time, anyone else once the offer expired.
+        ///
+        /// On success an `OfferCancelled` event is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `OfferNotFound` error if the offer doesn't exist.
+        ///
+        /// Returns `PermissionDenied` error if caller is not the maker and the offer hasn't expired.
+        ///
+        /// Returns `TokenTransferFailed` error if `maker_token` rejects the refund, leaving the offer open.
+        #[ink(message)]
+        pub fn cancel(&mut self, offer_id: u64) -> Result<()> {
+            let caller = self.env().caller();
+            let offer = match self.offer(offer_id) {
+                Some(offer) => offer,
+                None => {
+                    self.env().emit_event(TransactionFailed {
+                        error: format!("{:?}", Error::OfferNotFound)
+                    });
+                    return Err(Error::OfferNotFound);
+                }
+            };
+            if caller != offer.maker && self.env().block_timestamp() <= offer.expiry {
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::PermissionDenied)
+                });
+                return Err(Error::PermissionDenied);
+            }
+
+            self.offers.take(&offer_id);
+            if !token_transfer(offer.maker_token, offer.maker, offer.maker_amount) {
+                self.offers.insert(offer_id, offer);
+                self.env().emit_event(TransactionFailed {
+                    error: format!("{:?}", Error::TokenTransferFailed)
+                });
+                return Err(Error::TokenTransferFailed);
+            }
+
+            self.env().emit_event(OfferCancelled {
+                offer_id,
+                by: caller
+            });
+
+            Ok(())
+        }
+    }
+
+    /// Pulls `value` of `from`'s tokens to `to` through the token's `transfer_from`, returning whether it succeeded.
+    ///
+    /// Both legs are called through Entropy's interface, so tokens must expose the same messages.
+    #[cfg(not(test))]
+    fn token_transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer_from(from, to, value).is_ok()
+    }
+
+    /// Transfers the contract's own tokens through the token's `transfer`, returning whether it succeeded.
+    #[cfg(not(test))]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        use ink_env::call::FromAccountId;
+
+        let mut token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.transfer(to, value, None).is_ok()
+    }
+
+    /// Returns the token balance of `account`.
+    #[cfg(not(test))]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        use ink_env::call::FromAccountId;
+
+        let token: entropy::Entropy = FromAccountId::from_account_id(token);
+        token.balance_of(account)
+    }
+
+    // Off-chain stand-ins for the cross-contract calls, as ink's test environment can't execute other
+    // contracts. They operate on the ledgers of `tests::MockToken`.
+
+    #[cfg(test)]
+    fn token_transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+        tests::MockToken::transfer(token, from, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_transfer(token: AccountId, to: AccountId, value: Balance) -> bool {
+        let from = ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account");
+        tests::MockToken::transfer(token, from, to, value)
+    }
+
+    #[cfg(test)]
+    fn token_balance_of(token: AccountId, account: AccountId) -> Balance {
+        tests::MockToken::balance_of(token, account)
+    }
+
+    /// Unit tests
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use ink_lang as ink;
+        use std::{
+            cell::RefCell,
+            collections::{
+                HashMap,
+                HashSet,
+            },
+        };
+
+        type Event = <OtcSwap as ::ink_lang::BaseEvent>::Type;
+
+        /// Balances of each `MockToken` by token and holder, and the tokens rejecting transfers out of the contract
+        type Ledger = (HashMap<(AccountId, AccountId), Balance>, HashSet<AccountId>);
+
+        thread_local! {
+            static LEDGER: RefCell<Ledger> = RefCell::new(Default::default());
+        }
+
+        /// Stand-in for token contracts, charging no transfer fee.
+        pub(super) struct MockToken;
+
+        impl MockToken {
+            fn fund(token: AccountId, account: AccountId, balance: Balance) {
+                LEDGER.with(|ledger| ledger.borrow_mut().0.insert((token, account), balance));
+            }
+
+            /// Makes `token` reject transfers out of the contract, e.g. because it was paused.
+            fn break_transfers(token: AccountId) {
+                LEDGER.with(|ledger| ledger.borrow_mut().1.insert(token));
+            }
+
+            pub(super) fn balance_of(token: AccountId, account: AccountId) -> Balance {
+                LEDGER.with(|ledger| ledger.borrow().0.get(&(token, account)).copied().unwrap_or(0))
+            }
+
+            pub(super) fn transfer(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> bool {
+                LEDGER.with(|ledger| {
+                    let mut ledger = ledger.borrow_mut();
+                    let balance = ledger.0.get(&(token, from)).copied().unwrap_or(0);
+                    if balance < value || (ledger.1.contains(&token) && from == swap_account()) {
+                        return false;
+                    }
+                    ledger.0.insert((token, from), balance - value);
+                    *ledger.0.entry((token, to)).or_insert(0) += value;
+                    true
+                })
+            }
+        }
+
+        fn ent() -> AccountId {
+            AccountId::from([0x10; 32])
+        }
+
+        fn other_token() -> AccountId {
+            AccountId::from([0x11; 32])
+        }
+
+        fn swap_account() -> AccountId {
+            ink_env::account_id::<ink_env::DefaultEnvironment>().expect("Cannot get contract account")
+        }
+
+        /// Creates a swap desk where bob holds 1_000 ENT and charlie 500 of the other token, clearing the
+        /// ledgers left over by previous tests.
+        fn new_swap() -> OtcSwap {
+            LEDGER.with(|ledger| *ledger.borrow_mut() = Default::default());
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+            MockToken::fund(ent(), accounts.bob, 1_000);
+            MockToken::fund(other_token(), accounts.charlie, 500);
+            MockToken::fund(other_token(), accounts.django, 500);
+            OtcSwap::new()
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = swap_account();
+            let mut data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            data.push_arg(&caller);
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(caller, callee, 1000000, 1000000, data);
+        }
+
+        fn assert_offer_filled_event(
+            event: &ink_env::test::EmittedEvent,
+            expected_offer_id: u64,
+            expected_maker: AccountId,
+            expected_taker: AccountId,
+        ) {
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::OfferFilled(OfferFilled { offer_id, maker, taker }) = decoded_event {
+                assert_eq!(offer_id, expected_offer_id, "encountered invalid OfferFilled.offer_id");
+                assert_eq!(maker, expected_maker, "encountered invalid OfferFilled.maker");
+                assert_eq!(taker, expected_taker, "encountered invalid OfferFilled.taker");
+            } else {
+                panic!("encountered unexpected event kind: expected an OfferFilled event")
+            }
+        }
+
+        #[ink::test]
+        fn fill_works() {
+            let mut swap = new_swap();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(swap.create_offer(ent(), 0, other_token(), 400, None, 100), Err(Error::InvalidOffer));
+            assert_eq!(swap.create_offer(ent(), 1_001, other_token(), 400, None, 100), Err(Error::TokenTransferFailed));
+            assert_eq!(swap.create_offer(ent(), 1_000, other_token(), 400, None, 100), Ok(0));
+            assert_eq!(MockToken::balance_of(ent(), swap_account()), 1_000);
+
+            set_caller(accounts.charlie);
+            assert_eq!(swap.fill(0), Ok(()));
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_offer_filled_event(&emitted_events[emitted_events.len() - 1], 0, accounts.bob, accounts.charlie);
+            assert_eq!(MockToken::balance_of(ent(), accounts.charlie), 1_000);
+            assert_eq!(MockToken::balance_of(other_token(), accounts.bob), 400);
+            assert_eq!(MockToken::balance_of(other_token(), accounts.charlie), 100);
+            assert_eq!(MockToken::balance_of(ent(), swap_account()), 0);
+
+            // Offers are filled once
+            assert_eq!(swap.offer(0), None);
+            assert_eq!(swap.fill(0), Err(Error::OfferNotFound));
+            assert_eq!(swap.cancel(0), Err(Error::OfferNotFound));
+        }
+
+        #[ink::test]
+        fn wrong_taker_is_rejected() {
+            let mut swap = new_swap();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(swap.create_offer(ent(), 1_000, other_token(), 400, Some(accounts.django), 100), Ok(0));
+
+            set_caller(accounts.charlie);
+            assert_eq!(swap.fill(0), Err(Error::WrongTaker));
+            assert_eq!(swap.cancel(0), Err(Error::PermissionDenied));
+
+            // A failed pull leaves the offer open
+            set_caller(accounts.django);
+            MockToken::fund(other_token(), accounts.django, 399);
+            assert_eq!(swap.fill(0), Err(Error::TokenTransferFailed));
+            assert!(swap.offer(0).is_some());
+            MockToken::fund(other_token(), accounts.django, 400);
+            assert_eq!(swap.fill(0), Ok(()));
+            assert_eq!(MockToken::balance_of(ent(), accounts.django), 1_000);
+            assert_eq!(MockToken::balance_of(other_token(), accounts.charlie), 500);
+        }
+
+        #[ink::test]
+        fn expired_offer_is_refunded() {
+            let mut swap = new_swap();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(swap.create_offer(ent(), 600, other_token(), 400, None, 100), Ok(0));
+            assert_eq!(swap.create_offer(ent(), 400, other_token(), 100, None, 100), Ok(1));
+            assert_eq!(swap.cancel(1), Ok(()));
+            assert_eq!(MockToken::balance_of(ent(), accounts.bob), 400);
+
+            // Timestamp 105 after 21 blocks
+            for _ in 0..21 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>().expect("Cannot advance block");
+            }
+            set_caller(accounts.charlie);
+            assert_eq!(swap.fill(0), Err(Error::OfferExpired));
+            assert_eq!(swap.cancel(0), Ok(()));
+            assert_eq!(MockToken::balance_of(ent(), accounts.bob), 1_000);
+            assert_eq!(MockToken::balance_of(ent(), swap_account()), 0);
+
+            set_caller(accounts.bob);
+            assert_eq!(swap.create_offer(ent(), 1_000, other_token(), 400, None, 100), Err(Error::InvalidOffer));
+        }
+
+        #[ink::test]
+        fn failed_refund_keeps_offer_open() {
+            let mut swap = new_swap();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(swap.create_offer(ent(), 1_000, other_token(), 400, None, 100), Ok(0));
+            MockToken::break_transfers(ent());
+            assert_eq!(swap.cancel(0), Err(Error::TokenTransferFailed));
+            assert!(swap.offer(0).is_some());
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "settlement failed")]
+        fn failed_escrow_release_reverts_fill() {
+            let mut swap = new_swap();
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().expect("Cannot get accounts");
+
+            set_caller(accounts.bob);
+            assert_eq!(swap.create_offer(ent(), 1_000, other_token(), 400, None, 100), Ok(0));
+
+            // ENT rejects the release after charlie paid, which must trap to revert charlie's payment
+            MockToken::break_transfers(ent());
+            set_caller(accounts.charlie);
+            let _ = swap.fill(0);
+        }
+    }
+}